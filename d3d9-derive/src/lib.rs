@@ -0,0 +1,549 @@
+//! Derive macros for the `d3d9` crate.
+//!
+//! These are re-exported by `d3d9` when its `derive` feature is enabled, and should be used
+//! through that re-export.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parenthesized, parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Expr, Field,
+    Fields, Ident, Lit, LitInt, Type,
+};
+
+/// Derives `d3d9::std::vertex::Vertex` and `d3d9::std::lock::Pod` for a `#[repr(C)]` struct, so
+/// that it can be written straight into a locked vertex buffer.
+///
+/// Every field needs a `#[vertex(...)]` attribute naming its usage, optionally followed by a
+/// usage index and an explicit `kind`, or `#[vertex(skip)]` for padding. Every field must itself
+/// be `Pod`, and the struct must not contain implicit padding:
+///
+/// ```rs
+/// #[derive(Clone, Copy, Vertex)]
+/// #[repr(C)]
+/// struct TexturedVertex {
+///     #[vertex(position)]
+///     position: [f32; 3],
+///     #[vertex(color)]
+///     color: u32,
+///     #[vertex(texcoord(0))]
+///     uv: [f32; 2],
+///     #[vertex(texcoord(1), kind = Float16By2)]
+///     lightmap_uv: [u16; 2],
+/// }
+/// ```
+#[proc_macro_derive(Vertex, attributes(vertex))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand_vertex(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// The vertex element types, along with their size in bytes.
+const DECL_TYPES: &[(&str, usize)] = &[
+    ("Float1", 4),
+    ("Float2", 8),
+    ("Float3", 12),
+    ("Float4", 16),
+    ("Color", 4),
+    ("UByte4", 4),
+    ("Short2", 4),
+    ("Short4", 8),
+    ("UByte4N", 4),
+    ("Short2N", 4),
+    ("Short4N", 8),
+    ("UShort2N", 4),
+    ("UShort4N", 8),
+    ("UDec3", 4),
+    ("Dec3N", 4),
+    ("Float16By2", 4),
+    ("Float16By4", 8),
+];
+
+/// The attribute names of each vertex usage, along with the `DeclUsage` variant.
+const USAGES: &[(&str, &str)] = &[
+    ("position", "Position"),
+    ("blendweight", "BlendWeight"),
+    ("blendindices", "BlendIndices"),
+    ("normal", "Normal"),
+    ("psize", "PSize"),
+    ("texcoord", "TexCoord"),
+    ("tangent", "Tangent"),
+    ("binormal", "BiNormal"),
+    ("tessfactor", "TessFactor"),
+    ("positiont", "PositionT"),
+    ("color", "Color"),
+    ("fog", "Fog"),
+    ("depth", "Depth"),
+    ("sample", "Sample"),
+];
+
+struct Element<'a> {
+    field: &'a Field,
+    usage: &'static str,
+    usage_index: u8,
+    kind: &'static str,
+}
+
+fn expand_vertex(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "`Vertex` cannot be derived for generic structs",
+        ));
+    }
+
+    if !has_repr_c(&input) {
+        return Err(Error::new(
+            name.span(),
+            "`Vertex` requires `#[repr(C)]` so that field offsets are stable",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    name.span(),
+                    "`Vertex` can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "`Vertex` can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut elements = Vec::new();
+    let mut skipped = false;
+    for field in fields {
+        match parse_element(field)? {
+            Some(element) => elements.push(element),
+            None => skipped = true,
+        }
+    }
+
+    let element_tokens = elements.iter().map(|element| {
+        let field = element.field.ident.as_ref().unwrap();
+        let kind = Ident::new(element.kind, Span::call_site());
+        let usage = Ident::new(element.usage, Span::call_site());
+        let usage_index = element.usage_index;
+
+        quote! {
+            ::d3d9::std::types::VertexElement {
+                stream: 0,
+                offset: ::core::mem::offset_of!(#name, #field) as u16,
+                kind: ::d3d9::std::types::DeclType::#kind,
+                method: ::d3d9::std::types::DeclMethod::Default,
+                usage: ::d3d9::std::types::DeclUsage::#usage,
+                usage_index: #usage_index,
+            }
+        }
+    });
+
+    let size_checks = elements.iter().map(|element| {
+        let ty = &element.field.ty;
+        let size = decl_type_size(element.kind);
+        let message = format!(
+            "field `{}` does not match the size of `DeclType::{}` ({} bytes)",
+            element.field.ident.as_ref().unwrap(),
+            element.kind,
+            size,
+        );
+
+        quote! {
+            assert!(::core::mem::size_of::<#ty>() == #size, #message);
+        }
+    });
+
+    let field_types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+
+    let fvf = match (skipped, fvf(&elements)) {
+        (false, Some(fvf)) => quote! { Some(::d3d9::std::types::FVF(#fvf)) },
+        _ => quote! { None },
+    };
+
+    Ok(quote! {
+        impl ::d3d9::std::vertex::Vertex for #name {
+            const ELEMENTS: &'static [::d3d9::std::types::VertexElement] = &[#(#element_tokens),*];
+            const STRIDE: u32 = ::core::mem::size_of::<#name>() as u32;
+            const FVF: Option<::d3d9::std::types::FVF> = #fvf;
+        }
+
+        // Every field is `Pod` and the struct has no padding, both of which are checked below.
+        unsafe impl ::d3d9::std::lock::Pod for #name {}
+
+        const _: () = {
+            fn assert_pod<T: ::d3d9::std::lock::Pod>() {}
+            fn assert_fields_are_pod() {
+                #(assert_pod::<#field_types>();)*
+            }
+
+            #(#size_checks)*
+            assert!(
+                ::core::mem::size_of::<#name>() == 0 #(+ ::core::mem::size_of::<#field_types>())*,
+                "`Vertex` structs cannot contain padding, declare it as a `#[vertex(skip)]` field",
+            );
+            assert!(
+                ::core::mem::size_of::<#name>() <= u16::MAX as usize,
+                "vertex is too large for a 16-bit element offset",
+            );
+        };
+    })
+}
+
+fn has_repr_c(input: &DeriveInput) -> bool {
+    input.attrs.iter().any(|attr| {
+        let mut repr_c = false;
+        if attr.path().is_ident("repr") {
+            let _ = attr.parse_nested_meta(|meta| {
+                repr_c |= meta.path.is_ident("C");
+                Ok(())
+            });
+        }
+        repr_c
+    })
+}
+
+/// Parses the `#[vertex(...)]` attribute of a field, returning [`None`] for skipped fields.
+fn parse_element(field: &Field) -> Result<Option<Element<'_>>, Error> {
+    let attr = field
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("vertex"))
+        .ok_or_else(|| {
+            Error::new(
+                field.span(),
+                "every field needs a `#[vertex(...)]` attribute, or `#[vertex(skip)]`",
+            )
+        })?;
+
+    let mut skip = false;
+    let mut usage = None;
+    let mut usage_index = 0u8;
+    let mut kind = None;
+
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("skip") {
+            skip = true;
+            return Ok(());
+        }
+
+        if meta.path.is_ident("kind") {
+            let ident: Ident = meta.value()?.parse()?;
+            let known = DECL_TYPES
+                .iter()
+                .find(|(name, _)| ident == name)
+                .ok_or_else(|| meta.error(format!("unknown vertex element type `{ident}`")))?;
+            kind = Some(known.0);
+            return Ok(());
+        }
+
+        let known = USAGES
+            .iter()
+            .find(|(name, _)| meta.path.is_ident(name))
+            .ok_or_else(|| meta.error("unknown vertex usage"))?;
+        if usage.replace(known.1).is_some() {
+            return Err(meta.error("a field can only have one usage"));
+        }
+
+        if meta.input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in meta.input);
+            usage_index = content.parse::<LitInt>()?.base10_parse()?;
+        }
+
+        Ok(())
+    })?;
+
+    if skip {
+        return Ok(None);
+    }
+
+    let usage = usage.ok_or_else(|| Error::new(attr.span(), "missing vertex usage"))?;
+    let kind = match kind {
+        Some(kind) => kind,
+        None => infer_kind(&field.ty, usage).ok_or_else(|| {
+            Error::new(
+                field.ty.span(),
+                "cannot infer the vertex element type, specify one with `kind = ...`",
+            )
+        })?,
+    };
+
+    Ok(Some(Element {
+        field,
+        usage,
+        usage_index,
+        kind,
+    }))
+}
+
+/// Infers the vertex element type from common field types.
+fn infer_kind(ty: &Type, usage: &str) -> Option<&'static str> {
+    let kind = match scalar_and_length(ty)? {
+        ("f32", None | Some(1)) => "Float1",
+        ("f32", Some(2)) => "Float2",
+        ("f32", Some(3)) => "Float3",
+        ("f32", Some(4)) => "Float4",
+        ("u32", None) if usage == "Color" => "Color",
+        ("u8", Some(4)) if usage == "Color" => "UByte4N",
+        ("u8", Some(4)) => "UByte4",
+        ("i16", Some(2)) => "Short2",
+        ("i16", Some(4)) => "Short4",
+        ("u16", Some(2)) => "UShort2N",
+        ("u16", Some(4)) => "UShort4N",
+        _ => return None,
+    };
+
+    Some(kind)
+}
+
+/// Returns the primitive name and array length of `f32` or `[f32; 3]` style types.
+fn scalar_and_length(ty: &Type) -> Option<(&'static str, Option<usize>)> {
+    const PRIMITIVES: &[&str] = &["f32", "u32", "u8", "i16", "u16"];
+
+    let primitive = |ty: &Type| match ty {
+        Type::Path(path) => PRIMITIVES
+            .iter()
+            .find(|primitive| path.path.is_ident(primitive))
+            .copied(),
+        _ => None,
+    };
+
+    match ty {
+        Type::Array(array) => {
+            let Expr::Lit(length) = &array.len else {
+                return None;
+            };
+            let Lit::Int(length) = &length.lit else {
+                return None;
+            };
+            Some((primitive(&array.elem)?, Some(length.base10_parse().ok()?)))
+        }
+        ty => Some((primitive(ty)?, None)),
+    }
+}
+
+fn decl_type_size(kind: &str) -> usize {
+    DECL_TYPES
+        .iter()
+        .find(|(name, _)| *name == kind)
+        .map(|(_, size)| *size)
+        .unwrap()
+}
+
+/// Returns the `D3DFVF` code describing the elements, if the fixed function pipeline can express
+/// them. The elements must appear in the order the FVF lays them out.
+fn fvf(elements: &[Element<'_>]) -> Option<u32> {
+    const XYZ: u32 = 0x002;
+    const XYZRHW: u32 = 0x004;
+    const NORMAL: u32 = 0x010;
+    const PSIZE: u32 = 0x020;
+    const DIFFUSE: u32 = 0x040;
+    const SPECULAR: u32 = 0x080;
+    const TEX_COUNT_SHIFT: u32 = 8;
+
+    // Elements are assigned increasing stages, which must not go backwards.
+    let mut fvf = 0;
+    let mut stage = 0;
+    let mut n_texcoords = 0;
+
+    for element in elements {
+        let (next_stage, bits) = match (element.usage, element.usage_index, element.kind) {
+            ("Position", 0, "Float3") => (1, XYZ),
+            ("PositionT", 0, "Float4") => (1, XYZRHW),
+            ("Normal", 0, "Float3") => (2, NORMAL),
+            ("PSize", 0, "Float1") => (3, PSIZE),
+            ("Color", 0, "Color") => (4, DIFFUSE),
+            ("Color", 1, "Color") => (5, SPECULAR),
+            ("TexCoord", index, kind) if index == n_texcoords && n_texcoords < 8 => {
+                // D3DFVF_TEXCOORDSIZEn, where two components is the default of zero.
+                let size = match kind {
+                    "Float1" => 3,
+                    "Float2" => 0,
+                    "Float3" => 1,
+                    "Float4" => 2,
+                    _ => return None,
+                };
+                n_texcoords += 1;
+                (6, size << (16 + index as u32 * 2))
+            }
+            _ => return None,
+        };
+
+        if next_stage < stage || (next_stage == stage && next_stage != 6) {
+            return None;
+        }
+
+        stage = next_stage;
+        fvf |= bits;
+    }
+
+    // The fixed function pipeline always needs a position.
+    if fvf & (XYZ | XYZRHW) == 0 {
+        return None;
+    }
+
+    Some(fvf | (n_texcoords as u32) << TEX_COUNT_SHIFT)
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn error(input: DeriveInput) -> String {
+        expand_vertex(input).unwrap_err().to_string()
+    }
+
+    fn fvf_of(input: DeriveInput) -> Option<u32> {
+        let Data::Struct(data) = &input.data else {
+            unreachable!()
+        };
+        let elements = data
+            .fields
+            .iter()
+            .map(|field| parse_element(field).unwrap().unwrap())
+            .collect::<Vec<_>>();
+
+        fvf(&elements)
+    }
+
+    #[test]
+    fn infers_element_types() {
+        let input: DeriveInput = parse_quote! {
+            #[repr(C)]
+            struct Vertex {
+                #[vertex(position)]
+                position: [f32; 3],
+                #[vertex(color)]
+                color: u32,
+                #[vertex(color(1))]
+                specular: [u8; 4],
+                #[vertex(blendindices)]
+                indices: [u8; 4],
+                #[vertex(texcoord(0), kind = Float16By2)]
+                uv: [u16; 2],
+            }
+        };
+        let Data::Struct(data) = &input.data else {
+            unreachable!()
+        };
+
+        let kinds = data
+            .fields
+            .iter()
+            .map(|field| {
+                let element = parse_element(field).unwrap().unwrap();
+                (element.usage, element.usage_index, element.kind)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                ("Position", 0, "Float3"),
+                ("Color", 0, "Color"),
+                ("Color", 1, "UByte4N"),
+                ("BlendIndices", 0, "UByte4"),
+                ("TexCoord", 0, "Float16By2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn computes_fvf_codes() {
+        assert_eq!(
+            fvf_of(parse_quote! {
+                struct Vertex {
+                    #[vertex(position)]
+                    position: [f32; 3],
+                    #[vertex(normal)]
+                    normal: [f32; 3],
+                    #[vertex(color)]
+                    color: u32,
+                    #[vertex(texcoord(0))]
+                    uv: [f32; 2],
+                    #[vertex(texcoord(1))]
+                    uvw: [f32; 3],
+                }
+            }),
+            Some(0x002 | 0x010 | 0x040 | 0x200 | (1 << 18))
+        );
+
+        // Out of order elements and missing positions cannot be expressed.
+        assert_eq!(
+            fvf_of(parse_quote! {
+                struct Vertex {
+                    #[vertex(color)]
+                    color: u32,
+                    #[vertex(position)]
+                    position: [f32; 3],
+                }
+            }),
+            None
+        );
+        assert_eq!(
+            fvf_of(parse_quote! {
+                struct Vertex {
+                    #[vertex(texcoord(0))]
+                    uv: [f32; 2],
+                }
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_structs() {
+        assert!(error(parse_quote! {
+            struct Vertex {
+                #[vertex(position)]
+                position: [f32; 3],
+            }
+        })
+        .contains("#[repr(C)]"));
+        assert!(error(parse_quote! {
+            #[repr(C)]
+            struct Vertex<T> {
+                #[vertex(position)]
+                position: T,
+            }
+        })
+        .contains("generic"));
+        assert!(error(parse_quote! {
+            #[repr(C)]
+            struct Vertex {
+                position: [f32; 3],
+            }
+        })
+        .contains("#[vertex(...)]"));
+        assert_eq!(
+            error(parse_quote! {
+                #[repr(C)]
+                struct Vertex {
+                    #[vertex(velocity)]
+                    velocity: [f32; 3],
+                }
+            }),
+            "unknown vertex usage"
+        );
+        assert!(error(parse_quote! {
+            #[repr(C)]
+            struct Vertex {
+                #[vertex(position)]
+                position: [f64; 3],
+            }
+        })
+        .contains("cannot infer"));
+    }
+}
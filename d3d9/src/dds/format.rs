@@ -0,0 +1,217 @@
+//! Maps DDS pixel formats and DXGI formats onto [`Format`].
+
+use crate::{
+    dds::header::{
+        four_cc, PixelFormat, DDPF_ALPHA, DDPF_ALPHAPIXELS, DDPF_BUMPDUDV, DDPF_BUMPLUMINANCE,
+        DDPF_FOURCC, DDPF_LUMINANCE, DDPF_RGB, DDPF_YUV,
+    },
+    std::types::Format,
+};
+
+pub const FOURCC_DX10: u32 = four_cc(b"DX10");
+
+/// Formats identified by a four character code. Formats introduced after DirectDraw are stored
+/// by their numeric `D3DFORMAT` value.
+const FOUR_CC_FORMATS: &[(u32, Format)] = &[
+    (four_cc(b"DXT1"), Format::DXT1),
+    (four_cc(b"DXT2"), Format::DXT2),
+    (four_cc(b"DXT3"), Format::DXT3),
+    (four_cc(b"DXT4"), Format::DXT4),
+    (four_cc(b"DXT5"), Format::DXT5),
+    (four_cc(b"UYVY"), Format::UYVY),
+    (four_cc(b"YUY2"), Format::YUY2),
+    (four_cc(b"RGBG"), Format::R8g8B8g8),
+    (four_cc(b"GRGB"), Format::G8r8G8b8),
+    (36, Format::A16B16G16R16),
+    (110, Format::Q16W16V16U16),
+    (111, Format::R16F),
+    (112, Format::G16R16F),
+    (113, Format::A16B16G16R16F),
+    (114, Format::R32F),
+    (115, Format::G32R32F),
+    (116, Format::A32B32G32R32F),
+    (117, Format::CxV8U8),
+];
+
+/// Formats identified by their pixel format flags, bit count and `[r, g, b, a]` masks.
+const MASKED_FORMATS: &[(u32, u32, [u32; 4], Format)] = &[
+    (DDPF_RGB, 24, [0xFF0000, 0xFF00, 0xFF, 0], Format::R8G8B8),
+    (
+        DDPF_RGB,
+        32,
+        [0xFF0000, 0xFF00, 0xFF, 0xFF000000],
+        Format::A8R8G8B8,
+    ),
+    (DDPF_RGB, 32, [0xFF0000, 0xFF00, 0xFF, 0], Format::X8R8G8B8),
+    (
+        DDPF_RGB,
+        32,
+        [0xFF, 0xFF00, 0xFF0000, 0xFF000000],
+        Format::A8B8G8R8,
+    ),
+    (DDPF_RGB, 32, [0xFF, 0xFF00, 0xFF0000, 0], Format::X8B8G8R8),
+    (DDPF_RGB, 16, [0xF800, 0x7E0, 0x1F, 0], Format::R5G6B5),
+    (
+        DDPF_RGB,
+        16,
+        [0x7C00, 0x3E0, 0x1F, 0x8000],
+        Format::A1R5G5B5,
+    ),
+    (DDPF_RGB, 16, [0x7C00, 0x3E0, 0x1F, 0], Format::X1R5G5B5),
+    (DDPF_RGB, 16, [0xF00, 0xF0, 0xF, 0xF000], Format::A4R4G4B4),
+    (DDPF_RGB, 16, [0xF00, 0xF0, 0xF, 0], Format::X4R4G4B4),
+    (DDPF_RGB, 8, [0xE0, 0x1C, 0x3, 0], Format::R3G3B2),
+    (DDPF_RGB, 16, [0xE0, 0x1C, 0x3, 0xFF00], Format::A8R3G3B2),
+    (
+        DDPF_RGB,
+        32,
+        [0x3FF00000, 0xFFC00, 0x3FF, 0xC0000000],
+        Format::A2R10G10B10,
+    ),
+    (
+        DDPF_RGB,
+        32,
+        [0x3FF, 0xFFC00, 0x3FF00000, 0xC0000000],
+        Format::A2B10G10R10,
+    ),
+    (DDPF_RGB, 32, [0xFFFF, 0xFFFF0000, 0, 0], Format::G16R16),
+    (DDPF_LUMINANCE, 8, [0xFF, 0, 0, 0], Format::L8),
+    (DDPF_LUMINANCE, 16, [0xFFFF, 0, 0, 0], Format::L16),
+    (DDPF_LUMINANCE, 16, [0xFF, 0, 0, 0xFF00], Format::A8L8),
+    (DDPF_LUMINANCE, 8, [0xF, 0, 0, 0xF0], Format::A4L4),
+    (DDPF_ALPHA, 8, [0, 0, 0, 0xFF], Format::A8),
+    (DDPF_BUMPDUDV, 16, [0xFF, 0xFF00, 0, 0], Format::V8U8),
+    (
+        DDPF_BUMPDUDV,
+        32,
+        [0xFFFF, 0xFFFF0000, 0, 0],
+        Format::V16U16,
+    ),
+    (
+        DDPF_BUMPDUDV,
+        32,
+        [0xFF, 0xFF00, 0xFF0000, 0xFF000000],
+        Format::Q8W8V8U8,
+    ),
+    (
+        DDPF_BUMPDUDV,
+        32,
+        [0x3FF, 0xFFC00, 0x3FF00000, 0xC0000000],
+        Format::A2W10V10U10,
+    ),
+    (
+        DDPF_BUMPLUMINANCE,
+        16,
+        [0x1F, 0x3E0, 0xFC00, 0],
+        Format::L6V5U5,
+    ),
+    (
+        DDPF_BUMPLUMINANCE,
+        32,
+        [0xFF, 0xFF00, 0xFF0000, 0],
+        Format::X8L8V8U8,
+    ),
+];
+
+/// DXGI formats which have a Direct3D 9 equivalent, along with whether they are sRGB encoded.
+const DXGI_FORMATS: &[(u32, Format, bool)] = &[
+    (2, Format::A32B32G32R32F, false),
+    (10, Format::A16B16G16R16F, false),
+    (11, Format::A16B16G16R16, false),
+    (13, Format::Q16W16V16U16, false),
+    (16, Format::G32R32F, false),
+    (24, Format::A2B10G10R10, false),
+    (28, Format::A8B8G8R8, false),
+    (29, Format::A8B8G8R8, true),
+    (31, Format::Q8W8V8U8, false),
+    (34, Format::G16R16F, false),
+    (35, Format::G16R16, false),
+    (37, Format::V16U16, false),
+    (41, Format::R32F, false),
+    (51, Format::V8U8, false),
+    (54, Format::R16F, false),
+    (56, Format::L16, false),
+    (61, Format::L8, false),
+    (65, Format::A8, false),
+    (68, Format::R8g8B8g8, false),
+    (69, Format::G8r8G8b8, false),
+    (71, Format::DXT1, false),
+    (72, Format::DXT1, true),
+    (74, Format::DXT3, false),
+    (75, Format::DXT3, true),
+    (77, Format::DXT5, false),
+    (78, Format::DXT5, true),
+    (85, Format::R5G6B5, false),
+    (86, Format::A1R5G5B5, false),
+    (87, Format::A8R8G8B8, false),
+    (88, Format::X8R8G8B8, false),
+    (91, Format::A8R8G8B8, true),
+    (93, Format::X8R8G8B8, true),
+    (107, Format::YUY2, false),
+    (115, Format::A4R4G4B4, false),
+];
+
+/// Returns the [`Format`] described by a legacy (non-DX10) pixel format.
+pub fn from_pixel_format(pixel_format: &PixelFormat) -> Option<Format> {
+    if pixel_format.flags & DDPF_FOURCC != 0 {
+        return FOUR_CC_FORMATS
+            .iter()
+            .find(|(code, _)| *code == pixel_format.four_cc)
+            .map(|(_, format)| *format);
+    }
+
+    let class = pixel_format.flags
+        & (DDPF_RGB | DDPF_LUMINANCE | DDPF_ALPHA | DDPF_BUMPDUDV | DDPF_BUMPLUMINANCE | DDPF_YUV);
+    let masks = [
+        pixel_format.r_bit_mask,
+        pixel_format.g_bit_mask,
+        pixel_format.b_bit_mask,
+        if pixel_format.flags & (DDPF_ALPHAPIXELS | DDPF_ALPHA) != 0 {
+            pixel_format.a_bit_mask
+        } else {
+            0
+        },
+    ];
+
+    MASKED_FORMATS
+        .iter()
+        .find(|(flags, bit_count, format_masks, _)| {
+            *flags == class && *bit_count == pixel_format.rgb_bit_count && *format_masks == masks
+        })
+        .map(|(_, _, _, format)| *format)
+}
+
+/// Returns the legacy pixel format which describes `format`.
+pub fn to_pixel_format(format: Format) -> Option<PixelFormat> {
+    if let Some((code, _)) = FOUR_CC_FORMATS.iter().find(|(_, f)| *f == format) {
+        return Some(PixelFormat::with_four_cc(*code));
+    }
+
+    MASKED_FORMATS
+        .iter()
+        .find(|(_, _, _, f)| *f == format)
+        .map(|(flags, bit_count, masks, _)| {
+            let flags = if masks[3] != 0 && *flags != DDPF_ALPHA {
+                *flags | DDPF_ALPHAPIXELS
+            } else {
+                *flags
+            };
+            PixelFormat::with_masks(flags, *bit_count, *masks)
+        })
+}
+
+/// Returns the [`Format`] and sRGB flag for a DXGI format.
+pub fn from_dxgi(dxgi_format: u32) -> Option<(Format, bool)> {
+    DXGI_FORMATS
+        .iter()
+        .find(|(code, _, _)| *code == dxgi_format)
+        .map(|(_, format, srgb)| (*format, *srgb))
+}
+
+/// Returns the DXGI format for a [`Format`] with the given sRGB encoding.
+pub fn to_dxgi(format: Format, srgb: bool) -> Option<u32> {
+    DXGI_FORMATS
+        .iter()
+        .find(|(_, f, s)| *f == format && *s == srgb)
+        .map(|(code, _, _)| *code)
+}
@@ -0,0 +1,238 @@
+//! Provides the raw on-disk structures of a DDS file.
+
+use crate::dds::DdsError;
+
+/// The magic number at the start of every DDS file (`"DDS "`).
+pub const MAGIC: u32 = 0x2053_4444;
+
+pub const DDSD_CAPS: u32 = 0x1;
+pub const DDSD_HEIGHT: u32 = 0x2;
+pub const DDSD_WIDTH: u32 = 0x4;
+pub const DDSD_PITCH: u32 = 0x8;
+pub const DDSD_PIXELFORMAT: u32 = 0x1000;
+pub const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+pub const DDSD_LINEARSIZE: u32 = 0x80000;
+pub const DDSD_DEPTH: u32 = 0x800000;
+
+pub const DDPF_ALPHAPIXELS: u32 = 0x1;
+pub const DDPF_ALPHA: u32 = 0x2;
+pub const DDPF_FOURCC: u32 = 0x4;
+pub const DDPF_RGB: u32 = 0x40;
+pub const DDPF_YUV: u32 = 0x200;
+pub const DDPF_LUMINANCE: u32 = 0x20000;
+pub const DDPF_BUMPLUMINANCE: u32 = 0x40000;
+pub const DDPF_BUMPDUDV: u32 = 0x80000;
+
+pub const DDSCAPS_COMPLEX: u32 = 0x8;
+pub const DDSCAPS_TEXTURE: u32 = 0x1000;
+pub const DDSCAPS_MIPMAP: u32 = 0x400000;
+
+pub const DDSCAPS2_CUBEMAP: u32 = 0x200;
+pub const DDSCAPS2_CUBEMAP_ALLFACES: u32 = 0xFC00;
+pub const DDSCAPS2_VOLUME: u32 = 0x200000;
+
+pub const D3D10_RESOURCE_DIMENSION_TEXTURE2D: u32 = 3;
+pub const D3D10_RESOURCE_DIMENSION_TEXTURE3D: u32 = 4;
+pub const D3D10_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+/// Builds a four character code from its ASCII representation.
+pub const fn four_cc(code: &[u8; 4]) -> u32 {
+    u32::from_le_bytes(*code)
+}
+
+/// Represents the `DDS_PIXELFORMAT` structure.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PixelFormat {
+    pub flags: u32,
+    pub four_cc: u32,
+    pub rgb_bit_count: u32,
+    pub r_bit_mask: u32,
+    pub g_bit_mask: u32,
+    pub b_bit_mask: u32,
+    pub a_bit_mask: u32,
+}
+
+impl PixelFormat {
+    /// The size of the structure on disk, in bytes.
+    pub const SIZE: usize = 32;
+
+    /// Returns a pixel format described by a four character code.
+    pub const fn with_four_cc(four_cc: u32) -> Self {
+        Self {
+            flags: DDPF_FOURCC,
+            four_cc,
+            rgb_bit_count: 0,
+            r_bit_mask: 0,
+            g_bit_mask: 0,
+            b_bit_mask: 0,
+            a_bit_mask: 0,
+        }
+    }
+
+    /// Returns a pixel format described by its bit count and channel masks.
+    pub const fn with_masks(flags: u32, rgb_bit_count: u32, masks: [u32; 4]) -> Self {
+        Self {
+            flags,
+            four_cc: 0,
+            rgb_bit_count,
+            r_bit_mask: masks[0],
+            g_bit_mask: masks[1],
+            b_bit_mask: masks[2],
+            a_bit_mask: masks[3],
+        }
+    }
+
+    fn read(bytes: &[u8]) -> Result<Self, DdsError> {
+        if read_u32(bytes, 0) as usize != Self::SIZE {
+            return Err(DdsError::InvalidHeader("pixel format size is not 32"));
+        }
+
+        Ok(Self {
+            flags: read_u32(bytes, 4),
+            four_cc: read_u32(bytes, 8),
+            rgb_bit_count: read_u32(bytes, 12),
+            r_bit_mask: read_u32(bytes, 16),
+            g_bit_mask: read_u32(bytes, 20),
+            b_bit_mask: read_u32(bytes, 24),
+            a_bit_mask: read_u32(bytes, 28),
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        for value in [
+            Self::SIZE as u32,
+            self.flags,
+            self.four_cc,
+            self.rgb_bit_count,
+            self.r_bit_mask,
+            self.g_bit_mask,
+            self.b_bit_mask,
+            self.a_bit_mask,
+        ] {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+/// Represents the `DDS_HEADER` structure which follows the magic number.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Header {
+    pub flags: u32,
+    pub height: u32,
+    pub width: u32,
+    pub pitch_or_linear_size: u32,
+    pub depth: u32,
+    pub mip_map_count: u32,
+    pub pixel_format: PixelFormat,
+    pub caps: u32,
+    pub caps2: u32,
+    pub caps3: u32,
+    pub caps4: u32,
+}
+
+impl Header {
+    /// The size of the structure on disk, in bytes.
+    pub const SIZE: usize = 124;
+
+    /// Parses the header from the bytes immediately following the magic number.
+    pub fn read(bytes: &[u8]) -> Result<Self, DdsError> {
+        if bytes.len() < Self::SIZE {
+            return Err(DdsError::UnexpectedEof);
+        }
+
+        if read_u32(bytes, 0) as usize != Self::SIZE {
+            return Err(DdsError::InvalidHeader("header size is not 124"));
+        }
+
+        Ok(Self {
+            flags: read_u32(bytes, 4),
+            height: read_u32(bytes, 8),
+            width: read_u32(bytes, 12),
+            pitch_or_linear_size: read_u32(bytes, 16),
+            depth: read_u32(bytes, 20),
+            mip_map_count: read_u32(bytes, 24),
+            pixel_format: PixelFormat::read(&bytes[72..104])?,
+            caps: read_u32(bytes, 104),
+            caps2: read_u32(bytes, 108),
+            caps3: read_u32(bytes, 112),
+            caps4: read_u32(bytes, 116),
+        })
+    }
+
+    /// Appends the encoded header to `out`.
+    pub fn write(&self, out: &mut Vec<u8>) {
+        for value in [
+            Self::SIZE as u32,
+            self.flags,
+            self.height,
+            self.width,
+            self.pitch_or_linear_size,
+            self.depth,
+            self.mip_map_count,
+        ] {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+
+        // dwReserved1[11]
+        out.extend_from_slice(&[0; 44]);
+
+        self.pixel_format.write(out);
+
+        for value in [self.caps, self.caps2, self.caps3, self.caps4, 0] {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+/// Represents the `DDS_HEADER_DXT10` extension header, present when the pixel format's four
+/// character code is `DX10`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HeaderDx10 {
+    pub dxgi_format: u32,
+    pub resource_dimension: u32,
+    pub misc_flag: u32,
+    pub array_size: u32,
+    pub misc_flags2: u32,
+}
+
+impl HeaderDx10 {
+    /// The size of the structure on disk, in bytes.
+    pub const SIZE: usize = 20;
+
+    /// Parses the extension header from the bytes immediately following [`Header`].
+    pub fn read(bytes: &[u8]) -> Result<Self, DdsError> {
+        if bytes.len() < Self::SIZE {
+            return Err(DdsError::UnexpectedEof);
+        }
+
+        Ok(Self {
+            dxgi_format: read_u32(bytes, 0),
+            resource_dimension: read_u32(bytes, 4),
+            misc_flag: read_u32(bytes, 8),
+            array_size: read_u32(bytes, 12),
+            misc_flags2: read_u32(bytes, 16),
+        })
+    }
+
+    /// Appends the encoded extension header to `out`.
+    pub fn write(&self, out: &mut Vec<u8>) {
+        for value in [
+            self.dxgi_format,
+            self.resource_dimension,
+            self.misc_flag,
+            self.array_size,
+            self.misc_flags2,
+        ] {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
@@ -0,0 +1,581 @@
+//! Provides a platform-independent reader and writer for DirectDraw Surface (`.dds`) files.
+//!
+//! Parsing and encoding never touch Direct3D, so they work on any platform. A parsed [`Dds`] can
+//! then be uploaded into a [`Texture`](crate::std::interfaces::Texture),
+//! [`CubeTexture`](crate::std::interfaces::CubeTexture) or
+//! [`VolumeTexture`](crate::std::interfaces::VolumeTexture).
+//!
+//! # Usage
+//!
+//! ```rs
+//! let dds = Dds::from_bytes(&std::fs::read("grass.dds")?)?;
+//! let texture = dds.create_texture(&device, Usage::default(), Pool::Managed)?;
+//! ```
+
+pub(crate) mod format;
+pub mod header;
+mod upload;
+
+use std::io::{Read, Write};
+
+use crate::{
+    dds::header::{
+        Header, HeaderDx10, D3D10_RESOURCE_DIMENSION_TEXTURE2D, D3D10_RESOURCE_DIMENSION_TEXTURE3D,
+        D3D10_RESOURCE_MISC_TEXTURECUBE, DDPF_FOURCC, DDSCAPS2_CUBEMAP, DDSCAPS2_CUBEMAP_ALLFACES,
+        DDSCAPS2_VOLUME, DDSCAPS_COMPLEX, DDSCAPS_MIPMAP, DDSCAPS_TEXTURE, DDSD_CAPS, DDSD_DEPTH,
+        DDSD_HEIGHT, DDSD_LINEARSIZE, DDSD_MIPMAPCOUNT, DDSD_PITCH, DDSD_PIXELFORMAT, DDSD_WIDTH,
+        MAGIC,
+    },
+    error::WindowsError,
+    std::types::Format,
+};
+
+/// Represents an error which occurred while reading, writing or uploading a DDS file.
+#[derive(Debug, thiserror::Error)]
+pub enum DdsError {
+    #[error("IO Error: {from}")]
+    Io {
+        #[from]
+        from: std::io::Error,
+    },
+
+    #[error("Windows Error: {from}")]
+    Windows {
+        #[from]
+        from: WindowsError,
+    },
+
+    #[error("file does not start with the DDS magic number")]
+    InvalidMagic,
+
+    #[error("invalid header: {0}")]
+    InvalidHeader(&'static str),
+
+    #[error("file ended before all surfaces were read")]
+    UnexpectedEof,
+
+    #[error("unsupported format: {0}")]
+    UnsupportedFormat(String),
+
+    #[error("expected a {expected:?} but the file contains a {found:?}")]
+    WrongKind { expected: DdsKind, found: DdsKind },
+
+    #[error("the file has no surface for face {face} level {level}")]
+    MissingSurface { face: u32, level: u32 },
+
+    #[error("invalid surface: {0}")]
+    InvalidSurface(&'static str),
+}
+
+/// Represents the kind of resource stored in a DDS file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DdsKind {
+    Texture,
+    CubeTexture,
+    VolumeTexture,
+}
+
+/// Represents a single mip level of a single face within a DDS file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DdsSurface {
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    /// The row pitch of `data`, in bytes. A row of a block compressed format is a row of blocks.
+    pub row_pitch: usize,
+    /// The number of rows in a single depth slice.
+    pub rows: usize,
+    pub data: Vec<u8>,
+}
+
+impl DdsSurface {
+    /// Returns the number of bytes in a single depth slice.
+    pub fn slice_pitch(&self) -> usize {
+        self.row_pitch * self.rows
+    }
+
+    /// Returns the number of bytes in all depth slices, or an error if it overflows a [`usize`].
+    fn size(&self) -> Result<usize, DdsError> {
+        self.row_pitch
+            .checked_mul(self.rows)
+            .and_then(|slice_pitch| slice_pitch.checked_mul(self.depth as usize))
+            .ok_or(DdsError::InvalidSurface("surface is too large"))
+    }
+}
+
+/// Represents a parsed DDS file.
+///
+/// Surfaces are stored face-major: every mip level of the first face, followed by every mip level
+/// of the next face. Volume textures store each mip level as a single surface containing all of
+/// its depth slices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dds {
+    pub kind: DdsKind,
+    pub format: Format,
+    /// Whether the data is sRGB encoded. Only DX10 headers can express this.
+    pub srgb: bool,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    pub levels: u32,
+    pub surfaces: Vec<DdsSurface>,
+}
+
+impl Dds {
+    /// Returns a new, zero-filled [`Dds`] with a full set of surfaces for the given dimensions.
+    pub fn new(
+        kind: DdsKind,
+        format: Format,
+        width: u32,
+        height: u32,
+        depth: u32,
+        levels: u32,
+    ) -> Result<Self, DdsError> {
+        if width == 0 || height == 0 {
+            return Err(DdsError::InvalidHeader("width and height must be non-zero"));
+        }
+
+        let depth = if kind == DdsKind::VolumeTexture {
+            depth.max(1)
+        } else {
+            1
+        };
+
+        let mut dds = Self {
+            kind,
+            format,
+            srgb: false,
+            width,
+            height,
+            depth,
+            levels: levels.max(1),
+            surfaces: Vec::new(),
+        };
+        dds.check_levels()?;
+
+        for _ in 0..dds.faces() {
+            for level in 0..dds.levels {
+                let mut surface = dds.empty_surface(level)?;
+                surface.data = vec![0; surface.size()?];
+                dds.surfaces.push(surface);
+            }
+        }
+
+        Ok(dds)
+    }
+
+    /// Parses a DDS file from a reader.
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, DdsError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Parses a DDS file from its bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DdsError> {
+        if bytes.len() < 4 {
+            return Err(DdsError::UnexpectedEof);
+        }
+
+        if u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) != MAGIC {
+            return Err(DdsError::InvalidMagic);
+        }
+
+        let header = Header::read(&bytes[4..])?;
+        let mut offset = 4 + Header::SIZE;
+
+        if header.width == 0 || header.height == 0 {
+            return Err(DdsError::InvalidHeader("width and height must be non-zero"));
+        }
+
+        let levels = if header.flags & DDSD_MIPMAPCOUNT != 0 {
+            header.mip_map_count.max(1)
+        } else {
+            1
+        };
+
+        let (kind, format, srgb, depth) = if header.pixel_format.flags & DDPF_FOURCC != 0
+            && header.pixel_format.four_cc == format::FOURCC_DX10
+        {
+            let dx10 = HeaderDx10::read(&bytes[offset..])?;
+            offset += HeaderDx10::SIZE;
+
+            let (format, srgb) = format::from_dxgi(dx10.dxgi_format).ok_or_else(|| {
+                DdsError::UnsupportedFormat(format!("DXGI format {}", dx10.dxgi_format))
+            })?;
+
+            if dx10.array_size > 1 {
+                return Err(DdsError::UnsupportedFormat(
+                    "texture arrays are not supported by Direct3D 9".to_string(),
+                ));
+            }
+
+            match dx10.resource_dimension {
+                D3D10_RESOURCE_DIMENSION_TEXTURE2D
+                    if dx10.misc_flag & D3D10_RESOURCE_MISC_TEXTURECUBE != 0 =>
+                {
+                    (DdsKind::CubeTexture, format, srgb, 1)
+                }
+                D3D10_RESOURCE_DIMENSION_TEXTURE2D => (DdsKind::Texture, format, srgb, 1),
+                D3D10_RESOURCE_DIMENSION_TEXTURE3D => {
+                    (DdsKind::VolumeTexture, format, srgb, header.depth.max(1))
+                }
+                _ => {
+                    return Err(DdsError::InvalidHeader(
+                        "unsupported DX10 resource dimension",
+                    ))
+                }
+            }
+        } else {
+            let format = format::from_pixel_format(&header.pixel_format)
+                .ok_or_else(|| DdsError::UnsupportedFormat(format!("{:?}", header.pixel_format)))?;
+
+            if header.caps2 & DDSCAPS2_CUBEMAP != 0 {
+                if header.caps2 & DDSCAPS2_CUBEMAP_ALLFACES != DDSCAPS2_CUBEMAP_ALLFACES {
+                    return Err(DdsError::UnsupportedFormat(
+                        "cube maps must contain all six faces".to_string(),
+                    ));
+                }
+                (DdsKind::CubeTexture, format, false, 1)
+            } else if header.caps2 & DDSCAPS2_VOLUME != 0 {
+                (DdsKind::VolumeTexture, format, false, header.depth.max(1))
+            } else {
+                (DdsKind::Texture, format, false, 1)
+            }
+        };
+
+        let mut dds = Self {
+            kind,
+            format,
+            srgb,
+            width: header.width,
+            height: header.height,
+            depth,
+            levels,
+            surfaces: Vec::new(),
+        };
+        dds.check_levels()?;
+
+        for _ in 0..dds.faces() {
+            for level in 0..levels {
+                let mut surface = dds.empty_surface(level)?;
+                let end = offset
+                    .checked_add(surface.size()?)
+                    .ok_or(DdsError::UnexpectedEof)?;
+
+                let data = bytes.get(offset..end).ok_or(DdsError::UnexpectedEof)?;
+                surface.data = data.to_vec();
+                offset = end;
+
+                dds.surfaces.push(surface);
+            }
+        }
+
+        Ok(dds)
+    }
+
+    /// Encodes the file and writes it into a writer.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), DdsError> {
+        writer.write_all(&self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Encodes the file into its bytes.
+    ///
+    /// A legacy header is written unless the data is sRGB encoded, in which case a DX10 header is
+    /// required to preserve that information.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DdsError> {
+        let top = self.empty_surface(0)?;
+
+        let mut header = Header {
+            flags: DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT,
+            height: self.height,
+            width: self.width,
+            caps: DDSCAPS_TEXTURE,
+            ..Default::default()
+        };
+
+        if self.format.info().is_some_and(|info| info.is_compressed()) {
+            header.flags |= DDSD_LINEARSIZE;
+            header.pitch_or_linear_size = top.slice_pitch() as u32;
+        } else {
+            header.flags |= DDSD_PITCH;
+            header.pitch_or_linear_size = top.row_pitch as u32;
+        }
+
+        if self.levels > 1 {
+            header.flags |= DDSD_MIPMAPCOUNT;
+            header.mip_map_count = self.levels;
+            header.caps |= DDSCAPS_COMPLEX | DDSCAPS_MIPMAP;
+        }
+
+        match self.kind {
+            DdsKind::Texture => {}
+            DdsKind::CubeTexture => {
+                header.caps |= DDSCAPS_COMPLEX;
+                header.caps2 |= DDSCAPS2_CUBEMAP | DDSCAPS2_CUBEMAP_ALLFACES;
+            }
+            DdsKind::VolumeTexture => {
+                header.flags |= DDSD_DEPTH;
+                header.depth = self.depth;
+                header.caps |= DDSCAPS_COMPLEX;
+                header.caps2 |= DDSCAPS2_VOLUME;
+            }
+        }
+
+        let dx10 = if self.srgb {
+            let dxgi_format = format::to_dxgi(self.format, true)
+                .ok_or_else(|| DdsError::UnsupportedFormat(format!("sRGB {:?}", self.format)))?;
+
+            header.pixel_format = header::PixelFormat::with_four_cc(format::FOURCC_DX10);
+
+            Some(HeaderDx10 {
+                dxgi_format,
+                resource_dimension: match self.kind {
+                    DdsKind::VolumeTexture => D3D10_RESOURCE_DIMENSION_TEXTURE3D,
+                    _ => D3D10_RESOURCE_DIMENSION_TEXTURE2D,
+                },
+                misc_flag: match self.kind {
+                    DdsKind::CubeTexture => D3D10_RESOURCE_MISC_TEXTURECUBE,
+                    _ => 0,
+                },
+                array_size: 1,
+                misc_flags2: 0,
+            })
+        } else {
+            header.pixel_format = format::to_pixel_format(self.format)
+                .ok_or_else(|| DdsError::UnsupportedFormat(format!("{:?}", self.format)))?;
+            None
+        };
+
+        if self.surfaces.len() != (self.faces() * self.levels) as usize {
+            return Err(DdsError::InvalidHeader(
+                "surface count does not match faces and levels",
+            ));
+        }
+
+        let mut out = Vec::new();
+        out.extend_from_slice(&MAGIC.to_le_bytes());
+        header.write(&mut out);
+        if let Some(dx10) = dx10 {
+            dx10.write(&mut out);
+        }
+
+        for (index, surface) in self.surfaces.iter().enumerate() {
+            let expected = self.empty_surface(index as u32 % self.levels)?;
+            if surface.data.len() != expected.size()? {
+                return Err(DdsError::InvalidHeader(
+                    "surface data does not match its dimensions",
+                ));
+            }
+
+            out.extend_from_slice(&surface.data);
+        }
+
+        Ok(out)
+    }
+
+    /// Returns the number of faces stored in the file (six for cube textures, otherwise one).
+    pub fn faces(&self) -> u32 {
+        match self.kind {
+            DdsKind::CubeTexture => 6,
+            _ => 1,
+        }
+    }
+
+    /// Returns the surface for a face and mip level.
+    pub fn surface(&self, face: u32, level: u32) -> Option<&DdsSurface> {
+        if face >= self.faces() || level >= self.levels {
+            return None;
+        }
+
+        self.surfaces.get((face * self.levels + level) as usize)
+    }
+
+    /// Returns a mutable reference to the surface for a face and mip level.
+    pub fn surface_mut(&mut self, face: u32, level: u32) -> Option<&mut DdsSurface> {
+        if face >= self.faces() || level >= self.levels {
+            return None;
+        }
+
+        self.surfaces.get_mut((face * self.levels + level) as usize)
+    }
+
+    /// Returns the largest number of mip levels which the dimensions can hold.
+    pub fn max_levels(&self) -> u32 {
+        32 - self.width.max(self.height).max(self.depth).leading_zeros()
+    }
+
+    fn check_levels(&self) -> Result<(), DdsError> {
+        if self.levels > self.max_levels() {
+            return Err(DdsError::InvalidHeader(
+                "mip level count exceeds the dimensions",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn empty_surface(&self, level: u32) -> Result<DdsSurface, DdsError> {
+        let width = (self.width >> level).max(1);
+        let height = (self.height >> level).max(1);
+        let depth = (self.depth >> level).max(1);
+
+        let (row_pitch, rows) = self
+            .format
+            .info()
+            .map(|info| info.surface_layout(width, height))
+            .ok_or_else(|| DdsError::UnsupportedFormat(format!("{:?}", self.format)))?;
+
+        Ok(DdsSurface {
+            width,
+            height,
+            depth,
+            row_pitch,
+            rows,
+            data: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(
+        kind: DdsKind,
+        format: Format,
+        width: u32,
+        height: u32,
+        depth: u32,
+        levels: u32,
+    ) -> Dds {
+        let mut dds = Dds::new(kind, format, width, height, depth, levels).unwrap();
+        let mut value = 0u8;
+        for byte in dds
+            .surfaces
+            .iter_mut()
+            .flat_map(|surface| surface.data.iter_mut())
+        {
+            *byte = value;
+            value = value.wrapping_add(7);
+        }
+        dds
+    }
+
+    fn patch_u32(bytes: &mut [u8], offset: usize, value: u32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    #[test]
+    fn round_trips_every_kind() {
+        for (kind, format, width, height, depth, levels) in [
+            (DdsKind::Texture, Format::A8R8G8B8, 13, 7, 1, 4),
+            (DdsKind::Texture, Format::DXT1, 16, 16, 1, 5),
+            (DdsKind::Texture, Format::DXT5, 5, 3, 1, 3),
+            (DdsKind::CubeTexture, Format::R5G6B5, 8, 8, 1, 4),
+            (DdsKind::VolumeTexture, Format::L8, 8, 4, 4, 4),
+            (DdsKind::Texture, Format::A32B32G32R32F, 3, 3, 1, 1),
+            (DdsKind::Texture, Format::YUY2, 3, 3, 1, 1),
+            (DdsKind::Texture, Format::A8L8, 3, 3, 1, 1),
+            (DdsKind::Texture, Format::V8U8, 3, 3, 1, 1),
+        ] {
+            let dds = filled(kind, format, width, height, depth, levels);
+            let parsed = Dds::from_bytes(&dds.to_bytes().unwrap()).unwrap();
+            assert_eq!(parsed, dds, "{format:?}");
+        }
+    }
+
+    #[test]
+    fn round_trips_srgb_through_dx10_header() {
+        for format in [Format::DXT1, Format::A8R8G8B8] {
+            let mut dds = filled(DdsKind::Texture, format, 8, 8, 1, 2);
+            dds.srgb = true;
+
+            let parsed = Dds::from_bytes(&dds.to_bytes().unwrap()).unwrap();
+            assert_eq!(parsed, dds, "{format:?}");
+        }
+    }
+
+    #[test]
+    fn computes_surface_sizes() {
+        let dds = Dds::new(DdsKind::Texture, Format::DXT1, 16, 16, 1, 5).unwrap();
+        let sizes: Vec<usize> = dds
+            .surfaces
+            .iter()
+            .map(|surface| surface.data.len())
+            .collect();
+        assert_eq!(sizes, [128, 32, 8, 8, 8]);
+
+        let dds = Dds::new(DdsKind::VolumeTexture, Format::A8R8G8B8, 4, 4, 4, 3).unwrap();
+        let sizes: Vec<usize> = dds
+            .surfaces
+            .iter()
+            .map(|surface| surface.data.len())
+            .collect();
+        assert_eq!(sizes, [256, 32, 4]);
+        assert_eq!(dds.to_bytes().unwrap().len(), 128 + 292);
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let bytes = filled(DdsKind::Texture, Format::A8R8G8B8, 4, 4, 1, 3)
+            .to_bytes()
+            .unwrap();
+
+        assert!(matches!(
+            Dds::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DdsError::UnexpectedEof)
+        ));
+        assert!(matches!(
+            Dds::from_bytes(b"XXXX"),
+            Err(DdsError::InvalidMagic)
+        ));
+        assert!(matches!(
+            Dds::from_bytes(b"DD"),
+            Err(DdsError::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn rejects_too_many_levels() {
+        assert_eq!(
+            Dds::new(DdsKind::Texture, Format::L8, 16, 4, 1, 5)
+                .unwrap()
+                .levels,
+            5
+        );
+        assert!(matches!(
+            Dds::new(DdsKind::Texture, Format::L8, 16, 4, 1, 6),
+            Err(DdsError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            Dds::new(DdsKind::Texture, Format::L8, 0, 4, 1, 1),
+            Err(DdsError::InvalidHeader(_))
+        ));
+
+        // The mip map count lives at offset 28, after the magic number and six header fields.
+        let mut bytes = filled(DdsKind::Texture, Format::L8, 16, 4, 1, 5)
+            .to_bytes()
+            .unwrap();
+        patch_u32(&mut bytes, 28, 64);
+        assert!(matches!(
+            Dds::from_bytes(&bytes),
+            Err(DdsError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_surfaces_too_large_to_address() {
+        let mut bytes = filled(DdsKind::Texture, Format::A32B32G32R32F, 1, 1, 1, 1)
+            .to_bytes()
+            .unwrap();
+        patch_u32(&mut bytes, 12, u32::MAX);
+        patch_u32(&mut bytes, 16, u32::MAX);
+
+        assert!(matches!(
+            Dds::from_bytes(&bytes),
+            Err(DdsError::InvalidSurface(_))
+        ));
+    }
+}
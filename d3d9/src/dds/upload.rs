@@ -0,0 +1,265 @@
+//! Uploads a parsed [`Dds`] into Direct3D textures.
+
+use crate::dds::DdsError;
+#[cfg(windows)]
+use crate::{
+    dds::{Dds, DdsKind, DdsSurface},
+    std::{
+        interfaces::{CubeTexture, Device, Surface, Texture, VolumeTexture},
+        types::{CubeFace, LockFlags, Pool, Usage},
+    },
+};
+
+#[cfg(windows)]
+impl Dds {
+    /// Creates a [`Texture`] containing every mip level of the file.
+    ///
+    /// [`Pool::Default`] textures cannot be locked, so they are filled through a system memory
+    /// staging texture and [`Device::update_texture`].
+    pub fn create_texture(
+        &self,
+        device: &Device,
+        usage: Usage,
+        pool: Pool,
+    ) -> Result<Texture, DdsError> {
+        self.expect_kind(DdsKind::Texture)?;
+
+        let create = |pool: Pool, usage: Usage| {
+            device.create_texture(
+                self.width,
+                self.height,
+                self.levels,
+                usage,
+                self.format,
+                pool,
+            )
+        };
+
+        let fill = |texture: &Texture| -> Result<(), DdsError> {
+            for level in 0..self.levels {
+                let mut surface = texture.get_surface_level(level)?;
+                upload_surface(&mut surface, self.expect_surface(0, level)?)?;
+            }
+            Ok(())
+        };
+
+        let texture = create(pool, usage)?;
+        if let Pool::Default = pool {
+            let staging = create(Pool::SystemMem, Usage::default())?;
+            fill(&staging)?;
+            device.update_texture(&staging, &texture)?;
+        } else {
+            fill(&texture)?;
+        }
+
+        Ok(texture)
+    }
+
+    /// Creates a [`CubeTexture`] containing every face and mip level of the file.
+    ///
+    /// [`Pool::Default`] textures are filled through a system memory staging texture.
+    pub fn create_cube_texture(
+        &self,
+        device: &Device,
+        usage: Usage,
+        pool: Pool,
+    ) -> Result<CubeTexture, DdsError> {
+        self.expect_kind(DdsKind::CubeTexture)?;
+
+        let create = |pool: Pool, usage: Usage| {
+            device.create_cube_texture(self.width, self.levels, usage.0, self.format, pool)
+        };
+
+        let fill = |texture: &CubeTexture| -> Result<(), DdsError> {
+            for (index, face) in CubeFace::ALL.into_iter().enumerate() {
+                for level in 0..self.levels {
+                    let mut surface = texture.get_cube_map_surface(face, level)?;
+                    upload_surface(&mut surface, self.expect_surface(index as u32, level)?)?;
+                }
+            }
+            Ok(())
+        };
+
+        let texture = create(pool, usage)?;
+        if let Pool::Default = pool {
+            let staging = create(Pool::SystemMem, Usage::default())?;
+            fill(&staging)?;
+            device.update_texture(&staging, &texture)?;
+        } else {
+            fill(&texture)?;
+        }
+
+        Ok(texture)
+    }
+
+    /// Creates a [`VolumeTexture`] containing every mip level of the file.
+    ///
+    /// [`Pool::Default`] textures are filled through a system memory staging texture.
+    pub fn create_volume_texture(
+        &self,
+        device: &Device,
+        usage: Usage,
+        pool: Pool,
+    ) -> Result<VolumeTexture, DdsError> {
+        self.expect_kind(DdsKind::VolumeTexture)?;
+
+        let create = |pool: Pool, usage: Usage| {
+            device.create_volume_texture(
+                self.width,
+                self.height,
+                self.depth,
+                self.levels,
+                usage,
+                self.format,
+                pool,
+            )
+        };
+
+        let fill = |texture: &mut VolumeTexture| -> Result<(), DdsError> {
+            for level in 0..self.levels {
+                let data = self.expect_surface(0, level)?;
+                let rows = data
+                    .rows
+                    .checked_mul(data.depth as usize)
+                    .ok_or(DdsError::InvalidSurface("surface is too large"))?;
+
+                // The slices of the file are tightly packed, so they can be copied as one run of
+                // rows.
+                let mut lock = texture.lock_box::<u8>(level, LockFlags::default())?;
+                copy_rows(lock.rows_mut(), &data.data, data.row_pitch, rows)?;
+                lock.unlock()?;
+            }
+            Ok(())
+        };
+
+        let mut texture = create(pool, usage)?;
+        if let Pool::Default = pool {
+            let mut staging = create(Pool::SystemMem, Usage::default())?;
+            fill(&mut staging)?;
+            device.update_texture(&staging, &texture)?;
+        } else {
+            fill(&mut texture)?;
+        }
+
+        Ok(texture)
+    }
+
+    fn expect_kind(&self, expected: DdsKind) -> Result<(), DdsError> {
+        if self.kind != expected {
+            return Err(DdsError::WrongKind {
+                expected,
+                found: self.kind,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn expect_surface(&self, face: u32, level: u32) -> Result<&DdsSurface, DdsError> {
+        self.surface(face, level)
+            .ok_or(DdsError::MissingSurface { face, level })
+    }
+}
+
+#[cfg(windows)]
+fn upload_surface(surface: &mut Surface, data: &DdsSurface) -> Result<(), DdsError> {
+    let mut lock = surface.lock_rect::<u8>(LockFlags::default())?;
+    copy_rows(lock.rows_mut(), &data.data, data.row_pitch, data.rows)?;
+    lock.unlock()?;
+
+    Ok(())
+}
+
+/// Copies `rows` tightly packed rows of `source` into the rows of a locked surface.
+///
+/// Fails rather than writing out of bounds if the source is shorter than `rows` rows, or the
+/// destination has fewer or narrower rows.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn copy_rows<'a>(
+    destination: impl ExactSizeIterator<Item = &'a mut [u8]>,
+    source: &[u8],
+    source_pitch: usize,
+    rows: usize,
+) -> Result<(), DdsError> {
+    if rows == 0 || source_pitch == 0 {
+        return Ok(());
+    }
+
+    if destination.len() < rows {
+        return Err(DdsError::InvalidSurface(
+            "locked surface has fewer rows than the data",
+        ));
+    }
+
+    let source_size = source_pitch
+        .checked_mul(rows)
+        .ok_or(DdsError::InvalidSurface("surface is too large"))?;
+    if source.len() < source_size {
+        return Err(DdsError::InvalidSurface(
+            "surface data is shorter than its rows",
+        ));
+    }
+
+    for (destination, source) in destination
+        .zip(source.chunks_exact(source_pitch))
+        .take(rows)
+    {
+        destination
+            .get_mut(..source_pitch)
+            .ok_or(DdsError::InvalidSurface("row pitch exceeds the locked row"))?
+            .copy_from_slice(source);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows_of(destination: &mut [u8], width: usize) -> impl ExactSizeIterator<Item = &mut [u8]> {
+        destination.chunks_exact_mut(width)
+    }
+
+    #[test]
+    fn copies_rows_into_wider_rows() {
+        let source = [1, 2, 3, 4, 5, 6];
+        let mut destination = [0; 8];
+        copy_rows(rows_of(&mut destination, 4), &source, 3, 2).unwrap();
+
+        assert_eq!(destination, [1, 2, 3, 0, 4, 5, 6, 0]);
+    }
+
+    #[test]
+    fn copies_only_the_requested_rows() {
+        let mut destination = [0; 9];
+        copy_rows(rows_of(&mut destination, 3), &[1, 2, 3, 4, 5, 6, 7], 3, 2).unwrap();
+
+        assert_eq!(destination, [1, 2, 3, 4, 5, 6, 0, 0, 0]);
+    }
+
+    #[test]
+    fn rejects_rows_which_do_not_fit() {
+        let mut destination = [0; 8];
+
+        // The source is shorter than two rows.
+        assert!(matches!(
+            copy_rows(rows_of(&mut destination, 4), &[1, 2, 3, 4, 5], 3, 2),
+            Err(DdsError::InvalidSurface(_))
+        ));
+
+        // The source rows are wider than the destination rows.
+        assert!(matches!(
+            copy_rows(rows_of(&mut destination, 2), &[1, 2, 3, 4, 5, 6], 3, 2),
+            Err(DdsError::InvalidSurface(_))
+        ));
+
+        // The destination holds fewer rows than the source.
+        assert!(matches!(
+            copy_rows(rows_of(&mut destination, 4), &[0; 9], 3, 3),
+            Err(DdsError::InvalidSurface(_))
+        ));
+
+        assert_eq!(destination, [0; 8]);
+    }
+}
@@ -0,0 +1,294 @@
+//! Provides the table of known Direct3D, D3DX and COM error codes.
+//!
+//! Nothing here calls into Windows, so codes can be decoded on any platform.
+
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+};
+
+use crate::sys::HRESULT;
+
+macro_rules! error_codes {
+    ($($variant:ident = $value:literal => $name:literal, $description:literal;)*) => {
+        /// Represents a known error code returned by Direct3D, D3DX or COM.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[repr(i32)]
+        pub enum ErrorCode {
+            $(
+                #[doc = $description]
+                $variant = $value as i32,
+            )*
+        }
+
+        impl ErrorCode {
+            /// Every known error code.
+            pub const ALL: &'static [Self] = &[$(Self::$variant),*];
+
+            /// Looks up the error code for the given [`HRESULT`].
+            pub fn from_hresult(hresult: HRESULT) -> Option<Self> {
+                match hresult as u32 {
+                    $($value => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Returns the name of the constant, for example `D3DERR_DEVICELOST`.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+
+            /// Returns a short description of the error.
+            pub fn description(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $description,)*
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
+    WrongTextureFormat = 0x88760818u32 => "D3DERR_WRONGTEXTUREFORMAT",
+        "The pixel format of the texture surface is not valid.";
+    UnsupportedColorOperation = 0x88760819u32 => "D3DERR_UNSUPPORTEDCOLOROPERATION",
+        "The device does not support a specified texture-blending operation for color values.";
+    UnsupportedColorArg = 0x8876081Au32 => "D3DERR_UNSUPPORTEDCOLORARG",
+        "The device does not support a specified texture-blending argument for color values.";
+    UnsupportedAlphaOperation = 0x8876081Bu32 => "D3DERR_UNSUPPORTEDALPHAOPERATION",
+        "The device does not support a specified texture-blending operation for the alpha channel.";
+    UnsupportedAlphaArg = 0x8876081Cu32 => "D3DERR_UNSUPPORTEDALPHAARG",
+        "The device does not support a specified texture-blending argument for the alpha channel.";
+    TooManyOperations = 0x8876081Du32 => "D3DERR_TOOMANYOPERATIONS",
+        "The application is requesting more texture-filtering operations than the device supports.";
+    ConflictingTextureFilter = 0x8876081Eu32 => "D3DERR_CONFLICTINGTEXTUREFILTER",
+        "The current texture filters cannot be used together.";
+    UnsupportedFactorValue = 0x8876081Fu32 => "D3DERR_UNSUPPORTEDFACTORVALUE",
+        "The device does not support the specified texture factor value.";
+    ConflictingRenderState = 0x88760821u32 => "D3DERR_CONFLICTINGRENDERSTATE",
+        "The currently set render states cannot be used together.";
+    UnsupportedTextureFilter = 0x88760822u32 => "D3DERR_UNSUPPORTEDTEXTUREFILTER",
+        "The device does not support the specified texture filter.";
+    ConflictingTexturePalette = 0x88760826u32 => "D3DERR_CONFLICTINGTEXTUREPALETTE",
+        "The current textures cannot be used simultaneously.";
+    DriverInternalError = 0x88760827u32 => "D3DERR_DRIVERINTERNALERROR",
+        "An internal driver error occurred.";
+    NotFound = 0x88760866u32 => "D3DERR_NOTFOUND",
+        "The requested item was not found.";
+    MoreData = 0x88760867u32 => "D3DERR_MOREDATA",
+        "There is more data available than the specified buffer size can hold.";
+    DeviceLost = 0x88760868u32 => "D3DERR_DEVICELOST",
+        "The device has been lost but cannot be reset at this time.";
+    DeviceNotReset = 0x88760869u32 => "D3DERR_DEVICENOTRESET",
+        "The device has been lost but can be reset at this time.";
+    NotAvailable = 0x8876086Au32 => "D3DERR_NOTAVAILABLE",
+        "This device does not support the queried technique.";
+    OutOfVideoMemory = 0x8876017Cu32 => "D3DERR_OUTOFVIDEOMEMORY",
+        "Direct3D does not have enough display memory to perform the operation.";
+    InvalidDevice = 0x8876086Bu32 => "D3DERR_INVALIDDEVICE",
+        "The requested device type is not valid.";
+    InvalidCall = 0x8876086Cu32 => "D3DERR_INVALIDCALL",
+        "The method call is invalid, for example a parameter may have an invalid value.";
+    DriverInvalidCall = 0x8876086Du32 => "D3DERR_DRIVERINVALIDCALL",
+        "The driver rejected the call.";
+    WasStillDrawing = 0x8876021Cu32 => "D3DERR_WASSTILLDRAWING",
+        "The previous blit operation that is transferring information to or from this surface is incomplete.";
+    DeviceRemoved = 0x88760870u32 => "D3DERR_DEVICEREMOVED",
+        "The hardware adapter has been removed.";
+    UnsupportedOverlay = 0x8876087Bu32 => "D3DERR_UNSUPPORTEDOVERLAY",
+        "The device does not support overlay for the specified size or display mode.";
+    UnsupportedOverlayFormat = 0x8876087Cu32 => "D3DERR_UNSUPPORTEDOVERLAYFORMAT",
+        "The device does not support overlay for the specified surface format.";
+    CannotProtectContent = 0x8876087Du32 => "D3DERR_CANNOTPROTECTCONTENT",
+        "The specified content cannot be protected.";
+    DeviceHung = 0x88760874u32 => "D3DERR_DEVICEHUNG",
+        "The device stopped responding and has to be recreated.";
+    UnsupportedCrypto = 0x8876087Eu32 => "D3DERR_UNSUPPORTEDCRYPTO",
+        "The specified cryptographic algorithm is not supported.";
+    PresentStatisticsDisjoint = 0x88760884u32 => "D3DERR_PRESENT_STATISTICS_DISJOINT",
+        "The present statistics have no orderly sequence.";
+    CannotModifyIndexBuffer = 0x88760B54u32 => "D3DXERR_CANNOTMODIFYINDEXBUFFER",
+        "The index buffer cannot be modified.";
+    InvalidMesh = 0x88760B55u32 => "D3DXERR_INVALIDMESH",
+        "The mesh is invalid.";
+    CannotAttrSort = 0x88760B56u32 => "D3DXERR_CANNOTATTRSORT",
+        "Attribute sort is not supported as an optimization technique.";
+    SkinningNotSupported = 0x88760B57u32 => "D3DXERR_SKINNINGNOTSUPPORTED",
+        "Skinning is not supported.";
+    TooManyInfluences = 0x88760B58u32 => "D3DXERR_TOOMANYINFLUENCES",
+        "Too many influences specified.";
+    InvalidData = 0x88760B59u32 => "D3DXERR_INVALIDDATA",
+        "The data is invalid.";
+    LoadedMeshHasNoData = 0x88760B5Au32 => "D3DXERR_LOADEDMESHASNODATA",
+        "The mesh has no data.";
+    DuplicateNamedFragment = 0x88760B5Bu32 => "D3DXERR_DUPLICATENAMEDFRAGMENT",
+        "A fragment with that name already exists.";
+    CannotRemoveLastItem = 0x88760B5Cu32 => "D3DXERR_CANNOTREMOVELASTITEM",
+        "The last item cannot be deleted.";
+    NotImplemented = 0x80004001u32 => "E_NOTIMPL",
+        "The method is not implemented.";
+    NoInterface = 0x80004002u32 => "E_NOINTERFACE",
+        "The requested interface is not supported.";
+    Fail = 0x80004005u32 => "E_FAIL",
+        "An undetermined error occurred.";
+    OutOfMemory = 0x8007000Eu32 => "E_OUTOFMEMORY",
+        "Direct3D could not allocate sufficient memory to complete the call.";
+    InvalidArg = 0x80070057u32 => "E_INVALIDARG",
+        "An invalid parameter was passed to the returning function.";
+}
+
+impl ErrorCode {
+    pub fn hresult(self) -> HRESULT {
+        self as HRESULT
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Extracts the name of the method called by an expression passed to [`crate::check_hresult`], for
+/// example `Present` from `self.inner.Present(...)`.
+#[doc(hidden)]
+pub fn method_name(expression: &'static str) -> &'static str {
+    let call = match expression.find('(') {
+        Some(end) => &expression[..end],
+        None => expression,
+    };
+
+    let call = call.trim_end();
+    match call.rfind(|c: char| !(c.is_alphanumeric() || c == '_')) {
+        Some(start) => &call[start + 1..],
+        None => call,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `MAKE_D3DHRESULT` and `MAKE_DDHRESULT` from `d3d9.h` and `d3dx9.h`.
+    fn make_d3d_hresult(code: u32) -> HRESULT {
+        (0x88760000 | code) as HRESULT
+    }
+
+    #[test]
+    fn decodes_every_direct3d_and_d3dx_error() {
+        let codes = [
+            ("D3DERR_WRONGTEXTUREFORMAT", 2072),
+            ("D3DERR_UNSUPPORTEDCOLOROPERATION", 2073),
+            ("D3DERR_UNSUPPORTEDCOLORARG", 2074),
+            ("D3DERR_UNSUPPORTEDALPHAOPERATION", 2075),
+            ("D3DERR_UNSUPPORTEDALPHAARG", 2076),
+            ("D3DERR_TOOMANYOPERATIONS", 2077),
+            ("D3DERR_CONFLICTINGTEXTUREFILTER", 2078),
+            ("D3DERR_UNSUPPORTEDFACTORVALUE", 2079),
+            ("D3DERR_CONFLICTINGRENDERSTATE", 2081),
+            ("D3DERR_UNSUPPORTEDTEXTUREFILTER", 2082),
+            ("D3DERR_CONFLICTINGTEXTUREPALETTE", 2086),
+            ("D3DERR_DRIVERINTERNALERROR", 2087),
+            ("D3DERR_NOTFOUND", 2150),
+            ("D3DERR_MOREDATA", 2151),
+            ("D3DERR_DEVICELOST", 2152),
+            ("D3DERR_DEVICENOTRESET", 2153),
+            ("D3DERR_NOTAVAILABLE", 2154),
+            ("D3DERR_OUTOFVIDEOMEMORY", 380),
+            ("D3DERR_INVALIDDEVICE", 2155),
+            ("D3DERR_INVALIDCALL", 2156),
+            ("D3DERR_DRIVERINVALIDCALL", 2157),
+            ("D3DERR_WASSTILLDRAWING", 540),
+            ("D3DERR_DEVICEREMOVED", 2160),
+            ("D3DERR_DEVICEHUNG", 2164),
+            ("D3DERR_UNSUPPORTEDOVERLAY", 2171),
+            ("D3DERR_UNSUPPORTEDOVERLAYFORMAT", 2172),
+            ("D3DERR_CANNOTPROTECTCONTENT", 2173),
+            ("D3DERR_UNSUPPORTEDCRYPTO", 2174),
+            ("D3DERR_PRESENT_STATISTICS_DISJOINT", 2180),
+            ("D3DXERR_CANNOTMODIFYINDEXBUFFER", 2900),
+            ("D3DXERR_INVALIDMESH", 2901),
+            ("D3DXERR_CANNOTATTRSORT", 2902),
+            ("D3DXERR_SKINNINGNOTSUPPORTED", 2903),
+            ("D3DXERR_TOOMANYINFLUENCES", 2904),
+            ("D3DXERR_INVALIDDATA", 2905),
+            ("D3DXERR_LOADEDMESHASNODATA", 2906),
+            ("D3DXERR_DUPLICATENAMEDFRAGMENT", 2907),
+            ("D3DXERR_CANNOTREMOVELASTITEM", 2908),
+        ];
+
+        for (name, code) in codes {
+            let hresult = make_d3d_hresult(code);
+            let decoded = ErrorCode::from_hresult(hresult).unwrap_or_else(|| panic!("{}", name));
+
+            assert_eq!(decoded.name(), name);
+            assert_eq!(decoded.hresult(), hresult);
+        }
+
+        let com_codes = ErrorCode::ALL
+            .iter()
+            .filter(|code| code.name().starts_with("E_"))
+            .count();
+        assert_eq!(ErrorCode::ALL.len(), codes.len() + com_codes);
+    }
+
+    #[test]
+    fn decodes_com_errors() {
+        assert_eq!(
+            ErrorCode::from_hresult(0x80004001u32 as HRESULT),
+            Some(ErrorCode::NotImplemented)
+        );
+        assert_eq!(
+            ErrorCode::from_hresult(0x80004005u32 as HRESULT),
+            Some(ErrorCode::Fail)
+        );
+        assert_eq!(
+            ErrorCode::from_hresult(0x80070057u32 as HRESULT),
+            Some(ErrorCode::InvalidArg)
+        );
+    }
+
+    #[test]
+    fn round_trips_every_code() {
+        for code in ErrorCode::ALL {
+            assert_eq!(ErrorCode::from_hresult(code.hresult()), Some(*code));
+            assert_eq!(code.to_string(), code.name());
+            assert!(!code.description().is_empty());
+        }
+    }
+
+    #[test]
+    fn does_not_decode_unknown_hresults() {
+        assert_eq!(ErrorCode::from_hresult(0), None);
+        assert_eq!(ErrorCode::from_hresult(0x12345678), None);
+        assert_eq!(ErrorCode::from_hresult(make_d3d_hresult(2080)), None);
+    }
+
+    #[test]
+    fn extracts_method_names() {
+        assert_eq!(
+            method_name(stringify!(self.inner.Present(
+                ptr::null(),
+                ptr::null(),
+                ptr::null_mut(),
+                ptr::null()
+            ))),
+            "Present"
+        );
+        assert_eq!(
+            method_name(stringify!(self.inner.Reset(&mut params.into()))),
+            "Reset"
+        );
+        assert_eq!(
+            method_name(stringify!(D3DXCompileShader(source, len))),
+            "D3DXCompileShader"
+        );
+        assert_eq!(
+            method_name("self\n.inner\n.GetRenderTargetData(a, b)"),
+            "GetRenderTargetData"
+        );
+        assert_eq!(method_name("BeginScene"), "BeginScene");
+    }
+}
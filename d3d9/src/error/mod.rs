@@ -0,0 +1,215 @@
+//! Provides error handling for Windows APIs.
+use std::{
+    error::Error,
+    fmt,
+    fmt::{Debug, Display, Formatter},
+};
+
+#[cfg(windows)]
+use winapi::um::winbase::{
+    FormatMessageA, FORMAT_MESSAGE_FROM_SYSTEM, FORMAT_MESSAGE_IGNORE_INSERTS,
+};
+
+use crate::sys::HRESULT;
+
+mod code;
+
+#[doc(hidden)]
+pub use code::method_name;
+pub use code::ErrorCode;
+
+/// A result type wrapping [`WindowsError`].
+pub type WindowsResult<T> = Result<T, WindowsError>;
+
+/// Represents an error returned by the Windows API.
+///
+/// Known Direct3D and D3DX codes are described by [`ErrorCode`], anything else falls back to the
+/// message provided by the system.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WindowsError {
+    hresult: HRESULT,
+    code: Option<ErrorCode>,
+    method: Option<&'static str>,
+    message: String,
+}
+
+impl WindowsError {
+    /// Creates a new instance from the given [`HRESULT`] code.
+    pub fn from_hresult(hresult: HRESULT) -> Self {
+        let code = ErrorCode::from_hresult(hresult);
+        let message = match code {
+            Some(code) => code.description().to_string(),
+            None => system_message(hresult),
+        };
+
+        Self {
+            hresult,
+            code,
+            method: None,
+            message,
+        }
+    }
+
+    /// Records the name of the method which failed.
+    pub fn with_method(mut self, method: &'static str) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Returns the raw [`HRESULT`].
+    pub fn hresult(&self) -> HRESULT {
+        self.hresult
+    }
+
+    /// Returns the error code, or [`None`] if it is not in the built-in table.
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.code
+    }
+
+    /// Returns the name of the method which failed, if it is known.
+    pub fn method(&self) -> Option<&'static str> {
+        self.method
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns whether the error has the given code.
+    pub fn is(&self, code: ErrorCode) -> bool {
+        self.code == Some(code)
+    }
+}
+
+impl From<ErrorCode> for WindowsError {
+    fn from(value: ErrorCode) -> Self {
+        Self::from_hresult(value.hresult())
+    }
+}
+
+impl Error for WindowsError {}
+
+impl Display for WindowsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "{} ({:#010X})", code.name(), self.hresult as u32)?,
+            None => write!(f, "HRESULT {:#010X}", self.hresult as u32)?,
+        }
+
+        if let Some(method) = self.method {
+            write!(f, " in {}", method)?;
+        }
+
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Asks the system for the message of an [`HRESULT`], returning an empty string if there is none.
+#[cfg(windows)]
+fn system_message(hresult: HRESULT) -> String {
+    use std::ptr;
+
+    const N_BUFFER: usize = 512;
+    let mut buffer = [0u8; N_BUFFER];
+
+    let len = unsafe {
+        FormatMessageA(
+            FORMAT_MESSAGE_FROM_SYSTEM | FORMAT_MESSAGE_IGNORE_INSERTS,
+            ptr::null(),
+            hresult as u32,
+            0,
+            buffer.as_mut_ptr() as *mut _,
+            N_BUFFER as u32,
+            ptr::null_mut(),
+        )
+    };
+
+    let message = &buffer[..(len as usize).min(N_BUFFER)];
+    let message = match message.iter().position(|byte| *byte == 0) {
+        Some(end) => &message[..end],
+        None => message,
+    };
+
+    String::from_utf8_lossy(message).trim_end().to_string()
+}
+
+/// There is no system message table outside of Windows.
+#[cfg(not(windows))]
+fn system_message(_hresult: HRESULT) -> String {
+    String::new()
+}
+
+#[macro_export]
+macro_rules! check_hresult {
+    ($hresult:expr) => {{
+        let f = || -> Result<(), $crate::error::WindowsError> {
+            let result = $hresult;
+            if result != 0 {
+                Err($crate::error::WindowsError::from_hresult(result)
+                    .with_method($crate::error::method_name(stringify!($hresult))))
+            } else {
+                Ok(())
+            }
+        };
+        f()
+    }};
+}
+
+#[macro_export]
+macro_rules! check_hresult_mut {
+    ($hresult:expr) => {{
+        let mut f = || -> Result<(), $crate::error::WindowsError> {
+            let result = $hresult;
+            if result != 0 {
+                Err($crate::error::WindowsError::from_hresult(result)
+                    .with_method($crate::error::method_name(stringify!($hresult))))
+            } else {
+                Ok(())
+            }
+        };
+        f()
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_call(_: i32) -> HRESULT {
+        ErrorCode::InvalidCall.hresult()
+    }
+
+    #[test]
+    fn displays_known_codes() {
+        let error = WindowsError::from(ErrorCode::DeviceLost).with_method("Present");
+
+        assert!(error.is(ErrorCode::DeviceLost));
+        assert_eq!(error.hresult(), 0x88760868u32 as HRESULT);
+        assert_eq!(
+            error.to_string(),
+            "D3DERR_DEVICELOST (0x88760868) in Present: \
+             The device has been lost but cannot be reset at this time."
+        );
+    }
+
+    #[test]
+    fn displays_unknown_codes() {
+        let error = WindowsError::from_hresult(0x12345678);
+
+        assert_eq!(error.code(), None);
+        assert!(error.to_string().starts_with("HRESULT 0x12345678"));
+    }
+
+    #[test]
+    fn check_hresult_records_the_method() {
+        let result: WindowsResult<()> = crate::check_hresult!(fake_call(3));
+        let error = result.unwrap_err();
+
+        assert!(error.is(ErrorCode::InvalidCall));
+        assert_eq!(error.method(), Some("fake_call"));
+    }
+}
@@ -0,0 +1,810 @@
+//! Compresses and decompresses the `DXT1` to `DXT5` block formats, also known as BC1 to BC3.
+//!
+//! `DXT2` and `DXT4` are stored exactly like `DXT3` and `DXT5`. They only signal that the color
+//! has been premultiplied by alpha, which is left to the caller.
+//!
+//! # Usage
+//!
+//! ```rs
+//! let options = CompressOptions::new().quality(Quality::High).dither_alpha(true);
+//! let blocks = compress(Format::DXT5, width, height, &rgba, &options)?;
+//! let rgba = decompress(Format::DXT5, width, height, &blocks)?;
+//! ```
+
+use crate::{format::FormatError, std::types::Format};
+
+/// Represents the 4x4 pixels of a block as `[r, g, b, a]` bytes, row by row.
+pub type BlockPixels = [[u8; 4]; 16];
+
+/// Represents how much effort the compressor spends on each block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Quality {
+    /// Uses the bounding box of the colors as endpoints.
+    Fast,
+    /// Uses the principal axis of the colors as endpoints.
+    #[default]
+    Normal,
+    /// Refines the endpoints by least squares and tries every block mode.
+    High,
+}
+
+/// Represents the options passed to the block compressor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CompressOptions {
+    pub quality: Quality,
+    /// Diffuses the alpha quantization error of `DXT1` and `DXT3` across each block.
+    pub dither_alpha: bool,
+}
+
+impl CompressOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn quality(mut self, quality: Quality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    pub fn dither_alpha(mut self, dither_alpha: bool) -> Self {
+        self.dither_alpha = dither_alpha;
+        self
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Variant {
+    Bc1,
+    Bc2,
+    Bc3,
+}
+
+impl Variant {
+    fn from_format(format: Format) -> Result<Self, FormatError> {
+        match format {
+            Format::DXT1 => Ok(Self::Bc1),
+            Format::DXT2 | Format::DXT3 => Ok(Self::Bc2),
+            Format::DXT4 | Format::DXT5 => Ok(Self::Bc3),
+            _ => Err(FormatError::Unsupported(format)),
+        }
+    }
+
+    fn block_bytes(self) -> usize {
+        match self {
+            Self::Bc1 => 8,
+            Self::Bc2 | Self::Bc3 => 16,
+        }
+    }
+}
+
+/// Returns whether `format` can be compressed and decompressed.
+pub fn is_supported(format: Format) -> bool {
+    Variant::from_format(format).is_ok()
+}
+
+/// Compresses tightly packed `[r, g, b, a]` bytes into tightly packed rows of blocks.
+///
+/// Blocks which extend past the edge of the image repeat its last row and column.
+pub fn compress(
+    format: Format,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+    options: &CompressOptions,
+) -> Result<Vec<u8>, FormatError> {
+    let variant = Variant::from_format(format)?;
+    let (width, height) = (width as usize, height as usize);
+    check_len(rgba, width * height * 4)?;
+
+    let (blocks_x, blocks_y) = (width.max(1).div_ceil(4), height.max(1).div_ceil(4));
+    let block_bytes = variant.block_bytes();
+    let mut data = vec![0; blocks_x * blocks_y * block_bytes];
+    if width == 0 || height == 0 {
+        return Ok(data);
+    }
+
+    for (index, block) in data.chunks_exact_mut(block_bytes).enumerate() {
+        let (block_x, block_y) = (index % blocks_x * 4, index / blocks_x * 4);
+        let mut pixels = [[0; 4]; 16];
+
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            let x = (block_x + i % 4).min(width - 1);
+            let y = (block_y + i / 4).min(height - 1);
+            pixel.copy_from_slice(&rgba[(y * width + x) * 4..][..4]);
+        }
+
+        encode(variant, &pixels, options, block);
+    }
+
+    Ok(data)
+}
+
+/// Decompresses tightly packed rows of blocks into tightly packed `[r, g, b, a]` bytes.
+pub fn decompress(
+    format: Format,
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> Result<Vec<u8>, FormatError> {
+    let variant = Variant::from_format(format)?;
+    let (width, height) = (width as usize, height as usize);
+
+    let (blocks_x, blocks_y) = (width.max(1).div_ceil(4), height.max(1).div_ceil(4));
+    let block_bytes = variant.block_bytes();
+    check_len(data, blocks_x * blocks_y * block_bytes)?;
+
+    let mut rgba = vec![0; width * height * 4];
+
+    for (index, block) in data
+        .chunks_exact(block_bytes)
+        .take(blocks_x * blocks_y)
+        .enumerate()
+    {
+        let (block_x, block_y) = (index % blocks_x * 4, index / blocks_x * 4);
+        let pixels = decode(variant, block);
+
+        for (i, pixel) in pixels.iter().enumerate() {
+            let (x, y) = (block_x + i % 4, block_y + i / 4);
+            if x < width && y < height {
+                rgba[(y * width + x) * 4..][..4].copy_from_slice(pixel);
+            }
+        }
+    }
+
+    Ok(rgba)
+}
+
+/// Compresses a single block into `block`, which must hold 8 bytes for `DXT1` and 16 otherwise.
+pub fn compress_block(
+    format: Format,
+    pixels: &BlockPixels,
+    options: &CompressOptions,
+    block: &mut [u8],
+) -> Result<(), FormatError> {
+    let variant = Variant::from_format(format)?;
+    check_len(block, variant.block_bytes())?;
+
+    encode(
+        variant,
+        pixels,
+        options,
+        &mut block[..variant.block_bytes()],
+    );
+    Ok(())
+}
+
+/// Decompresses a single block.
+pub fn decompress_block(format: Format, block: &[u8]) -> Result<BlockPixels, FormatError> {
+    let variant = Variant::from_format(format)?;
+    check_len(block, variant.block_bytes())?;
+
+    Ok(decode(variant, block))
+}
+
+fn check_len(buffer: &[u8], expected: usize) -> Result<(), FormatError> {
+    if buffer.len() < expected {
+        return Err(FormatError::BufferTooSmall {
+            expected,
+            found: buffer.len(),
+        });
+    }
+
+    Ok(())
+}
+
+fn encode(variant: Variant, pixels: &BlockPixels, options: &CompressOptions, block: &mut [u8]) {
+    let alphas = pixels.map(|pixel| pixel[3]);
+
+    match variant {
+        Variant::Bc1 => {
+            let alphas = quantize_alpha(&alphas, options.dither_alpha, |a| {
+                if a >= 128.0 {
+                    255.0
+                } else {
+                    0.0
+                }
+            });
+            let opaque = alphas
+                .iter()
+                .enumerate()
+                .fold(0, |mask, (i, a)| mask | ((*a >= 128.0) as u16) << i);
+
+            block.copy_from_slice(&encode_color(pixels, opaque, options.quality, true));
+        }
+        Variant::Bc2 => {
+            let alphas =
+                quantize_alpha(&alphas, options.dither_alpha, |a| (a / 17.0).round() * 17.0);
+            let bits = alphas
+                .iter()
+                .enumerate()
+                .fold(0u64, |bits, (i, a)| bits | ((*a as u64 / 17) << (i * 4)));
+
+            block[..8].copy_from_slice(&bits.to_le_bytes());
+            block[8..].copy_from_slice(&encode_color(pixels, 0xFFFF, options.quality, false));
+        }
+        Variant::Bc3 => {
+            block[..8].copy_from_slice(&encode_alpha(&alphas, options.quality));
+            block[8..].copy_from_slice(&encode_color(pixels, 0xFFFF, options.quality, false));
+        }
+    }
+}
+
+fn decode(variant: Variant, block: &[u8]) -> BlockPixels {
+    let (alphas, color) = match variant {
+        Variant::Bc1 => (None, block),
+        Variant::Bc2 => {
+            let bits = u64::from_le_bytes(block[..8].try_into().unwrap());
+            let alphas = std::array::from_fn(|i| ((bits >> (i * 4)) & 0xF) as u8 * 17);
+            (Some(alphas), &block[8..])
+        }
+        Variant::Bc3 => (Some(decode_alpha(&block[..8])), &block[8..]),
+    };
+
+    let color0 = u16::from_le_bytes([color[0], color[1]]);
+    let color1 = u16::from_le_bytes([color[2], color[3]]);
+    let indices = u32::from_le_bytes(color[4..8].try_into().unwrap());
+    let palette = color_palette(color0, color1, variant == Variant::Bc1);
+
+    std::array::from_fn(|i| {
+        let mut pixel = palette[(indices >> (i * 2)) as usize & 3];
+        if let Some(alphas) = alphas {
+            pixel[3] = alphas[i];
+        }
+        pixel
+    })
+}
+
+/// Quantizes the alpha of each pixel, optionally diffusing the error with Floyd-Steinberg.
+fn quantize_alpha(alphas: &[u8; 16], dither: bool, quantize: impl Fn(f32) -> f32) -> [f32; 16] {
+    let mut values = alphas.map(|a| a as f32);
+
+    for i in 0..16 {
+        let quantized = quantize(values[i].clamp(0.0, 255.0));
+        let error = values[i] - quantized;
+        values[i] = quantized;
+
+        if !dither {
+            continue;
+        }
+
+        let (x, y) = (i % 4, i / 4);
+        for (dx, dy, weight) in [(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)] {
+            let (nx, ny) = (x as isize + dx, y + dy);
+            if (0..4).contains(&nx) && ny < 4 {
+                values[ny * 4 + nx as usize] += error * weight / 16.0;
+            }
+        }
+    }
+
+    values
+}
+
+/// Expands a 5:6:5 color to 8 bits per channel.
+fn expand_565(color: u16) -> [u8; 3] {
+    let (r, g, b) = ((color >> 11) & 0x1F, (color >> 5) & 0x3F, color & 0x1F);
+    [
+        (r << 3 | r >> 2) as u8,
+        (g << 2 | g >> 4) as u8,
+        (b << 3 | b >> 2) as u8,
+    ]
+}
+
+fn quantize_565(color: [f32; 3]) -> u16 {
+    let channel = |value: f32, max: f32| (value.clamp(0.0, 255.0) * max / 255.0).round() as u16;
+    channel(color[0], 31.0) << 11 | channel(color[1], 63.0) << 5 | channel(color[2], 31.0)
+}
+
+/// Returns the colors a block can select from. Only `DXT1` has the three color mode, in which the
+/// last entry is transparent black.
+fn color_palette(color0: u16, color1: u16, dxt1: bool) -> [[u8; 4]; 4] {
+    let (c0, c1) = (expand_565(color0), expand_565(color1));
+    let mix = |w0: u16, w1: u16| -> [u8; 4] {
+        let channel =
+            |i: usize| ((c0[i] as u16 * w0 + c1[i] as u16 * w1 + (w0 + w1) / 2) / (w0 + w1)) as u8;
+        [channel(0), channel(1), channel(2), 255]
+    };
+
+    if !dxt1 || color0 > color1 {
+        [mix(1, 0), mix(0, 1), mix(2, 1), mix(1, 2)]
+    } else {
+        [mix(1, 0), mix(0, 1), mix(1, 1), [0; 4]]
+    }
+}
+
+/// Encodes the color of a block. Pixels whose bit is clear in `opaque` are given the transparent
+/// index, which only exists in `DXT1`.
+fn encode_color(pixels: &BlockPixels, opaque: u16, quality: Quality, dxt1: bool) -> [u8; 8] {
+    if opaque == 0 {
+        return [0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF];
+    }
+
+    let colors = pixels
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| opaque & 1 << i != 0)
+        .map(|(_, pixel)| [pixel[0] as f32, pixel[1] as f32, pixel[2] as f32])
+        .collect::<Vec<_>>();
+
+    let endpoints = match quality {
+        Quality::Fast => bounding_box(&colors),
+        Quality::Normal | Quality::High => principal_axis(&colors),
+    };
+
+    // Transparent pixels need the three color mode, which the other formats do not have.
+    let modes: &[bool] = match (dxt1, opaque == 0xFFFF, quality) {
+        (true, false, _) => &[true],
+        (true, true, Quality::High) => &[false, true],
+        _ => &[false],
+    };
+
+    let mut best: Option<ColorFit> = None;
+    for &three_color in modes {
+        let mut fit = fit_color(pixels, opaque, endpoints, three_color, dxt1);
+
+        if quality == Quality::High {
+            for _ in 0..2 {
+                let Some(refined) = refine(pixels, opaque, &fit, three_color) else {
+                    break;
+                };
+                let refined = fit_color(pixels, opaque, refined, three_color, dxt1);
+                if refined.error >= fit.error {
+                    break;
+                }
+                fit = refined;
+            }
+        }
+
+        if best.as_ref().is_none_or(|best| fit.error < best.error) {
+            best = Some(fit);
+        }
+    }
+
+    best.expect("no block mode was tried").block
+}
+
+struct ColorFit {
+    block: [u8; 8],
+    indices: [u8; 16],
+    error: u32,
+}
+
+fn fit_color(
+    pixels: &BlockPixels,
+    opaque: u16,
+    endpoints: ([f32; 3], [f32; 3]),
+    three_color: bool,
+    dxt1: bool,
+) -> ColorFit {
+    let (mut color0, mut color1) = (quantize_565(endpoints.0), quantize_565(endpoints.1));
+    // The mode is chosen by the order of the endpoints.
+    if (color0 < color1) != three_color && color0 != color1 {
+        std::mem::swap(&mut color0, &mut color1);
+    }
+
+    let palette = color_palette(color0, color1, dxt1);
+    // Equal endpoints select the three color mode of `DXT1`, whose last entry is transparent.
+    let choices = if dxt1 && color0 <= color1 { 3 } else { 4 };
+
+    let mut indices = [0; 16];
+    let mut error = 0;
+    for (i, pixel) in pixels.iter().enumerate() {
+        if opaque & 1 << i == 0 {
+            indices[i] = 3;
+            continue;
+        }
+
+        let (index, distance) = palette[..choices]
+            .iter()
+            .map(|entry| {
+                (0..3)
+                    .map(|c| (entry[c] as i32 - pixel[c] as i32).pow(2) as u32)
+                    .sum::<u32>()
+            })
+            .enumerate()
+            .min_by_key(|(_, distance)| *distance)
+            .unwrap();
+
+        indices[i] = index as u8;
+        error += distance;
+    }
+
+    let bits = indices
+        .iter()
+        .enumerate()
+        .fold(0u32, |bits, (i, index)| bits | (*index as u32) << (i * 2));
+
+    let mut block = [0; 8];
+    block[..2].copy_from_slice(&color0.to_le_bytes());
+    block[2..4].copy_from_slice(&color1.to_le_bytes());
+    block[4..].copy_from_slice(&bits.to_le_bytes());
+
+    ColorFit {
+        block,
+        indices,
+        error,
+    }
+}
+
+fn bounding_box(colors: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
+    colors
+        .iter()
+        .fold(([0.0; 3], [255.0; 3]), |(max, min), color| {
+            (
+                std::array::from_fn(|c| max[c].max(color[c])),
+                std::array::from_fn(|c| min[c].min(color[c])),
+            )
+        })
+}
+
+/// Returns the extremes of the colors along the axis of greatest variance.
+fn principal_axis(colors: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
+    let count = colors.len() as f32;
+    let mean: [f32; 3] =
+        std::array::from_fn(|c| colors.iter().map(|color| color[c]).sum::<f32>() / count);
+
+    let mut covariance = [[0.0f32; 3]; 3];
+    for color in colors {
+        let d: [f32; 3] = std::array::from_fn(|c| color[c] - mean[c]);
+        for (i, row) in covariance.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value += d[i] * d[j];
+            }
+        }
+    }
+
+    let mut axis = [1.0f32; 3];
+    for _ in 0..8 {
+        let next: [f32; 3] =
+            std::array::from_fn(|i| (0..3).map(|j| covariance[i][j] * axis[j]).sum());
+        let length = next.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if length < 1e-6 {
+            return bounding_box(colors);
+        }
+        axis = next.map(|v| v / length);
+    }
+
+    let (min, max) = colors
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(min, max), color| {
+            let t = (0..3).map(|c| (color[c] - mean[c]) * axis[c]).sum::<f32>();
+            (min.min(t), max.max(t))
+        });
+
+    (
+        std::array::from_fn(|c| mean[c] + axis[c] * max),
+        std::array::from_fn(|c| mean[c] + axis[c] * min),
+    )
+}
+
+/// Solves for the endpoints which best reproduce the pixels with the indices of `fit`.
+fn refine(
+    pixels: &BlockPixels,
+    opaque: u16,
+    fit: &ColorFit,
+    three_color: bool,
+) -> Option<([f32; 3], [f32; 3])> {
+    // The weight of the first endpoint for each index.
+    let weights: [f32; 4] = if three_color {
+        [1.0, 0.0, 0.5, 0.0]
+    } else {
+        [1.0, 0.0, 2.0 / 3.0, 1.0 / 3.0]
+    };
+
+    let (mut aa, mut ab, mut bb) = (0.0f32, 0.0f32, 0.0f32);
+    let (mut ax, mut bx) = ([0.0f32; 3], [0.0f32; 3]);
+    for (i, pixel) in pixels.iter().enumerate() {
+        if opaque & 1 << i == 0 {
+            continue;
+        }
+
+        let a = weights[fit.indices[i] as usize];
+        let b = 1.0 - a;
+        aa += a * a;
+        ab += a * b;
+        bb += b * b;
+        for c in 0..3 {
+            ax[c] += a * pixel[c] as f32;
+            bx[c] += b * pixel[c] as f32;
+        }
+    }
+
+    let determinant = aa * bb - ab * ab;
+    if determinant.abs() < 1e-6 {
+        return None;
+    }
+
+    Some((
+        std::array::from_fn(|c| (bb * ax[c] - ab * bx[c]) / determinant),
+        std::array::from_fn(|c| (aa * bx[c] - ab * ax[c]) / determinant),
+    ))
+}
+
+/// Returns the alphas a `DXT5` block can select from.
+fn alpha_palette(alpha0: u8, alpha1: u8) -> [u8; 8] {
+    let (a0, a1) = (alpha0 as u16, alpha1 as u16);
+
+    if alpha0 > alpha1 {
+        std::array::from_fn(|i| match i {
+            0 => alpha0,
+            1 => alpha1,
+            _ => (((8 - i as u16) * a0 + (i as u16 - 1) * a1 + 3) / 7) as u8,
+        })
+    } else {
+        std::array::from_fn(|i| match i {
+            0 => alpha0,
+            1 => alpha1,
+            6 => 0,
+            7 => 255,
+            _ => (((6 - i as u16) * a0 + (i as u16 - 1) * a1 + 2) / 5) as u8,
+        })
+    }
+}
+
+fn decode_alpha(block: &[u8]) -> [u8; 16] {
+    let palette = alpha_palette(block[0], block[1]);
+    let mut bits = [0; 8];
+    bits[..6].copy_from_slice(&block[2..8]);
+    let bits = u64::from_le_bytes(bits);
+
+    std::array::from_fn(|i| palette[(bits >> (i * 3)) as usize & 7])
+}
+
+fn encode_alpha(alphas: &[u8; 16], quality: Quality) -> [u8; 8] {
+    let min = *alphas.iter().min().unwrap();
+    let max = *alphas.iter().max().unwrap();
+
+    let mut candidates = vec![(max, min)];
+    if quality != Quality::Fast {
+        // The six alpha mode represents 0 and 255 exactly, so its range only covers the rest.
+        let inner = alphas.iter().filter(|a| !matches!(a, 0 | 255));
+        let inner_min = inner.clone().min().copied().unwrap_or(0);
+        let inner_max = inner.max().copied().unwrap_or(0);
+        candidates.push((inner_min, inner_max));
+    }
+    if quality == Quality::High && max > min {
+        for low in min..=min.saturating_add(4).min(max) {
+            for high in max.saturating_sub(4).max(low)..=max {
+                if high > low {
+                    candidates.push((high, low));
+                }
+            }
+        }
+    }
+
+    candidates
+        .into_iter()
+        .map(|(alpha0, alpha1)| fit_alpha(alphas, alpha0, alpha1))
+        .min_by_key(|(_, error)| *error)
+        .unwrap()
+        .0
+}
+
+fn fit_alpha(alphas: &[u8; 16], alpha0: u8, alpha1: u8) -> ([u8; 8], u32) {
+    let palette = alpha_palette(alpha0, alpha1);
+
+    let mut bits = 0u64;
+    let mut error = 0;
+    for (i, alpha) in alphas.iter().enumerate() {
+        let (index, distance) = palette
+            .iter()
+            .map(|entry| (*entry as i32 - *alpha as i32).pow(2) as u32)
+            .enumerate()
+            .min_by_key(|(_, distance)| *distance)
+            .unwrap();
+
+        bits |= (index as u64) << (i * 3);
+        error += distance;
+    }
+
+    let mut block = [0; 8];
+    block[0] = alpha0;
+    block[1] = alpha1;
+    block[2..].copy_from_slice(&bits.to_le_bytes()[..6]);
+
+    (block, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Image;
+
+    fn gradient(width: u32, height: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+
+        for y in 0..height {
+            for x in 0..width {
+                data.extend([
+                    (x * 255 / (width - 1)) as u8,
+                    (y * 255 / (height - 1)) as u8,
+                    ((x + y) * 4 % 256) as u8,
+                    ((x * 16 + y * 3) % 256) as u8,
+                ]);
+            }
+        }
+
+        data
+    }
+
+    /// Returns the root mean square error of the given channels.
+    fn rmse(left: &[u8], right: &[u8], channels: std::ops::Range<usize>) -> f64 {
+        let mut sum = 0.0;
+        let mut count = 0.0;
+
+        for (left, right) in left.chunks(4).zip(right.chunks(4)) {
+            for channel in channels.clone() {
+                sum += (left[channel] as f64 - right[channel] as f64).powi(2);
+                count += 1.0;
+            }
+        }
+
+        (sum / count).sqrt()
+    }
+
+    #[test]
+    fn decodes_dxt1_blocks() {
+        // Red and blue endpoints, with the pixels cycling through the four indices.
+        let block = [0x00, 0xF8, 0x1F, 0x00, 0xE4, 0xE4, 0xE4, 0xE4];
+        let pixels = decompress_block(Format::DXT1, &block).unwrap();
+        assert_eq!(pixels[0], [255, 0, 0, 255]);
+        assert_eq!(pixels[1], [0, 0, 255, 255]);
+        assert_eq!(pixels[2], [170, 0, 85, 255]);
+        assert_eq!(pixels[3], [85, 0, 170, 255]);
+
+        // Swapping the endpoints selects the mode with transparent black.
+        let block = [0x1F, 0x00, 0x00, 0xF8, 0xE4, 0xE4, 0xE4, 0xE4];
+        let pixels = decompress_block(Format::DXT1, &block).unwrap();
+        assert_eq!(pixels[2], [128, 0, 128, 255]);
+        assert_eq!(pixels[3], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn round_trips_within_error_bounds() {
+        let (width, height) = (37, 21);
+
+        for quality in [Quality::Fast, Quality::Normal, Quality::High] {
+            for format in [Format::DXT1, Format::DXT3, Format::DXT5] {
+                let options = CompressOptions::new().quality(quality);
+                let mut source = gradient(width, height);
+                if format == Format::DXT1 {
+                    source.chunks_mut(4).for_each(|pixel| pixel[3] = 255);
+                }
+
+                let compressed = compress(format, width, height, &source, &options).unwrap();
+                assert_eq!(
+                    compressed.len(),
+                    format.info().unwrap().surface_size(width, height)
+                );
+                assert_eq!(
+                    compressed,
+                    compress(format, width, height, &source, &options).unwrap()
+                );
+
+                let decompressed = decompress(format, width, height, &compressed).unwrap();
+                let error = rmse(&source, &decompressed, 0..3);
+                assert!(error < 8.0, "{:?} {:?} {}", format, quality, error);
+
+                match format {
+                    Format::DXT3 => assert!(rmse(&source, &decompressed, 3..4) < 10.0),
+                    Format::DXT5 => assert!(rmse(&source, &decompressed, 3..4) < 5.0),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn high_quality_is_no_worse_than_normal() {
+        let (width, height) = (32, 32);
+        let source = gradient(width, height);
+        let error = |quality| {
+            let options = CompressOptions::new().quality(quality);
+            let compressed = compress(Format::DXT5, width, height, &source, &options).unwrap();
+            let decompressed = decompress(Format::DXT5, width, height, &compressed).unwrap();
+            rmse(&source, &decompressed, 0..4)
+        };
+
+        assert!(error(Quality::High) <= error(Quality::Normal) + 1e-9);
+    }
+
+    #[test]
+    fn compresses_solid_and_transparent_blocks() {
+        let solid = [[10u8, 200, 30, 255]; 16];
+        let mut block = [0u8; 8];
+
+        compress_block(Format::DXT1, &solid, &CompressOptions::new(), &mut block).unwrap();
+        for pixel in decompress_block(Format::DXT1, &block).unwrap() {
+            for channel in 0..3 {
+                assert!((pixel[channel] as i32 - solid[0][channel] as i32).abs() <= 4);
+            }
+            assert_eq!(pixel[3], 255);
+        }
+
+        let mut punched = solid;
+        punched[5] = [0, 0, 0, 0];
+        punched[6][3] = 10;
+        compress_block(Format::DXT1, &punched, &CompressOptions::new(), &mut block).unwrap();
+        let pixels = decompress_block(Format::DXT1, &block).unwrap();
+        assert_eq!([pixels[0][3], pixels[5][3], pixels[6][3]], [255, 0, 0]);
+
+        let transparent = [[0u8; 4]; 16];
+        compress_block(
+            Format::DXT1,
+            &transparent,
+            &CompressOptions::new(),
+            &mut block,
+        )
+        .unwrap();
+        assert!(decompress_block(Format::DXT1, &block)
+            .unwrap()
+            .iter()
+            .all(|pixel| pixel[3] == 0));
+
+        let black = [[0u8, 0, 0, 255]; 16];
+        for format in [Format::DXT1, Format::DXT5] {
+            let mut block = [0u8; 16];
+            let options = CompressOptions::new().quality(Quality::High);
+            compress_block(format, &black, &options, &mut block).unwrap();
+            for pixel in decompress_block(format, &block).unwrap() {
+                assert_eq!(pixel, [0, 0, 0, 255]);
+            }
+        }
+    }
+
+    #[test]
+    fn dithers_alpha() {
+        let translucent = [[128u8, 128, 128, 100]; 16];
+        let dithered = CompressOptions::new().dither_alpha(true);
+
+        let mut block = [0u8; 16];
+        compress_block(Format::DXT3, &translucent, &dithered, &mut block).unwrap();
+        let pixels = decompress_block(Format::DXT3, &block).unwrap();
+        let mean = pixels.iter().map(|pixel| pixel[3] as f64).sum::<f64>() / 16.0;
+        assert!((mean - 100.0).abs() < 3.0, "{}", mean);
+
+        let mut block = [0u8; 8];
+        compress_block(Format::DXT1, &translucent, &dithered, &mut block).unwrap();
+        let pixels = decompress_block(Format::DXT1, &block).unwrap();
+        let opaque = pixels.iter().filter(|pixel| pixel[3] == 255).count();
+        assert!((5..=8).contains(&opaque), "{}", opaque);
+
+        compress_block(
+            Format::DXT1,
+            &translucent,
+            &CompressOptions::new(),
+            &mut block,
+        )
+        .unwrap();
+        assert!(decompress_block(Format::DXT1, &block)
+            .unwrap()
+            .iter()
+            .all(|pixel| pixel[3] == 0));
+    }
+
+    #[test]
+    fn converts_images() {
+        let (width, height) = (9, 6);
+        let source = gradient(width, height);
+        let image = Image::from_rgba8(width, height, source.clone()).unwrap();
+
+        let compressed = image.convert(Format::DXT5).unwrap();
+        assert_eq!(compressed.pitch, 48);
+        assert_eq!(compressed.rows(), 2);
+        assert!(rmse(&source, &compressed.to_rgba8().unwrap(), 0..4) < 16.0);
+        assert_eq!(
+            compressed.to_rgba_f32().unwrap().len(),
+            (width * height) as usize
+        );
+        assert_eq!(
+            compressed.convert(Format::R5G6B5).unwrap().data.len(),
+            9 * 6 * 2
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert!(!is_supported(Format::A8R8G8B8));
+        assert!(compress(Format::A8R8G8B8, 4, 4, &[0; 64], &CompressOptions::new()).is_err());
+        assert!(decompress(Format::DXT1, 8, 8, &[0; 8]).is_err());
+    }
+}
@@ -0,0 +1,301 @@
+//! Captures render targets and back buffers, and saves images as PNG or DDS files.
+//!
+//! Render targets cannot be locked, so [`capture_surface`] copies them into a system memory
+//! surface with [`Device::get_render_target_data`] and converts the result to RGBA8. Encoding
+//! with [`encode_dds`] and [`encode_png`] never touches Direct3D. PNG files require the `image`
+//! feature.
+//!
+//! # Usage
+//!
+//! ```rs
+//! let screenshot = capture_back_buffer(&device)?;
+//! save_image(&screenshot, "screenshot.png")?;
+//! ```
+
+use std::{fs, path::Path};
+
+#[cfg(windows)]
+use crate::std::{
+    interfaces::{Device, Surface},
+    states::TextureFilterType,
+    types::{Format, MultiSampleType, Pool},
+};
+use crate::{
+    dds::{Dds, DdsError, DdsKind},
+    error::WindowsError,
+    format::{FormatError, Image},
+};
+
+/// Represents an error which occurred while capturing or saving an image.
+#[derive(Debug, thiserror::Error)]
+pub enum CaptureError {
+    #[error("Windows Error: {from}")]
+    Windows {
+        #[from]
+        from: WindowsError,
+    },
+
+    #[error("IO Error: {from}")]
+    Io {
+        #[from]
+        from: std::io::Error,
+    },
+
+    #[error("failed to convert image: {from}")]
+    Format {
+        #[from]
+        from: FormatError,
+    },
+
+    #[error("failed to encode DDS file: {from}")]
+    Dds {
+        #[from]
+        from: DdsError,
+    },
+
+    #[cfg(feature = "image")]
+    #[error("failed to encode image: {from}")]
+    Encode {
+        #[from]
+        from: ::image::ImageError,
+    },
+
+    #[error("{0:?} files require the `image` feature")]
+    FeatureDisabled(CaptureFileType),
+
+    #[error("the file type could not be determined")]
+    UnknownFileType,
+}
+
+/// Represents the file formats which images can be saved as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CaptureFileType {
+    Png,
+    Dds,
+}
+
+impl CaptureFileType {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "png" => Some(Self::Png),
+            "dds" => Some(Self::Dds),
+            _ => None,
+        }
+    }
+}
+
+/// Copies the contents of a surface into a [`Format::A8B8G8R8`] image.
+///
+/// Lockable surfaces are read directly. Other surfaces must be render targets, which are copied
+/// through a [`Pool::SystemMem`] surface, after being resolved if they are multisampled.
+#[cfg(windows)]
+pub fn capture_surface(device: &Device, surface: &Surface) -> Result<Image, CaptureError> {
+    let desc = surface.get_desc()?;
+
+    let image = match desc.pool {
+        Pool::Managed | Pool::SystemMem | Pool::Scratch => {
+            // Clones share the lock, so this fails rather than aliasing a locked surface.
+            surface.clone().read_image()?
+        }
+        Pool::Default | Pool::Other(_) => {
+            let resolved;
+            let source = if desc.multi_sample_type == MultiSampleType::None {
+                surface
+            } else {
+                resolved = device.create_render_target(
+                    desc.width,
+                    desc.height,
+                    desc.format,
+                    MultiSampleType::None,
+                    0,
+                    false,
+                )?;
+                device.stretch_rect(surface, &resolved, TextureFilterType::None)?;
+                &resolved
+            };
+
+            let mut staging = device.create_offscreen_plain_surface(
+                desc.width,
+                desc.height,
+                desc.format,
+                Pool::SystemMem,
+            )?;
+            device.get_render_target_data(source, &staging)?;
+            staging.read_image()?
+        }
+    };
+
+    Ok(image.convert(Format::A8B8G8R8)?)
+}
+
+/// Captures the back buffer which will be presented next, as a [`Format::A8B8G8R8`] image.
+#[cfg(windows)]
+pub fn capture_back_buffer(device: &Device) -> Result<Image, CaptureError> {
+    let back_buffer = device.get_back_buffer(0, 0)?;
+    capture_surface(device, &back_buffer)
+}
+
+/// Captures the front buffer, as a [`Format::A8B8G8R8`] image.
+///
+/// This is slow, and in windowed mode it captures the whole desktop rather than the window.
+#[cfg(windows)]
+pub fn capture_front_buffer(device: &Device) -> Result<Image, CaptureError> {
+    let mode = device.get_display_mode(0)?;
+    let mut staging = device.create_offscreen_plain_surface(
+        mode.width,
+        mode.height,
+        Format::A8R8G8B8,
+        Pool::SystemMem,
+    )?;
+    device.get_front_buffer_data(0, &staging)?;
+
+    Ok(staging.read_image()?.convert(Format::A8B8G8R8)?)
+}
+
+/// Encodes an image as a DDS file, keeping its format.
+pub fn encode_dds(image: &Image) -> Result<Vec<u8>, DdsError> {
+    let mut dds = Dds::new(
+        DdsKind::Texture,
+        image.format,
+        image.width,
+        image.height,
+        1,
+        1,
+    )?;
+
+    let surface = dds.surface_mut(0, 0).expect("dds has no surface");
+    surface.data = (0..image.rows())
+        .flat_map(|row| image.row(row))
+        .copied()
+        .collect();
+
+    dds.to_bytes()
+}
+
+/// Encodes an image as an 8 bit RGBA PNG file.
+#[cfg(feature = "image")]
+pub fn encode_png(image: &Image) -> Result<Vec<u8>, CaptureError> {
+    let rgba = ::image::RgbaImage::from_raw(image.width, image.height, image.to_rgba8()?)
+        .expect("rgba buffer does not match the image size");
+
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    rgba.write_to(&mut bytes, ::image::ImageFormat::Png)?;
+
+    Ok(bytes.into_inner())
+}
+
+/// Encodes an image as the given file type.
+pub fn encode(image: &Image, file_type: CaptureFileType) -> Result<Vec<u8>, CaptureError> {
+    match file_type {
+        CaptureFileType::Dds => Ok(encode_dds(image)?),
+        #[cfg(feature = "image")]
+        CaptureFileType::Png => encode_png(image),
+        #[cfg(not(feature = "image"))]
+        CaptureFileType::Png => Err(CaptureError::FeatureDisabled(file_type)),
+    }
+}
+
+/// Saves an image to a file, whose type is chosen by its extension.
+pub fn save_image<P: AsRef<Path>>(image: &Image, path: P) -> Result<(), CaptureError> {
+    let file_type = CaptureFileType::from_path(&path).ok_or(CaptureError::UnknownFileType)?;
+    fs::write(path, encode(image, file_type)?)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std::types::Format;
+
+    /// Builds an `X8R8G8B8` image with padded rows, whose bytes are `B, G, R, X` in memory.
+    fn padded_x8r8g8b8(width: u32, height: u32, pitch: usize) -> Image {
+        let mut data = vec![0xEE; pitch * height as usize];
+        for y in 0..height as usize {
+            for x in 0..width as usize {
+                data[y * pitch + x * 4..][..4].copy_from_slice(&[
+                    (x * 10) as u8,
+                    (y * 20) as u8,
+                    200,
+                    0x12,
+                ]);
+            }
+        }
+
+        Image::with_pitch(Format::X8R8G8B8, width, height, pitch, data).unwrap()
+    }
+
+    #[test]
+    fn file_types_come_from_the_extension() {
+        assert_eq!(
+            CaptureFileType::from_path("shots/frame.png"),
+            Some(CaptureFileType::Png)
+        );
+        assert_eq!(
+            CaptureFileType::from_path("frame.DDS"),
+            Some(CaptureFileType::Dds)
+        );
+        assert_eq!(CaptureFileType::from_path("frame.bmp"), None);
+        assert_eq!(CaptureFileType::from_path("frame"), None);
+    }
+
+    #[test]
+    fn encode_dds_keeps_the_format_and_drops_padding() {
+        let image = padded_x8r8g8b8(3, 2, 16);
+        let dds = Dds::from_bytes(&encode_dds(&image).unwrap()).unwrap();
+
+        assert_eq!(dds.format, Format::X8R8G8B8);
+        assert_eq!((dds.width, dds.height), (3, 2));
+        assert_eq!(dds.surfaces[0].data.len(), 3 * 2 * 4);
+        assert_eq!(dds.surfaces[0].data[..12], image.data[..12]);
+        assert_eq!(dds.surfaces[0].data[12..], image.data[16..28]);
+
+        let rgba = image.convert(Format::A8B8G8R8).unwrap();
+        let dds = Dds::from_bytes(&encode(&rgba, CaptureFileType::Dds).unwrap()).unwrap();
+        assert_eq!(dds.format, Format::A8B8G8R8);
+        assert_eq!(dds.surfaces[0].data, rgba.data);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn encode_png_writes_rgba8() {
+        let image = padded_x8r8g8b8(6, 5, 28);
+        let bytes = encode_png(&image).unwrap();
+
+        let decoded = ::image::load_from_memory(&bytes).unwrap().to_rgba8();
+        assert_eq!(decoded.dimensions(), (6, 5));
+        assert_eq!(decoded.into_raw(), image.to_rgba8().unwrap());
+        assert_eq!(encode(&image, CaptureFileType::Png).unwrap(), bytes);
+    }
+
+    #[cfg(not(feature = "image"))]
+    #[test]
+    fn encode_png_requires_the_image_feature() {
+        let image = padded_x8r8g8b8(2, 2, 8);
+
+        assert!(matches!(
+            encode(&image, CaptureFileType::Png),
+            Err(CaptureError::FeatureDisabled(CaptureFileType::Png))
+        ));
+    }
+
+    #[test]
+    fn save_image_picks_the_type_from_the_extension() {
+        let dir = std::env::temp_dir().join(format!("d3d9-capture-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let image = padded_x8r8g8b8(2, 2, 8);
+
+        save_image(&image, dir.join("frame.dds")).unwrap();
+        assert!(Dds::from_bytes(&fs::read(dir.join("frame.dds")).unwrap()).is_ok());
+
+        assert!(matches!(
+            save_image(&image, dir.join("frame.bmp")),
+            Err(CaptureError::UnknownFileType)
+        ));
+        assert!(!dir.join("frame.bmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
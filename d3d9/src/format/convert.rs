@@ -0,0 +1,359 @@
+//! Converts rows of pixels to and from `[r, g, b, a]` floating point values.
+//!
+//! Channels which a format does not have are read as Direct3D 9 samples them: `1`, except that
+//! the color of alpha-only formats is black. Luminance is written as the Rec. 709 luma of the
+//! color, and bump formats map `u`, `v`, `w` and `q` onto red, green, blue and alpha.
+
+use crate::{
+    format::{Channel, ChannelInfo, ChannelType, FormatError, FormatInfo, FormatKind},
+    std::types::Format,
+};
+
+/// Returns whether rows of `format` can be decoded and encoded.
+pub fn is_supported(format: Format) -> bool {
+    format.info().is_some_and(|info| pixel_info(info).is_some())
+}
+
+/// Decodes `pixels.len()` pixels from a row of `format`.
+pub fn decode_row(format: Format, row: &[u8], pixels: &mut [[f32; 4]]) -> Result<(), FormatError> {
+    let info = format
+        .info()
+        .and_then(pixel_info)
+        .ok_or(FormatError::Unsupported(format))?;
+    let bytes = info.block_bytes as usize;
+    check_len(row, pixels.len() * bytes)?;
+
+    let defaults = if info.channels.iter().all(|c| c.channel == Channel::Alpha) {
+        [0.0, 0.0, 0.0, 1.0]
+    } else {
+        [1.0; 4]
+    };
+
+    for (pixel, source) in pixels.iter_mut().zip(row.chunks_exact(bytes)) {
+        let value = read_bits(source);
+        *pixel = defaults;
+
+        for channel in info.channels {
+            let decoded = decode_channel(channel, extract(value, channel));
+            match target(info.kind, channel.channel) {
+                Target::Component(index) => pixel[index] = decoded,
+                Target::Color => pixel[..3].fill(decoded),
+                Target::None => {}
+            }
+        }
+
+        // The third component of `CxV8U8` is reconstructed from the other two.
+        if format == Format::CxV8U8 {
+            pixel[2] = (1.0 - pixel[0] * pixel[0] - pixel[1] * pixel[1])
+                .max(0.0)
+                .sqrt();
+        }
+    }
+
+    Ok(())
+}
+
+/// Encodes `pixels` into a row of `format`.
+pub fn encode_row(format: Format, pixels: &[[f32; 4]], row: &mut [u8]) -> Result<(), FormatError> {
+    let info = format
+        .info()
+        .and_then(pixel_info)
+        .ok_or(FormatError::Unsupported(format))?;
+    let bytes = info.block_bytes as usize;
+    check_len(row, pixels.len() * bytes)?;
+
+    for (pixel, destination) in pixels.iter().zip(row.chunks_exact_mut(bytes)) {
+        let mut value = 0u128;
+
+        for channel in info.channels {
+            let source = match target(info.kind, channel.channel) {
+                Target::Component(index) => pixel[index],
+                Target::Color => 0.2126 * pixel[0] + 0.7152 * pixel[1] + 0.0722 * pixel[2],
+                Target::None => continue,
+            };
+            value |= (encode_channel(channel, source) as u128) << channel.offset;
+        }
+
+        destination.copy_from_slice(&value.to_le_bytes()[..bytes]);
+    }
+
+    Ok(())
+}
+
+/// Converts `width` pixels from a row of one format into a row of another.
+pub fn convert_row(
+    source_format: Format,
+    source: &[u8],
+    destination_format: Format,
+    destination: &mut [u8],
+    width: usize,
+) -> Result<(), FormatError> {
+    if source_format == destination_format {
+        let bytes = source_format
+            .info()
+            .ok_or(FormatError::Unsupported(source_format))?
+            .row_pitch(width as u32);
+        check_len(source, bytes)?;
+        check_len(destination, bytes)?;
+
+        destination[..bytes].copy_from_slice(&source[..bytes]);
+        return Ok(());
+    }
+
+    let mut pixels = vec![[0.0; 4]; width];
+    decode_row(source_format, source, &mut pixels)?;
+    encode_row(destination_format, &pixels, destination)
+}
+
+/// Converts an sRGB encoded value to linear.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear value to sRGB.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Converts a half precision float to single precision.
+pub fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half as u32) & 0x8000) << 16;
+    let exponent = (half >> 10) & 0x1F;
+    let mantissa = (half & 0x3FF) as u32;
+
+    match exponent {
+        0 => {
+            let value = mantissa as f32 / (1 << 24) as f32;
+            if sign != 0 {
+                -value
+            } else {
+                value
+            }
+        }
+        0x1F => f32::from_bits(sign | 0x7F80_0000 | mantissa << 13),
+        _ => f32::from_bits(sign | (exponent as u32 + 112) << 23 | mantissa << 13),
+    }
+}
+
+/// Converts a single precision float to half precision, rounding to the nearest even value.
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let magnitude = bits & 0x7FFF_FFFF;
+
+    if value.is_nan() {
+        return sign | 0x7E00;
+    }
+    // 65520 and above round to infinity.
+    if magnitude >= 0x477F_F000 {
+        return sign | 0x7C00;
+    }
+    // Below the smallest normal half, `2^-14`, the value is a multiple of `2^-24`.
+    if magnitude < 0x3880_0000 {
+        let subnormal = (f32::from_bits(magnitude) * (1 << 24) as f32).round_ties_even();
+        return sign | subnormal as u16;
+    }
+
+    let exponent = (magnitude >> 23) - 112;
+    let mantissa = magnitude & 0x7F_FFFF;
+    let mut half = (exponent << 10 | mantissa >> 13) as u16;
+
+    let remainder = mantissa & 0x1FFF;
+    if remainder > 0x1000 || (remainder == 0x1000 && half & 1 == 1) {
+        half += 1;
+    }
+
+    sign | half
+}
+
+enum Target {
+    Component(usize),
+    /// Luminance, which is read into red, green and blue.
+    Color,
+    None,
+}
+
+fn target(kind: FormatKind, channel: Channel) -> Target {
+    match channel {
+        Channel::Red | Channel::U | Channel::Depth => Target::Component(0),
+        Channel::Green | Channel::V | Channel::Stencil => Target::Component(1),
+        Channel::Blue | Channel::W => Target::Component(2),
+        Channel::Alpha | Channel::Q => Target::Component(3),
+        // Bump formats sample luminance into the third component.
+        Channel::Luminance if kind == FormatKind::Bump => Target::Component(2),
+        Channel::Luminance => Target::Color,
+        Channel::Palette => Target::None,
+    }
+}
+
+/// Returns `info` if it describes single pixels whose channels can be converted.
+fn pixel_info(info: &FormatInfo) -> Option<&FormatInfo> {
+    let convertible = info.block_width == 1
+        && info.block_height == 1
+        && !info.channels.is_empty()
+        && !matches!(info.kind, FormatKind::Palette | FormatKind::Index)
+        // 24 bit floats, as used by `D24FS8`, are not supported.
+        && info
+            .channels
+            .iter()
+            .all(|c| c.channel_type != ChannelType::Float || matches!(c.bits, 16 | 32));
+
+    convertible.then_some(info)
+}
+
+fn check_len(buffer: &[u8], expected: usize) -> Result<(), FormatError> {
+    if buffer.len() < expected {
+        return Err(FormatError::BufferTooSmall {
+            expected,
+            found: buffer.len(),
+        });
+    }
+
+    Ok(())
+}
+
+fn read_bits(bytes: &[u8]) -> u128 {
+    let mut buffer = [0; 16];
+    buffer[..bytes.len()].copy_from_slice(bytes);
+    u128::from_le_bytes(buffer)
+}
+
+fn mask(bits: u8) -> u128 {
+    (1u128 << bits) - 1
+}
+
+fn extract(value: u128, channel: &ChannelInfo) -> u32 {
+    ((value >> channel.offset) & mask(channel.bits)) as u32
+}
+
+fn decode_channel(channel: &ChannelInfo, raw: u32) -> f32 {
+    let bits = channel.bits as u32;
+
+    match channel.channel_type {
+        ChannelType::Unorm => (raw as f64 / mask(channel.bits) as f64) as f32,
+        ChannelType::Snorm => {
+            // Sign extend, then map both `-2^(n-1)` and `-2^(n-1) + 1` onto -1.
+            let signed = ((raw << (32 - bits)) as i32) >> (32 - bits);
+            let max = (1i64 << (bits - 1)) - 1;
+            (signed as f64 / max as f64).max(-1.0) as f32
+        }
+        ChannelType::Float if bits == 16 => f16_to_f32(raw as u16),
+        ChannelType::Float => f32::from_bits(raw),
+        ChannelType::Uint => raw as f32,
+    }
+}
+
+fn encode_channel(channel: &ChannelInfo, value: f32) -> u32 {
+    let bits = channel.bits as u32;
+
+    match channel.channel_type {
+        ChannelType::Unorm => {
+            let max = mask(channel.bits) as f64;
+            (value.clamp(0.0, 1.0) as f64 * max).round() as u32
+        }
+        ChannelType::Snorm => {
+            let max = ((1i64 << (bits - 1)) - 1) as f64;
+            let signed = (value.clamp(-1.0, 1.0) as f64 * max).round() as i32;
+            signed as u32 & mask(channel.bits) as u32
+        }
+        ChannelType::Float if bits == 16 => f32_to_f16(value) as u32,
+        ChannelType::Float => value.to_bits(),
+        ChannelType::Uint => (value.max(0.0).round() as u64).min(mask(channel.bits) as u64) as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_half_floats() {
+        for value in [0.0f32, 1.0, -2.5, 65504.0, 6.1035156e-5, 5.96e-8, 0.333] {
+            let converted = f16_to_f32(f32_to_f16(value));
+            assert!(
+                (converted - value).abs() <= value.abs() * 1e-3 + 6e-8,
+                "{} became {}",
+                value,
+                converted
+            );
+        }
+
+        assert_eq!(f32_to_f16(1.0), 0x3C00);
+        assert_eq!(f32_to_f16(70000.0), 0x7C00);
+        assert_eq!(f16_to_f32(0x0001), 5.9604645e-8);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+
+        for half in 0..0x7C00 {
+            assert_eq!(f32_to_f16(f16_to_f32(half)), half);
+        }
+    }
+
+    #[test]
+    fn encodes_channels() {
+        let pixels = [[1.0f32, 0.5, 0.0, 1.0]];
+
+        let mut row = [0u8; 4];
+        encode_row(Format::A8R8G8B8, &pixels, &mut row).unwrap();
+        assert_eq!(row, [0, 128, 255, 255]);
+
+        let mut row = [0u8; 2];
+        encode_row(Format::R5G6B5, &pixels, &mut row).unwrap();
+        assert_eq!(u16::from_le_bytes(row), 0xF800 | (32 << 5));
+        encode_row(Format::A1R5G5B5, &pixels, &mut row).unwrap();
+        assert_eq!(u16::from_le_bytes(row) >> 15, 1);
+        encode_row(Format::A4R4G4B4, &pixels, &mut row).unwrap();
+        assert_eq!(u16::from_le_bytes(row), 0xFF80);
+        encode_row(Format::A8L8, &[[0.5, 0.5, 0.5, 1.0]], &mut row).unwrap();
+        assert_eq!(row, [128, 255]);
+
+        let mut row = [0u8; 4];
+        encode_row(Format::G16R16F, &[[0.5, 2.0, 0.0, 0.0]], &mut row).unwrap();
+        assert_eq!(row, [0x00, 0x38, 0x00, 0x40]);
+    }
+
+    #[test]
+    fn decodes_channels() {
+        let mut pixels = [[0.0f32; 4]];
+
+        decode_row(Format::L8, &[255], &mut pixels).unwrap();
+        assert_eq!(pixels[0], [1.0; 4]);
+        decode_row(Format::A8, &[255], &mut pixels).unwrap();
+        assert_eq!(pixels[0], [0.0, 0.0, 0.0, 1.0]);
+        decode_row(Format::R16F, &f32_to_f16(0.25).to_le_bytes(), &mut pixels).unwrap();
+        assert_eq!(pixels[0], [0.25, 1.0, 1.0, 1.0]);
+        decode_row(Format::V8U8, &[0x81, 0x7F], &mut pixels).unwrap();
+        assert_eq!(pixels[0][..2], [-1.0, 1.0]);
+        decode_row(Format::R32F, &1.5f32.to_le_bytes(), &mut pixels).unwrap();
+        assert_eq!(pixels[0][0], 1.5);
+    }
+
+    #[test]
+    fn rejects_unsupported_formats_and_short_rows() {
+        let mut pixels = [[0.0f32; 4]];
+
+        assert!(!is_supported(Format::DXT1));
+        assert!(matches!(
+            decode_row(Format::DXT1, &[0; 8], &mut pixels),
+            Err(FormatError::Unsupported(_))
+        ));
+        assert!(matches!(
+            decode_row(Format::A8R8G8B8, &[0; 3], &mut pixels),
+            Err(FormatError::BufferTooSmall { .. })
+        ));
+    }
+
+    #[test]
+    fn round_trips_srgb() {
+        for value in [0.0f32, 0.001, 0.2, 0.5, 1.0] {
+            assert!((srgb_to_linear(linear_to_srgb(value)) - value).abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! Provides an in-memory image which can be converted between formats.
+
+use crate::{
+    format::{bc, convert, CompressOptions, FormatError, FormatInfo},
+    std::types::Format,
+};
+
+/// Represents a 2D array of pixels in memory.
+///
+/// Rows are `pitch` bytes apart. A row of a block compressed format is a row of blocks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub format: Format,
+    pub width: u32,
+    pub height: u32,
+    pub pitch: usize,
+    pub data: Vec<u8>,
+}
+
+impl Image {
+    /// Returns a new, zero-filled image with tightly packed rows.
+    pub fn new(format: Format, width: u32, height: u32) -> Result<Self, FormatError> {
+        let info = info(format)?;
+
+        Ok(Self {
+            format,
+            width,
+            height,
+            pitch: info.row_pitch(width),
+            data: vec![0; info.surface_size(width, height)],
+        })
+    }
+
+    /// Wraps tightly packed pixel data.
+    pub fn from_data(
+        format: Format,
+        width: u32,
+        height: u32,
+        data: Vec<u8>,
+    ) -> Result<Self, FormatError> {
+        let pitch = info(format)?.row_pitch(width);
+        Self::with_pitch(format, width, height, pitch, data)
+    }
+
+    /// Wraps pixel data whose rows are `pitch` bytes apart.
+    pub fn with_pitch(
+        format: Format,
+        width: u32,
+        height: u32,
+        pitch: usize,
+        data: Vec<u8>,
+    ) -> Result<Self, FormatError> {
+        let info = info(format)?;
+        let row_bytes = info.row_pitch(width);
+        if pitch < row_bytes {
+            return Err(FormatError::InvalidPitch { pitch, row_bytes });
+        }
+
+        let expected = pitch * (info.rows(height) - 1) + row_bytes;
+        if data.len() < expected {
+            return Err(FormatError::BufferTooSmall {
+                expected,
+                found: data.len(),
+            });
+        }
+
+        Ok(Self {
+            format,
+            width,
+            height,
+            pitch,
+            data,
+        })
+    }
+
+    /// Wraps tightly packed `[r, g, b, a]` bytes, which is the memory layout of
+    /// [`Format::A8B8G8R8`].
+    pub fn from_rgba8(width: u32, height: u32, data: Vec<u8>) -> Result<Self, FormatError> {
+        Self::from_data(Format::A8B8G8R8, width, height, data)
+    }
+
+    /// Returns a [`Format::A32B32G32R32F`] image containing `pixels`.
+    pub fn from_rgba_f32(
+        width: u32,
+        height: u32,
+        pixels: &[[f32; 4]],
+    ) -> Result<Self, FormatError> {
+        let data = pixels
+            .iter()
+            .flatten()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        Self::from_data(Format::A32B32G32R32F, width, height, data)
+    }
+
+    pub fn info(&self) -> &'static FormatInfo {
+        self.format.info().expect("image format has no layout")
+    }
+
+    /// Returns the number of rows, which is the number of rows of blocks for compressed formats.
+    pub fn rows(&self) -> usize {
+        self.info().rows(self.height)
+    }
+
+    /// Returns the number of bytes used by each row.
+    pub fn row_bytes(&self) -> usize {
+        self.info().row_pitch(self.width)
+    }
+
+    pub fn row(&self, row: usize) -> &[u8] {
+        &self.data[row * self.pitch..][..self.row_bytes()]
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> &mut [u8] {
+        let row_bytes = self.row_bytes();
+        &mut self.data[row * self.pitch..][..row_bytes]
+    }
+
+    /// Returns a copy of the image in another format, with tightly packed rows.
+    ///
+    /// Block compressed formats are compressed with the default [`CompressOptions`].
+    pub fn convert(&self, format: Format) -> Result<Self, FormatError> {
+        self.convert_with(format, &CompressOptions::default())
+    }
+
+    /// Returns a copy of the image in another format, compressing it with `options` if `format`
+    /// is block compressed.
+    pub fn convert_with(
+        &self,
+        format: Format,
+        options: &CompressOptions,
+    ) -> Result<Self, FormatError> {
+        if format != self.format && bc::is_supported(self.format) {
+            let rgba = bc::decompress(self.format, self.width, self.height, &self.packed())?;
+            return Self::from_rgba8(self.width, self.height, rgba)?.convert_with(format, options);
+        }
+
+        if format != self.format && bc::is_supported(format) {
+            let rgba = self.to_rgba8()?;
+            let data = bc::compress(format, self.width, self.height, &rgba, options)?;
+            return Self::from_data(format, self.width, self.height, data);
+        }
+
+        let mut converted = Self::new(format, self.width, self.height)?;
+
+        for row in 0..self.rows() {
+            convert::convert_row(
+                self.format,
+                self.row(row),
+                format,
+                converted.row_mut(row),
+                self.width as usize,
+            )?;
+        }
+
+        Ok(converted)
+    }
+
+    /// Decodes every pixel into `[r, g, b, a]` values, row by row.
+    pub fn to_rgba_f32(&self) -> Result<Vec<[f32; 4]>, FormatError> {
+        if bc::is_supported(self.format) {
+            return self.convert(Format::A32B32G32R32F)?.to_rgba_f32();
+        }
+
+        let width = self.width as usize;
+        let mut pixels = vec![[0.0; 4]; width * self.height as usize];
+
+        for (row, destination) in pixels.chunks_exact_mut(width.max(1)).enumerate() {
+            convert::decode_row(self.format, self.row(row), destination)?;
+        }
+
+        Ok(pixels)
+    }
+
+    /// Returns the pixels as tightly packed `[r, g, b, a]` bytes.
+    pub fn to_rgba8(&self) -> Result<Vec<u8>, FormatError> {
+        Ok(self.convert(Format::A8B8G8R8)?.data)
+    }
+
+    /// Returns the data without any padding between rows.
+    fn packed(&self) -> Vec<u8> {
+        (0..self.rows())
+            .flat_map(|row| self.row(row))
+            .copied()
+            .collect()
+    }
+}
+
+fn info(format: Format) -> Result<&'static FormatInfo, FormatError> {
+    format.info().ok_or(FormatError::Unsupported(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba8() -> Vec<u8> {
+        (0..4 * 4 * 3).map(|i| (i * 37 % 256) as u8).collect()
+    }
+
+    #[test]
+    fn converts_losslessly_between_wide_formats() {
+        let image = Image::from_rgba8(4, 3, rgba8()).unwrap();
+
+        for format in [
+            Format::A8R8G8B8,
+            Format::A8B8G8R8,
+            Format::A16B16G16R16,
+            Format::A16B16G16R16F,
+            Format::A32B32G32R32F,
+        ] {
+            assert!(convert::is_supported(format));
+            let converted = image.convert(format).unwrap();
+            assert_eq!(converted.to_rgba8().unwrap(), rgba8(), "{:?}", format);
+        }
+
+        let converted = image.convert(Format::A2R10G10B10).unwrap();
+        for (converted, source) in converted
+            .to_rgba8()
+            .unwrap()
+            .chunks(4)
+            .zip(rgba8().chunks(4))
+        {
+            assert_eq!(converted[..3], source[..3]);
+        }
+    }
+
+    #[test]
+    fn converts_to_narrow_formats() {
+        let image = Image::from_rgba8(4, 3, rgba8()).unwrap();
+
+        let converted = image.convert(Format::X8R8G8B8).unwrap();
+        for (converted, source) in converted
+            .to_rgba8()
+            .unwrap()
+            .chunks(4)
+            .zip(rgba8().chunks(4))
+        {
+            assert_eq!(converted[..3], source[..3]);
+            assert_eq!(converted[3], 255);
+        }
+
+        let converted = image.convert(Format::R5G6B5).unwrap();
+        for (converted, source) in converted
+            .to_rgba8()
+            .unwrap()
+            .chunks(4)
+            .zip(rgba8().chunks(4))
+        {
+            for channel in 0..3 {
+                assert!((converted[channel] as i32 - source[channel] as i32).abs() <= 4);
+            }
+        }
+    }
+
+    #[test]
+    fn removes_row_padding() {
+        let image =
+            Image::with_pitch(Format::L8, 4, 2, 6, vec![1, 2, 3, 4, 9, 9, 5, 6, 7, 8]).unwrap();
+        assert_eq!(
+            image.convert(Format::L8).unwrap().data,
+            [1, 2, 3, 4, 5, 6, 7, 8]
+        );
+
+        assert!(Image::with_pitch(Format::L8, 4, 2, 3, vec![0; 10]).is_err());
+        assert!(Image::from_data(Format::L8, 4, 2, vec![0; 7]).is_err());
+    }
+}
@@ -0,0 +1,426 @@
+//! Loads PNG, TGA, BMP, JPEG and HDR files into textures without D3DX. Requires the `image`
+//! feature.
+//!
+//! Loading happens in two stages. [`load_image`] decodes a file and prepares every mip level in
+//! the requested [`Format`] without touching Direct3D, and [`texture_from_image`] uploads the
+//! result into a new [`Texture`].
+//!
+//! # Usage
+//!
+//! ```rs
+//! let options = LoadOptions::new()
+//!     .format(Format::DXT5)
+//!     .srgb(true)
+//!     .mips(MipOptions::new().filter(MipFilter::Kaiser));
+//!
+//! let bytes = fs::read("grass.png")?;
+//! let texture =
+//!     texture_from_image(&device, &bytes, None, Usage::default(), Pool::Managed, &options)?;
+//! ```
+
+use std::path::Path;
+
+use ::image::{DynamicImage, ImageFormat};
+
+#[cfg(windows)]
+use crate::std::{
+    interfaces::{Device, Texture},
+    types::{Pool, Usage},
+};
+use crate::{
+    error::WindowsError,
+    format::{
+        linear_to_srgb, mip, srgb_to_linear, CompressOptions, FormatError, Image, MipOptions,
+    },
+    std::types::Format,
+};
+
+/// Represents an error which occurred while loading an image.
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+    #[error("Windows Error: {from}")]
+    Windows {
+        #[from]
+        from: WindowsError,
+    },
+
+    #[error("failed to decode image: {from}")]
+    Decode {
+        #[from]
+        from: ::image::ImageError,
+    },
+
+    #[error("failed to convert image: {from}")]
+    Format {
+        #[from]
+        from: FormatError,
+    },
+
+    #[error("the file type could not be determined")]
+    UnknownFileType,
+}
+
+/// Represents the file formats which can be loaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileType {
+    Png,
+    Tga,
+    Bmp,
+    Jpeg,
+    Hdr,
+}
+
+impl FileType {
+    /// Identifies a file by its signature. TGA files have none, so they are only recognized by
+    /// their extension.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match ::image::guess_format(bytes).ok()? {
+            ImageFormat::Png => Some(Self::Png),
+            ImageFormat::Bmp => Some(Self::Bmp),
+            ImageFormat::Jpeg => Some(Self::Jpeg),
+            ImageFormat::Hdr => Some(Self::Hdr),
+            _ => None,
+        }
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "png" => Some(Self::Png),
+            "tga" => Some(Self::Tga),
+            "bmp" | "dib" => Some(Self::Bmp),
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "hdr" => Some(Self::Hdr),
+            _ => None,
+        }
+    }
+
+    fn image_format(self) -> ImageFormat {
+        match self {
+            Self::Png => ImageFormat::Png,
+            Self::Tga => ImageFormat::Tga,
+            Self::Bmp => ImageFormat::Bmp,
+            Self::Jpeg => ImageFormat::Jpeg,
+            Self::Hdr => ImageFormat::Hdr,
+        }
+    }
+}
+
+/// Represents the options passed to [`load_image`] and [`texture_from_image`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LoadOptions {
+    /// The format of the texture. By default, 8 bit files are loaded as [`Format::A8R8G8B8`],
+    /// 16 bit files as [`Format::A16B16G16R16`] and HDR files as [`Format::A16B16G16R16F`].
+    pub format: Option<Format>,
+    /// Multiplies color by alpha.
+    pub premultiply_alpha: bool,
+    /// Treats the color of integer files as sRGB encoded. Premultiplication and mip filtering
+    /// then happen in linear space, and floating point formats receive linear values. HDR files
+    /// are encoded to sRGB when stored in an integer format.
+    pub srgb: bool,
+    /// Generates mip levels. Only the base level is loaded by default.
+    ///
+    /// The `srgb` option of the mips is replaced by whether the stored color is sRGB encoded.
+    pub mips: Option<MipOptions>,
+    /// The options used when `format` is block compressed.
+    pub compress: CompressOptions,
+}
+
+impl LoadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    pub fn premultiply_alpha(mut self, premultiply_alpha: bool) -> Self {
+        self.premultiply_alpha = premultiply_alpha;
+        self
+    }
+
+    pub fn srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    pub fn mips(mut self, mips: MipOptions) -> Self {
+        self.mips = Some(mips);
+        self
+    }
+
+    pub fn compress(mut self, compress: CompressOptions) -> Self {
+        self.compress = compress;
+        self
+    }
+}
+
+/// Decodes a file and returns its mip levels, converted as described by `options`.
+///
+/// `file_type` is detected from the contents of the file if it is [`None`], which is not
+/// possible for TGA files.
+pub fn load_image(
+    bytes: &[u8],
+    file_type: Option<FileType>,
+    options: &LoadOptions,
+) -> Result<Vec<Image>, LoadError> {
+    let file_type = file_type
+        .or_else(|| FileType::from_bytes(bytes))
+        .ok_or(LoadError::UnknownFileType)?;
+    let decoded = ::image::load_from_memory_with_format(bytes, file_type.image_format())?;
+
+    let (source_format, hdr) = match decoded {
+        DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => {
+            (Format::A16B16G16R16F, true)
+        }
+        DynamicImage::ImageLuma16(_)
+        | DynamicImage::ImageLumaA16(_)
+        | DynamicImage::ImageRgb16(_)
+        | DynamicImage::ImageRgba16(_) => (Format::A16B16G16R16, false),
+        _ => (Format::A8R8G8B8, false),
+    };
+    let format = options.format.unwrap_or(source_format);
+
+    let (width, height) = (decoded.width(), decoded.height());
+    let mut pixels = decoded
+        .to_rgba32f()
+        .into_raw()
+        .chunks_exact(4)
+        .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+        .collect::<Vec<_>>();
+
+    // Whether the decoded and stored color are sRGB encoded.
+    let source_srgb = options.srgb && !hdr;
+    let target_srgb = options.srgb && !format.info().is_some_and(|info| info.is_float());
+
+    if source_srgb && (options.premultiply_alpha || !target_srgb) {
+        pixels
+            .iter_mut()
+            .for_each(|pixel| transfer(pixel, srgb_to_linear));
+    }
+    if options.premultiply_alpha {
+        for pixel in &mut pixels {
+            let alpha = pixel[3];
+            pixel[..3].iter_mut().for_each(|c| *c *= alpha);
+        }
+    }
+    if target_srgb && (options.premultiply_alpha || !source_srgb) {
+        pixels
+            .iter_mut()
+            .for_each(|pixel| transfer(pixel, linear_to_srgb));
+    }
+
+    let base = Image::from_rgba_f32(width, height, &pixels)?;
+    let levels = match options.mips {
+        Some(mips) => mip::generate_mips(&base, &mips.srgb(target_srgb))?,
+        None => vec![base],
+    };
+
+    levels
+        .iter()
+        .map(|level| Ok(level.convert_with(format, &options.compress)?))
+        .collect()
+}
+
+/// Decodes a file and creates a [`Texture`] containing it.
+///
+/// [`Pool::Default`] textures are filled through a system memory staging texture and
+/// [`Device::update_texture`].
+#[cfg(windows)]
+pub fn texture_from_image(
+    device: &Device,
+    bytes: &[u8],
+    file_type: Option<FileType>,
+    usage: Usage,
+    pool: Pool,
+    options: &LoadOptions,
+) -> Result<Texture, LoadError> {
+    let levels = load_image(bytes, file_type, options)?;
+    let base = &levels[0];
+
+    let create = |pool: Pool, usage: Usage| {
+        device.create_texture(
+            base.width,
+            base.height,
+            levels.len() as u32,
+            usage,
+            base.format,
+            pool,
+        )
+    };
+
+    let fill = |texture: &mut Texture| -> Result<(), LoadError> {
+        for (level, image) in levels.iter().enumerate() {
+            texture.write_image(level as u32, image)?;
+        }
+        Ok(())
+    };
+
+    let mut texture = create(pool, usage)?;
+    if let Pool::Default = pool {
+        let mut staging = create(Pool::SystemMem, Usage::default())?;
+        fill(&mut staging)?;
+        device.update_texture(&staging, &texture)?;
+    } else {
+        fill(&mut texture)?;
+    }
+
+    Ok(texture)
+}
+
+fn transfer(pixel: &mut [f32; 4], function: fn(f32) -> f32) {
+    pixel[..3].iter_mut().for_each(|c| *c = function(*c));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use ::image::{Rgb, Rgb32FImage, Rgba, RgbaImage};
+
+    use super::*;
+
+    fn encode(image: DynamicImage, format: ImageFormat) -> Vec<u8> {
+        let mut bytes = Cursor::new(Vec::new());
+        image.write_to(&mut bytes, format).unwrap();
+        bytes.into_inner()
+    }
+
+    /// An 8x4 image, whose right half is translucent.
+    fn sample() -> RgbaImage {
+        RgbaImage::from_fn(8, 4, |x, y| {
+            Rgba([
+                (x * 32) as u8,
+                (y * 64) as u8,
+                200,
+                if x < 4 { 255 } else { 128 },
+            ])
+        })
+    }
+
+    fn png() -> Vec<u8> {
+        encode(DynamicImage::ImageRgba8(sample()), ImageFormat::Png)
+    }
+
+    #[test]
+    fn identifies_files() {
+        assert_eq!(FileType::from_bytes(&png()), Some(FileType::Png));
+        assert_eq!(
+            FileType::from_path("textures/grass.TGA"),
+            Some(FileType::Tga)
+        );
+        assert_eq!(
+            FileType::from_path("textures/grass.jpeg"),
+            Some(FileType::Jpeg)
+        );
+        assert_eq!(FileType::from_path("textures/grass"), None);
+
+        let tga = encode(DynamicImage::ImageRgba8(sample()), ImageFormat::Tga);
+        assert_eq!(FileType::from_bytes(&tga), None);
+        assert!(matches!(
+            load_image(&tga, None, &LoadOptions::new()),
+            Err(LoadError::UnknownFileType)
+        ));
+    }
+
+    #[test]
+    fn decodes_lossless_files() {
+        for (format, file_type) in [
+            (ImageFormat::Png, FileType::Png),
+            (ImageFormat::Bmp, FileType::Bmp),
+            (ImageFormat::Tga, FileType::Tga),
+        ] {
+            let bytes = encode(DynamicImage::ImageRgba8(sample()), format);
+            let levels = load_image(&bytes, Some(file_type), &LoadOptions::new()).unwrap();
+
+            assert_eq!(levels.len(), 1);
+            assert_eq!(levels[0].format, Format::A8R8G8B8);
+            assert_eq!(
+                levels[0].to_rgba8().unwrap(),
+                sample().into_raw(),
+                "{:?}",
+                file_type
+            );
+        }
+    }
+
+    #[test]
+    fn decodes_jpeg_files() {
+        let rgb = DynamicImage::ImageRgba8(sample()).to_rgb8();
+        let bytes = encode(DynamicImage::ImageRgb8(rgb), ImageFormat::Jpeg);
+        assert_eq!(FileType::from_bytes(&bytes), Some(FileType::Jpeg));
+
+        let levels = load_image(&bytes, None, &LoadOptions::new()).unwrap();
+        assert_eq!((levels[0].width, levels[0].height), (8, 4));
+    }
+
+    #[test]
+    fn keeps_hdr_files_in_floating_point() {
+        let hdr = Rgb32FImage::from_fn(4, 4, |x, _| Rgb([x as f32 * 2.0, 0.5, 0.25]));
+        let bytes = encode(DynamicImage::ImageRgb32F(hdr), ImageFormat::Hdr);
+        assert_eq!(FileType::from_bytes(&bytes), Some(FileType::Hdr));
+
+        let levels = load_image(&bytes, None, &LoadOptions::new()).unwrap();
+        assert_eq!(levels[0].format, Format::A16B16G16R16F);
+        assert!((levels[0].to_rgba_f32().unwrap()[3][0] - 6.0).abs() < 0.1);
+
+        // HDR files are linear, so storing them in an sRGB format encodes them.
+        let options = LoadOptions::new().format(Format::A8R8G8B8).srgb(true);
+        let levels = load_image(&bytes, None, &options).unwrap();
+        assert_eq!(
+            levels[0].to_rgba8().unwrap()[2],
+            (linear_to_srgb(0.25) * 255.0).round() as u8
+        );
+    }
+
+    #[test]
+    fn premultiplies_alpha() {
+        // The blue channel of the translucent pixel at x = 5.
+        let blue = 5 * 4 + 2;
+
+        let options = LoadOptions::new().premultiply_alpha(true);
+        let levels = load_image(&png(), None, &options).unwrap();
+        assert_eq!(levels[0].to_rgba8().unwrap()[blue], 100);
+
+        let options = LoadOptions::new().premultiply_alpha(true).srgb(true);
+        let levels = load_image(&png(), None, &options).unwrap();
+        let expected = linear_to_srgb(srgb_to_linear(200.0 / 255.0) * 128.0 / 255.0);
+        assert_eq!(
+            levels[0].to_rgba8().unwrap()[blue],
+            (expected * 255.0).round() as u8
+        );
+    }
+
+    #[test]
+    fn decodes_srgb_into_float_formats() {
+        let options = LoadOptions::new().srgb(true).format(Format::A16B16G16R16F);
+        let levels = load_image(&png(), None, &options).unwrap();
+
+        let pixel = levels[0].to_rgba_f32().unwrap()[0];
+        assert!((pixel[2] - srgb_to_linear(200.0 / 255.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn generates_compressed_mips() {
+        let options = LoadOptions::new()
+            .format(Format::DXT5)
+            .mips(MipOptions::new());
+        let levels = load_image(&png(), None, &options).unwrap();
+
+        assert_eq!(
+            levels
+                .iter()
+                .map(|level| (level.width, level.height, level.format))
+                .collect::<Vec<_>>(),
+            [
+                (8, 4, Format::DXT5),
+                (4, 2, Format::DXT5),
+                (2, 1, Format::DXT5),
+                (1, 1, Format::DXT5),
+            ]
+        );
+    }
+}
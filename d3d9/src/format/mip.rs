@@ -0,0 +1,426 @@
+//! Generates mip chains on the CPU, for devices or formats which cannot use
+//! [`Usage::auto_genmipmap`](crate::std::types::Usage::auto_genmipmap).
+//!
+//! Each level is half the size of the previous one, rounded down, as Direct3D expects. Levels are
+//! filtered from the previous level in floating point, so odd sizes are resampled rather than
+//! having a row or column dropped.
+//!
+//! # Usage
+//!
+//! ```rs
+//! let options = MipOptions::new().filter(MipFilter::Kaiser).srgb(true);
+//! texture.write_mips(&image, &options)?;
+//! ```
+
+use std::f32::consts::PI;
+
+use crate::format::{linear_to_srgb, srgb_to_linear, FormatError, Image};
+
+/// Represents the filter used to shrink each level.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MipFilter {
+    /// Averages the pixels each destination pixel covers.
+    #[default]
+    Box,
+    /// Weights pixels by their distance, which blurs slightly more than [`MipFilter::Box`].
+    Triangle,
+    /// A Kaiser windowed sinc, which keeps levels sharp at the cost of slight ringing.
+    Kaiser,
+}
+
+impl MipFilter {
+    /// Returns the radius of the filter, in destination pixels.
+    fn support(self) -> f32 {
+        match self {
+            Self::Box => 0.5,
+            Self::Triangle => 1.0,
+            Self::Kaiser => 3.0,
+        }
+    }
+
+    fn weight(self, t: f32) -> f32 {
+        match self {
+            Self::Box => (t.abs() <= 0.5) as u8 as f32,
+            Self::Triangle => (1.0 - t.abs()).max(0.0),
+            Self::Kaiser => {
+                const ALPHA: f32 = 4.0;
+                let width = self.support();
+                if t.abs() >= width {
+                    return 0.0;
+                }
+
+                let window =
+                    bessel_i0(ALPHA * (1.0 - (t / width).powi(2)).sqrt()) / bessel_i0(ALPHA);
+                sinc(t) * window
+            }
+        }
+    }
+}
+
+/// Represents the options passed to [`generate_mips`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MipOptions {
+    pub filter: MipFilter,
+    /// Filters color in linear space, treating the image as sRGB encoded.
+    pub srgb: bool,
+    /// Wraps around the edges when filtering, for textures which tile.
+    pub wrap: bool,
+    /// Scales the alpha of each level so that the fraction of pixels above this threshold
+    /// matches the base level, which keeps alpha tested foliage from thinning out.
+    pub alpha_coverage: Option<f32>,
+    /// The number of levels to generate, including the base level. `0` generates a full chain.
+    pub levels: u32,
+}
+
+impl MipOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn filter(mut self, filter: MipFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn alpha_coverage(mut self, threshold: f32) -> Self {
+        self.alpha_coverage = Some(threshold);
+        self
+    }
+
+    pub fn levels(mut self, levels: u32) -> Self {
+        self.levels = levels;
+        self
+    }
+}
+
+/// Returns the number of levels in a full chain, down to 1x1.
+pub fn level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// Returns the size of `level` of a chain.
+pub fn level_size(width: u32, height: u32, level: u32) -> (u32, u32) {
+    ((width >> level).max(1), (height >> level).max(1))
+}
+
+/// Generates a mip chain from `base`, which is returned as the first level.
+///
+/// Every level has the format of `base`.
+pub fn generate_mips(base: &Image, options: &MipOptions) -> Result<Vec<Image>, FormatError> {
+    let full = level_count(base.width, base.height);
+    let count = match options.levels {
+        0 => full,
+        levels => levels.min(full),
+    };
+
+    let mut pixels = base.to_rgba_f32()?;
+    if options.srgb {
+        for pixel in &mut pixels {
+            pixel[..3].iter_mut().for_each(|c| *c = srgb_to_linear(*c));
+        }
+    }
+
+    let coverage = options
+        .alpha_coverage
+        .map(|threshold| (threshold, alpha_coverage(&pixels, threshold)));
+
+    let (mut width, mut height) = (base.width, base.height);
+    let mut levels = vec![base.clone()];
+
+    for level in 1..count {
+        let (next_width, next_height) = level_size(base.width, base.height, level);
+
+        let horizontal = resample(&pixels, (width, height), next_width, false, options);
+        pixels = resample(
+            &horizontal,
+            (next_width, height),
+            next_height,
+            true,
+            options,
+        );
+        (width, height) = (next_width, next_height);
+
+        let mut output = pixels.clone();
+        if let Some((threshold, target)) = coverage {
+            scale_alpha_coverage(&mut output, threshold, target);
+        }
+        if options.srgb {
+            for pixel in &mut output {
+                pixel[..3].iter_mut().for_each(|c| *c = linear_to_srgb(*c));
+            }
+        }
+
+        levels.push(Image::from_rgba_f32(width, height, &output)?.convert(base.format)?);
+    }
+
+    Ok(levels)
+}
+
+/// Resamples `pixels` along one axis to `length` pixels.
+fn resample(
+    pixels: &[[f32; 4]],
+    (width, height): (u32, u32),
+    length: u32,
+    vertical: bool,
+    options: &MipOptions,
+) -> Vec<[f32; 4]> {
+    let (width, height) = (width as usize, height as usize);
+    let source_length = if vertical { height } else { width };
+    let weights = weights(source_length, length as usize, options);
+
+    let (output_width, output_height) = if vertical {
+        (width, length as usize)
+    } else {
+        (length as usize, height)
+    };
+
+    let mut output = vec![[0.0; 4]; output_width * output_height];
+    for y in 0..output_height {
+        for x in 0..output_width {
+            let taps = &weights[if vertical { y } else { x }];
+            let pixel = &mut output[y * output_width + x];
+
+            for &(index, weight) in taps {
+                let source = if vertical {
+                    pixels[index * width + x]
+                } else {
+                    pixels[y * width + index]
+                };
+                for c in 0..4 {
+                    pixel[c] += source[c] * weight;
+                }
+            }
+        }
+    }
+
+    output
+}
+
+/// Returns the source pixels and normalized weights which make up each destination pixel.
+fn weights(source: usize, destination: usize, options: &MipOptions) -> Vec<Vec<(usize, f32)>> {
+    let scale = source as f32 / destination as f32;
+    let radius = options.filter.support() * scale;
+
+    (0..destination)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * scale;
+            let first = (center - radius).floor() as isize;
+            let last = (center + radius).ceil() as isize;
+
+            let mut taps = (first..last)
+                .filter_map(|j| {
+                    let weight = match options.filter {
+                        // The box covers fractions of pixels when the size is odd.
+                        MipFilter::Box => {
+                            let start = (center - scale / 2.0).max(j as f32);
+                            let end = (center + scale / 2.0).min(j as f32 + 1.0);
+                            (end - start).max(0.0)
+                        }
+                        filter => filter.weight((j as f32 + 0.5 - center) / scale),
+                    };
+
+                    (weight != 0.0).then(|| (address(j, source, options.wrap), weight))
+                })
+                .collect::<Vec<_>>();
+
+            let total = taps.iter().map(|(_, weight)| weight).sum::<f32>();
+            if total != 0.0 {
+                taps.iter_mut().for_each(|(_, weight)| *weight /= total);
+            }
+            taps
+        })
+        .collect()
+}
+
+fn address(index: isize, length: usize, wrap: bool) -> usize {
+    if wrap {
+        index.rem_euclid(length as isize) as usize
+    } else {
+        index.clamp(0, length as isize - 1) as usize
+    }
+}
+
+/// Returns the fraction of pixels whose alpha is above `threshold`.
+fn alpha_coverage(pixels: &[[f32; 4]], threshold: f32) -> f32 {
+    alpha_coverage_scaled(pixels, threshold, 1.0)
+}
+
+fn alpha_coverage_scaled(pixels: &[[f32; 4]], threshold: f32, scale: f32) -> f32 {
+    let covered = pixels
+        .iter()
+        .filter(|pixel| (pixel[3] * scale).min(1.0) > threshold)
+        .count();
+    covered as f32 / pixels.len().max(1) as f32
+}
+
+/// Scales alpha by the smallest factor which keeps the coverage at `threshold` at `target`.
+fn scale_alpha_coverage(pixels: &mut [[f32; 4]], threshold: f32, target: f32) {
+    let (mut low, mut high) = (0.0f32, 4.0f32);
+    for _ in 0..16 {
+        let middle = (low + high) / 2.0;
+        if alpha_coverage_scaled(pixels, threshold, middle) < target {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    for pixel in pixels {
+        pixel[3] = (pixel[3] * high).min(1.0);
+    }
+}
+
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-6 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// The zeroth order modified Bessel function of the first kind.
+fn bessel_i0(x: f32) -> f32 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half = x / 2.0;
+
+    for k in 1..32 {
+        term *= half / k as f32;
+        let squared = term * term;
+        sum += squared;
+        if squared < sum * 1e-8 {
+            break;
+        }
+    }
+
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std::types::Format;
+
+    fn image(width: u32, height: u32, pixel: impl Fn(u32, u32) -> [f32; 4]) -> Image {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| pixel(x, y))
+            .collect::<Vec<_>>();
+
+        Image::from_rgba_f32(width, height, &pixels).unwrap()
+    }
+
+    /// Returns the fraction of pixels whose alpha is above `0.5`.
+    fn coverage(image: &Image) -> f32 {
+        let pixels = image.to_rgba_f32().unwrap();
+        pixels.iter().filter(|pixel| pixel[3] > 0.5).count() as f32 / pixels.len() as f32
+    }
+
+    #[test]
+    fn computes_chain_sizes() {
+        assert_eq!(level_count(256, 256), 9);
+        assert_eq!(level_count(300, 17), 9);
+        assert_eq!(level_count(1, 1), 1);
+        assert_eq!(level_size(300, 17, 5), (9, 1));
+
+        let base = Image::new(Format::A8R8G8B8, 13, 7).unwrap();
+        let levels = generate_mips(&base, &MipOptions::new()).unwrap();
+        assert_eq!(
+            levels
+                .iter()
+                .map(|level| (level.width, level.height))
+                .collect::<Vec<_>>(),
+            [(13, 7), (6, 3), (3, 1), (1, 1)]
+        );
+        assert!(levels.iter().all(|level| level.format == Format::A8R8G8B8));
+
+        let levels = generate_mips(&base, &MipOptions::new().levels(2)).unwrap();
+        assert_eq!(levels.len(), 2);
+    }
+
+    #[test]
+    fn preserves_constant_images_with_every_filter() {
+        let color = [0.25, 0.5, 0.75, 1.0];
+        let base = image(17, 9, |_, _| color);
+
+        for filter in [MipFilter::Box, MipFilter::Triangle, MipFilter::Kaiser] {
+            for wrap in [false, true] {
+                let options = MipOptions::new().filter(filter).wrap(wrap);
+
+                for level in generate_mips(&base, &options).unwrap() {
+                    for pixel in level.to_rgba_f32().unwrap() {
+                        for channel in 0..4 {
+                            assert!(
+                                (pixel[channel] - color[channel]).abs() < 1e-4,
+                                "{:?} {:?}",
+                                filter,
+                                pixel
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn averages_with_the_box_filter() {
+        let base = image(2, 2, |x, y| [(x + 2 * y) as f32 / 3.0, 0.0, 0.0, 1.0]);
+        let levels = generate_mips(&base, &MipOptions::new()).unwrap();
+        assert!((levels[1].to_rgba_f32().unwrap()[0][0] - 0.5).abs() < 1e-6);
+
+        // An odd width averages all three pixels into one.
+        let base = image(3, 1, |x, _| [x as f32, 0.0, 0.0, 1.0]);
+        let levels = generate_mips(&base, &MipOptions::new()).unwrap();
+        assert!((levels[1].to_rgba_f32().unwrap()[0][0] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn filters_srgb_images_in_linear_space() {
+        let base = image(2, 1, |x, _| [x as f32, x as f32, x as f32, 1.0]);
+
+        let linear = generate_mips(&base, &MipOptions::new()).unwrap();
+        assert!((linear[1].to_rgba_f32().unwrap()[0][0] - 0.5).abs() < 1e-6);
+
+        let srgb = generate_mips(&base, &MipOptions::new().srgb(true)).unwrap();
+        assert!((srgb[1].to_rgba_f32().unwrap()[0][0] - 0.7354).abs() < 1e-3);
+    }
+
+    #[test]
+    fn preserves_alpha_coverage() {
+        let base = image(16, 16, |x, y| {
+            let alpha = if (x * 7 + y * 3) % 5 == 0 { 1.0 } else { 0.0 };
+            [1.0, 1.0, 1.0, alpha]
+        });
+        let target = coverage(&base);
+
+        let plain = generate_mips(&base, &MipOptions::new()).unwrap();
+        let preserved = generate_mips(&base, &MipOptions::new().alpha_coverage(0.5)).unwrap();
+
+        for level in 1..3 {
+            assert!((coverage(&preserved[level]) - target).abs() <= 0.1);
+            assert!(coverage(&plain[level]) < target - 0.1);
+        }
+
+        assert_eq!(plain, generate_mips(&base, &MipOptions::new()).unwrap());
+    }
+
+    #[test]
+    fn keeps_compressed_formats() {
+        let base = Image::new(Format::DXT1, 8, 8).unwrap();
+        let levels = generate_mips(&base, &MipOptions::new()).unwrap();
+
+        assert_eq!(levels.len(), 4);
+        assert!(levels.iter().all(|level| level.format == Format::DXT1));
+    }
+}
@@ -0,0 +1,254 @@
+//! Provides metadata for every [`Format`] and platform-independent conversion between them.
+//!
+//! [`Format::info`] describes the memory layout of a format, such as the size of its pixels or
+//! blocks and the position of each channel. [`Image`] holds pixels in memory and can be converted
+//! between any of the formats [`convert`] understands, so that data can be uploaded in whichever
+//! format [`Context::check_device_format`] accepts. The `DXT` formats are handled by the block
+//! compressor in [`bc`], and [`mip`] generates mip chains. [`capture`] reads render targets back
+//! and saves them as DDS or PNG files. With the `image` feature, `load` reads image files into
+//! textures.
+//!
+//! # Usage
+//!
+//! ```rs
+//! let info = Format::DXT1.info().unwrap();
+//! assert_eq!(info.row_pitch(256), 512);
+//!
+//! let image = Image::from_rgba8(256, 256, pixels)?.convert(Format::R5G6B5)?;
+//! texture.write_image(0, &image)?;
+//! ```
+//!
+//! [`Context::check_device_format`]: crate::std::interfaces::Context::check_device_format
+
+pub mod bc;
+pub mod capture;
+pub mod convert;
+mod image;
+#[cfg(feature = "image")]
+pub mod load;
+pub mod mip;
+mod table;
+
+pub use bc::{CompressOptions, Quality};
+pub use convert::{linear_to_srgb, srgb_to_linear};
+pub use image::Image;
+pub use mip::{generate_mips, MipFilter, MipOptions};
+
+use crate::{error::WindowsError, std::types::Format};
+
+/// Represents an error which occurred while converting pixel data.
+#[derive(Debug, thiserror::Error)]
+pub enum FormatError {
+    #[error("Windows Error: {from}")]
+    Windows {
+        #[from]
+        from: WindowsError,
+    },
+
+    #[error("unsupported format: {0:?}")]
+    Unsupported(Format),
+
+    #[error("expected at least {expected} bytes, found {found}")]
+    BufferTooSmall { expected: usize, found: usize },
+
+    #[error("row pitch {pitch} is smaller than a row of {row_bytes} bytes")]
+    InvalidPitch { pitch: usize, row_bytes: usize },
+
+    #[error("expected a {}x{} image, found {}x{}", .expected.0, .expected.1, .found.0, .found.1)]
+    SizeMismatch {
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+}
+
+/// Represents the broad category of a format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FormatKind {
+    /// Red, green, blue and alpha channels.
+    Color,
+    /// A luminance channel, which is sampled into red, green and blue.
+    Luminance,
+    /// Signed bump map channels, optionally with an unsigned luminance channel.
+    Bump,
+    /// Depth and stencil channels.
+    Depth,
+    /// 4x4 blocks of compressed color.
+    Compressed,
+    /// Pairs of pixels which share chroma or green samples, such as `YUY2`.
+    Packed,
+    /// Indices into a palette.
+    Palette,
+    /// Index buffer elements.
+    Index,
+}
+
+/// Represents a channel of a pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Channel {
+    Red,
+    Green,
+    Blue,
+    Alpha,
+    Luminance,
+    U,
+    V,
+    W,
+    Q,
+    Depth,
+    Stencil,
+    Palette,
+}
+
+/// Represents how the bits of a channel are interpreted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChannelType {
+    /// An unsigned integer mapped onto `[0, 1]`.
+    Unorm,
+    /// A signed integer mapped onto `[-1, 1]`.
+    Snorm,
+    /// A 16 or 32 bit floating point number.
+    Float,
+    /// An integer which is not normalized, such as a palette index.
+    Uint,
+}
+
+/// Represents the position of a channel within a little-endian pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChannelInfo {
+    pub channel: Channel,
+    pub channel_type: ChannelType,
+    /// The offset of the lowest bit of the channel.
+    pub offset: u8,
+    pub bits: u8,
+}
+
+/// Represents the memory layout of a [`Format`].
+///
+/// Uncompressed formats are described as blocks of a single pixel, so that the row pitch of every
+/// format can be computed the same way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FormatInfo {
+    pub format: Format,
+    pub kind: FormatKind,
+    pub block_width: u32,
+    pub block_height: u32,
+    /// The number of bytes in a single block.
+    pub block_bytes: u32,
+    /// The channels of a pixel, lowest first. Empty for compressed and packed formats.
+    pub channels: &'static [ChannelInfo],
+    pub has_alpha: bool,
+    /// Whether the format can be read or written with sRGB conversion, provided the device
+    /// supports it for the usage in question.
+    pub srgb: bool,
+}
+
+impl FormatInfo {
+    /// Returns the average number of bits per pixel.
+    pub fn bits_per_pixel(&self) -> u32 {
+        self.block_bytes * 8 / (self.block_width * self.block_height)
+    }
+
+    /// Returns the number of bits of `channel`, or `0` if the format does not have it.
+    pub fn bits(&self, channel: Channel) -> u32 {
+        self.channel(channel).map_or(0, |info| info.bits as u32)
+    }
+
+    pub fn channel(&self, channel: Channel) -> Option<&ChannelInfo> {
+        self.channels.iter().find(|info| info.channel == channel)
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.kind == FormatKind::Compressed
+    }
+
+    pub fn is_depth(&self) -> bool {
+        self.kind == FormatKind::Depth && self.channel(Channel::Depth).is_some()
+    }
+
+    pub fn has_stencil(&self) -> bool {
+        self.channel(Channel::Stencil).is_some()
+    }
+
+    pub fn is_float(&self) -> bool {
+        self.channels
+            .iter()
+            .any(|info| info.channel_type == ChannelType::Float)
+    }
+
+    pub fn is_luminance(&self) -> bool {
+        self.kind == FormatKind::Luminance
+    }
+
+    pub fn is_bump(&self) -> bool {
+        self.kind == FormatKind::Bump
+    }
+
+    /// Returns the number of bytes in a tightly packed row of `width` pixels, where a row of a
+    /// block compressed format is a row of blocks.
+    pub fn row_pitch(&self, width: u32) -> usize {
+        width.max(1).div_ceil(self.block_width) as usize * self.block_bytes as usize
+    }
+
+    /// Returns the number of rows in a surface `height` pixels high.
+    pub fn rows(&self, height: u32) -> usize {
+        height.max(1).div_ceil(self.block_height) as usize
+    }
+
+    /// Returns the `(row pitch, row count)` of a tightly packed surface of the given size.
+    pub fn surface_layout(&self, width: u32, height: u32) -> (usize, usize) {
+        (self.row_pitch(width), self.rows(height))
+    }
+
+    /// Returns the number of bytes in a tightly packed surface of the given size.
+    pub fn surface_size(&self, width: u32, height: u32) -> usize {
+        self.row_pitch(width) * self.rows(height)
+    }
+}
+
+impl Format {
+    /// Returns the layout of the format, or [`None`] for formats without one, such as
+    /// [`Format::Unknown`] and [`Format::VertexData`].
+    pub fn info(self) -> Option<&'static FormatInfo> {
+        table::FORMATS.iter().find(|info| info.format == self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_pitches() {
+        let dxt1 = Format::DXT1.info().unwrap();
+        assert_eq!(dxt1.row_pitch(256), 512);
+        assert_eq!(dxt1.row_pitch(1), 8);
+        assert_eq!(dxt1.rows(5), 2);
+        assert_eq!(dxt1.bits_per_pixel(), 4);
+        assert_eq!(Format::DXT5.info().unwrap().surface_size(5, 5), 64);
+
+        assert_eq!(Format::R8G8B8.info().unwrap().row_pitch(3), 9);
+        assert_eq!(Format::YUY2.info().unwrap().row_pitch(3), 8);
+        assert_eq!(Format::A1.info().unwrap().row_pitch(9), 2);
+        assert_eq!(Format::A1.info().unwrap().bits_per_pixel(), 1);
+        assert_eq!(
+            Format::A8R8G8B8.info().unwrap().surface_layout(0, 0),
+            (4, 1)
+        );
+    }
+
+    #[test]
+    fn describes_formats() {
+        assert!(Format::Unknown.info().is_none());
+        assert!(Format::DXT3.info().unwrap().is_compressed());
+        assert!(Format::D24S8.info().unwrap().is_depth());
+        assert!(Format::D24S8.info().unwrap().has_stencil());
+        assert!(!Format::D24X8.info().unwrap().has_stencil());
+        assert!(Format::R16F.info().unwrap().is_float());
+        assert!(Format::A8L8.info().unwrap().is_luminance());
+        assert!(Format::V8U8.info().unwrap().is_bump());
+        assert!(Format::A8R8G8B8.info().unwrap().has_alpha);
+        assert!(!Format::X8R8G8B8.info().unwrap().has_alpha);
+        assert_eq!(Format::R5G6B5.info().unwrap().bits(Channel::Green), 6);
+        assert_eq!(Format::R5G6B5.info().unwrap().bits(Channel::Alpha), 0);
+    }
+}
@@ -0,0 +1,191 @@
+//! The layout of every format which has one.
+
+use crate::{
+    format::{
+        Channel::{self, *},
+        ChannelInfo, ChannelType, FormatInfo,
+        FormatKind::{self, Bump, Color, Compressed, Index, Packed},
+    },
+    std::types::Format,
+};
+
+const fn unorm(channel: Channel, offset: u8, bits: u8) -> ChannelInfo {
+    ChannelInfo {
+        channel,
+        channel_type: ChannelType::Unorm,
+        offset,
+        bits,
+    }
+}
+
+const fn snorm(channel: Channel, offset: u8, bits: u8) -> ChannelInfo {
+    ChannelInfo {
+        channel,
+        channel_type: ChannelType::Snorm,
+        offset,
+        bits,
+    }
+}
+
+const fn float(channel: Channel, offset: u8, bits: u8) -> ChannelInfo {
+    ChannelInfo {
+        channel,
+        channel_type: ChannelType::Float,
+        offset,
+        bits,
+    }
+}
+
+const fn uint(channel: Channel, offset: u8, bits: u8) -> ChannelInfo {
+    ChannelInfo {
+        channel,
+        channel_type: ChannelType::Uint,
+        offset,
+        bits,
+    }
+}
+
+const fn pixel(
+    format: Format,
+    kind: FormatKind,
+    bytes: u32,
+    channels: &'static [ChannelInfo],
+    srgb: bool,
+) -> FormatInfo {
+    let mut has_alpha = false;
+    let mut i = 0;
+    while i < channels.len() {
+        if matches!(channels[i].channel, Channel::Alpha) {
+            has_alpha = true;
+        }
+        i += 1;
+    }
+
+    FormatInfo {
+        format,
+        kind,
+        block_width: 1,
+        block_height: 1,
+        block_bytes: bytes,
+        channels,
+        has_alpha,
+        srgb,
+    }
+}
+
+const fn block(
+    format: Format,
+    kind: FormatKind,
+    (block_width, block_height, block_bytes): (u32, u32, u32),
+    has_alpha: bool,
+    srgb: bool,
+) -> FormatInfo {
+    FormatInfo {
+        format,
+        kind,
+        block_width,
+        block_height,
+        block_bytes,
+        channels: &[],
+        has_alpha,
+        srgb,
+    }
+}
+
+#[rustfmt::skip]
+pub(super) const FORMATS: &[FormatInfo] = &[
+    pixel(Format::R8G8B8, Color, 3, &[unorm(Blue, 0, 8), unorm(Green, 8, 8), unorm(Red, 16, 8)], true),
+    pixel(Format::A8R8G8B8, Color, 4, &[unorm(Blue, 0, 8), unorm(Green, 8, 8), unorm(Red, 16, 8), unorm(Alpha, 24, 8)], true),
+    pixel(Format::X8R8G8B8, Color, 4, &[unorm(Blue, 0, 8), unorm(Green, 8, 8), unorm(Red, 16, 8)], true),
+    pixel(Format::A8B8G8R8, Color, 4, &[unorm(Red, 0, 8), unorm(Green, 8, 8), unorm(Blue, 16, 8), unorm(Alpha, 24, 8)], true),
+    pixel(Format::X8B8G8R8, Color, 4, &[unorm(Red, 0, 8), unorm(Green, 8, 8), unorm(Blue, 16, 8)], true),
+    pixel(Format::R5G6B5, Color, 2, &[unorm(Blue, 0, 5), unorm(Green, 5, 6), unorm(Red, 11, 5)], false),
+    pixel(Format::X1R5G5B5, Color, 2, &[unorm(Blue, 0, 5), unorm(Green, 5, 5), unorm(Red, 10, 5)], false),
+    pixel(Format::A1R5G5B5, Color, 2, &[unorm(Blue, 0, 5), unorm(Green, 5, 5), unorm(Red, 10, 5), unorm(Alpha, 15, 1)], false),
+    pixel(Format::A4R4G4B4, Color, 2, &[unorm(Blue, 0, 4), unorm(Green, 4, 4), unorm(Red, 8, 4), unorm(Alpha, 12, 4)], false),
+    pixel(Format::X4R4G4B4, Color, 2, &[unorm(Blue, 0, 4), unorm(Green, 4, 4), unorm(Red, 8, 4)], false),
+    pixel(Format::R3G3B2, Color, 1, &[unorm(Blue, 0, 2), unorm(Green, 2, 3), unorm(Red, 5, 3)], false),
+    pixel(Format::A8R3G3B2, Color, 2, &[unorm(Blue, 0, 2), unorm(Green, 2, 3), unorm(Red, 5, 3), unorm(Alpha, 8, 8)], false),
+    pixel(Format::A8, Color, 1, &[unorm(Alpha, 0, 8)], false),
+    pixel(Format::A2B10G10R10, Color, 4, &[unorm(Red, 0, 10), unorm(Green, 10, 10), unorm(Blue, 20, 10), unorm(Alpha, 30, 2)], false),
+    pixel(Format::A2R10G10B10, Color, 4, &[unorm(Blue, 0, 10), unorm(Green, 10, 10), unorm(Red, 20, 10), unorm(Alpha, 30, 2)], false),
+    pixel(Format::G16R16, Color, 4, &[unorm(Red, 0, 16), unorm(Green, 16, 16)], false),
+    pixel(Format::A16B16G16R16, Color, 8, &[unorm(Red, 0, 16), unorm(Green, 16, 16), unorm(Blue, 32, 16), unorm(Alpha, 48, 16)], false),
+    pixel(Format::R16F, Color, 2, &[float(Red, 0, 16)], false),
+    pixel(Format::G16R16F, Color, 4, &[float(Red, 0, 16), float(Green, 16, 16)], false),
+    pixel(Format::A16B16G16R16F, Color, 8, &[float(Red, 0, 16), float(Green, 16, 16), float(Blue, 32, 16), float(Alpha, 48, 16)], false),
+    pixel(Format::R32F, Color, 4, &[float(Red, 0, 32)], false),
+    pixel(Format::G32R32F, Color, 8, &[float(Red, 0, 32), float(Green, 32, 32)], false),
+    pixel(Format::A32B32G32R32F, Color, 16, &[float(Red, 0, 32), float(Green, 32, 32), float(Blue, 64, 32), float(Alpha, 96, 32)], false),
+    pixel(Format::L8, FormatKind::Luminance, 1, &[unorm(Luminance, 0, 8)], true),
+    pixel(Format::A8L8, FormatKind::Luminance, 2, &[unorm(Luminance, 0, 8), unorm(Alpha, 8, 8)], true),
+    pixel(Format::A4L4, FormatKind::Luminance, 1, &[unorm(Luminance, 0, 4), unorm(Alpha, 4, 4)], false),
+    pixel(Format::L16, FormatKind::Luminance, 2, &[unorm(Luminance, 0, 16)], false),
+    pixel(Format::V8U8, Bump, 2, &[snorm(U, 0, 8), snorm(V, 8, 8)], false),
+    pixel(Format::L6V5U5, Bump, 2, &[snorm(U, 0, 5), snorm(V, 5, 5), unorm(Luminance, 10, 6)], false),
+    pixel(Format::X8L8V8U8, Bump, 4, &[snorm(U, 0, 8), snorm(V, 8, 8), unorm(Luminance, 16, 8)], false),
+    pixel(Format::Q8W8V8U8, Bump, 4, &[snorm(U, 0, 8), snorm(V, 8, 8), snorm(W, 16, 8), snorm(Q, 24, 8)], false),
+    pixel(Format::V16U16, Bump, 4, &[snorm(U, 0, 16), snorm(V, 16, 16)], false),
+    pixel(Format::A2W10V10U10, Bump, 4, &[snorm(U, 0, 10), snorm(V, 10, 10), snorm(W, 20, 10), unorm(Alpha, 30, 2)], false),
+    pixel(Format::Q16W16V16U16, Bump, 8, &[snorm(U, 0, 16), snorm(V, 16, 16), snorm(W, 32, 16), snorm(Q, 48, 16)], false),
+    pixel(Format::CxV8U8, Bump, 2, &[snorm(U, 0, 8), snorm(V, 8, 8)], false),
+    pixel(Format::D16Lockable, FormatKind::Depth, 2, &[unorm(Depth, 0, 16)], false),
+    pixel(Format::D16, FormatKind::Depth, 2, &[unorm(Depth, 0, 16)], false),
+    pixel(Format::D32, FormatKind::Depth, 4, &[unorm(Depth, 0, 32)], false),
+    pixel(Format::D32Lockable, FormatKind::Depth, 4, &[unorm(Depth, 0, 32)], false),
+    pixel(Format::D32fLockable, FormatKind::Depth, 4, &[float(Depth, 0, 32)], false),
+    pixel(Format::D15S1, FormatKind::Depth, 2, &[uint(Stencil, 0, 1), unorm(Depth, 1, 15)], false),
+    pixel(Format::D24S8, FormatKind::Depth, 4, &[uint(Stencil, 0, 8), unorm(Depth, 8, 24)], false),
+    pixel(Format::D24X8, FormatKind::Depth, 4, &[unorm(Depth, 8, 24)], false),
+    pixel(Format::D24X4S4, FormatKind::Depth, 4, &[uint(Stencil, 0, 4), unorm(Depth, 8, 24)], false),
+    pixel(Format::D24FS8, FormatKind::Depth, 4, &[uint(Stencil, 0, 8), float(Depth, 8, 24)], false),
+    pixel(Format::S8Lockable, FormatKind::Depth, 1, &[uint(Stencil, 0, 8)], false),
+    pixel(Format::P8, FormatKind::Palette, 1, &[uint(Palette, 0, 8)], false),
+    pixel(Format::A8P8, FormatKind::Palette, 2, &[uint(Palette, 0, 8), unorm(Alpha, 8, 8)], false),
+    pixel(Format::Index16, Index, 2, &[], false),
+    pixel(Format::Index32, Index, 4, &[], false),
+    block(Format::DXT1, Compressed, (4, 4, 8), true, true),
+    block(Format::DXT2, Compressed, (4, 4, 16), true, true),
+    block(Format::DXT3, Compressed, (4, 4, 16), true, true),
+    block(Format::DXT4, Compressed, (4, 4, 16), true, true),
+    block(Format::DXT5, Compressed, (4, 4, 16), true, true),
+    block(Format::UYVY, Packed, (2, 1, 4), false, false),
+    block(Format::YUY2, Packed, (2, 1, 4), false, false),
+    block(Format::R8g8B8g8, Packed, (2, 1, 4), false, false),
+    block(Format::G8r8G8b8, Packed, (2, 1, 4), false, false),
+    block(Format::A1, Color, (8, 1, 1), true, false),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_each_format_once() {
+        for (i, info) in FORMATS.iter().enumerate() {
+            assert!(
+                FORMATS[i + 1..]
+                    .iter()
+                    .all(|other| other.format != info.format),
+                "{:?} is listed twice",
+                info.format
+            );
+        }
+    }
+
+    #[test]
+    fn channels_fit_in_their_pixel_without_overlapping() {
+        for info in FORMATS {
+            let mut used = 0u128;
+
+            for channel in info.channels {
+                let end = channel.offset as u32 + channel.bits as u32;
+                assert!(end <= info.block_bytes * 8, "{:?}", info.format);
+
+                let bits = (u128::MAX >> (128 - channel.bits as u32)) << channel.offset;
+                assert_eq!(used & bits, 0, "{:?}", info.format);
+                used |= bits;
+            }
+        }
+    }
+}
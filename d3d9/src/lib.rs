@@ -23,13 +23,25 @@
 //! location in which the SDK is installed is typically constant, although it can be overridden
 //! via the `DIRECTX_SDK` environment variable when compiling.
 //!
+//! On other platforms the COM interfaces are unavailable, but the parts which never touch
+//! Direct3D, such as [`dds`], [`format`], [`shader`] and the recording backend, still build.
+//!
 //! # Safety
 //!
 //! For the most part, no *panics* should be thrown. If the `HRESULT` returned by an API function
 //! is not `0` (success), then any further processing will be terminated and an [`Err`] variant
 //! will be returned. This *should* prevent any null pointers.
 
+// The derive macros refer to `::d3d9`, which lets them be used in the crate's own tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as d3d9;
+
+#[cfg(windows)]
 pub mod com;
+pub mod dds;
 pub mod error;
+pub mod format;
+pub mod shader;
 pub mod std;
+mod sys;
 pub mod x;
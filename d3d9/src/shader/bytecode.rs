@@ -0,0 +1,807 @@
+//! Provides the typed representation of a shader token stream.
+
+use crate::{shader::ShaderError, std::types::DeclUsage};
+
+/// The token which terminates every shader.
+pub const END_TOKEN: u32 = 0x0000_FFFF;
+
+const COMMENT_OPCODE: u32 = 0xFFFE;
+const PREDICATED: u32 = 1 << 28;
+const CO_ISSUE: u32 = 1 << 30;
+const RELATIVE: u32 = 1 << 13;
+
+/// Represents the pipeline stage a shader runs in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ShaderType {
+    Vertex,
+    Pixel,
+}
+
+/// Represents the version token at the start of every shader.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShaderVersion {
+    pub shader_type: ShaderType,
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl ShaderVersion {
+    pub fn new(shader_type: ShaderType, major: u8, minor: u8) -> Self {
+        Self {
+            shader_type,
+            major,
+            minor,
+        }
+    }
+
+    /// Decodes a version token, returning [`None`] if it is not a Shader Model 1-3 version.
+    pub fn from_token(token: u32) -> Option<Self> {
+        let shader_type = match token >> 16 {
+            0xFFFE => ShaderType::Vertex,
+            0xFFFF => ShaderType::Pixel,
+            _ => return None,
+        };
+
+        let version = Self::new(shader_type, (token >> 8) as u8, token as u8);
+        (1..=3).contains(&version.major).then_some(version)
+    }
+
+    pub fn to_token(self) -> u32 {
+        let prefix = match self.shader_type {
+            ShaderType::Vertex => 0xFFFE,
+            ShaderType::Pixel => 0xFFFF,
+        };
+
+        prefix << 16 | (self.major as u32) << 8 | self.minor as u32
+    }
+
+    /// Returns whether the version is at least `major.minor`.
+    pub fn at_least(self, major: u8, minor: u8) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
+}
+
+macro_rules! opcodes {
+    ($($name:ident = $value:literal => $mnemonic:literal,)*) => {
+        /// Represents the operation performed by an [`Instruction`].
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[repr(u16)]
+        pub enum Opcode {
+            $($name = $value,)*
+        }
+
+        impl Opcode {
+            pub fn from_raw(value: u16) -> Option<Self> {
+                match value {
+                    $($value => Some(Self::$name),)*
+                    _ => None,
+                }
+            }
+
+            /// Returns the assembler mnemonic, before any version or control specific renaming.
+            pub fn mnemonic(self) -> &'static str {
+                match self {
+                    $(Self::$name => $mnemonic,)*
+                }
+            }
+        }
+    };
+}
+
+opcodes! {
+    Nop = 0 => "nop",
+    Mov = 1 => "mov",
+    Add = 2 => "add",
+    Sub = 3 => "sub",
+    Mad = 4 => "mad",
+    Mul = 5 => "mul",
+    Rcp = 6 => "rcp",
+    Rsq = 7 => "rsq",
+    Dp3 = 8 => "dp3",
+    Dp4 = 9 => "dp4",
+    Min = 10 => "min",
+    Max = 11 => "max",
+    Slt = 12 => "slt",
+    Sge = 13 => "sge",
+    Exp = 14 => "exp",
+    Log = 15 => "log",
+    Lit = 16 => "lit",
+    Dst = 17 => "dst",
+    Lrp = 18 => "lrp",
+    Frc = 19 => "frc",
+    M4x4 = 20 => "m4x4",
+    M4x3 = 21 => "m4x3",
+    M3x4 = 22 => "m3x4",
+    M3x3 = 23 => "m3x3",
+    M3x2 = 24 => "m3x2",
+    Call = 25 => "call",
+    CallNz = 26 => "callnz",
+    Loop = 27 => "loop",
+    Ret = 28 => "ret",
+    EndLoop = 29 => "endloop",
+    Label = 30 => "label",
+    Dcl = 31 => "dcl",
+    Pow = 32 => "pow",
+    Crs = 33 => "crs",
+    Sgn = 34 => "sgn",
+    Abs = 35 => "abs",
+    Nrm = 36 => "nrm",
+    SinCos = 37 => "sincos",
+    Rep = 38 => "rep",
+    EndRep = 39 => "endrep",
+    If = 40 => "if",
+    IfC = 41 => "if",
+    Else = 42 => "else",
+    EndIf = 43 => "endif",
+    Break = 44 => "break",
+    BreakC = 45 => "break",
+    MovA = 46 => "mova",
+    DefB = 47 => "defb",
+    DefI = 48 => "defi",
+    TexCoord = 64 => "texcoord",
+    TexKill = 65 => "texkill",
+    Tex = 66 => "tex",
+    TexBem = 67 => "texbem",
+    TexBemL = 68 => "texbeml",
+    TexReg2Ar = 69 => "texreg2ar",
+    TexReg2Gb = 70 => "texreg2gb",
+    TexM3x2Pad = 71 => "texm3x2pad",
+    TexM3x2Tex = 72 => "texm3x2tex",
+    TexM3x3Pad = 73 => "texm3x3pad",
+    TexM3x3Tex = 74 => "texm3x3tex",
+    TexM3x3Spec = 76 => "texm3x3spec",
+    TexM3x3VSpec = 77 => "texm3x3vspec",
+    ExpP = 78 => "expp",
+    LogP = 79 => "logp",
+    Cnd = 80 => "cnd",
+    Def = 81 => "def",
+    TexReg2Rgb = 82 => "texreg2rgb",
+    TexDp3Tex = 83 => "texdp3tex",
+    TexM3x2Depth = 84 => "texm3x2depth",
+    TexDp3 = 85 => "texdp3",
+    TexM3x3 = 86 => "texm3x3",
+    TexDepth = 87 => "texdepth",
+    Cmp = 88 => "cmp",
+    Bem = 89 => "bem",
+    Dp2Add = 90 => "dp2add",
+    Dsx = 91 => "dsx",
+    Dsy = 92 => "dsy",
+    TexLdd = 93 => "texldd",
+    SetP = 94 => "setp",
+    TexLdl = 95 => "texldl",
+    BreakP = 96 => "breakp",
+    Phase = 0xFFFD => "phase",
+}
+
+impl Opcode {
+    /// Returns whether the instruction has a destination parameter and how many source
+    /// parameters follow it.
+    ///
+    /// Declarations and constant definitions are laid out differently and report no sources.
+    pub fn operands(self, version: ShaderVersion) -> (bool, usize) {
+        use Opcode::*;
+
+        match self {
+            Nop | Ret | EndLoop | EndRep | Else | EndIf | Break | Phase => (false, 0),
+            Call | Label | Rep | If | BreakP => (false, 1),
+            CallNz | Loop | IfC | BreakC => (false, 2),
+            Dcl | Def | DefI | DefB | TexKill | TexDepth => (true, 0),
+            Mov | Rcp | Rsq | Exp | Log | Lit | Frc | Abs | Nrm | MovA | TexBem | TexBemL
+            | TexReg2Ar | TexReg2Gb | TexM3x2Pad | TexM3x2Tex | TexM3x3Pad | TexM3x3Tex
+            | TexM3x3VSpec | ExpP | LogP | TexReg2Rgb | TexDp3Tex | TexM3x2Depth | TexDp3
+            | TexM3x3 | Dsx | Dsy => (true, 1),
+            Add | Sub | Mul | Dp3 | Dp4 | Min | Max | Slt | Sge | Dst | M4x4 | M4x3 | M3x4
+            | M3x3 | M3x2 | Pow | Crs | TexM3x3Spec | Bem | SetP | TexLdl => (true, 2),
+            Mad | Lrp | Cnd | Cmp | Dp2Add => (true, 3),
+            TexLdd => (true, 4),
+            SinCos if version.major < 3 => (true, 3),
+            SinCos => (true, 1),
+            Sgn if version.major < 3 => (true, 3),
+            Sgn => (true, 1),
+            TexCoord if version.at_least(1, 4) => (true, 1),
+            TexCoord => (true, 0),
+            Tex if version.major >= 2 => (true, 2),
+            Tex if version.at_least(1, 4) => (true, 1),
+            Tex => (true, 0),
+        }
+    }
+}
+
+/// Represents the register file a parameter refers to.
+///
+/// Some values are shared between register files, for example [`RegisterType::Address`] refers to
+/// the texture registers in pixel shaders.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RegisterType {
+    Temp,
+    Input,
+    Const,
+    Address,
+    RastOut,
+    AttrOut,
+    TexCrdOut,
+    ConstInt,
+    ColorOut,
+    DepthOut,
+    Sampler,
+    Const2,
+    Const3,
+    Const4,
+    ConstBool,
+    Loop,
+    TempFloat16,
+    MiscType,
+    Label,
+    Predicate,
+}
+
+impl RegisterType {
+    pub fn from_raw(value: u32) -> Option<Self> {
+        use RegisterType::*;
+
+        const TYPES: [RegisterType; 20] = [
+            Temp,
+            Input,
+            Const,
+            Address,
+            RastOut,
+            AttrOut,
+            TexCrdOut,
+            ConstInt,
+            ColorOut,
+            DepthOut,
+            Sampler,
+            Const2,
+            Const3,
+            Const4,
+            ConstBool,
+            Loop,
+            TempFloat16,
+            MiscType,
+            Label,
+            Predicate,
+        ];
+
+        TYPES.get(value as usize).copied()
+    }
+}
+
+/// Represents a single register, such as `r0` or `c12`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Register {
+    pub register_type: RegisterType,
+    pub index: u32,
+}
+
+impl Register {
+    /// Returns the index of a constant register within the whole float constant file, which is
+    /// split across [`RegisterType::Const`] to [`RegisterType::Const4`].
+    pub fn constant_index(&self) -> Option<u32> {
+        match self.register_type {
+            RegisterType::Const => Some(self.index),
+            RegisterType::Const2 => Some(self.index + 2048),
+            RegisterType::Const3 => Some(self.index + 4096),
+            RegisterType::Const4 => Some(self.index + 6144),
+            _ => None,
+        }
+    }
+}
+
+/// Represents the register used to index another register, such as `a0.x` in `c[a0.x + 4]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RelativeAddress {
+    pub register: Register,
+    pub component: u8,
+}
+
+/// Represents the modifier applied to a source parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SourceModifier {
+    None,
+    Negate,
+    Bias,
+    BiasNegate,
+    Sign,
+    SignNegate,
+    Complement,
+    X2,
+    X2Negate,
+    Dz,
+    Dw,
+    Abs,
+    AbsNegate,
+    Not,
+}
+
+impl SourceModifier {
+    pub fn from_raw(value: u32) -> Option<Self> {
+        use SourceModifier::*;
+
+        const MODIFIERS: [SourceModifier; 14] = [
+            None, Negate, Bias, BiasNegate, Sign, SignNegate, Complement, X2, X2Negate, Dz, Dw,
+            Abs, AbsNegate, Not,
+        ];
+
+        MODIFIERS.get(value as usize).copied()
+    }
+}
+
+/// Represents the register an instruction writes to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DestinationParameter {
+    pub register: Register,
+    pub relative: Option<RelativeAddress>,
+    /// The written components, with `x` in the lowest bit.
+    pub write_mask: u8,
+    pub saturate: bool,
+    pub partial_precision: bool,
+    pub centroid: bool,
+    /// The power of two the result is scaled by, from `-8` to `7`.
+    pub shift: i8,
+}
+
+/// Represents a register an instruction reads from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SourceParameter {
+    pub register: Register,
+    pub relative: Option<RelativeAddress>,
+    /// The source component read for each of `x`, `y`, `z` and `w`.
+    pub swizzle: [u8; 4],
+    pub modifier: SourceModifier,
+}
+
+/// Represents the resource type declared for a sampler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SamplerTextureType {
+    Unknown,
+    Texture2D,
+    Cube,
+    Volume,
+}
+
+/// Represents the extra token of a `dcl` instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Declaration {
+    Usage { usage: DeclUsage, index: u32 },
+    Sampler(SamplerTextureType),
+}
+
+/// Represents the value of a `def`, `defi` or `defb` instruction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Constant {
+    Float([f32; 4]),
+    Int([i32; 4]),
+    Bool(bool),
+}
+
+/// Represents the comparison encoded in the control bits of `ifc`, `breakc` and `setp`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Comparison {
+    Gt,
+    Eq,
+    Ge,
+    Lt,
+    Ne,
+    Le,
+}
+
+/// Represents a single instruction and its parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    /// The opcode specific control bits, such as the comparison of `ifc`.
+    pub control: u8,
+    pub co_issue: bool,
+    pub predicate: Option<SourceParameter>,
+    pub destination: Option<DestinationParameter>,
+    pub sources: Vec<SourceParameter>,
+    pub declaration: Option<Declaration>,
+    pub constant: Option<Constant>,
+}
+
+impl Instruction {
+    pub fn comparison(&self) -> Option<Comparison> {
+        if !matches!(self.opcode, Opcode::IfC | Opcode::BreakC | Opcode::SetP) {
+            return None;
+        }
+
+        match self.control & 0x7 {
+            1 => Some(Comparison::Gt),
+            2 => Some(Comparison::Eq),
+            3 => Some(Comparison::Ge),
+            4 => Some(Comparison::Lt),
+            5 => Some(Comparison::Ne),
+            6 => Some(Comparison::Le),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a comment block, which carries data such as the constant table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment {
+    pub data: Vec<u32>,
+}
+
+impl Comment {
+    /// Returns the four character code most comment blocks start with, such as `CTAB`.
+    pub fn four_cc(&self) -> Option<[u8; 4]> {
+        self.data.first().map(|code| code.to_le_bytes())
+    }
+
+    pub fn bytes(&self) -> Vec<u8> {
+        self.data
+            .iter()
+            .flat_map(|token| token.to_le_bytes())
+            .collect()
+    }
+}
+
+/// Represents either an instruction or a comment, in token stream order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Statement {
+    Instruction(Instruction),
+    Comment(Comment),
+}
+
+/// Represents a parsed shader.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shader {
+    pub version: ShaderVersion,
+    pub statements: Vec<Statement>,
+}
+
+impl Shader {
+    /// Parses a token stream, which must start with a version token and finish with an end token.
+    ///
+    /// Any tokens after the end token are ignored.
+    pub fn parse(tokens: &[u32]) -> Result<Self, ShaderError> {
+        let mut reader = Reader { tokens, offset: 0 };
+
+        let version_token = reader.next()?;
+        let version = ShaderVersion::from_token(version_token)
+            .ok_or(ShaderError::InvalidVersion(version_token))?;
+
+        let mut statements = Vec::new();
+        loop {
+            let offset = reader.offset;
+            let token = reader.next()?;
+
+            if token == END_TOKEN {
+                break;
+            }
+
+            if token & 0xFFFF == COMMENT_OPCODE {
+                let length = (token >> 16) & 0x7FFF;
+                let data = reader.take(length as usize)?.to_vec();
+                statements.push(Statement::Comment(Comment { data }));
+                continue;
+            }
+
+            let instruction = reader.instruction(version, token, offset)?;
+            statements.push(Statement::Instruction(instruction));
+        }
+
+        Ok(Self {
+            version,
+            statements,
+        })
+    }
+
+    /// Parses a little-endian byte stream, such as the contents of a compiled shader buffer.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ShaderError> {
+        if !bytes.len().is_multiple_of(4) {
+            return Err(ShaderError::UnalignedLength(bytes.len()));
+        }
+
+        let tokens = bytes
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect::<Vec<_>>();
+
+        Self::parse(&tokens)
+    }
+
+    pub fn instructions(&self) -> impl Iterator<Item = &Instruction> {
+        self.statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Instruction(instruction) => Some(instruction),
+                Statement::Comment(_) => None,
+            })
+    }
+
+    pub fn comments(&self) -> impl Iterator<Item = &Comment> {
+        self.statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Comment(comment) => Some(comment),
+                Statement::Instruction(_) => None,
+            })
+    }
+}
+
+struct Reader<'a> {
+    tokens: &'a [u32],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn next(&mut self) -> Result<u32, ShaderError> {
+        let token = *self
+            .tokens
+            .get(self.offset)
+            .ok_or(ShaderError::UnexpectedEnd)?;
+        self.offset += 1;
+
+        Ok(token)
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u32], ShaderError> {
+        let tokens = self
+            .tokens
+            .get(self.offset..self.offset + count)
+            .ok_or(ShaderError::UnexpectedEnd)?;
+        self.offset += count;
+
+        Ok(tokens)
+    }
+
+    fn instruction(
+        &mut self,
+        version: ShaderVersion,
+        token: u32,
+        offset: usize,
+    ) -> Result<Instruction, ShaderError> {
+        let opcode = Opcode::from_raw(token as u16).ok_or(ShaderError::UnknownOpcode {
+            opcode: token as u16,
+            offset,
+        })?;
+
+        let mut instruction = Instruction {
+            opcode,
+            control: (token >> 16) as u8,
+            co_issue: token & CO_ISSUE != 0,
+            predicate: None,
+            destination: None,
+            sources: Vec::new(),
+            declaration: None,
+            constant: None,
+        };
+
+        let start = self.offset;
+        match opcode {
+            Opcode::Dcl => {
+                let declaration = self.next()?;
+                let destination = self.destination(version)?;
+                instruction.declaration = Some(
+                    if destination.register.register_type == RegisterType::Sampler {
+                        Declaration::Sampler(match (declaration >> 27) & 0xF {
+                            0 => SamplerTextureType::Unknown,
+                            2 => SamplerTextureType::Texture2D,
+                            3 => SamplerTextureType::Cube,
+                            4 => SamplerTextureType::Volume,
+                            _ => return Err(ShaderError::InvalidDeclaration { offset }),
+                        })
+                    } else {
+                        Declaration::Usage {
+                            usage: decl_usage_from_raw(declaration & 0x1F)
+                                .ok_or(ShaderError::InvalidDeclaration { offset })?,
+                            index: (declaration >> 16) & 0xF,
+                        }
+                    },
+                );
+                instruction.destination = Some(destination);
+            }
+            Opcode::Def => {
+                instruction.destination = Some(self.destination(version)?);
+                let values = <[u32; 4]>::try_from(self.take(4)?).unwrap();
+                instruction.constant = Some(Constant::Float(values.map(f32::from_bits)));
+            }
+            Opcode::DefI => {
+                instruction.destination = Some(self.destination(version)?);
+                let values = <[u32; 4]>::try_from(self.take(4)?).unwrap();
+                instruction.constant = Some(Constant::Int(values.map(|value| value as i32)));
+            }
+            Opcode::DefB => {
+                instruction.destination = Some(self.destination(version)?);
+                instruction.constant = Some(Constant::Bool(self.next()? != 0));
+            }
+            _ => {
+                let (has_destination, n_sources) = opcode.operands(version);
+                if has_destination {
+                    instruction.destination = Some(self.destination(version)?);
+                }
+                if token & PREDICATED != 0 {
+                    instruction.predicate = Some(self.source(version)?);
+                }
+                for _ in 0..n_sources {
+                    let source = self.source(version)?;
+                    instruction.sources.push(source);
+                }
+            }
+        }
+
+        // Shader Model 2 and later encode the number of parameter tokens in the instruction.
+        if version.major >= 2 {
+            let expected = ((token >> 24) & 0xF) as usize;
+            let found = self.offset - start;
+            if expected != found {
+                return Err(ShaderError::LengthMismatch {
+                    offset,
+                    expected,
+                    found,
+                });
+            }
+        }
+
+        Ok(instruction)
+    }
+
+    fn register(&self, token: u32) -> Result<Register, ShaderError> {
+        let raw = (token >> 28) & 0x7 | (token >> 8) & 0x18;
+        let register_type =
+            RegisterType::from_raw(raw).ok_or(ShaderError::UnknownRegisterType {
+                register_type: raw,
+                offset: self.offset - 1,
+            })?;
+
+        Ok(Register {
+            register_type,
+            index: token & 0x7FF,
+        })
+    }
+
+    fn relative(
+        &mut self,
+        version: ShaderVersion,
+        token: u32,
+    ) -> Result<Option<RelativeAddress>, ShaderError> {
+        if token & RELATIVE == 0 {
+            return Ok(None);
+        }
+
+        // Shader Model 1 always addresses relative to `a0.x`, without an extra token.
+        if version.major < 2 {
+            return Ok(Some(RelativeAddress {
+                register: Register {
+                    register_type: RegisterType::Address,
+                    index: 0,
+                },
+                component: 0,
+            }));
+        }
+
+        let token = self.next()?;
+        Ok(Some(RelativeAddress {
+            register: self.register(token)?,
+            component: ((token >> 16) & 0x3) as u8,
+        }))
+    }
+
+    fn destination(&mut self, version: ShaderVersion) -> Result<DestinationParameter, ShaderError> {
+        let token = self.next()?;
+        let register = self.register(token)?;
+        let modifiers = (token >> 20) & 0xF;
+
+        Ok(DestinationParameter {
+            register,
+            relative: self.relative(version, token)?,
+            write_mask: ((token >> 16) & 0xF) as u8,
+            saturate: modifiers & 0x1 != 0,
+            partial_precision: modifiers & 0x2 != 0,
+            centroid: modifiers & 0x4 != 0,
+            shift: (((token >> 24) & 0xF) as i8) << 4 >> 4,
+        })
+    }
+
+    fn source(&mut self, version: ShaderVersion) -> Result<SourceParameter, ShaderError> {
+        let token = self.next()?;
+        let register = self.register(token)?;
+        let swizzle = (token >> 16) as u8;
+        let modifier =
+            SourceModifier::from_raw((token >> 24) & 0xF).ok_or(ShaderError::UnknownModifier {
+                modifier: (token >> 24) & 0xF,
+                offset: self.offset - 1,
+            })?;
+
+        Ok(SourceParameter {
+            register,
+            relative: self.relative(version, token)?,
+            swizzle: [0, 2, 4, 6].map(|shift| (swizzle >> shift) & 0x3),
+            modifier,
+        })
+    }
+}
+
+/// Returns the [`DeclUsage`] with the given `D3DDECLUSAGE` value.
+pub fn decl_usage_from_raw(value: u32) -> Option<DeclUsage> {
+    use DeclUsage::*;
+
+    const USAGES: [DeclUsage; 14] = [
+        Position,
+        BlendWeight,
+        BlendIndices,
+        Normal,
+        PSize,
+        TexCoord,
+        Tangent,
+        BiNormal,
+        TessFactor,
+        PositionT,
+        Color,
+        Fog,
+        Depth,
+        Sample,
+    ];
+
+    USAGES.get(value as usize).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_versions_and_comments() {
+        let shader = Shader::parse(&[
+            0xFFFE0300,
+            0x0002FFFE,
+            u32::from_le_bytes(*b"CTAB"),
+            0,
+            0x0000FFFF,
+        ])
+        .unwrap();
+
+        assert_eq!(shader.version, ShaderVersion::new(ShaderType::Vertex, 3, 0));
+        assert_eq!(shader.comments().next().unwrap().four_cc(), Some(*b"CTAB"));
+        assert_eq!(shader.instructions().count(), 0);
+    }
+
+    #[test]
+    fn sgn_takes_three_sources_before_shader_model_3() {
+        let shader = Shader::parse(&[
+            0xFFFE0200, 0x04000022, 0x800F0000, 0x80E40001, 0x80E40002, 0x80E40003, 0x0000FFFF,
+        ])
+        .unwrap();
+        let sgn = shader.instructions().next().unwrap();
+        assert_eq!(sgn.opcode, Opcode::Sgn);
+        assert_eq!(sgn.sources.len(), 3);
+
+        let shader =
+            Shader::parse(&[0xFFFE0300, 0x02000022, 0x800F0000, 0x80E40001, 0x0000FFFF]).unwrap();
+        let sgn = shader.instructions().next().unwrap();
+        assert_eq!(sgn.opcode, Opcode::Sgn);
+        assert_eq!(sgn.sources.len(), 1);
+    }
+
+    #[test]
+    fn rejects_malformed_token_streams() {
+        assert!(matches!(
+            Shader::parse(&[0xFFFE0300, 0x02000001, 0x800F0000]),
+            Err(ShaderError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            Shader::parse(&[0xFFFE0300, 0x03000001, 0x800F0000, 0x80E40000, 0x0000FFFF]),
+            Err(ShaderError::LengthMismatch {
+                offset: 1,
+                expected: 3,
+                found: 2,
+            })
+        ));
+        assert!(matches!(
+            Shader::parse(&[0xFFFE0300, 0x000000FF, 0x0000FFFF]),
+            Err(ShaderError::UnknownOpcode { opcode: 0xFF, .. })
+        ));
+        assert!(matches!(
+            Shader::parse(&[0x12345678]),
+            Err(ShaderError::InvalidVersion(0x12345678))
+        ));
+        assert!(matches!(
+            Shader::from_bytes(&[0; 5]),
+            Err(ShaderError::UnalignedLength(5))
+        ));
+    }
+}
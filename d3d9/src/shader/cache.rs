@@ -0,0 +1,720 @@
+//! Provides an on-disk cache for compiled shaders.
+//!
+//! Entries are keyed by a [`CacheKey`], which hashes everything passed to the compiler. The
+//! includes opened during compilation are recorded with each entry and requested again from the
+//! [`IncludeHandler`] on lookup, so editing an included file invalidates every shader using it.
+//!
+//! # Usage
+//!
+//! ```rs
+//! let cache = ShaderCache::new("cache/shaders").with_max_size(64 * 1024 * 1024);
+//! let shader = compile_shader_cached(&cache, source, "main", "vs_3_0", &options, None)?;
+//! let vertex_shader = device.create_vertex_shader(&shader.tokens)?;
+//! ```
+
+use std::{
+    fs,
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{
+    shader::{Ctab, CtabConstant, IncludeHandler, IncludeType, ResolvedInclude, ShaderVersion},
+    x::types::{CompileOptions, RegisterSet},
+};
+
+const MAGIC: [u8; 4] = *b"D9SC";
+const VERSION: u32 = 1;
+const EXTENSION: &str = "d9sc";
+
+/// A 64-bit FNV-1a hash, which unlike [`std::hash::DefaultHasher`] is stable across builds.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Self(0xCBF2_9CE4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01B3);
+        }
+    }
+
+    /// Writes a length-prefixed string, so that adjacent strings cannot run into each other.
+    fn write_str(&mut self, value: &str) {
+        self.write(&(value.len() as u64).to_le_bytes());
+        self.write(value.as_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn hash(bytes: &[u8]) -> u64 {
+    let mut fnv = Fnv::new();
+    fnv.write(bytes);
+    fnv.finish()
+}
+
+/// Identifies a compilation by its source, entry point, profile, flags and macros.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey(pub u64);
+
+impl CacheKey {
+    pub fn new(source: &str, function_name: &str, profile: &str, options: &CompileOptions) -> Self {
+        let mut fnv = Fnv::new();
+        fnv.write(&VERSION.to_le_bytes());
+        fnv.write_str(source);
+        fnv.write_str(function_name);
+        fnv.write_str(profile);
+        fnv.write(&options.flags().to_le_bytes());
+
+        fnv.write(&(options.macros.len() as u64).to_le_bytes());
+        for define in &options.macros {
+            fnv.write_str(&define.name);
+            fnv.write_str(&define.definition);
+        }
+
+        Self(fnv.finish())
+    }
+}
+
+/// Represents a constant recorded alongside a cached shader.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedConstant {
+    pub name: String,
+    pub register_set: RegisterSet,
+    pub register_index: u32,
+    pub register_count: u32,
+}
+
+impl From<&CtabConstant> for CachedConstant {
+    fn from(value: &CtabConstant) -> Self {
+        Self {
+            name: value.name.clone(),
+            register_set: value.register_set,
+            register_index: value.register_index,
+            register_count: value.register_count,
+        }
+    }
+}
+
+/// Represents a shader loaded from a [`ShaderCache`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedShader {
+    /// The token stream, as accepted by [`Device::create_vertex_shader`] and
+    /// [`Device::create_pixel_shader`].
+    ///
+    /// [`Device::create_vertex_shader`]: crate::std::interfaces::Device::create_vertex_shader
+    /// [`Device::create_pixel_shader`]: crate::std::interfaces::Device::create_pixel_shader
+    pub tokens: Vec<u32>,
+    pub constants: Vec<CachedConstant>,
+}
+
+impl CachedShader {
+    /// Creates an entry from compiled bytecode, reading the constants from its `CTAB` block.
+    pub fn from_bytecode(bytecode: &[u8]) -> io::Result<Self> {
+        if !bytecode.len().is_multiple_of(4) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "shader bytecode is not a whole number of tokens",
+            ));
+        }
+
+        let tokens = bytecode
+            .chunks_exact(4)
+            .map(|token| u32::from_le_bytes([token[0], token[1], token[2], token[3]]))
+            .collect();
+
+        // Shaders compiled without a constant table still work, there is just nothing to record.
+        let constants = match Ctab::from_bytecode(bytecode) {
+            Ok(Some(ctab)) => ctab.constants.iter().map(CachedConstant::from).collect(),
+            _ => Vec::new(),
+        };
+
+        Ok(Self { tokens, constants })
+    }
+
+    pub fn version(&self) -> Option<ShaderVersion> {
+        self.tokens
+            .first()
+            .copied()
+            .and_then(ShaderVersion::from_token)
+    }
+
+    pub fn constant_by_name(&self, name: &str) -> Option<&CachedConstant> {
+        self.constants.iter().find(|constant| constant.name == name)
+    }
+}
+
+/// Represents an include recorded in a cache entry, without its data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct IncludeRecord {
+    include_type: IncludeType,
+    name: String,
+    parent: Option<String>,
+    path: String,
+    hash: u64,
+}
+
+impl From<&ResolvedInclude> for IncludeRecord {
+    fn from(value: &ResolvedInclude) -> Self {
+        Self {
+            include_type: value.include_type,
+            name: value.name.clone(),
+            parent: value.parent.clone(),
+            path: value.path.clone(),
+            hash: hash(&value.data),
+        }
+    }
+}
+
+impl IncludeRecord {
+    /// Returns whether the handler still resolves the include to the same file and contents.
+    fn is_current(&self, handler: &mut dyn IncludeHandler) -> bool {
+        handler
+            .open(self.include_type, &self.name, self.parent.as_deref())
+            .is_ok_and(|file| file.path == self.path && hash(&file.data) == self.hash)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Entry {
+    key: CacheKey,
+    includes: Vec<IncludeRecord>,
+    shader: CachedShader,
+}
+
+impl Entry {
+    fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer(Vec::new());
+        writer.bytes(&MAGIC);
+        writer.u32(VERSION);
+        writer.u64(self.key.0);
+
+        writer.u32(self.includes.len() as u32);
+        for include in &self.includes {
+            writer.u32(match include.include_type {
+                IncludeType::Local => 0,
+                IncludeType::System => 1,
+            });
+            writer.str(&include.name);
+            match &include.parent {
+                Some(parent) => {
+                    writer.u32(1);
+                    writer.str(parent);
+                }
+                None => writer.u32(0),
+            }
+            writer.str(&include.path);
+            writer.u64(include.hash);
+        }
+
+        writer.u32(self.shader.constants.len() as u32);
+        for constant in &self.shader.constants {
+            writer.str(&constant.name);
+            writer.u32(constant.register_set.into());
+            writer.u32(constant.register_index);
+            writer.u32(constant.register_count);
+        }
+
+        writer.u32(self.shader.tokens.len() as u32);
+        for token in &self.shader.tokens {
+            writer.u32(*token);
+        }
+
+        let checksum = hash(&writer.0);
+        writer.u64(checksum);
+        writer.0
+    }
+
+    /// Decodes an entry, returning [`None`] if it is corrupt or from another version.
+    fn decode(data: &[u8]) -> Option<Self> {
+        let (data, checksum) = data.split_at(data.len().checked_sub(8)?);
+        if hash(data) != u64::from_le_bytes(checksum.try_into().ok()?) {
+            return None;
+        }
+
+        let mut reader = Reader(data);
+        if reader.bytes(4)? != MAGIC || reader.u32()? != VERSION {
+            return None;
+        }
+
+        let key = CacheKey(reader.u64()?);
+
+        let includes = (0..reader.u32()?)
+            .map(|_| {
+                Some(IncludeRecord {
+                    include_type: match reader.u32()? {
+                        0 => IncludeType::Local,
+                        1 => IncludeType::System,
+                        _ => return None,
+                    },
+                    name: reader.str()?,
+                    parent: match reader.u32()? {
+                        0 => None,
+                        _ => Some(reader.str()?),
+                    },
+                    path: reader.str()?,
+                    hash: reader.u64()?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let constants = (0..reader.u32()?)
+            .map(|_| {
+                Some(CachedConstant {
+                    name: reader.str()?,
+                    register_set: RegisterSet::from_raw(reader.u32()?)?,
+                    register_index: reader.u32()?,
+                    register_count: reader.u32()?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let tokens = (0..reader.u32()?)
+            .map(|_| reader.u32())
+            .collect::<Option<Vec<_>>>()?;
+
+        reader.0.is_empty().then_some(Self {
+            key,
+            includes,
+            shader: CachedShader { tokens, constants },
+        })
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn str(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.bytes(value.as_bytes());
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+
+    fn str(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.bytes(len)?.to_vec()).ok()
+    }
+}
+
+/// Stores compiled shaders in a directory, one file per [`CacheKey`].
+///
+/// Entries which are corrupt, were written by another version, or whose includes have changed
+/// are deleted when they are looked up. With a maximum size, the least recently used entries are
+/// evicted whenever a new one is inserted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderCache {
+    directory: PathBuf,
+    max_size: Option<u64>,
+}
+
+impl ShaderCache {
+    /// Creates a cache in `directory`, which is created once the first entry is inserted.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        Self {
+            directory: directory.into(),
+            max_size: None,
+        }
+    }
+
+    /// Limits the total size of the entries in bytes.
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    fn entry_path(&self, key: CacheKey) -> PathBuf {
+        self.directory.join(format!("{:016x}.{}", key.0, EXTENSION))
+    }
+
+    /// Looks up an entry, checking its includes against `include_handler`.
+    ///
+    /// Entries with includes are always stale without a handler.
+    pub fn get(
+        &self,
+        key: CacheKey,
+        mut include_handler: Option<&mut dyn IncludeHandler>,
+    ) -> io::Result<Option<CachedShader>> {
+        let path = self.entry_path(key);
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+
+        let entry = Entry::decode(&data).filter(|entry| {
+            entry.key == key
+                && entry
+                    .includes
+                    .iter()
+                    .all(|include| match &mut include_handler {
+                        Some(handler) => include.is_current(*handler),
+                        None => false,
+                    })
+        });
+
+        let Some(entry) = entry else {
+            remove_file(&path)?;
+            return Ok(None);
+        };
+
+        // The modification time doubles as the last use for eviction, and failing to update it
+        // only makes the entry more likely to be evicted.
+        if let Ok(file) = File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+
+        Ok(Some(entry.shader))
+    }
+
+    /// Stores compiled bytecode along with the includes it was compiled with.
+    pub fn insert(
+        &self,
+        key: CacheKey,
+        includes: &[ResolvedInclude],
+        bytecode: &[u8],
+    ) -> io::Result<CachedShader> {
+        let entry = Entry {
+            key,
+            includes: includes.iter().map(IncludeRecord::from).collect(),
+            shader: CachedShader::from_bytecode(bytecode)?,
+        };
+
+        fs::create_dir_all(&self.directory)?;
+
+        // Writing to a temporary file first keeps readers from seeing a partial entry.
+        let path = self.entry_path(key);
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, entry.encode())?;
+        fs::rename(&temporary, &path)?;
+
+        self.evict()?;
+
+        Ok(entry.shader)
+    }
+
+    /// Removes an entry, returning whether it existed.
+    pub fn remove(&self, key: CacheKey) -> io::Result<bool> {
+        remove_file(&self.entry_path(key))
+    }
+
+    /// Removes every entry.
+    pub fn clear(&self) -> io::Result<()> {
+        for file in self.files()? {
+            remove_file(&file.path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the total size of the entries in bytes.
+    pub fn size(&self) -> io::Result<u64> {
+        Ok(self.files()?.iter().map(|file| file.size).sum())
+    }
+
+    /// Removes the least recently used entries until the cache fits its maximum size, returning
+    /// how many were removed.
+    pub fn evict(&self) -> io::Result<usize> {
+        let Some(max_size) = self.max_size else {
+            return Ok(0);
+        };
+
+        let mut files = self.files()?;
+        files.sort_by_key(|file| file.modified);
+
+        let mut size = files.iter().map(|file| file.size).sum::<u64>();
+        let mut removed = 0;
+
+        for file in files {
+            if size <= max_size {
+                break;
+            }
+
+            remove_file(&file.path)?;
+            size -= file.size;
+            removed += 1;
+        }
+
+        Ok(removed)
+    }
+
+    fn files(&self) -> io::Result<Vec<EntryFile>> {
+        let directory = match fs::read_dir(&self.directory) {
+            Ok(directory) => directory,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+
+        let mut files = Vec::new();
+
+        for file in directory {
+            let file = file?;
+            let path = file.path();
+            if path
+                .extension()
+                .is_none_or(|extension| extension != EXTENSION)
+            {
+                continue;
+            }
+
+            let metadata = file.metadata()?;
+            files.push(EntryFile {
+                path,
+                size: metadata.len(),
+                modified: metadata.modified()?,
+            });
+        }
+
+        Ok(files)
+    }
+}
+
+/// Represents the file of an entry, as listed for eviction.
+struct EntryFile {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// Removes a file, returning whether it existed.
+fn remove_file(path: &Path) -> io::Result<bool> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::shader::MemoryIncludeHandler;
+
+    /// An empty `vs_3_0` shader.
+    fn bytecode() -> Vec<u8> {
+        [0xFFFE0300u32, 0x0000FFFF]
+            .iter()
+            .flat_map(|token| token.to_le_bytes())
+            .collect()
+    }
+
+    /// Returns a cache in an empty directory, which is unique to the test.
+    fn cache(name: &str) -> ShaderCache {
+        let directory =
+            std::env::temp_dir().join(format!("d3d9-shader-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        ShaderCache::new(directory)
+    }
+
+    fn key(source: &str) -> CacheKey {
+        CacheKey::new(source, "main", "vs_3_0", &CompileOptions::new())
+    }
+
+    fn set_modified(cache: &ShaderCache, key: CacheKey, seconds: u64) {
+        File::options()
+            .write(true)
+            .open(cache.entry_path(key))
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+            .unwrap();
+    }
+
+    #[test]
+    fn keys_depend_on_every_input() {
+        let options = CompileOptions::new();
+        let key = CacheKey::new("source", "main", "vs_3_0", &options);
+
+        assert_eq!(key, CacheKey::new("source", "main", "vs_3_0", &options));
+        assert_ne!(key, CacheKey::new("source", "main", "vs_2_0", &options));
+        assert_ne!(key, CacheKey::new("source", "other", "vs_3_0", &options));
+        assert_ne!(
+            key,
+            CacheKey::new("source", "main", "vs_3_0", &options.clone().debug(true))
+        );
+        assert_ne!(
+            key,
+            CacheKey::new(
+                "source",
+                "main",
+                "vs_3_0",
+                &options.clone().define("A", "1")
+            )
+        );
+        assert_ne!(
+            CacheKey::new("ab", "c", "vs_3_0", &options),
+            CacheKey::new("a", "bc", "vs_3_0", &options)
+        );
+    }
+
+    #[test]
+    fn round_trips_entries() {
+        let entry = Entry {
+            key: key("source"),
+            includes: vec![IncludeRecord {
+                include_type: IncludeType::System,
+                name: "common.hlsl".to_string(),
+                parent: Some("main.hlsl".to_string()),
+                path: "include/common.hlsl".to_string(),
+                hash: 42,
+            }],
+            shader: CachedShader {
+                tokens: vec![0xFFFE0300, 0x0000FFFF],
+                constants: vec![CachedConstant {
+                    name: "world_view_projection".to_string(),
+                    register_set: RegisterSet::Float4,
+                    register_index: 4,
+                    register_count: 4,
+                }],
+            },
+        };
+
+        let mut data = entry.encode();
+        let decoded = Entry::decode(&data).unwrap();
+        assert_eq!(decoded.key, entry.key);
+        assert_eq!(decoded.includes, entry.includes);
+        assert_eq!(decoded.shader, entry.shader);
+        assert_eq!(
+            decoded.shader.constant_by_name("world_view_projection"),
+            Some(&entry.shader.constants[0])
+        );
+
+        data[10] ^= 1;
+        assert!(Entry::decode(&data).is_none());
+        assert!(Entry::decode(&data[..4]).is_none());
+    }
+
+    #[test]
+    fn stores_and_loads_shaders() {
+        let cache = cache("store");
+        let key = key("source");
+
+        assert_eq!(cache.get(key, None).unwrap(), None);
+
+        let shader = cache.insert(key, &[], &bytecode()).unwrap();
+        assert_eq!(shader.version().unwrap().major, 3);
+        assert_eq!(cache.get(key, None).unwrap(), Some(shader));
+
+        assert!(cache.remove(key).unwrap());
+        assert!(!cache.remove(key).unwrap());
+        assert!(cache.insert(key, &[], &[1, 2, 3]).is_err());
+
+        fs::remove_dir_all(cache.directory()).unwrap();
+    }
+
+    #[test]
+    fn invalidates_entries_when_includes_change() {
+        let cache = cache("includes");
+        let key = key("#include \"common.hlsl\"");
+        let include = ResolvedInclude {
+            include_type: IncludeType::Local,
+            name: "common.hlsl".to_string(),
+            parent: None,
+            path: "common.hlsl".to_string(),
+            data: b"one".to_vec(),
+        };
+
+        let mut unchanged = MemoryIncludeHandler::new().with_file("common.hlsl", "one");
+        let mut changed = MemoryIncludeHandler::new().with_file("common.hlsl", "two");
+
+        cache.insert(key, &[include], &bytecode()).unwrap();
+        assert!(cache.get(key, Some(&mut unchanged)).unwrap().is_some());
+        assert!(cache.get(key, Some(&mut changed)).unwrap().is_none());
+
+        // The stale entry was removed.
+        assert!(cache.get(key, Some(&mut unchanged)).unwrap().is_none());
+        assert_eq!(cache.size().unwrap(), 0);
+
+        fs::remove_dir_all(cache.directory()).unwrap();
+    }
+
+    #[test]
+    fn removes_corrupt_entries() {
+        let cache = cache("corrupt");
+        let key = key("source");
+        cache.insert(key, &[], &bytecode()).unwrap();
+
+        let path = cache.entry_path(key);
+        let mut data = fs::read(&path).unwrap();
+        data[10] ^= 1;
+        fs::write(&path, data).unwrap();
+
+        assert_eq!(cache.get(key, None).unwrap(), None);
+        assert!(!path.exists());
+
+        fs::remove_dir_all(cache.directory()).unwrap();
+    }
+
+    #[test]
+    fn evicts_least_recently_used_entries() {
+        let cache = cache("evict");
+        let [first, second, third] = [key("first"), key("second"), key("third")];
+
+        cache.insert(first, &[], &bytecode()).unwrap();
+        let entry_size = cache.size().unwrap();
+        let cache = cache.with_max_size(entry_size * 2);
+
+        cache.insert(second, &[], &bytecode()).unwrap();
+        set_modified(&cache, first, 1);
+        set_modified(&cache, second, 2);
+
+        // Looking up the first entry makes the second the least recently used.
+        assert!(cache.get(first, None).unwrap().is_some());
+        cache.insert(third, &[], &bytecode()).unwrap();
+
+        assert!(cache.get(second, None).unwrap().is_none());
+        assert!(cache.get(first, None).unwrap().is_some());
+        assert!(cache.get(third, None).unwrap().is_some());
+        assert_eq!(cache.size().unwrap(), entry_size * 2);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.size().unwrap(), 0);
+
+        fs::remove_dir_all(cache.directory()).unwrap();
+    }
+}
@@ -0,0 +1,420 @@
+//! Provides a parser for the `CTAB` comment block which compiled shaders embed.
+//!
+//! This reads the same information as [`ConstantTable`](crate::x::interfaces::ConstantTable)
+//! without calling into D3DX, so it also works on precompiled shaders.
+
+use std::cell::Cell;
+
+use crate::{
+    shader::{Shader, ShaderError},
+    x::types::{DefaultValue, ParameterClass, ParameterType, RegisterSet},
+};
+
+const CTAB: [u8; 4] = *b"CTAB";
+const MAX_DEPTH: u32 = 32;
+/// The number of types a table may contain in total, counting every struct member separately.
+const MAX_TYPES: u32 = 1 << 16;
+
+/// Represents the constant table of a compiled shader.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ctab {
+    pub creator: String,
+    /// The version token of the shader the table was compiled for.
+    pub version: u32,
+    pub flags: u32,
+    /// The profile the shader was compiled with, such as `vs_3_0`.
+    pub target: String,
+    pub constants: Vec<CtabConstant>,
+}
+
+/// Represents a top-level constant within a [`Ctab`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CtabConstant {
+    pub name: String,
+    pub register_set: RegisterSet,
+    pub register_index: u32,
+    pub register_count: u32,
+    pub type_info: TypeInfo,
+    pub default_value: Option<DefaultValue>,
+}
+
+/// Represents the type of a constant or struct member.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeInfo {
+    pub class: ParameterClass,
+    pub kind: ParameterType,
+    pub rows: u32,
+    pub columns: u32,
+    pub elements: u32,
+    pub members: Vec<StructMember>,
+}
+
+impl TypeInfo {
+    /// Returns the size of the type in bytes, as reported by D3DX, or [`None`] if it overflows a
+    /// [`u32`].
+    pub fn bytes(&self) -> Option<u32> {
+        let element = if self.members.is_empty() {
+            self.rows.checked_mul(self.columns)?.checked_mul(4)?
+        } else {
+            self.members.iter().try_fold(0u32, |bytes, member| {
+                bytes.checked_add(member.type_info.bytes()?)
+            })?
+        };
+
+        element.checked_mul(self.elements.max(1))
+    }
+}
+
+/// Represents a member of a struct constant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructMember {
+    pub name: String,
+    pub type_info: TypeInfo,
+}
+
+impl Ctab {
+    /// Parses the contents of a `CTAB` comment block, excluding the leading four character code.
+    pub fn parse(data: &[u8]) -> Result<Self, ShaderError> {
+        let reader = Reader {
+            data,
+            types: Cell::new(0),
+        };
+
+        let size = reader.u32(0)?;
+        if size != 28 {
+            return Err(ShaderError::InvalidConstantTable("unexpected header size"));
+        }
+
+        let creator = reader.string(reader.u32(4)?)?;
+        let version = reader.u32(8)?;
+        let n_constants = reader.u32(12)?;
+        let constant_info = reader.u32(16)? as usize;
+        let flags = reader.u32(20)?;
+        let target = reader.string(reader.u32(24)?)?;
+
+        let constants = (0..n_constants)
+            .map(|index| reader.constant(constant_info + index as usize * 20))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            creator,
+            version,
+            flags,
+            target,
+            constants,
+        })
+    }
+
+    /// Parses the constant table embedded in `shader`, returning [`None`] if it has none.
+    pub fn from_shader(shader: &Shader) -> Result<Option<Self>, ShaderError> {
+        shader
+            .comments()
+            .find(|comment| comment.four_cc() == Some(CTAB))
+            .map(|comment| Self::parse(&comment.bytes()[4..]))
+            .transpose()
+    }
+
+    /// Parses the constant table embedded in a compiled shader blob.
+    pub fn from_bytecode(bytes: &[u8]) -> Result<Option<Self>, ShaderError> {
+        Self::from_shader(&Shader::from_bytes(bytes)?)
+    }
+
+    pub fn constant_by_name(&self, name: &str) -> Option<&CtabConstant> {
+        self.constants.iter().find(|constant| constant.name == name)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    /// The number of types read so far.
+    types: Cell<u32>,
+}
+
+impl Reader<'_> {
+    fn bytes(&self, offset: usize, length: usize) -> Result<&[u8], ShaderError> {
+        self.data
+            .get(offset..)
+            .and_then(|data| data.get(..length))
+            .ok_or(ShaderError::InvalidConstantTable("offset out of bounds"))
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16, ShaderError> {
+        Ok(u16::from_le_bytes(
+            self.bytes(offset, 2)?.try_into().unwrap(),
+        ))
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32, ShaderError> {
+        Ok(u32::from_le_bytes(
+            self.bytes(offset, 4)?.try_into().unwrap(),
+        ))
+    }
+
+    fn string(&self, offset: u32) -> Result<String, ShaderError> {
+        let data = self
+            .data
+            .get(offset as usize..)
+            .ok_or(ShaderError::InvalidConstantTable("offset out of bounds"))?;
+        let length = data
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or(ShaderError::InvalidConstantTable("unterminated string"))?;
+
+        Ok(String::from_utf8_lossy(&data[..length]).into_owned())
+    }
+
+    fn constant(&self, offset: usize) -> Result<CtabConstant, ShaderError> {
+        let name = self.string(self.u32(offset)?)?;
+        let register_set = RegisterSet::from_raw(self.u16(offset + 4)? as u32)
+            .ok_or(ShaderError::InvalidConstantTable("unknown register set"))?;
+        let register_index = self.u16(offset + 6)? as u32;
+        let register_count = self.u16(offset + 8)? as u32;
+        let type_info = self.type_info(self.u32(offset + 12)? as usize, 0)?;
+
+        let default_value = match self.u32(offset + 16)? {
+            0 => None,
+            default_value => {
+                let length = DefaultValue::data_len(register_set, register_count);
+                let data = self
+                    .bytes(default_value as usize, length * 4)?
+                    .chunks_exact(4)
+                    .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+                    .collect::<Vec<_>>();
+                DefaultValue::from_registers(register_set, &data)
+            }
+        };
+
+        Ok(CtabConstant {
+            name,
+            register_set,
+            register_index,
+            register_count,
+            type_info,
+            default_value,
+        })
+    }
+
+    fn type_info(&self, offset: usize, depth: u32) -> Result<TypeInfo, ShaderError> {
+        // Offsets may point backwards, so a malformed table could otherwise recurse forever.
+        if depth > MAX_DEPTH {
+            return Err(ShaderError::InvalidConstantTable(
+                "struct nesting is too deep",
+            ));
+        }
+
+        // Members may share a type, so a small table could otherwise describe a huge tree.
+        let types = self.types.get() + 1;
+        if types > MAX_TYPES {
+            return Err(ShaderError::InvalidConstantTable("too many types"));
+        }
+        self.types.set(types);
+
+        let class = ParameterClass::from_raw(self.u16(offset)? as u32)
+            .ok_or(ShaderError::InvalidConstantTable("unknown parameter class"))?;
+        let kind = ParameterType::from_raw(self.u16(offset + 2)? as u32)
+            .ok_or(ShaderError::InvalidConstantTable("unknown parameter type"))?;
+        let n_members = self.u16(offset + 10)? as u32;
+        let member_info = self.u32(offset + 12)? as usize;
+
+        let members = (0..n_members)
+            .map(|index| {
+                let offset = member_info + index as usize * 8;
+                Ok(StructMember {
+                    name: self.string(self.u32(offset)?)?,
+                    type_info: self.type_info(self.u32(offset + 4)? as usize, depth + 1)?,
+                })
+            })
+            .collect::<Result<Vec<_>, ShaderError>>()?;
+
+        Ok(TypeInfo {
+            class,
+            kind,
+            rows: self.u16(offset + 4)? as u32,
+            columns: self.u16(offset + 6)? as u32,
+            elements: self.u16(offset + 8)? as u32,
+            members,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Writer(Vec<u8>);
+
+    impl Writer {
+        fn u16s(&mut self, values: &[u16]) {
+            for value in values {
+                self.0.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        fn u32s(&mut self, values: &[u32]) {
+            for value in values {
+                self.0.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        fn string(&mut self, offset: usize, value: &str) {
+            self.0.resize(offset, 0);
+            self.0.extend_from_slice(value.as_bytes());
+            self.0.push(0);
+        }
+    }
+
+    /// Builds a table with a `float4x4` constant, which has a default value, and a struct of two
+    /// `float4` members.
+    fn table() -> Vec<u8> {
+        let mut writer = Writer::default();
+
+        // Header, followed by the constant infos at 28.
+        writer.u32s(&[28, 200, 0xFFFE0300, 2, 28, 0x100, 220]);
+        writer.u32s(&[230]);
+        writer.u16s(&[2, 0, 4, 0]);
+        writer.u32s(&[68, 132]);
+        writer.u32s(&[240]);
+        writer.u16s(&[2, 4, 2, 0]);
+        writer.u32s(&[84, 0]);
+
+        // A column-major float4x4 at 68, the struct at 84 with its members at 100 and a float4
+        // at 116.
+        writer.u16s(&[3, 3, 4, 4, 1, 0]);
+        writer.u32s(&[0]);
+        writer.u16s(&[5, 0, 1, 8, 1, 2]);
+        writer.u32s(&[100]);
+        writer.u32s(&[250, 116, 260, 116]);
+        writer.u16s(&[1, 3, 1, 4, 1, 0]);
+        writer.u32s(&[0]);
+
+        // The default value of the matrix at 132.
+        for i in 0..16 {
+            writer.u32s(&[(i as f32).to_bits()]);
+        }
+
+        writer.string(200, "Microsoft (R) HLSL");
+        writer.string(220, "vs_3_0");
+        writer.string(230, "WorldView");
+        writer.string(240, "light");
+        writer.string(250, "color");
+        writer.string(260, "dir");
+        writer.0.resize(writer.0.len().next_multiple_of(4), 0);
+
+        writer.0
+    }
+
+    #[test]
+    fn parses_constants_types_and_defaults() {
+        let ctab = Ctab::parse(&table()).unwrap();
+        assert_eq!(ctab.creator, "Microsoft (R) HLSL");
+        assert_eq!(ctab.target, "vs_3_0");
+        assert_eq!(ctab.version, 0xFFFE0300);
+        assert_eq!(ctab.constants.len(), 2);
+
+        let matrix = ctab.constant_by_name("WorldView").unwrap();
+        assert_eq!(matrix.register_set, RegisterSet::Float4);
+        assert_eq!(matrix.register_count, 4);
+        assert_eq!(matrix.type_info.class, ParameterClass::MatrixColumns);
+        assert_eq!(matrix.type_info.kind, ParameterType::Float);
+        assert_eq!(matrix.type_info.bytes(), Some(64));
+        match &matrix.default_value {
+            Some(DefaultValue::Float(values)) => {
+                assert_eq!(values.len(), 16);
+                assert_eq!(values[5], 5.0);
+            }
+            other => panic!("unexpected default value {other:?}"),
+        }
+
+        let light = ctab.constant_by_name("light").unwrap();
+        assert_eq!(light.register_index, 4);
+        assert_eq!(light.type_info.class, ParameterClass::Struct);
+        assert_eq!(light.type_info.members.len(), 2);
+        assert_eq!(light.type_info.members[1].name, "dir");
+        assert_eq!(light.type_info.bytes(), Some(32));
+        assert!(light.default_value.is_none());
+    }
+
+    #[test]
+    fn reads_the_table_from_a_shader_comment() {
+        let mut data = CTAB.to_vec();
+        data.extend(table());
+
+        let mut tokens = vec![0xFFFE0300, 0xFFFE | ((data.len() as u32 / 4) << 16)];
+        tokens.extend(
+            data.chunks(4)
+                .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap())),
+        );
+        tokens.push(0x0000FFFF);
+
+        let shader = Shader::parse(&tokens).unwrap();
+        assert_eq!(
+            Ctab::from_shader(&shader).unwrap().unwrap().constants.len(),
+            2
+        );
+
+        let shader = Shader::parse(&[0xFFFE0300, 0x0000FFFF]).unwrap();
+        assert!(Ctab::from_shader(&shader).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_types_with_too_many_members_in_total() {
+        // Each struct has two members of the next struct's type, so there are 2^24 leaves.
+        const LEVELS: u32 = 24;
+        let leaf = 48 + LEVELS * 32;
+        let strings = leaf + 16;
+
+        let mut writer = Writer::default();
+        writer.u32s(&[28, strings, 0xFFFE0300, 1, 28, 0, strings]);
+        writer.u32s(&[strings]);
+        writer.u16s(&[2, 0, 1, 0]);
+        writer.u32s(&[48, 0]);
+        for level in 0..LEVELS {
+            let offset = 48 + level * 32;
+            let next = if level + 1 == LEVELS {
+                leaf
+            } else {
+                offset + 32
+            };
+            writer.u16s(&[5, 0, 1, 8, 1, 2]);
+            writer.u32s(&[offset + 16, strings, next, strings, next]);
+        }
+        writer.u16s(&[1, 3, 1, 4, 1, 0]);
+        writer.u32s(&[0]);
+        writer.string(strings as usize, "s");
+
+        assert!(matches!(
+            Ctab::parse(&writer.0),
+            Err(ShaderError::InvalidConstantTable("too many types"))
+        ));
+    }
+
+    #[test]
+    fn returns_none_for_sizes_which_overflow() {
+        let type_info = TypeInfo {
+            class: ParameterClass::MatrixRows,
+            kind: ParameterType::Float,
+            rows: u16::MAX as u32,
+            columns: u16::MAX as u32,
+            elements: 1,
+            members: Vec::new(),
+        };
+        assert_eq!(type_info.bytes(), None);
+
+        let type_info = TypeInfo {
+            rows: 4,
+            columns: 4,
+            elements: u16::MAX as u32,
+            ..type_info
+        };
+        assert_eq!(type_info.bytes(), Some(64 * 65535));
+    }
+
+    #[test]
+    fn rejects_truncated_tables() {
+        assert!(matches!(
+            Ctab::parse(&table()[..100]),
+            Err(ShaderError::InvalidConstantTable(_))
+        ));
+    }
+}
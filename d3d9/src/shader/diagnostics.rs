@@ -0,0 +1,308 @@
+//! Provides a parser for the messages which the HLSL compiler writes to its error buffer.
+//!
+//! Both fxc and D3DX print one message per line, in the form
+//! `file(line,column): severity code: message`. Messages which span several lines continue on
+//! indented lines.
+
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+};
+
+/// Represents how serious a [`Diagnostic`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    /// A line which the compiler printed without a severity, such as
+    /// `compilation failed; no code produced`.
+    Info,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+        })
+    }
+}
+
+/// Represents a single message reported by the HLSL compiler.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The file the message refers to. Shaders compiled from memory are reported as `memory`, or
+    /// without a file at all.
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub severity: Severity,
+    /// The error code, such as `X3004`.
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Parses a single line of compiler output, returning [`None`] if it is not a message.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() {
+            return None;
+        }
+
+        let (file, position, rest) = match split_location(line) {
+            Some((file, position, rest)) => (file, Some(position), rest),
+            None => (None, None, line),
+        };
+
+        let (severity, rest) = match split_severity(rest) {
+            Some((severity, rest)) => (severity, rest),
+            // A location followed by something other than a severity is still an error.
+            None if position.is_some() => (Severity::Error, rest),
+            None => return None,
+        };
+
+        let (code, message) = split_code(rest);
+        let (line, column) = position.unzip();
+
+        Some(Self {
+            file: file.map(str::to_string),
+            line,
+            column: column.flatten(),
+            severity,
+            code: code.map(str::to_string),
+            message: message.trim().to_string(),
+        })
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.severity == Severity::Warning
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            f.write_str(file)?;
+        }
+
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "({},{}): ", line, column)?,
+            (Some(line), None) => write!(f, "({}): ", line)?,
+            _ if self.file.is_some() => f.write_str(": ")?,
+            _ => {}
+        }
+
+        if self.severity != Severity::Info || self.code.is_some() {
+            write!(f, "{}", self.severity)?;
+
+            if let Some(code) = &self.code {
+                write!(f, " {}", code)?;
+            }
+
+            f.write_str(": ")?;
+        }
+
+        f.write_str(&self.message)
+    }
+}
+
+/// Parses the complete output of the compiler.
+///
+/// Indented lines are appended to the message before them, and lines which are not messages are
+/// kept as [`Severity::Info`].
+pub fn parse_diagnostics(log: &str) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    for line in log.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let is_continuation = line.starts_with(char::is_whitespace);
+        if let (true, Some(last)) = (is_continuation, diagnostics.last_mut()) {
+            last.message.push('\n');
+            last.message.push_str(line.trim());
+            continue;
+        }
+
+        diagnostics.push(Diagnostic::parse(line).unwrap_or_else(|| Diagnostic {
+            file: None,
+            line: None,
+            column: None,
+            severity: Severity::Info,
+            code: None,
+            message: line.trim().to_string(),
+        }));
+    }
+
+    diagnostics
+}
+
+/// Splits `file(line,column): rest`, allowing the file to contain colons and parentheses.
+#[allow(clippy::type_complexity)]
+fn split_location(line: &str) -> Option<(Option<&str>, (u32, Option<u32>), &str)> {
+    let mut search = 0;
+
+    while let Some(offset) = line[search..].find("): ") {
+        let close = search + offset;
+        search = close + 1;
+
+        let Some(open) = line[..close].rfind('(') else {
+            continue;
+        };
+        let Some(position) = parse_position(&line[open + 1..close]) else {
+            continue;
+        };
+
+        let file = line[..open].trim();
+        let file = (!file.is_empty()).then_some(file);
+
+        return Some((file, position, &line[close + 3..]));
+    }
+
+    None
+}
+
+/// Parses `line`, `line,column` or `line,column-column`.
+fn parse_position(position: &str) -> Option<(u32, Option<u32>)> {
+    fn parse_start(range: &str) -> Option<u32> {
+        let start = range.split('-').next()?;
+        start.trim().parse().ok()
+    }
+
+    let mut parts = position.split(',');
+    let line = parse_start(parts.next()?)?;
+    let column = match parts.next() {
+        Some(column) => Some(parse_start(column)?),
+        None => None,
+    };
+
+    match parts.next() {
+        Some(_) => None,
+        None => Some((line, column)),
+    }
+}
+
+fn split_severity(rest: &str) -> Option<(Severity, &str)> {
+    let rest = rest.trim_start();
+
+    [("error", Severity::Error), ("warning", Severity::Warning)]
+        .into_iter()
+        .find_map(|(word, severity)| {
+            let tail = rest.strip_prefix(word)?;
+            tail.starts_with([' ', ':'])
+                .then_some((severity, tail.trim_start()))
+        })
+}
+
+/// Splits `X3004: message` into the code and the message.
+fn split_code(rest: &str) -> (Option<&str>, &str) {
+    let rest = rest.strip_prefix(':').unwrap_or(rest).trim_start();
+
+    match rest.split_once(':') {
+        Some((code, message)) if is_code(code) => (Some(code), message),
+        _ => (None, rest),
+    }
+}
+
+/// Returns whether `code` looks like `X3004`.
+fn is_code(code: &str) -> bool {
+    let mut chars = code.chars();
+    chars.next().is_some_and(|c| c.is_ascii_uppercase())
+        && !chars.as_str().is_empty()
+        && chars.all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\
+C:\\Shaders (old)\\water.hlsl(12,5): error X3004: undeclared identifier 'foo'
+memory(3,10-25): warning X3206: implicit truncation of vector type
+(7): error X3013: 'tex2D': no matching 2 parameter intrinsic function
+    Possible intrinsic functions are:
+        tex2D(sampler2D, float2)
+error X3501: 'main': entrypoint not found
+
+compilation failed; no code produced
+";
+
+    #[test]
+    fn parses_compiler_output() {
+        let diagnostics = parse_diagnostics(LOG);
+        assert_eq!(diagnostics.len(), 5);
+
+        let [undeclared, truncation, intrinsic, entry_point, failed] = &diagnostics[..] else {
+            unreachable!()
+        };
+
+        assert_eq!(
+            undeclared.file.as_deref(),
+            Some("C:\\Shaders (old)\\water.hlsl")
+        );
+        assert_eq!((undeclared.line, undeclared.column), (Some(12), Some(5)));
+        assert_eq!(undeclared.code.as_deref(), Some("X3004"));
+        assert_eq!(undeclared.message, "undeclared identifier 'foo'");
+        assert!(undeclared.is_error());
+
+        assert!(truncation.is_warning());
+        assert_eq!(truncation.file.as_deref(), Some("memory"));
+        assert_eq!((truncation.line, truncation.column), (Some(3), Some(10)));
+
+        assert_eq!(intrinsic.file, None);
+        assert_eq!((intrinsic.line, intrinsic.column), (Some(7), None));
+        assert_eq!(
+            intrinsic.message,
+            "'tex2D': no matching 2 parameter intrinsic function\n\
+             Possible intrinsic functions are:\n\
+             tex2D(sampler2D, float2)"
+        );
+
+        assert_eq!(entry_point.line, None);
+        assert_eq!(entry_point.code.as_deref(), Some("X3501"));
+        assert_eq!(entry_point.message, "'main': entrypoint not found");
+
+        assert_eq!(failed.severity, Severity::Info);
+        assert_eq!(failed.message, "compilation failed; no code produced");
+    }
+
+    #[test]
+    fn displays_diagnostics() {
+        let diagnostics = parse_diagnostics(LOG);
+
+        assert_eq!(
+            diagnostics[1].to_string(),
+            "memory(3,10): warning X3206: implicit truncation of vector type"
+        );
+        assert_eq!(
+            diagnostics[3].to_string(),
+            "error X3501: 'main': entrypoint not found"
+        );
+        assert_eq!(
+            diagnostics[4].to_string(),
+            "compilation failed; no code produced"
+        );
+    }
+
+    #[test]
+    fn parses_single_lines() {
+        let without_code = Diagnostic::parse("error: bad thing: here").unwrap();
+        assert_eq!(without_code.code, None);
+        assert_eq!(without_code.message, "bad thing: here");
+
+        let without_severity = Diagnostic::parse("shader.hlsl(4): unexpected token").unwrap();
+        assert!(without_severity.is_error());
+        assert_eq!(without_severity.message, "unexpected token");
+
+        assert_eq!(Diagnostic::parse("hello"), None);
+        assert_eq!(Diagnostic::parse("errors: 3"), None);
+        assert_eq!(Diagnostic::parse("   "), None);
+    }
+}
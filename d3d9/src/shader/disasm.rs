@@ -0,0 +1,404 @@
+//! Prints parsed shaders in the Direct3D assembly syntax.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    shader::bytecode::{
+        Comparison, Constant, Declaration, DestinationParameter, Instruction, Opcode, Register,
+        RegisterType, RelativeAddress, SamplerTextureType, Shader, ShaderType, ShaderVersion,
+        SourceModifier, SourceParameter,
+    },
+    std::types::DeclUsage,
+};
+
+const COMPONENTS: [char; 4] = ['x', 'y', 'z', 'w'];
+
+impl Display for ShaderVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let prefix = match self.shader_type {
+            ShaderType::Vertex => "vs",
+            ShaderType::Pixel => "ps",
+        };
+
+        if self.major == 2 && self.minor == 1 {
+            write!(f, "{prefix}_2_x")
+        } else {
+            write!(f, "{prefix}_{}_{}", self.major, self.minor)
+        }
+    }
+}
+
+/// Prints the version followed by one instruction per line. Comment blocks are skipped.
+impl Display for Shader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.version)?;
+        for instruction in self.instructions() {
+            writeln!(f, "{}", instruction.display(self.version))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Instruction {
+    /// Returns a value which prints the instruction as it appears in a shader of `version`.
+    pub fn display(&self, version: ShaderVersion) -> impl Display + '_ {
+        InstructionDisplay {
+            instruction: self,
+            version,
+        }
+    }
+}
+
+struct InstructionDisplay<'a> {
+    instruction: &'a Instruction,
+    version: ShaderVersion,
+}
+
+impl Display for InstructionDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Self {
+            instruction,
+            version,
+        } = *self;
+
+        if instruction.co_issue {
+            write!(f, "+")?;
+        }
+
+        if let Some(predicate) = &instruction.predicate {
+            write!(f, "(")?;
+            write_source(f, predicate, version)?;
+            write!(f, ") ")?;
+        }
+
+        write_mnemonic(f, instruction, version)?;
+
+        let mut separator = " ";
+        if let Some(destination) = &instruction.destination {
+            write!(f, "{separator}")?;
+            write_destination(f, destination, version)?;
+            separator = ", ";
+        }
+
+        for source in &instruction.sources {
+            write!(f, "{separator}")?;
+            write_source(f, source, version)?;
+            separator = ", ";
+        }
+
+        match instruction.constant {
+            Some(Constant::Float(values)) => {
+                values.iter().try_for_each(|value| write!(f, ", {value}"))
+            }
+            Some(Constant::Int(values)) => {
+                values.iter().try_for_each(|value| write!(f, ", {value}"))
+            }
+            Some(Constant::Bool(value)) => write!(f, ", {value}"),
+            None => Ok(()),
+        }
+    }
+}
+
+fn write_mnemonic(
+    f: &mut Formatter<'_>,
+    instruction: &Instruction,
+    version: ShaderVersion,
+) -> fmt::Result {
+    match instruction.opcode {
+        Opcode::Tex if version.at_least(1, 4) => match instruction.control & 0x3 {
+            1 => write!(f, "texldp")?,
+            2 => write!(f, "texldb")?,
+            _ => write!(f, "texld")?,
+        },
+        Opcode::TexCoord if version.at_least(1, 4) => write!(f, "texcrd")?,
+        Opcode::Dcl => write_declaration(f, instruction, version)?,
+        opcode => write!(f, "{}", opcode.mnemonic())?,
+    }
+
+    if let Some(comparison) = instruction.comparison() {
+        let suffix = match comparison {
+            Comparison::Gt => "gt",
+            Comparison::Eq => "eq",
+            Comparison::Ge => "ge",
+            Comparison::Lt => "lt",
+            Comparison::Ne => "ne",
+            Comparison::Le => "le",
+        };
+        write!(f, "_{suffix}")?;
+    }
+
+    if let Some(destination) = &instruction.destination {
+        match destination.shift {
+            0 => {}
+            shift if shift > 0 => write!(f, "_x{}", 1 << shift)?,
+            shift => write!(f, "_d{}", 1 << -shift)?,
+        }
+        if destination.saturate {
+            write!(f, "_sat")?;
+        }
+        if destination.partial_precision {
+            write!(f, "_pp")?;
+        }
+        if destination.centroid {
+            write!(f, "_centroid")?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_declaration(
+    f: &mut Formatter<'_>,
+    instruction: &Instruction,
+    version: ShaderVersion,
+) -> fmt::Result {
+    match instruction.declaration {
+        Some(Declaration::Sampler(texture_type)) => match texture_type {
+            SamplerTextureType::Unknown => write!(f, "dcl"),
+            SamplerTextureType::Texture2D => write!(f, "dcl_2d"),
+            SamplerTextureType::Cube => write!(f, "dcl_cube"),
+            SamplerTextureType::Volume => write!(f, "dcl_volume"),
+        },
+        // Pixel shaders before 3.0 declare inputs without a semantic, as do system values.
+        Some(Declaration::Usage { .. })
+            if version.shader_type == ShaderType::Pixel && version.major < 3 =>
+        {
+            write!(f, "dcl")
+        }
+        Some(Declaration::Usage { .. })
+            if instruction.destination.is_some_and(|destination| {
+                destination.register.register_type == RegisterType::MiscType
+            }) =>
+        {
+            write!(f, "dcl")
+        }
+        Some(Declaration::Usage { usage, index }) => {
+            write!(f, "dcl_{}", usage_name(usage))?;
+            if index != 0 {
+                write!(f, "{index}")?;
+            }
+            Ok(())
+        }
+        None => write!(f, "dcl"),
+    }
+}
+
+pub(crate) fn usage_name(usage: DeclUsage) -> &'static str {
+    match usage {
+        DeclUsage::Position => "position",
+        DeclUsage::BlendWeight => "blendweight",
+        DeclUsage::BlendIndices => "blendindices",
+        DeclUsage::Normal => "normal",
+        DeclUsage::PSize => "psize",
+        DeclUsage::TexCoord => "texcoord",
+        DeclUsage::Tangent => "tangent",
+        DeclUsage::BiNormal => "binormal",
+        DeclUsage::TessFactor => "tessfactor",
+        DeclUsage::PositionT => "positiont",
+        DeclUsage::Color => "color",
+        DeclUsage::Fog => "fog",
+        DeclUsage::Depth => "depth",
+        DeclUsage::Sample => "sample",
+    }
+}
+
+fn write_destination(
+    f: &mut Formatter<'_>,
+    destination: &DestinationParameter,
+    version: ShaderVersion,
+) -> fmt::Result {
+    write_register(f, &destination.register, destination.relative, version)?;
+
+    if destination.write_mask != 0 && destination.write_mask != 0xF {
+        write!(f, ".")?;
+        for (component, name) in COMPONENTS.iter().enumerate() {
+            if destination.write_mask & (1 << component) != 0 {
+                write!(f, "{name}")?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_source(
+    f: &mut Formatter<'_>,
+    source: &SourceParameter,
+    version: ShaderVersion,
+) -> fmt::Result {
+    let (prefix, suffix) = match source.modifier {
+        SourceModifier::None => ("", ""),
+        SourceModifier::Negate => ("-", ""),
+        SourceModifier::Bias => ("", "_bias"),
+        SourceModifier::BiasNegate => ("-", "_bias"),
+        SourceModifier::Sign => ("", "_bx2"),
+        SourceModifier::SignNegate => ("-", "_bx2"),
+        SourceModifier::Complement => ("1-", ""),
+        SourceModifier::X2 => ("", "_x2"),
+        SourceModifier::X2Negate => ("-", "_x2"),
+        SourceModifier::Dz => ("", "_dz"),
+        SourceModifier::Dw => ("", "_dw"),
+        SourceModifier::Abs => ("", "_abs"),
+        SourceModifier::AbsNegate => ("-", "_abs"),
+        SourceModifier::Not => ("!", ""),
+    };
+
+    write!(f, "{prefix}")?;
+    write_register(f, &source.register, source.relative, version)?;
+    write!(f, "{suffix}")?;
+
+    let swizzle = source.swizzle;
+    if swizzle != [0, 1, 2, 3] {
+        write!(f, ".")?;
+        if swizzle.iter().all(|component| *component == swizzle[0]) {
+            write!(f, "{}", COMPONENTS[swizzle[0] as usize])?;
+        } else {
+            for component in swizzle {
+                write!(f, "{}", COMPONENTS[component as usize])?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_register(
+    f: &mut Formatter<'_>,
+    register: &Register,
+    relative: Option<RelativeAddress>,
+    version: ShaderVersion,
+) -> fmt::Result {
+    let index = register.constant_index().unwrap_or(register.index);
+    let name = match (register.register_type, version.shader_type) {
+        (RegisterType::Temp, _) => "r",
+        (RegisterType::Input, _) => "v",
+        (
+            RegisterType::Const
+            | RegisterType::Const2
+            | RegisterType::Const3
+            | RegisterType::Const4,
+            _,
+        ) => "c",
+        (RegisterType::Address, ShaderType::Vertex) => "a",
+        (RegisterType::Address, ShaderType::Pixel) => "t",
+        (RegisterType::RastOut, _) => {
+            return match register.index {
+                0 => write!(f, "oPos"),
+                1 => write!(f, "oFog"),
+                _ => write!(f, "oPts"),
+            };
+        }
+        (RegisterType::AttrOut, _) => "oD",
+        (RegisterType::TexCrdOut, _) if version.major >= 3 => "o",
+        (RegisterType::TexCrdOut, _) => "oT",
+        (RegisterType::ConstInt, _) => "i",
+        (RegisterType::ColorOut, _) => "oC",
+        (RegisterType::DepthOut, _) => return write!(f, "oDepth"),
+        (RegisterType::Sampler, _) => "s",
+        (RegisterType::ConstBool, _) => "b",
+        (RegisterType::Loop, _) => return write!(f, "aL"),
+        (RegisterType::TempFloat16, _) => "half",
+        (RegisterType::MiscType, _) => {
+            return match register.index {
+                0 => write!(f, "vPos"),
+                _ => write!(f, "vFace"),
+            };
+        }
+        (RegisterType::Label, _) => "l",
+        (RegisterType::Predicate, _) => "p",
+    };
+
+    let Some(relative) = relative else {
+        return write!(f, "{name}{index}");
+    };
+
+    // Shader Model 1 writes the offset inside the brackets, later versions before them.
+    if version.major < 2 {
+        write!(f, "{name}[")?;
+        write_register(f, &relative.register, None, version)?;
+        write!(f, ".{}", COMPONENTS[relative.component as usize])?;
+        if index != 0 {
+            write!(f, " + {index}")?;
+        }
+        write!(f, "]")
+    } else {
+        write!(f, "{name}{index}[")?;
+        write_register(f, &relative.register, None, version)?;
+        if relative.register.register_type != RegisterType::Loop {
+            write!(f, ".{}", COMPONENTS[relative.component as usize])?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disassemble(tokens: &[u32]) -> String {
+        Shader::parse(tokens).unwrap().to_string()
+    }
+
+    #[test]
+    fn disassembles_vs_3_0() {
+        let tokens = [
+            0xFFFE0300, 0x0200001F, 0x80000000, 0x900F0000, 0x0200001F, 0x80000000, 0xE00F0000,
+            0x0002FFFE, 0x42415443, 0x00000000, 0x02000001, 0xE00F0000, 0x90E40000, 0x05000051,
+            0xA00F0000, 0x3F800000, 0x3F000000, 0x00000000, 0x00000000, 0x04000004, 0x80030000,
+            0x91000000, 0xABE40000, 0x80E40001, 0x03000001, 0x800F0000, 0xA0E42002, 0xB0000000,
+            0x0000FFFF,
+        ];
+
+        assert_eq!(
+            disassemble(&tokens),
+            "vs_3_0\n\
+             dcl_position v0\n\
+             dcl_position o0\n\
+             mov o0, v0\n\
+             def c0, 1, 0.5, 0, 0\n\
+             mad r0.xy, -v0.x, c0_abs, r1\n\
+             mov r0, c2[a0.x]\n"
+        );
+    }
+
+    #[test]
+    fn disassembles_ps_2_0() {
+        let tokens = [
+            0xFFFF0200, 0x0200001F, 0x90000000, 0xA00F0800, 0x0200001F, 0x80000000, 0xB0030000,
+            0x03000042, 0x800F0000, 0xB0E40000, 0xA0E40800, 0x02000001, 0x801F0800, 0x80E40000,
+            0x0000FFFF,
+        ];
+
+        assert_eq!(
+            disassemble(&tokens),
+            "ps_2_0\n\
+             dcl_2d s0\n\
+             dcl t0.xy\n\
+             texld r0, t0, s0\n\
+             mov_sat oC0, r0\n"
+        );
+    }
+
+    #[test]
+    fn disassembles_vs_1_1_relative_addressing() {
+        assert_eq!(
+            disassemble(&[0xFFFE0101, 0x00000001, 0x800F0000, 0xA0E42005, 0x0000FFFF]),
+            "vs_1_1\nmov r0, c[a0.x + 5]\n"
+        );
+    }
+
+    #[test]
+    fn disassembles_sgn_for_each_version() {
+        assert_eq!(
+            disassemble(&[
+                0xFFFE0200, 0x04000022, 0x800F0000, 0x80E40001, 0x80E40002, 0x80E40003, 0x0000FFFF,
+            ]),
+            "vs_2_0\nsgn r0, r1, r2, r3\n"
+        );
+        assert_eq!(
+            disassemble(&[0xFFFE0300, 0x02000022, 0x800F0000, 0x80E40001, 0x0000FFFF]),
+            "vs_3_0\nsgn r0, r1\n"
+        );
+    }
+}
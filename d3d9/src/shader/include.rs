@@ -0,0 +1,519 @@
+//! Provides the resolution of `#include` directives for the shader compiler.
+//!
+//! An [`IncludeHandler`] locates and reads included files, while the [`IncludeResolver`] keeps
+//! track of which file included which, so that local includes are found relative to their parent
+//! and cycles are reported instead of recursing until the compiler gives up.
+//!
+//! # Usage
+//!
+//! ```rs
+//! let mut handler = FileIncludeHandler::new("shaders").with_directory("shaders/common");
+//! let compiled = compile_shader(source, "main", "ps_3_0", &options, Some(&mut handler))?;
+//! ```
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+/// Represents whether an include used quotes or angle brackets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IncludeType {
+    /// `#include "file"`, searched relative to the including file first.
+    Local,
+    /// `#include <file>`, searched only in the handler's directories.
+    System,
+}
+
+/// Represents an error which occurred while resolving an include.
+#[derive(Debug, thiserror::Error)]
+pub enum IncludeError {
+    #[error("include {name:?} was not found")]
+    NotFound { name: String },
+
+    #[error("include cycle: {}", .chain.join(" -> "))]
+    Cycle { chain: Vec<String> },
+
+    #[error("failed to read include {path:?}: {from}")]
+    Io { path: String, from: io::Error },
+}
+
+/// Represents a file returned by an [`IncludeHandler`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IncludedFile {
+    /// The resolved path of the file, which identifies it when it includes other files.
+    pub path: String,
+    pub data: Vec<u8>,
+}
+
+/// Locates and reads the files named by `#include` directives.
+pub trait IncludeHandler {
+    /// Opens the file called `name`. `parent` is the resolved path of the file containing the
+    /// directive, or [`None`] if it is the source being compiled.
+    fn open(
+        &mut self,
+        include_type: IncludeType,
+        name: &str,
+        parent: Option<&str>,
+    ) -> Result<IncludedFile, IncludeError>;
+}
+
+/// Reads includes from a list of directories.
+///
+/// Local includes are searched for next to the including file first, then in each directory in
+/// order. System includes are only searched for in the directories.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileIncludeHandler {
+    directories: Vec<PathBuf>,
+}
+
+impl FileIncludeHandler {
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        Self {
+            directories: vec![directory.into()],
+        }
+    }
+
+    /// Adds a directory to search after the existing ones.
+    pub fn with_directory<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.directories.push(directory.into());
+        self
+    }
+
+    pub fn directories(&self) -> &[PathBuf] {
+        &self.directories
+    }
+
+    /// Returns the paths which are tried for an include, in order.
+    pub fn candidates(
+        &self,
+        include_type: IncludeType,
+        name: &str,
+        parent: Option<&str>,
+    ) -> Vec<PathBuf> {
+        let name = Path::new(name);
+        if name.is_absolute() {
+            return vec![name.to_path_buf()];
+        }
+
+        let mut candidates = Vec::new();
+
+        if include_type == IncludeType::Local {
+            if let Some(directory) = parent.and_then(|parent| Path::new(parent).parent()) {
+                candidates.push(directory.join(name));
+            }
+        }
+
+        candidates.extend(
+            self.directories
+                .iter()
+                .map(|directory| directory.join(name)),
+        );
+        candidates
+    }
+}
+
+impl IncludeHandler for FileIncludeHandler {
+    fn open(
+        &mut self,
+        include_type: IncludeType,
+        name: &str,
+        parent: Option<&str>,
+    ) -> Result<IncludedFile, IncludeError> {
+        let path = self
+            .candidates(include_type, name, parent)
+            .into_iter()
+            .find(|candidate| candidate.is_file())
+            .ok_or_else(|| IncludeError::NotFound {
+                name: name.to_string(),
+            })?;
+
+        let path = normalize(&path).to_string_lossy().into_owned();
+        let data = fs::read(&path).map_err(|from| IncludeError::Io {
+            path: path.clone(),
+            from,
+        })?;
+
+        Ok(IncludedFile { path, data })
+    }
+}
+
+/// Serves includes from memory, keyed by `/`-separated paths.
+///
+/// Local includes are looked up relative to the directory of the including file first, then by
+/// their name alone.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryIncludeHandler {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryIncludeHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert<N: Into<String>, D: Into<Vec<u8>>>(&mut self, name: N, data: D) {
+        self.files.insert(normalize_key(&name.into()), data.into());
+    }
+
+    pub fn with_file<N: Into<String>, D: Into<Vec<u8>>>(mut self, name: N, data: D) -> Self {
+        self.insert(name, data);
+        self
+    }
+}
+
+impl IncludeHandler for MemoryIncludeHandler {
+    fn open(
+        &mut self,
+        include_type: IncludeType,
+        name: &str,
+        parent: Option<&str>,
+    ) -> Result<IncludedFile, IncludeError> {
+        let mut candidates = Vec::new();
+
+        if include_type == IncludeType::Local {
+            if let Some((directory, _)) = parent.and_then(|parent| parent.rsplit_once('/')) {
+                candidates.push(normalize_key(&format!("{}/{}", directory, name)));
+            }
+        }
+
+        candidates.push(normalize_key(name));
+
+        candidates
+            .into_iter()
+            .find_map(|path| {
+                let data = self.files.get(&path)?.clone();
+                Some(IncludedFile { path, data })
+            })
+            .ok_or_else(|| IncludeError::NotFound {
+                name: name.to_string(),
+            })
+    }
+}
+
+/// Identifies a file opened through an [`IncludeResolver`].
+pub type IncludeId = usize;
+
+/// Represents an include which was opened during compilation, along with how it was requested.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedInclude {
+    pub include_type: IncludeType,
+    /// The name used in the `#include` directive.
+    pub name: String,
+    /// The resolved path of the including file, or [`None`] for the source being compiled.
+    pub parent: Option<String>,
+    pub path: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct OpenFile {
+    include_type: IncludeType,
+    name: String,
+    path: String,
+    data: Box<[u8]>,
+    parent: Option<IncludeId>,
+}
+
+/// Tracks the files opened through an [`IncludeHandler`] during a single compilation.
+///
+/// The data of every file stays in place until the resolver is dropped, so pointers to it remain
+/// valid while the compiler runs.
+pub struct IncludeResolver<'a> {
+    handler: &'a mut dyn IncludeHandler,
+    root: Option<String>,
+    files: Vec<OpenFile>,
+}
+
+impl<'a> IncludeResolver<'a> {
+    pub fn new(handler: &'a mut dyn IncludeHandler) -> Self {
+        Self {
+            handler,
+            root: None,
+            files: Vec::new(),
+        }
+    }
+
+    /// Sets the path of the source being compiled, which local includes are relative to.
+    pub fn with_root<S: Into<String>>(mut self, root: S) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Opens an include from the file `parent`, or from the root source if it is [`None`].
+    pub fn open(
+        &mut self,
+        include_type: IncludeType,
+        name: &str,
+        parent: Option<IncludeId>,
+    ) -> Result<IncludeId, IncludeError> {
+        let parent_path = match parent {
+            Some(parent) => Some(self.files[parent].path.as_str()),
+            None => self.root.as_deref(),
+        };
+
+        let file = self.handler.open(include_type, name, parent_path)?;
+
+        let chain = self.chain(parent);
+        if chain.contains(&file.path.as_str()) {
+            let mut chain = chain
+                .into_iter()
+                .rev()
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            chain.push(file.path);
+
+            return Err(IncludeError::Cycle { chain });
+        }
+
+        self.files.push(OpenFile {
+            include_type,
+            name: name.to_string(),
+            path: file.path,
+            data: file.data.into_boxed_slice(),
+            parent,
+        });
+
+        Ok(self.files.len() - 1)
+    }
+
+    /// Returns the paths from `id` up to the root source.
+    fn chain(&self, mut id: Option<IncludeId>) -> Vec<&str> {
+        let mut chain = Vec::new();
+
+        while let Some(current) = id {
+            chain.push(self.files[current].path.as_str());
+            id = self.files[current].parent;
+        }
+
+        if let Some(root) = &self.root {
+            chain.push(root.as_str());
+        }
+
+        chain
+    }
+
+    pub fn data(&self, id: IncludeId) -> &[u8] {
+        &self.files[id].data
+    }
+
+    pub fn path(&self, id: IncludeId) -> &str {
+        &self.files[id].path
+    }
+
+    /// Finds the file whose data starts at `ptr`.
+    pub fn find(&self, ptr: *const u8) -> Option<IncludeId> {
+        self.files.iter().position(|file| file.data.as_ptr() == ptr)
+    }
+
+    /// Returns the path of every file which was opened, in order.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.iter().map(|file| file.path.as_str())
+    }
+
+    /// Returns every file which was opened, in order.
+    pub fn into_includes(self) -> Vec<ResolvedInclude> {
+        let parents = self
+            .files
+            .iter()
+            .map(|file| match file.parent {
+                Some(parent) => Some(self.files[parent].path.clone()),
+                None => self.root.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        self.files
+            .into_iter()
+            .zip(parents)
+            .map(|(file, parent)| ResolvedInclude {
+                include_type: file.include_type,
+                name: file.name,
+                parent,
+                path: file.path,
+                data: file.data.into_vec(),
+            })
+            .collect()
+    }
+}
+
+/// Removes `.` and resolves `..` components without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+fn normalize_key(name: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+
+    for part in name.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|last| *last != "..") => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_handler() -> MemoryIncludeHandler {
+        MemoryIncludeHandler::new()
+            .with_file("common/math.hlsl", "static const float PI = 3.14159;")
+            .with_file("common/util.hlsl", "#include \"math.hlsl\"")
+            .with_file("lights.hlsl", "float3 light_direction;")
+            .with_file("a.hlsl", "#include \"b.hlsl\"")
+            .with_file("b.hlsl", "#include \"a.hlsl\"")
+    }
+
+    #[test]
+    fn resolves_local_includes_relative_to_their_parent() {
+        let mut handler = memory_handler();
+        let mut resolver = IncludeResolver::new(&mut handler);
+
+        let util = resolver
+            .open(IncludeType::System, "common/util.hlsl", None)
+            .unwrap();
+        let math = resolver
+            .open(IncludeType::Local, "math.hlsl", Some(util))
+            .unwrap();
+        assert_eq!(resolver.path(math), "common/math.hlsl");
+        assert_eq!(resolver.data(math), b"static const float PI = 3.14159;");
+
+        let lights = resolver
+            .open(IncludeType::Local, "../lights.hlsl", Some(math))
+            .unwrap();
+        assert_eq!(resolver.path(lights), "lights.hlsl");
+
+        assert!(matches!(
+            resolver.open(IncludeType::System, "math.hlsl", Some(util)),
+            Err(IncludeError::NotFound { name }) if name == "math.hlsl"
+        ));
+
+        assert_eq!(resolver.find(resolver.data(math).as_ptr()), Some(math));
+        assert_eq!(resolver.find(b"".as_ptr()), None);
+        assert_eq!(
+            resolver.paths().collect::<Vec<_>>(),
+            ["common/util.hlsl", "common/math.hlsl", "lights.hlsl"]
+        );
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let mut handler = memory_handler();
+        let mut resolver = IncludeResolver::new(&mut handler).with_root("main.hlsl");
+
+        let a = resolver.open(IncludeType::Local, "a.hlsl", None).unwrap();
+        let b = resolver
+            .open(IncludeType::Local, "b.hlsl", Some(a))
+            .unwrap();
+
+        match resolver.open(IncludeType::Local, "a.hlsl", Some(b)) {
+            Err(IncludeError::Cycle { chain }) => {
+                assert_eq!(chain, ["main.hlsl", "a.hlsl", "b.hlsl", "a.hlsl"])
+            }
+            other => panic!("expected a cycle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn lists_resolved_includes() {
+        let mut handler = memory_handler();
+        let mut resolver = IncludeResolver::new(&mut handler).with_root("main.hlsl");
+
+        let util = resolver
+            .open(IncludeType::Local, "common/util.hlsl", None)
+            .unwrap();
+        resolver
+            .open(IncludeType::Local, "math.hlsl", Some(util))
+            .unwrap();
+
+        let includes = resolver.into_includes();
+        assert_eq!(includes.len(), 2);
+        assert_eq!(includes[0].parent.as_deref(), Some("main.hlsl"));
+        assert_eq!(includes[1].name, "math.hlsl");
+        assert_eq!(includes[1].parent.as_deref(), Some("common/util.hlsl"));
+        assert_eq!(includes[1].path, "common/math.hlsl");
+    }
+
+    #[test]
+    fn normalizes_memory_keys() {
+        assert_eq!(normalize_key("a/./b\\..\\c.hlsl"), "a/c.hlsl");
+        assert_eq!(normalize_key("../../a.hlsl"), "../../a.hlsl");
+        assert_eq!(normalize_key("/a//b.hlsl"), "a/b.hlsl");
+    }
+
+    #[test]
+    fn searches_parent_then_directories() {
+        let handler = FileIncludeHandler::new("shaders").with_directory("common");
+
+        assert_eq!(
+            handler.candidates(IncludeType::Local, "light.hlsl", Some("src/main.hlsl")),
+            [
+                PathBuf::from("src/light.hlsl"),
+                PathBuf::from("shaders/light.hlsl"),
+                PathBuf::from("common/light.hlsl"),
+            ]
+        );
+        assert_eq!(
+            handler.candidates(IncludeType::System, "light.hlsl", Some("src/main.hlsl")),
+            [
+                PathBuf::from("shaders/light.hlsl"),
+                PathBuf::from("common/light.hlsl"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_includes_from_disk() {
+        let directory = std::env::temp_dir().join(format!("d3d9-include-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(directory.join("src/sub")).unwrap();
+        fs::create_dir_all(directory.join("system")).unwrap();
+        fs::write(directory.join("src/main.hlsl"), "#include \"sub/x.hlsl\"").unwrap();
+        fs::write(directory.join("src/sub/x.hlsl"), "#include \"y.hlsl\"").unwrap();
+        fs::write(directory.join("src/sub/y.hlsl"), "local").unwrap();
+        fs::write(directory.join("system/y.hlsl"), "system").unwrap();
+
+        let root = directory.join("src/main.hlsl");
+        let mut handler = FileIncludeHandler::new(directory.join("system"));
+        let mut resolver =
+            IncludeResolver::new(&mut handler).with_root(root.to_string_lossy().into_owned());
+
+        let x = resolver
+            .open(IncludeType::Local, "sub/x.hlsl", None)
+            .unwrap();
+        let local = resolver
+            .open(IncludeType::Local, "y.hlsl", Some(x))
+            .unwrap();
+        let system = resolver
+            .open(IncludeType::System, "y.hlsl", Some(x))
+            .unwrap();
+
+        assert_eq!(resolver.data(local), b"local");
+        assert_eq!(resolver.data(system), b"system");
+        assert!(matches!(
+            resolver.open(IncludeType::Local, "../main.hlsl", Some(x)),
+            Err(IncludeError::Cycle { .. })
+        ));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
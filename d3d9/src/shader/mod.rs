@@ -0,0 +1,78 @@
+//! Provides a platform-independent parser and disassembler for Shader Model 1-3 bytecode, along
+//! with a parser for the compiler's [`diagnostics`], the resolution of [`include`]s, an on-disk
+//! [`cache`] of compiled shaders and the [`reflect`]ion of their inputs and outputs.
+//!
+//! The token streams accepted by [`Device::create_vertex_shader`] and
+//! [`Device::create_pixel_shader`], or produced by [`compile_shader`], can be parsed into a
+//! [`Shader`] and printed in the usual assembly syntax.
+//!
+//! # Usage
+//!
+//! ```rs
+//! let shader = Shader::from_bytes(buffer.as_bytes())?;
+//! println!("{shader}");
+//! // vs_3_0
+//! // dcl_position v0
+//! // mov oPos, v0
+//! ```
+//!
+//! [`Device::create_vertex_shader`]: crate::std::interfaces::Device::create_vertex_shader
+//! [`Device::create_pixel_shader`]: crate::std::interfaces::Device::create_pixel_shader
+//! [`compile_shader`]: crate::x::functions::compile_shader
+
+pub mod bytecode;
+pub mod cache;
+pub mod ctab;
+pub mod diagnostics;
+mod disasm;
+pub mod include;
+pub mod reflect;
+
+pub use bytecode::{
+    Comment, Comparison, Constant, Declaration, DestinationParameter, Instruction, Opcode,
+    Register, RegisterType, RelativeAddress, SamplerTextureType, Shader, ShaderType, ShaderVersion,
+    SourceModifier, SourceParameter, Statement,
+};
+pub use cache::{CacheKey, CachedConstant, CachedShader, ShaderCache};
+pub use ctab::{Ctab, CtabConstant, StructMember, TypeInfo};
+pub use diagnostics::{parse_diagnostics, Diagnostic, Severity};
+pub use include::{
+    FileIncludeHandler, IncludeError, IncludeHandler, IncludeResolver, IncludeType, IncludedFile,
+    MemoryIncludeHandler, ResolvedInclude,
+};
+pub use reflect::{validate_layout, LayoutError, Parameter, Reflection, Sampler, Semantic};
+
+/// Represents an error which occurred while parsing a shader token stream.
+#[derive(Debug, thiserror::Error)]
+pub enum ShaderError {
+    #[error("byte length {0} is not a multiple of 4")]
+    UnalignedLength(usize),
+
+    #[error("token stream ended before the end token")]
+    UnexpectedEnd,
+
+    #[error("invalid version token {0:#010X}")]
+    InvalidVersion(u32),
+
+    #[error("unknown opcode {opcode:#06X} at token {offset}")]
+    UnknownOpcode { opcode: u16, offset: usize },
+
+    #[error("unknown register type {register_type} at token {offset}")]
+    UnknownRegisterType { register_type: u32, offset: usize },
+
+    #[error("unknown source modifier {modifier} at token {offset}")]
+    UnknownModifier { modifier: u32, offset: usize },
+
+    #[error("invalid declaration at token {offset}")]
+    InvalidDeclaration { offset: usize },
+
+    #[error("invalid constant table: {0}")]
+    InvalidConstantTable(&'static str),
+
+    #[error("instruction at token {offset} has length {expected} but {found} tokens were read")]
+    LengthMismatch {
+        offset: usize,
+        expected: usize,
+        found: usize,
+    },
+}
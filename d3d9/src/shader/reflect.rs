@@ -0,0 +1,475 @@
+//! Provides reflection of the inputs, outputs and samplers declared by a shader.
+//!
+//! # Usage
+//!
+//! ```rs
+//! let shader = Shader::from_bytes(compiled.shader.as_bytes())?;
+//! validate_layout(ColoredVertex::ELEMENTS, &shader)?;
+//!
+//! for sampler in Reflection::from_shader(&shader).samplers {
+//!     println!("s{}: {:?}", sampler.index, sampler.texture_type);
+//! }
+//! ```
+
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+};
+
+use crate::{
+    shader::{
+        disasm::usage_name, Declaration, Register, RegisterType, SamplerTextureType, Shader,
+        ShaderError, ShaderType, ShaderVersion,
+    },
+    std::types::{DeclUsage, VertexElement},
+};
+
+/// Represents a usage and usage index, such as `TEXCOORD1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Semantic {
+    pub usage: DeclUsage,
+    pub index: u32,
+}
+
+impl Semantic {
+    pub fn new(usage: DeclUsage, index: u32) -> Self {
+        Self { usage, index }
+    }
+}
+
+impl Display for Semantic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", usage_name(self.usage).to_uppercase(), self.index)
+    }
+}
+
+/// Represents a register a shader reads from or writes to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Parameter {
+    pub register: Register,
+    /// The semantic of the register, either declared or implied by the register itself.
+    pub semantic: Option<Semantic>,
+    /// The components which are declared, read or written, with `x` in the lowest bit.
+    pub mask: u8,
+}
+
+/// Represents a sampler declared or used by a shader.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Sampler {
+    pub index: u32,
+    pub texture_type: SamplerTextureType,
+}
+
+/// Represents the interface of a shader, as recovered from its bytecode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reflection {
+    pub version: ShaderVersion,
+    pub inputs: Vec<Parameter>,
+    pub outputs: Vec<Parameter>,
+    pub samplers: Vec<Sampler>,
+}
+
+impl Reflection {
+    /// Collects the inputs, outputs and samplers of a shader.
+    ///
+    /// Declarations are used where the shader model has them. Otherwise, such as for the outputs
+    /// of vertex shaders before 3.0, the registers which are accessed are listed instead.
+    pub fn from_shader(shader: &Shader) -> Self {
+        let version = shader.version;
+        let mut reflection = Self {
+            version,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            samplers: Vec::new(),
+        };
+
+        for instruction in shader.instructions() {
+            if let (Some(declaration), Some(destination)) =
+                (&instruction.declaration, &instruction.destination)
+            {
+                let register = destination.register;
+                match *declaration {
+                    Declaration::Sampler(texture_type) => {
+                        reflection.add_sampler(register.index, texture_type)
+                    }
+                    Declaration::Usage { usage, index } => {
+                        // Pixel shaders before 3.0 have no usage, only `dcl v0` or `dcl t0`.
+                        let semantic =
+                            if version.shader_type == ShaderType::Pixel && version.major < 3 {
+                                implied_semantic(version, register)
+                            } else {
+                                Some(Semantic::new(usage, index))
+                            };
+
+                        let parameter = Parameter {
+                            register,
+                            semantic,
+                            mask: destination.write_mask,
+                        };
+                        if is_output(version, register.register_type) {
+                            add(&mut reflection.outputs, parameter);
+                        } else {
+                            add(&mut reflection.inputs, parameter);
+                        }
+                    }
+                }
+
+                continue;
+            }
+
+            if let Some(destination) = &instruction.destination {
+                let register = destination.register;
+                let parameter = Parameter {
+                    register,
+                    semantic: implied_semantic(version, register),
+                    mask: destination.write_mask,
+                };
+
+                if is_output(version, register.register_type) {
+                    add(&mut reflection.outputs, parameter);
+                } else if version.shader_type == ShaderType::Pixel
+                    && version.major < 2
+                    && register.register_type == RegisterType::Address
+                {
+                    // Texture instructions such as `tex t0` sample with the coordinates in `t0`.
+                    add(&mut reflection.inputs, parameter);
+                }
+            }
+
+            for source in &instruction.sources {
+                let register = source.register;
+                match register.register_type {
+                    RegisterType::Sampler => {
+                        reflection.add_sampler(register.index, SamplerTextureType::Unknown)
+                    }
+                    register_type if is_input(version, register_type) => add(
+                        &mut reflection.inputs,
+                        Parameter {
+                            register,
+                            semantic: implied_semantic(version, register),
+                            mask: source
+                                .swizzle
+                                .iter()
+                                .fold(0, |mask, component| mask | 1 << component),
+                        },
+                    ),
+                    _ => {}
+                }
+            }
+        }
+
+        reflection
+    }
+
+    pub fn from_bytecode(bytes: &[u8]) -> Result<Self, ShaderError> {
+        Ok(Self::from_shader(&Shader::from_bytes(bytes)?))
+    }
+
+    /// Finds the input with the given semantic.
+    pub fn input(&self, usage: DeclUsage, index: u32) -> Option<&Parameter> {
+        let semantic = Semantic::new(usage, index);
+        self.inputs
+            .iter()
+            .find(|input| input.semantic == Some(semantic))
+    }
+
+    /// Finds the output with the given semantic.
+    pub fn output(&self, usage: DeclUsage, index: u32) -> Option<&Parameter> {
+        let semantic = Semantic::new(usage, index);
+        self.outputs
+            .iter()
+            .find(|output| output.semantic == Some(semantic))
+    }
+
+    pub fn sampler(&self, index: u32) -> Option<&Sampler> {
+        self.samplers.iter().find(|sampler| sampler.index == index)
+    }
+
+    /// Adds a sampler, preferring a declared texture type over [`SamplerTextureType::Unknown`].
+    fn add_sampler(&mut self, index: u32, texture_type: SamplerTextureType) {
+        match self
+            .samplers
+            .iter_mut()
+            .find(|sampler| sampler.index == index)
+        {
+            Some(sampler) if sampler.texture_type == SamplerTextureType::Unknown => {
+                sampler.texture_type = texture_type
+            }
+            Some(_) => {}
+            None => self.samplers.push(Sampler {
+                index,
+                texture_type,
+            }),
+        }
+    }
+}
+
+/// Adds a parameter, merging its mask into an existing one for the same register.
+fn add(parameters: &mut Vec<Parameter>, parameter: Parameter) {
+    match parameters
+        .iter_mut()
+        .find(|existing| existing.register == parameter.register)
+    {
+        Some(existing) => {
+            existing.mask |= parameter.mask;
+            existing.semantic = existing.semantic.or(parameter.semantic);
+        }
+        None => parameters.push(parameter),
+    }
+}
+
+fn is_input(version: ShaderVersion, register_type: RegisterType) -> bool {
+    match (version.shader_type, register_type) {
+        (_, RegisterType::Input) => true,
+        // `t#` registers of pixel shaders share their type with the address register.
+        (ShaderType::Pixel, RegisterType::Address) => true,
+        _ => false,
+    }
+}
+
+fn is_output(version: ShaderVersion, register_type: RegisterType) -> bool {
+    match version.shader_type {
+        ShaderType::Vertex => matches!(
+            register_type,
+            RegisterType::RastOut | RegisterType::AttrOut | RegisterType::TexCrdOut
+        ),
+        ShaderType::Pixel => matches!(
+            register_type,
+            RegisterType::ColorOut | RegisterType::DepthOut
+        ),
+    }
+}
+
+/// Returns the semantic implied by a register which has no declared usage.
+fn implied_semantic(version: ShaderVersion, register: Register) -> Option<Semantic> {
+    let Register {
+        register_type,
+        index,
+    } = register;
+
+    match (version.shader_type, register_type) {
+        (ShaderType::Vertex, RegisterType::RastOut) => match index {
+            0 => Some(Semantic::new(DeclUsage::Position, 0)),
+            1 => Some(Semantic::new(DeclUsage::Fog, 0)),
+            2 => Some(Semantic::new(DeclUsage::PSize, 0)),
+            _ => None,
+        },
+        (ShaderType::Vertex, RegisterType::AttrOut) => Some(Semantic::new(DeclUsage::Color, index)),
+        // From 3.0 on, output registers are generic and must be declared.
+        (ShaderType::Vertex, RegisterType::TexCrdOut) if version.major < 3 => {
+            Some(Semantic::new(DeclUsage::TexCoord, index))
+        }
+        (ShaderType::Pixel, RegisterType::Input) if version.major < 3 => {
+            Some(Semantic::new(DeclUsage::Color, index))
+        }
+        (ShaderType::Pixel, RegisterType::Address) => {
+            Some(Semantic::new(DeclUsage::TexCoord, index))
+        }
+        (ShaderType::Pixel, RegisterType::ColorOut) => Some(Semantic::new(DeclUsage::Color, index)),
+        (ShaderType::Pixel, RegisterType::DepthOut) => Some(Semantic::new(DeclUsage::Depth, 0)),
+        _ => None,
+    }
+}
+
+/// Represents a mismatch between a vertex declaration and a vertex shader.
+#[derive(Debug, thiserror::Error)]
+pub enum LayoutError {
+    #[error("expected a vertex shader, found {0}")]
+    NotVertexShader(ShaderVersion),
+
+    #[error("input v{} ({}) is not supplied by the vertex declaration", .register.index, .semantic)]
+    MissingInput {
+        register: Register,
+        semantic: Semantic,
+    },
+
+    #[error("input v{} has no declared semantic", .0.index)]
+    UndeclaredInput(Register),
+}
+
+/// Checks that a vertex declaration supplies every input a vertex shader declares.
+///
+/// Elements which the shader does not read are allowed.
+pub fn validate_layout(elements: &[VertexElement], shader: &Shader) -> Result<(), LayoutError> {
+    if shader.version.shader_type != ShaderType::Vertex {
+        return Err(LayoutError::NotVertexShader(shader.version));
+    }
+
+    for input in Reflection::from_shader(shader).inputs {
+        let Some(semantic) = input.semantic else {
+            return Err(LayoutError::UndeclaredInput(input.register));
+        };
+
+        // Stream `0xFF` marks the end of a declaration rather than an element.
+        let supplied = elements.iter().any(|element| {
+            element.stream != 0xFF
+                && element.usage == semantic.usage
+                && element.usage_index as u32 == semantic.index
+        });
+        if !supplied {
+            return Err(LayoutError::MissingInput {
+                register: input.register,
+                semantic,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std::types::{DeclMethod, DeclType};
+
+    fn element(usage: DeclUsage, usage_index: u8) -> VertexElement {
+        VertexElement {
+            stream: 0,
+            offset: 0,
+            kind: DeclType::Float4,
+            method: DeclMethod::Default,
+            usage,
+            usage_index,
+        }
+    }
+
+    /// ```text
+    /// vs_3_0
+    /// dcl_position v0
+    /// dcl_texcoord1 v1
+    /// dcl_position o0
+    /// mov o0, v0
+    /// ```
+    fn vs_3_0() -> Shader {
+        Shader::parse(&[
+            0xFFFE0300, 0x0200001F, 0x80000000, 0x900F0000, 0x0200001F, 0x80010005, 0x900F0001,
+            0x0200001F, 0x80000000, 0xE00F0000, 0x02000001, 0xE00F0000, 0x90E40000, 0x0000FFFF,
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn reflects_declared_vertex_shader_semantics() {
+        let reflection = Reflection::from_shader(&vs_3_0());
+
+        assert_eq!(reflection.inputs.len(), 2);
+        assert_eq!(
+            reflection.input(DeclUsage::TexCoord, 1).unwrap().register,
+            Register {
+                register_type: RegisterType::Input,
+                index: 1
+            }
+        );
+        assert_eq!(reflection.outputs.len(), 1);
+        assert_eq!(
+            reflection
+                .output(DeclUsage::Position, 0)
+                .unwrap()
+                .register
+                .register_type,
+            RegisterType::TexCrdOut
+        );
+    }
+
+    #[test]
+    fn implies_vertex_shader_outputs_before_3_0() {
+        // vs_2_0
+        // dcl_position v0
+        // dcl_color v1
+        // dcl_texcoord v2
+        // mov oPos, v0
+        // mov oD0, v1
+        // mov oT0.xy, v2
+        let shader = Shader::parse(&[
+            0xFFFE0200, 0x0200001F, 0x80000000, 0x900F0000, 0x0200001F, 0x8000000A, 0x900F0001,
+            0x0200001F, 0x80000005, 0x900F0002, 0x02000001, 0xC00F0000, 0x90E40000, 0x02000001,
+            0xD00F0000, 0x90E40001, 0x02000001, 0xE0030000, 0x90E40002, 0x0000FFFF,
+        ])
+        .unwrap();
+        let reflection = Reflection::from_shader(&shader);
+
+        assert_eq!(
+            reflection
+                .outputs
+                .iter()
+                .map(|output| (output.semantic, output.mask))
+                .collect::<Vec<_>>(),
+            [
+                (Some(Semantic::new(DeclUsage::Position, 0)), 0b1111),
+                (Some(Semantic::new(DeclUsage::Color, 0)), 0b1111),
+                (Some(Semantic::new(DeclUsage::TexCoord, 0)), 0b0011),
+            ]
+        );
+        assert!(reflection.input(DeclUsage::Color, 0).is_some());
+    }
+
+    #[test]
+    fn reflects_pixel_shader_inputs_and_samplers() {
+        // ps_2_0
+        // dcl t0.xy
+        // dcl_2d s0
+        // texld r0, t0, s0
+        // mov oC0, r0
+        let shader = Shader::parse(&[
+            0xFFFF0200, 0x0200001F, 0x80000000, 0xB0030000, 0x0200001F, 0x90000000, 0xA00F0800,
+            0x03000042, 0x800F0000, 0xB0E40000, 0xA0E40800, 0x02000001, 0x800F0800, 0x80E40000,
+            0x0000FFFF,
+        ])
+        .unwrap();
+        let reflection = Reflection::from_shader(&shader);
+
+        assert_eq!(reflection.inputs.len(), 1);
+        assert_eq!(
+            reflection.inputs[0].semantic,
+            Some(Semantic::new(DeclUsage::TexCoord, 0))
+        );
+        assert_eq!(reflection.inputs[0].mask, 0b1111);
+        assert_eq!(
+            reflection.sampler(0),
+            Some(&Sampler {
+                index: 0,
+                texture_type: SamplerTextureType::Texture2D
+            })
+        );
+        assert_eq!(
+            reflection.outputs[0].semantic,
+            Some(Semantic::new(DeclUsage::Color, 0))
+        );
+        assert!(matches!(
+            validate_layout(&[], &shader),
+            Err(LayoutError::NotVertexShader(_))
+        ));
+    }
+
+    #[test]
+    fn validates_vertex_layouts() {
+        let shader = vs_3_0();
+
+        assert!(validate_layout(
+            &[
+                element(DeclUsage::Position, 0),
+                element(DeclUsage::TexCoord, 1),
+                element(DeclUsage::Normal, 0),
+            ],
+            &shader
+        )
+        .is_ok());
+
+        let error = validate_layout(
+            &[
+                element(DeclUsage::Position, 0),
+                element(DeclUsage::TexCoord, 0),
+            ],
+            &shader,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "input v1 (TEXCOORD1) is not supplied by the vertex declaration"
+        );
+
+        let mut end = element(DeclUsage::TexCoord, 1);
+        end.stream = 0xFF;
+        assert!(validate_layout(&[element(DeclUsage::Position, 0), end], &shader).is_err());
+    }
+}
@@ -0,0 +1,727 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+use crate::{
+    error::WindowsResult,
+    std::{
+        backend::DeviceBackend,
+        states::{
+            RenderState, RenderStateType, SamplerState, SamplerStateType, StateError,
+            TextureFilterType, TextureStageState, TextureStageStateType,
+        },
+        types::{
+            Clear, Color, CooperativeLevel, DisplayMode, Format, Handle, MultiSampleType, Pool,
+            PresentationParameters, PrimitiveType, QueryType, Rect, RegionData, StateBlockType,
+            Usage, VertexElement, FVF,
+        },
+    },
+    sys::HWND,
+};
+
+/// Represents the number of state changes a [`StateCache`] has seen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of calls which were passed on to the device.
+    pub issued: u64,
+    /// The number of calls which were skipped because they would not change anything.
+    pub filtered: u64,
+}
+
+/// Wraps a [`DeviceBackend`], skipping calls which would bind a resource or set a state to the
+/// value it already has.
+///
+/// The cache only knows about changes made through it. After applying a state block or calling
+/// into the device directly, call [`StateCache::invalidate`]. Resetting the device through the
+/// cache invalidates it automatically. Calls made while recording a state block are always passed
+/// on, since they do not change the device state.
+///
+/// Bound resources are kept alive by the cache until they are replaced or the cache is
+/// invalidated.
+pub struct StateCache<B: DeviceBackend> {
+    device: B,
+    bindings: RefCell<Bindings<B>>,
+    recording_state_block: Cell<bool>,
+    stats: Cell<CacheStats>,
+}
+
+struct Bindings<B: DeviceBackend> {
+    textures: HashMap<u32, Option<B::Texture>>,
+    stream_sources: HashMap<u32, Option<(B::VertexBuffer, u32, u32)>>,
+    indices: Option<B::IndexBuffer>,
+    vertex_shader: Option<B::VertexShader>,
+    pixel_shader: Option<B::PixelShader>,
+    vertex_declaration: Option<B::VertexDeclaration>,
+    render_states: HashMap<RenderStateType, Option<RenderState>>,
+    sampler_states: HashMap<(u32, SamplerStateType), Option<SamplerState>>,
+    texture_stage_states: HashMap<(u32, TextureStageStateType), Option<TextureStageState>>,
+}
+
+impl<B: DeviceBackend> Default for Bindings<B> {
+    fn default() -> Self {
+        Self {
+            textures: HashMap::new(),
+            stream_sources: HashMap::new(),
+            indices: None,
+            vertex_shader: None,
+            pixel_shader: None,
+            vertex_declaration: None,
+            render_states: HashMap::new(),
+            sampler_states: HashMap::new(),
+            texture_stage_states: HashMap::new(),
+        }
+    }
+}
+
+impl<B: DeviceBackend> StateCache<B> {
+    pub fn new(device: B) -> Self {
+        Self {
+            device,
+            bindings: RefCell::default(),
+            recording_state_block: Cell::new(false),
+            stats: Cell::default(),
+        }
+    }
+
+    pub fn device(&self) -> &B {
+        &self.device
+    }
+
+    pub fn into_inner(self) -> B {
+        self.device
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats.get()
+    }
+
+    pub fn reset_stats(&self) {
+        self.stats.take();
+    }
+
+    /// Forgets every cached binding and state, so the next call of each kind is passed on.
+    pub fn invalidate(&self) {
+        self.bindings.take();
+    }
+
+    /// Calls `set` unless `slot` already holds `value`, then updates `slot` to match the device.
+    fn filter<V: PartialEq, E>(
+        &self,
+        slot: &mut Option<V>,
+        value: V,
+        set: impl FnOnce() -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut stats = self.stats.get();
+        let recording = self.recording_state_block.get();
+
+        if !recording && slot.as_ref() == Some(&value) {
+            stats.filtered += 1;
+            self.stats.set(stats);
+            return Ok(());
+        }
+
+        stats.issued += 1;
+        self.stats.set(stats);
+
+        let result = set();
+        if !recording {
+            // A failed call leaves the device in an unknown state.
+            *slot = result.is_ok().then_some(value);
+        }
+
+        result
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+impl<B: DeviceBackend> DeviceBackend for StateCache<B> {
+    type CubeTexture = B::CubeTexture;
+    type IndexBuffer = B::IndexBuffer;
+    type PixelShader = B::PixelShader;
+    type Query = B::Query;
+    type StateBlock = B::StateBlock;
+    type Surface = B::Surface;
+    type SwapChain = B::SwapChain;
+    type Texture = B::Texture;
+    type VertexBuffer = B::VertexBuffer;
+    type VertexDeclaration = B::VertexDeclaration;
+    type VertexShader = B::VertexShader;
+    type VolumeTexture = B::VolumeTexture;
+
+    fn begin_scene(&self) -> WindowsResult<()> {
+        self.device.begin_scene()
+    }
+
+    fn begin_state_block(&self) -> WindowsResult<()> {
+        self.device.begin_state_block()?;
+        self.recording_state_block.set(true);
+
+        Ok(())
+    }
+
+    fn clear(
+        &self,
+        count: u32,
+        rects: Option<&[Rect]>,
+        flags: Clear,
+        color: Color,
+        z: f32,
+        stencil: u32,
+    ) -> WindowsResult<()> {
+        self.device.clear(count, rects, flags, color, z, stencil)
+    }
+
+    fn color_fill(
+        &self,
+        surface: &Self::Surface,
+        rect: Option<Rect>,
+        color: Color,
+    ) -> WindowsResult<()> {
+        self.device.color_fill(surface, rect, color)
+    }
+
+    fn create_additional_swap_chain(
+        &self,
+        presentation_parameters: &mut PresentationParameters,
+    ) -> WindowsResult<Self::SwapChain> {
+        self.device
+            .create_additional_swap_chain(presentation_parameters)
+    }
+
+    fn create_cube_texture(
+        &self,
+        edge_length: u32,
+        levels: u32,
+        usage: u32,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Self::CubeTexture> {
+        self.device
+            .create_cube_texture(edge_length, levels, usage, format, pool)
+    }
+
+    fn create_depth_stencil_surface(
+        &self,
+        width: u32,
+        height: u32,
+        format: Format,
+        multi_sample_type: MultiSampleType,
+        multi_sample_quality: u32,
+        discard: bool,
+    ) -> WindowsResult<Self::Surface> {
+        self.device.create_depth_stencil_surface(
+            width,
+            height,
+            format,
+            multi_sample_type,
+            multi_sample_quality,
+            discard,
+        )
+    }
+
+    fn create_index_buffer(
+        &self,
+        length: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Self::IndexBuffer> {
+        self.device.create_index_buffer(length, usage, format, pool)
+    }
+
+    fn create_offscreen_plain_surface(
+        &self,
+        width: u32,
+        height: u32,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Self::Surface> {
+        self.device
+            .create_offscreen_plain_surface(width, height, format, pool)
+    }
+
+    fn create_pixel_shader(&self, shader: &[u32]) -> WindowsResult<Self::PixelShader> {
+        self.device.create_pixel_shader(shader)
+    }
+
+    fn create_query(&self, query_type: QueryType) -> WindowsResult<Self::Query> {
+        self.device.create_query(query_type)
+    }
+
+    fn create_render_target(
+        &self,
+        width: u32,
+        height: u32,
+        format: Format,
+        multi_sample_type: MultiSampleType,
+        multi_sample_quality: u32,
+        lockable: bool,
+    ) -> WindowsResult<Self::Surface> {
+        self.device.create_render_target(
+            width,
+            height,
+            format,
+            multi_sample_type,
+            multi_sample_quality,
+            lockable,
+        )
+    }
+
+    fn create_state_block(
+        &self,
+        state_block_type: StateBlockType,
+    ) -> WindowsResult<Self::StateBlock> {
+        self.device.create_state_block(state_block_type)
+    }
+
+    fn create_texture(
+        &self,
+        width: u32,
+        height: u32,
+        levels: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Self::Texture> {
+        self.device
+            .create_texture(width, height, levels, usage, format, pool)
+    }
+
+    fn create_vertex_buffer(
+        &self,
+        length: u32,
+        usage: Usage,
+        fvf: FVF,
+        pool: Pool,
+    ) -> WindowsResult<Self::VertexBuffer> {
+        self.device.create_vertex_buffer(length, usage, fvf, pool)
+    }
+
+    fn create_vertex_declaration(
+        &self,
+        vertex_elements: &[VertexElement],
+    ) -> WindowsResult<Self::VertexDeclaration> {
+        self.device.create_vertex_declaration(vertex_elements)
+    }
+
+    fn create_vertex_shader(&self, shader: &[u32]) -> WindowsResult<Self::VertexShader> {
+        self.device.create_vertex_shader(shader)
+    }
+
+    fn create_volume_texture(
+        &self,
+        width: u32,
+        height: u32,
+        depth: u32,
+        levels: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Self::VolumeTexture> {
+        self.device
+            .create_volume_texture(width, height, depth, levels, usage, format, pool)
+    }
+
+    fn delete_patch(&self, handle: Handle) -> WindowsResult<()> {
+        self.device.delete_patch(handle)
+    }
+
+    fn draw_indexed_primitive(
+        &self,
+        primitive_type: PrimitiveType,
+        base_vertex_index: i32,
+        minimum_vertex_index: u32,
+        n_vertices: u32,
+        indices_index: u32,
+        n_primitives: u32,
+    ) -> WindowsResult<()> {
+        self.device.draw_indexed_primitive(
+            primitive_type,
+            base_vertex_index,
+            minimum_vertex_index,
+            n_vertices,
+            indices_index,
+            n_primitives,
+        )
+    }
+
+    fn draw_indexed_primitive_up<V, I>(
+        &self,
+        primitive_type: PrimitiveType,
+        minimum_vertex_index: u32,
+        n_primitives: u32,
+        indices: Vec<I>,
+        indices_format: Format,
+        vertices: Vec<V>,
+        vertices_stride: u32,
+    ) -> WindowsResult<()> {
+        // Direct3D unbinds stream 0 and the index buffer after drawing from user memory.
+        let mut bindings = self.bindings.borrow_mut();
+        bindings.stream_sources.remove(&0);
+        bindings.indices = None;
+
+        self.device.draw_indexed_primitive_up(
+            primitive_type,
+            minimum_vertex_index,
+            n_primitives,
+            indices,
+            indices_format,
+            vertices,
+            vertices_stride,
+        )
+    }
+
+    fn draw_primitive(
+        &self,
+        primitive_type: PrimitiveType,
+        start_vertex: u32,
+        n_primitives: u32,
+    ) -> WindowsResult<()> {
+        self.device
+            .draw_primitive(primitive_type, start_vertex, n_primitives)
+    }
+
+    fn draw_primitive_up<V>(
+        &self,
+        primitive_type: PrimitiveType,
+        n_primitives: u32,
+        vertices: Vec<V>,
+        vertices_stride: u32,
+    ) -> WindowsResult<()> {
+        // Direct3D unbinds stream 0 after drawing from user memory.
+        self.bindings.borrow_mut().stream_sources.remove(&0);
+
+        self.device
+            .draw_primitive_up(primitive_type, n_primitives, vertices, vertices_stride)
+    }
+
+    fn end_scene(&self) -> WindowsResult<()> {
+        self.device.end_scene()
+    }
+
+    fn end_state_block(&self) -> WindowsResult<Self::StateBlock> {
+        self.recording_state_block.set(false);
+        self.device.end_state_block()
+    }
+
+    fn get_back_buffer(&self, swap_chain: u32, index: u32) -> WindowsResult<Self::Surface> {
+        self.device.get_back_buffer(swap_chain, index)
+    }
+
+    fn get_display_mode(&self, swap_chain: u32) -> WindowsResult<DisplayMode> {
+        self.device.get_display_mode(swap_chain)
+    }
+
+    fn get_front_buffer_data(
+        &self,
+        swap_chain: u32,
+        destination: &Self::Surface,
+    ) -> WindowsResult<()> {
+        self.device.get_front_buffer_data(swap_chain, destination)
+    }
+
+    fn get_render_target(&self, index: u32) -> WindowsResult<Self::Surface> {
+        self.device.get_render_target(index)
+    }
+
+    fn get_render_target_data(
+        &self,
+        render_target: &Self::Surface,
+        destination: &Self::Surface,
+    ) -> WindowsResult<()> {
+        self.device
+            .get_render_target_data(render_target, destination)
+    }
+
+    fn present(
+        &self,
+        src_rect: Option<&Rect>,
+        dest_rect: Option<&Rect>,
+        window_override: Option<HWND>,
+        dirty_region_data: Option<&RegionData>,
+    ) -> WindowsResult<()> {
+        self.device
+            .present(src_rect, dest_rect, window_override, dirty_region_data)
+    }
+
+    fn reset(&self, presentation_parameters: &mut PresentationParameters) -> WindowsResult<()> {
+        // A reset puts every state back to its default.
+        self.invalidate();
+        self.device.reset(presentation_parameters)
+    }
+
+    fn set_vertex_declaration(
+        &self,
+        vertex_declaration: &Self::VertexDeclaration,
+    ) -> WindowsResult<()> {
+        let mut bindings = self.bindings.borrow_mut();
+        self.filter(
+            &mut bindings.vertex_declaration,
+            vertex_declaration.clone(),
+            || self.device.set_vertex_declaration(vertex_declaration),
+        )
+    }
+
+    fn set_indices(&self, index_buffer: &Self::IndexBuffer) -> WindowsResult<()> {
+        let mut bindings = self.bindings.borrow_mut();
+        self.filter(&mut bindings.indices, index_buffer.clone(), || {
+            self.device.set_indices(index_buffer)
+        })
+    }
+
+    fn set_vertex_shader(&self, vertex_shader: &Self::VertexShader) -> WindowsResult<()> {
+        let mut bindings = self.bindings.borrow_mut();
+        self.filter(&mut bindings.vertex_shader, vertex_shader.clone(), || {
+            self.device.set_vertex_shader(vertex_shader)
+        })
+    }
+
+    fn set_pixel_shader(&self, pixel_shader: &Self::PixelShader) -> WindowsResult<()> {
+        let mut bindings = self.bindings.borrow_mut();
+        self.filter(&mut bindings.pixel_shader, pixel_shader.clone(), || {
+            self.device.set_pixel_shader(pixel_shader)
+        })
+    }
+
+    fn set_stream_source(
+        &self,
+        index: u32,
+        vertex_buffer: &Self::VertexBuffer,
+        byte_offset: u32,
+        stride: u32,
+    ) -> WindowsResult<()> {
+        let mut bindings = self.bindings.borrow_mut();
+        self.filter(
+            bindings.stream_sources.entry(index).or_default(),
+            (vertex_buffer.clone(), byte_offset, stride),
+            || {
+                self.device
+                    .set_stream_source(index, vertex_buffer, byte_offset, stride)
+            },
+        )
+    }
+
+    fn set_viewport(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        max_z: f32,
+        min_z: f32,
+    ) -> WindowsResult<()> {
+        self.device.set_viewport(x, y, width, height, max_z, min_z)
+    }
+
+    fn set_clipping(&self, value: bool) -> WindowsResult<()> {
+        let mut bindings = self.bindings.borrow_mut();
+        self.filter(
+            bindings
+                .render_states
+                .entry(RenderStateType::Clipping)
+                .or_default(),
+            RenderState::Clipping(value),
+            || self.device.set_clipping(value),
+        )
+    }
+
+    /// Returns the cached value of the state if it is known, without calling into the device.
+    fn get_render_state(&self, kind: RenderStateType) -> Result<RenderState, StateError> {
+        let cached = self.bindings.borrow().render_states.get(&kind).copied();
+
+        match cached.flatten() {
+            Some(state) => Ok(state),
+            None => self.device.get_render_state(kind),
+        }
+    }
+
+    fn set_render_state(&self, state: RenderState) -> Result<(), StateError> {
+        let mut bindings = self.bindings.borrow_mut();
+        self.filter(
+            bindings.render_states.entry(state.kind()).or_default(),
+            state,
+            || self.device.set_render_state(state),
+        )
+    }
+
+    fn set_sampler_state(&self, sampler: u32, state: SamplerState) -> Result<(), StateError> {
+        let mut bindings = self.bindings.borrow_mut();
+        self.filter(
+            bindings
+                .sampler_states
+                .entry((sampler, state.kind()))
+                .or_default(),
+            state,
+            || self.device.set_sampler_state(sampler, state),
+        )
+    }
+
+    fn set_texture_stage_state(
+        &self,
+        stage: u32,
+        state: TextureStageState,
+    ) -> Result<(), StateError> {
+        let mut bindings = self.bindings.borrow_mut();
+        self.filter(
+            bindings
+                .texture_stage_states
+                .entry((stage, state.kind()))
+                .or_default(),
+            state,
+            || self.device.set_texture_stage_state(stage, state),
+        )
+    }
+
+    fn set_texture(&self, stage: u32, texture: &Self::Texture) -> WindowsResult<()> {
+        let mut bindings = self.bindings.borrow_mut();
+        self.filter(
+            bindings.textures.entry(stage).or_default(),
+            texture.clone(),
+            || self.device.set_texture(stage, texture),
+        )
+    }
+
+    fn stretch_rect(
+        &self,
+        source: &Self::Surface,
+        destination: &Self::Surface,
+        filter: TextureFilterType,
+    ) -> WindowsResult<()> {
+        self.device.stretch_rect(source, destination, filter)
+    }
+
+    fn test_cooperative_level(&self) -> WindowsResult<CooperativeLevel> {
+        self.device.test_cooperative_level()
+    }
+
+    fn update_texture(
+        &self,
+        source: &Self::Texture,
+        destination: &Self::Texture,
+    ) -> WindowsResult<()> {
+        self.device.update_texture(source, destination)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std::{
+        backend::recording::{Call, RecordingDevice},
+        states::{Cull, TextureAddress},
+    };
+
+    #[test]
+    fn filters_redundant_calls() {
+        let cache = StateCache::new(RecordingDevice::new());
+        let texture = cache
+            .create_texture(4, 4, 1, Usage::default(), Format::A8R8G8B8, Pool::Managed)
+            .unwrap();
+        let vertex_buffer = cache
+            .create_vertex_buffer(36, Usage::default(), FVF::default(), Pool::Managed)
+            .unwrap();
+        cache.device().take_calls();
+
+        for _ in 0..3 {
+            cache
+                .set_render_state(RenderState::CullMode(Cull::None))
+                .unwrap();
+            cache
+                .set_sampler_state(0, SamplerState::AddressU(TextureAddress::Clamp))
+                .unwrap();
+            cache.set_texture(0, &texture).unwrap();
+            cache.set_stream_source(0, &vertex_buffer, 0, 12).unwrap();
+        }
+        assert_eq!(cache.device().calls().len(), 4);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                issued: 4,
+                filtered: 8,
+            }
+        );
+
+        // Different values, slots and strides are passed on.
+        cache
+            .set_render_state(RenderState::CullMode(Cull::Cw))
+            .unwrap();
+        cache
+            .set_sampler_state(1, SamplerState::AddressU(TextureAddress::Clamp))
+            .unwrap();
+        cache.set_texture(1, &texture).unwrap();
+        cache.set_stream_source(0, &vertex_buffer, 0, 16).unwrap();
+        assert_eq!(cache.device().calls().len(), 8);
+        assert_eq!(cache.stats().issued, 8);
+
+        cache.reset_stats();
+        assert_eq!(cache.stats(), CacheStats::default());
+    }
+
+    #[test]
+    fn answers_get_render_state_from_the_cache() {
+        let cache = StateCache::new(RecordingDevice::new());
+        cache
+            .set_render_state(RenderState::CullMode(Cull::None))
+            .unwrap();
+        cache.device().take_calls();
+
+        assert_eq!(
+            cache.get_render_state(RenderStateType::CullMode).unwrap(),
+            RenderState::CullMode(Cull::None)
+        );
+    }
+
+    #[test]
+    fn does_not_cache_failed_calls() {
+        let cache = StateCache::new(RecordingDevice::new());
+
+        for _ in 0..2 {
+            assert!(cache
+                .set_sampler_state(0, SamplerState::MaxAnisotropy(0))
+                .is_err());
+        }
+        assert_eq!(cache.stats().issued, 2);
+        assert!(cache.device().calls().is_empty());
+    }
+
+    #[test]
+    fn invalidates_on_reset() {
+        let cache = StateCache::new(RecordingDevice::new());
+        let state = RenderState::CullMode(Cull::None);
+
+        cache.set_render_state(state).unwrap();
+        cache.reset(&mut PresentationParameters::default()).unwrap();
+        cache.set_render_state(state).unwrap();
+
+        cache.invalidate();
+        cache.set_render_state(state).unwrap();
+
+        let calls = cache.device().take_calls();
+        assert_eq!(calls.len(), 4);
+        assert!(matches!(calls[1], Call::Reset { .. }));
+        assert_eq!(calls[3], Call::SetRenderState { state });
+        assert_eq!(cache.stats().filtered, 0);
+    }
+
+    #[test]
+    fn passes_on_calls_while_recording_a_state_block() {
+        let cache = StateCache::new(RecordingDevice::new());
+        let state = RenderState::CullMode(Cull::None);
+
+        cache.set_render_state(state).unwrap();
+        cache.begin_state_block().unwrap();
+        cache.set_render_state(state).unwrap();
+        cache.end_state_block().unwrap();
+        cache.set_render_state(state).unwrap();
+
+        assert_eq!(cache.device().calls().len(), 4);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                issued: 2,
+                filtered: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,696 @@
+//! Provides the [`DeviceBackend`] trait, an abstraction over [`Device`] which allows rendering
+//! code to run against something other than a real Direct3D device.
+//!
+//! [`Device`] implements the trait by calling into `IDirect3DDevice9`, while [`RecordingDevice`]
+//! keeps every call in memory so it can be inspected on any platform. [`StateCache`] wraps either
+//! of them and skips redundant state changes, and [`ResourceRegistry`] recreates resources after a
+//! device reset.
+//!
+//! # Usage
+//!
+//! ```rs
+//! fn draw_frame<B: DeviceBackend>(device: &B, vertices: &B::VertexBuffer) -> WindowsResult<()> {
+//!     device.begin_scene()?;
+//!     device.set_stream_source(0, vertices, 0, 12)?;
+//!     device.draw_primitive(PrimitiveType::TriangleList, 0, 1)?;
+//!     device.end_scene()
+//! }
+//! ```
+
+mod cache;
+mod recording;
+mod registry;
+
+pub use cache::{CacheStats, StateCache};
+pub use recording::{Call, RecordingDevice, Resource};
+pub use registry::{DeviceStatus, Managed, ResourceRegistry};
+
+#[cfg(windows)]
+use crate::std::interfaces::{
+    CubeTexture, Device, IndexBuffer, PixelShader, Query, StateBlock, Surface, SwapChain, Texture,
+    VertexBuffer, VertexDeclaration, VertexShader, VolumeTexture,
+};
+use crate::{
+    error::WindowsResult,
+    std::{
+        states::{
+            RenderState, RenderStateType, SamplerState, StateError, TextureFilterType,
+            TextureStageState,
+        },
+        types::{
+            Clear, Color, CooperativeLevel, DisplayMode, Format, Handle, MultiSampleType, Pool,
+            PresentationParameters, PrimitiveType, QueryType, Rect, RegionData, StateBlockType,
+            Usage, VertexElement, FVF,
+        },
+    },
+    sys::HWND,
+};
+
+/// The set of [`Device`] operations which rendering code is written against.
+///
+/// Every method mirrors the [`Device`] method of the same name. Resources are represented by the
+/// associated types, so a backend is free to hand out its own handles. Handles of resources which
+/// can be bound must compare equal when they refer to the same resource.
+#[allow(clippy::too_many_arguments)]
+pub trait DeviceBackend {
+    type CubeTexture: Clone;
+    type IndexBuffer: Clone + PartialEq;
+    type PixelShader: Clone + PartialEq;
+    type Query: Clone;
+    type StateBlock: Clone;
+    type Surface: Clone;
+    type SwapChain: Clone;
+    type Texture: Clone + PartialEq;
+    type VertexBuffer: Clone + PartialEq;
+    type VertexDeclaration: Clone + PartialEq;
+    type VertexShader: Clone + PartialEq;
+    type VolumeTexture: Clone;
+
+    fn begin_scene(&self) -> WindowsResult<()>;
+
+    fn begin_state_block(&self) -> WindowsResult<()>;
+
+    fn clear(
+        &self,
+        count: u32,
+        rects: Option<&[Rect]>,
+        flags: Clear,
+        color: Color,
+        z: f32,
+        stencil: u32,
+    ) -> WindowsResult<()>;
+
+    fn color_fill(
+        &self,
+        surface: &Self::Surface,
+        rect: Option<Rect>,
+        color: Color,
+    ) -> WindowsResult<()>;
+
+    fn create_additional_swap_chain(
+        &self,
+        presentation_parameters: &mut PresentationParameters,
+    ) -> WindowsResult<Self::SwapChain>;
+
+    fn create_cube_texture(
+        &self,
+        edge_length: u32,
+        levels: u32,
+        usage: u32,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Self::CubeTexture>;
+
+    fn create_depth_stencil_surface(
+        &self,
+        width: u32,
+        height: u32,
+        format: Format,
+        multi_sample_type: MultiSampleType,
+        multi_sample_quality: u32,
+        discard: bool,
+    ) -> WindowsResult<Self::Surface>;
+
+    fn create_index_buffer(
+        &self,
+        length: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Self::IndexBuffer>;
+
+    fn create_offscreen_plain_surface(
+        &self,
+        width: u32,
+        height: u32,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Self::Surface>;
+
+    fn create_pixel_shader(&self, shader: &[u32]) -> WindowsResult<Self::PixelShader>;
+
+    fn create_query(&self, query_type: QueryType) -> WindowsResult<Self::Query>;
+
+    fn create_render_target(
+        &self,
+        width: u32,
+        height: u32,
+        format: Format,
+        multi_sample_type: MultiSampleType,
+        multi_sample_quality: u32,
+        lockable: bool,
+    ) -> WindowsResult<Self::Surface>;
+
+    fn create_state_block(
+        &self,
+        state_block_type: StateBlockType,
+    ) -> WindowsResult<Self::StateBlock>;
+
+    fn create_texture(
+        &self,
+        width: u32,
+        height: u32,
+        levels: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Self::Texture>;
+
+    fn create_vertex_buffer(
+        &self,
+        length: u32,
+        usage: Usage,
+        fvf: FVF,
+        pool: Pool,
+    ) -> WindowsResult<Self::VertexBuffer>;
+
+    fn create_vertex_declaration(
+        &self,
+        vertex_elements: &[VertexElement],
+    ) -> WindowsResult<Self::VertexDeclaration>;
+
+    fn create_vertex_shader(&self, shader: &[u32]) -> WindowsResult<Self::VertexShader>;
+
+    fn create_volume_texture(
+        &self,
+        width: u32,
+        height: u32,
+        depth: u32,
+        levels: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Self::VolumeTexture>;
+
+    fn delete_patch(&self, handle: Handle) -> WindowsResult<()>;
+
+    fn draw_indexed_primitive(
+        &self,
+        primitive_type: PrimitiveType,
+        base_vertex_index: i32,
+        minimum_vertex_index: u32,
+        n_vertices: u32,
+        indices_index: u32,
+        n_primitives: u32,
+    ) -> WindowsResult<()>;
+
+    fn draw_indexed_primitive_up<V, I>(
+        &self,
+        primitive_type: PrimitiveType,
+        minimum_vertex_index: u32,
+        n_primitives: u32,
+        indices: Vec<I>,
+        indices_format: Format,
+        vertices: Vec<V>,
+        vertices_stride: u32,
+    ) -> WindowsResult<()>;
+
+    fn draw_primitive(
+        &self,
+        primitive_type: PrimitiveType,
+        start_vertex: u32,
+        n_primitives: u32,
+    ) -> WindowsResult<()>;
+
+    fn draw_primitive_up<V>(
+        &self,
+        primitive_type: PrimitiveType,
+        n_primitives: u32,
+        vertices: Vec<V>,
+        vertices_stride: u32,
+    ) -> WindowsResult<()>;
+
+    fn end_scene(&self) -> WindowsResult<()>;
+
+    fn end_state_block(&self) -> WindowsResult<Self::StateBlock>;
+
+    fn get_back_buffer(&self, swap_chain: u32, index: u32) -> WindowsResult<Self::Surface>;
+
+    fn get_display_mode(&self, swap_chain: u32) -> WindowsResult<DisplayMode>;
+
+    fn get_front_buffer_data(
+        &self,
+        swap_chain: u32,
+        destination: &Self::Surface,
+    ) -> WindowsResult<()>;
+
+    fn get_render_target(&self, index: u32) -> WindowsResult<Self::Surface>;
+
+    fn get_render_target_data(
+        &self,
+        render_target: &Self::Surface,
+        destination: &Self::Surface,
+    ) -> WindowsResult<()>;
+
+    fn present(
+        &self,
+        src_rect: Option<&Rect>,
+        dest_rect: Option<&Rect>,
+        window_override: Option<HWND>,
+        dirty_region_data: Option<&RegionData>,
+    ) -> WindowsResult<()>;
+
+    fn reset(&self, presentation_parameters: &mut PresentationParameters) -> WindowsResult<()>;
+
+    fn set_vertex_declaration(
+        &self,
+        vertex_declaration: &Self::VertexDeclaration,
+    ) -> WindowsResult<()>;
+
+    fn set_indices(&self, index_buffer: &Self::IndexBuffer) -> WindowsResult<()>;
+
+    fn set_vertex_shader(&self, vertex_shader: &Self::VertexShader) -> WindowsResult<()>;
+
+    fn set_pixel_shader(&self, pixel_shader: &Self::PixelShader) -> WindowsResult<()>;
+
+    fn set_stream_source(
+        &self,
+        index: u32,
+        vertex_buffer: &Self::VertexBuffer,
+        byte_offset: u32,
+        stride: u32,
+    ) -> WindowsResult<()>;
+
+    fn set_viewport(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        max_z: f32,
+        min_z: f32,
+    ) -> WindowsResult<()>;
+
+    fn set_clipping(&self, value: bool) -> WindowsResult<()>;
+
+    fn get_render_state(&self, kind: RenderStateType) -> Result<RenderState, StateError>;
+
+    fn set_render_state(&self, state: RenderState) -> Result<(), StateError>;
+
+    fn set_sampler_state(&self, sampler: u32, state: SamplerState) -> Result<(), StateError>;
+
+    fn set_texture_stage_state(
+        &self,
+        stage: u32,
+        state: TextureStageState,
+    ) -> Result<(), StateError>;
+
+    fn set_texture(&self, stage: u32, texture: &Self::Texture) -> WindowsResult<()>;
+
+    fn stretch_rect(
+        &self,
+        source: &Self::Surface,
+        destination: &Self::Surface,
+        filter: TextureFilterType,
+    ) -> WindowsResult<()>;
+
+    fn test_cooperative_level(&self) -> WindowsResult<CooperativeLevel>;
+
+    fn update_texture(
+        &self,
+        source: &Self::Texture,
+        destination: &Self::Texture,
+    ) -> WindowsResult<()>;
+}
+
+#[cfg(windows)]
+impl DeviceBackend for Device {
+    type CubeTexture = CubeTexture;
+    type IndexBuffer = IndexBuffer;
+    type PixelShader = PixelShader;
+    type Query = Query;
+    type StateBlock = StateBlock;
+    type Surface = Surface;
+    type SwapChain = SwapChain;
+    type Texture = Texture;
+    type VertexBuffer = VertexBuffer;
+    type VertexDeclaration = VertexDeclaration;
+    type VertexShader = VertexShader;
+    type VolumeTexture = VolumeTexture;
+
+    fn begin_scene(&self) -> WindowsResult<()> {
+        Device::begin_scene(self)
+    }
+
+    fn begin_state_block(&self) -> WindowsResult<()> {
+        Device::begin_state_block(self)
+    }
+
+    fn clear(
+        &self,
+        count: u32,
+        rects: Option<&[Rect]>,
+        flags: Clear,
+        color: Color,
+        z: f32,
+        stencil: u32,
+    ) -> WindowsResult<()> {
+        Device::clear(self, count, rects, flags, color, z, stencil)
+    }
+
+    fn color_fill(&self, surface: &Surface, rect: Option<Rect>, color: Color) -> WindowsResult<()> {
+        Device::color_fill(self, surface, rect, color)
+    }
+
+    fn create_additional_swap_chain(
+        &self,
+        presentation_parameters: &mut PresentationParameters,
+    ) -> WindowsResult<SwapChain> {
+        Device::create_additional_swap_chain(self, presentation_parameters)
+    }
+
+    fn create_cube_texture(
+        &self,
+        edge_length: u32,
+        levels: u32,
+        usage: u32,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<CubeTexture> {
+        Device::create_cube_texture(self, edge_length, levels, usage, format, pool)
+    }
+
+    fn create_depth_stencil_surface(
+        &self,
+        width: u32,
+        height: u32,
+        format: Format,
+        multi_sample_type: MultiSampleType,
+        multi_sample_quality: u32,
+        discard: bool,
+    ) -> WindowsResult<Surface> {
+        Device::create_depth_stencil_surface(
+            self,
+            width,
+            height,
+            format,
+            multi_sample_type,
+            multi_sample_quality,
+            discard,
+        )
+    }
+
+    fn create_index_buffer(
+        &self,
+        length: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<IndexBuffer> {
+        Device::create_index_buffer(self, length, usage, format, pool)
+    }
+
+    fn create_offscreen_plain_surface(
+        &self,
+        width: u32,
+        height: u32,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Surface> {
+        Device::create_offscreen_plain_surface(self, width, height, format, pool)
+    }
+
+    fn create_pixel_shader(&self, shader: &[u32]) -> WindowsResult<PixelShader> {
+        Device::create_pixel_shader(self, shader)
+    }
+
+    fn create_query(&self, query_type: QueryType) -> WindowsResult<Query> {
+        Device::create_query(self, query_type)
+    }
+
+    fn create_render_target(
+        &self,
+        width: u32,
+        height: u32,
+        format: Format,
+        multi_sample_type: MultiSampleType,
+        multi_sample_quality: u32,
+        lockable: bool,
+    ) -> WindowsResult<Surface> {
+        Device::create_render_target(
+            self,
+            width,
+            height,
+            format,
+            multi_sample_type,
+            multi_sample_quality,
+            lockable,
+        )
+    }
+
+    fn create_state_block(&self, state_block_type: StateBlockType) -> WindowsResult<StateBlock> {
+        Device::create_state_block(self, state_block_type)
+    }
+
+    fn create_texture(
+        &self,
+        width: u32,
+        height: u32,
+        levels: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Texture> {
+        Device::create_texture(self, width, height, levels, usage, format, pool)
+    }
+
+    fn create_vertex_buffer(
+        &self,
+        length: u32,
+        usage: Usage,
+        fvf: FVF,
+        pool: Pool,
+    ) -> WindowsResult<VertexBuffer> {
+        Device::create_vertex_buffer(self, length, usage, fvf, pool)
+    }
+
+    fn create_vertex_declaration(
+        &self,
+        vertex_elements: &[VertexElement],
+    ) -> WindowsResult<VertexDeclaration> {
+        Device::create_vertex_declaration(self, vertex_elements)
+    }
+
+    fn create_vertex_shader(&self, shader: &[u32]) -> WindowsResult<VertexShader> {
+        Device::create_vertex_shader(self, shader)
+    }
+
+    fn create_volume_texture(
+        &self,
+        width: u32,
+        height: u32,
+        depth: u32,
+        levels: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<VolumeTexture> {
+        Device::create_volume_texture(self, width, height, depth, levels, usage, format, pool)
+    }
+
+    fn delete_patch(&self, handle: Handle) -> WindowsResult<()> {
+        Device::delete_patch(self, handle)
+    }
+
+    fn draw_indexed_primitive(
+        &self,
+        primitive_type: PrimitiveType,
+        base_vertex_index: i32,
+        minimum_vertex_index: u32,
+        n_vertices: u32,
+        indices_index: u32,
+        n_primitives: u32,
+    ) -> WindowsResult<()> {
+        Device::draw_indexed_primitive(
+            self,
+            primitive_type,
+            base_vertex_index,
+            minimum_vertex_index,
+            n_vertices,
+            indices_index,
+            n_primitives,
+        )
+    }
+
+    fn draw_indexed_primitive_up<V, I>(
+        &self,
+        primitive_type: PrimitiveType,
+        minimum_vertex_index: u32,
+        n_primitives: u32,
+        indices: Vec<I>,
+        indices_format: Format,
+        vertices: Vec<V>,
+        vertices_stride: u32,
+    ) -> WindowsResult<()> {
+        Device::draw_indexed_primitive_up(
+            self,
+            primitive_type,
+            minimum_vertex_index,
+            n_primitives,
+            indices,
+            indices_format,
+            vertices,
+            vertices_stride,
+        )
+    }
+
+    fn draw_primitive(
+        &self,
+        primitive_type: PrimitiveType,
+        start_vertex: u32,
+        n_primitives: u32,
+    ) -> WindowsResult<()> {
+        Device::draw_primitive(self, primitive_type, start_vertex, n_primitives)
+    }
+
+    fn draw_primitive_up<V>(
+        &self,
+        primitive_type: PrimitiveType,
+        n_primitives: u32,
+        vertices: Vec<V>,
+        vertices_stride: u32,
+    ) -> WindowsResult<()> {
+        Device::draw_primitive_up(
+            self,
+            primitive_type,
+            n_primitives,
+            vertices,
+            vertices_stride,
+        )
+    }
+
+    fn end_scene(&self) -> WindowsResult<()> {
+        Device::end_scene(self)
+    }
+
+    fn end_state_block(&self) -> WindowsResult<StateBlock> {
+        Device::end_state_block(self)
+    }
+
+    fn get_back_buffer(&self, swap_chain: u32, index: u32) -> WindowsResult<Surface> {
+        Device::get_back_buffer(self, swap_chain, index)
+    }
+
+    fn get_display_mode(&self, swap_chain: u32) -> WindowsResult<DisplayMode> {
+        Device::get_display_mode(self, swap_chain)
+    }
+
+    fn get_front_buffer_data(&self, swap_chain: u32, destination: &Surface) -> WindowsResult<()> {
+        Device::get_front_buffer_data(self, swap_chain, destination)
+    }
+
+    fn get_render_target(&self, index: u32) -> WindowsResult<Surface> {
+        Device::get_render_target(self, index)
+    }
+
+    fn get_render_target_data(
+        &self,
+        render_target: &Surface,
+        destination: &Surface,
+    ) -> WindowsResult<()> {
+        Device::get_render_target_data(self, render_target, destination)
+    }
+
+    fn present(
+        &self,
+        src_rect: Option<&Rect>,
+        dest_rect: Option<&Rect>,
+        window_override: Option<HWND>,
+        dirty_region_data: Option<&RegionData>,
+    ) -> WindowsResult<()> {
+        Device::present(
+            self,
+            src_rect,
+            dest_rect,
+            window_override,
+            dirty_region_data,
+        )
+    }
+
+    fn reset(&self, presentation_parameters: &mut PresentationParameters) -> WindowsResult<()> {
+        Device::reset(self, presentation_parameters)
+    }
+
+    fn set_vertex_declaration(&self, vertex_declaration: &VertexDeclaration) -> WindowsResult<()> {
+        Device::set_vertex_declaration(self, vertex_declaration)
+    }
+
+    fn set_indices(&self, index_buffer: &IndexBuffer) -> WindowsResult<()> {
+        Device::set_indices(self, index_buffer)
+    }
+
+    fn set_vertex_shader(&self, vertex_shader: &VertexShader) -> WindowsResult<()> {
+        Device::set_vertex_shader(self, vertex_shader)
+    }
+
+    fn set_pixel_shader(&self, pixel_shader: &PixelShader) -> WindowsResult<()> {
+        Device::set_pixel_shader(self, pixel_shader)
+    }
+
+    fn set_stream_source(
+        &self,
+        index: u32,
+        vertex_buffer: &VertexBuffer,
+        byte_offset: u32,
+        stride: u32,
+    ) -> WindowsResult<()> {
+        Device::set_stream_source(self, index, vertex_buffer, byte_offset, stride)
+    }
+
+    fn set_viewport(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        max_z: f32,
+        min_z: f32,
+    ) -> WindowsResult<()> {
+        Device::set_viewport(self, x, y, width, height, max_z, min_z)
+    }
+
+    fn set_clipping(&self, value: bool) -> WindowsResult<()> {
+        Device::set_clipping(self, value)
+    }
+
+    fn get_render_state(&self, kind: RenderStateType) -> Result<RenderState, StateError> {
+        Device::get_render_state(self, kind)
+    }
+
+    fn set_render_state(&self, state: RenderState) -> Result<(), StateError> {
+        Device::set_render_state(self, state)
+    }
+
+    fn set_sampler_state(&self, sampler: u32, state: SamplerState) -> Result<(), StateError> {
+        Device::set_sampler_state(self, sampler, state)
+    }
+
+    fn set_texture_stage_state(
+        &self,
+        stage: u32,
+        state: TextureStageState,
+    ) -> Result<(), StateError> {
+        Device::set_texture_stage_state(self, stage, state)
+    }
+
+    fn set_texture(&self, stage: u32, texture: &Texture) -> WindowsResult<()> {
+        Device::set_texture(self, stage, texture)
+    }
+
+    fn stretch_rect(
+        &self,
+        source: &Surface,
+        destination: &Surface,
+        filter: TextureFilterType,
+    ) -> WindowsResult<()> {
+        Device::stretch_rect(self, source, destination, filter)
+    }
+
+    fn test_cooperative_level(&self) -> WindowsResult<CooperativeLevel> {
+        Device::test_cooperative_level(self)
+    }
+
+    fn update_texture(&self, source: &Texture, destination: &Texture) -> WindowsResult<()> {
+        Device::update_texture(self, source, destination)
+    }
+}
@@ -0,0 +1,1049 @@
+use std::{
+    cell::{Cell, Ref, RefCell},
+    collections::HashMap,
+};
+
+use crate::{
+    error::{ErrorCode, WindowsError, WindowsResult},
+    std::{
+        backend::DeviceBackend,
+        states::{
+            RenderState, RenderStateType, SamplerState, StateError, TextureFilterType,
+            TextureStageState,
+        },
+        types::{
+            Clear, Color, CooperativeLevel, DisplayMode, Format, Handle, MultiSampleType, Pool,
+            PresentationParameters, PrimitiveType, QueryType, Rect, RegionData, StateBlockType,
+            Usage, VertexElement, FVF,
+        },
+    },
+    sys::HWND,
+};
+
+/// Represents a resource created by a [`RecordingDevice`].
+///
+/// Identifiers are handed out in creation order, starting at zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Resource(pub u32);
+
+/// Represents a single call made against a [`RecordingDevice`], along with its arguments.
+///
+/// Calls which create or return a resource also store the [`Resource`] that was returned.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Call {
+    BeginScene,
+    BeginStateBlock,
+    Clear {
+        count: u32,
+        rects: Option<Vec<Rect>>,
+        flags: Clear,
+        color: Color,
+        z: f32,
+        stencil: u32,
+    },
+    ColorFill {
+        surface: Resource,
+        rect: Option<Rect>,
+        color: Color,
+    },
+    CreateAdditionalSwapChain {
+        presentation_parameters: PresentationParameters,
+        swap_chain: Resource,
+    },
+    CreateCubeTexture {
+        edge_length: u32,
+        levels: u32,
+        usage: u32,
+        format: Format,
+        pool: Pool,
+        cube_texture: Resource,
+    },
+    CreateDepthStencilSurface {
+        width: u32,
+        height: u32,
+        format: Format,
+        multi_sample_type: MultiSampleType,
+        multi_sample_quality: u32,
+        discard: bool,
+        surface: Resource,
+    },
+    CreateIndexBuffer {
+        length: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+        index_buffer: Resource,
+    },
+    CreateOffscreenPlainSurface {
+        width: u32,
+        height: u32,
+        format: Format,
+        pool: Pool,
+        surface: Resource,
+    },
+    CreatePixelShader {
+        shader: Vec<u32>,
+        pixel_shader: Resource,
+    },
+    CreateQuery {
+        query_type: QueryType,
+        query: Resource,
+    },
+    CreateRenderTarget {
+        width: u32,
+        height: u32,
+        format: Format,
+        multi_sample_type: MultiSampleType,
+        multi_sample_quality: u32,
+        lockable: bool,
+        surface: Resource,
+    },
+    CreateStateBlock {
+        state_block_type: StateBlockType,
+        state_block: Resource,
+    },
+    CreateTexture {
+        width: u32,
+        height: u32,
+        levels: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+        texture: Resource,
+    },
+    CreateVertexBuffer {
+        length: u32,
+        usage: Usage,
+        fvf: FVF,
+        pool: Pool,
+        vertex_buffer: Resource,
+    },
+    CreateVertexDeclaration {
+        vertex_elements: Vec<VertexElement>,
+        vertex_declaration: Resource,
+    },
+    CreateVertexShader {
+        shader: Vec<u32>,
+        vertex_shader: Resource,
+    },
+    CreateVolumeTexture {
+        width: u32,
+        height: u32,
+        depth: u32,
+        levels: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+        volume_texture: Resource,
+    },
+    DeletePatch {
+        handle: Handle,
+    },
+    DrawIndexedPrimitive {
+        primitive_type: PrimitiveType,
+        base_vertex_index: i32,
+        minimum_vertex_index: u32,
+        n_vertices: u32,
+        indices_index: u32,
+        n_primitives: u32,
+    },
+    /// The user pointer data itself is not kept, only the number of indices and vertices.
+    DrawIndexedPrimitiveUp {
+        primitive_type: PrimitiveType,
+        minimum_vertex_index: u32,
+        n_primitives: u32,
+        n_indices: usize,
+        indices_format: Format,
+        n_vertices: usize,
+        vertices_stride: u32,
+    },
+    DrawPrimitive {
+        primitive_type: PrimitiveType,
+        start_vertex: u32,
+        n_primitives: u32,
+    },
+    /// The user pointer data itself is not kept, only the number of vertices.
+    DrawPrimitiveUp {
+        primitive_type: PrimitiveType,
+        n_primitives: u32,
+        n_vertices: usize,
+        vertices_stride: u32,
+    },
+    EndScene,
+    EndStateBlock {
+        state_block: Resource,
+    },
+    GetBackBuffer {
+        swap_chain: u32,
+        index: u32,
+        surface: Resource,
+    },
+    GetDisplayMode {
+        swap_chain: u32,
+    },
+    GetFrontBufferData {
+        swap_chain: u32,
+        destination: Resource,
+    },
+    GetRenderTarget {
+        index: u32,
+        surface: Resource,
+    },
+    GetRenderTargetData {
+        render_target: Resource,
+        destination: Resource,
+    },
+    Present {
+        src_rect: Option<Rect>,
+        dest_rect: Option<Rect>,
+        window_override: Option<HWND>,
+        dirty_region_data: bool,
+    },
+    Reset {
+        presentation_parameters: PresentationParameters,
+    },
+    SetVertexDeclaration {
+        vertex_declaration: Resource,
+    },
+    SetIndices {
+        index_buffer: Resource,
+    },
+    SetVertexShader {
+        vertex_shader: Resource,
+    },
+    SetPixelShader {
+        pixel_shader: Resource,
+    },
+    SetStreamSource {
+        index: u32,
+        vertex_buffer: Resource,
+        byte_offset: u32,
+        stride: u32,
+    },
+    SetViewport {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        max_z: f32,
+        min_z: f32,
+    },
+    SetClipping {
+        value: bool,
+    },
+    SetRenderState {
+        state: RenderState,
+    },
+    SetSamplerState {
+        sampler: u32,
+        state: SamplerState,
+    },
+    SetTextureStageState {
+        stage: u32,
+        state: TextureStageState,
+    },
+    SetTexture {
+        stage: u32,
+        texture: Resource,
+    },
+    StretchRect {
+        source: Resource,
+        destination: Resource,
+        filter: TextureFilterType,
+    },
+    UpdateTexture {
+        source: Resource,
+        destination: Resource,
+    },
+}
+
+/// Represents an in-memory [`DeviceBackend`] which records every call made against it.
+///
+/// Every created resource is a [`Resource`] identifier. Every call succeeds, except for
+/// [`DeviceBackend::present`] and [`DeviceBackend::reset`] while the device is lost, which can be
+/// simulated with [`RecordingDevice::set_cooperative_level`]. Failed calls are still recorded.
+///
+/// Surfaces returned by [`DeviceBackend::get_back_buffer`] and
+/// [`DeviceBackend::get_render_target`] are new identifiers on every call, and
+/// [`DeviceBackend::get_display_mode`] returns the mode set with
+/// [`RecordingDevice::set_display_mode`].
+#[derive(Debug, Default)]
+pub struct RecordingDevice {
+    calls: RefCell<Vec<Call>>,
+    next_resource: Cell<u32>,
+    /// The render states set so far, which outlive the call log.
+    render_states: RefCell<HashMap<RenderStateType, RenderState>>,
+    cooperative_level: Cell<CooperativeLevel>,
+    display_mode: RefCell<DisplayMode>,
+}
+
+impl RecordingDevice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the calls recorded so far, in the order they were made.
+    pub fn calls(&self) -> Ref<'_, [Call]> {
+        Ref::map(self.calls.borrow(), Vec::as_slice)
+    }
+
+    /// Returns the calls recorded so far and clears the log.
+    pub fn take_calls(&self) -> Vec<Call> {
+        self.calls.take()
+    }
+
+    /// Sets the level returned by [`DeviceBackend::test_cooperative_level`].
+    ///
+    /// A successful reset sets the level back to [`CooperativeLevel::Ok`].
+    pub fn set_cooperative_level(&self, level: CooperativeLevel) {
+        self.cooperative_level.set(level);
+    }
+
+    /// Sets the mode returned by [`DeviceBackend::get_display_mode`].
+    pub fn set_display_mode(&self, display_mode: DisplayMode) {
+        self.display_mode.replace(display_mode);
+    }
+
+    fn record(&self, call: Call) -> WindowsResult<()> {
+        self.calls.borrow_mut().push(call);
+
+        Ok(())
+    }
+
+    fn create(&self, call: impl FnOnce(Resource) -> Call) -> WindowsResult<Resource> {
+        let resource = Resource(self.next_resource.get());
+        self.next_resource.set(resource.0 + 1);
+        self.record(call(resource))?;
+
+        Ok(resource)
+    }
+}
+
+impl DeviceBackend for RecordingDevice {
+    type CubeTexture = Resource;
+    type IndexBuffer = Resource;
+    type PixelShader = Resource;
+    type Query = Resource;
+    type StateBlock = Resource;
+    type Surface = Resource;
+    type SwapChain = Resource;
+    type Texture = Resource;
+    type VertexBuffer = Resource;
+    type VertexDeclaration = Resource;
+    type VertexShader = Resource;
+    type VolumeTexture = Resource;
+
+    fn begin_scene(&self) -> WindowsResult<()> {
+        self.record(Call::BeginScene)
+    }
+
+    fn begin_state_block(&self) -> WindowsResult<()> {
+        self.record(Call::BeginStateBlock)
+    }
+
+    fn clear(
+        &self,
+        count: u32,
+        rects: Option<&[Rect]>,
+        flags: Clear,
+        color: Color,
+        z: f32,
+        stencil: u32,
+    ) -> WindowsResult<()> {
+        self.record(Call::Clear {
+            count,
+            rects: rects.map(<[Rect]>::to_vec),
+            flags,
+            color,
+            z,
+            stencil,
+        })
+    }
+
+    fn color_fill(
+        &self,
+        surface: &Resource,
+        rect: Option<Rect>,
+        color: Color,
+    ) -> WindowsResult<()> {
+        self.record(Call::ColorFill {
+            surface: *surface,
+            rect,
+            color,
+        })
+    }
+
+    fn create_additional_swap_chain(
+        &self,
+        presentation_parameters: &mut PresentationParameters,
+    ) -> WindowsResult<Resource> {
+        self.create(|swap_chain| Call::CreateAdditionalSwapChain {
+            presentation_parameters: presentation_parameters.clone(),
+            swap_chain,
+        })
+    }
+
+    fn create_cube_texture(
+        &self,
+        edge_length: u32,
+        levels: u32,
+        usage: u32,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Resource> {
+        self.create(|cube_texture| Call::CreateCubeTexture {
+            edge_length,
+            levels,
+            usage,
+            format,
+            pool,
+            cube_texture,
+        })
+    }
+
+    fn create_depth_stencil_surface(
+        &self,
+        width: u32,
+        height: u32,
+        format: Format,
+        multi_sample_type: MultiSampleType,
+        multi_sample_quality: u32,
+        discard: bool,
+    ) -> WindowsResult<Resource> {
+        self.create(|surface| Call::CreateDepthStencilSurface {
+            width,
+            height,
+            format,
+            multi_sample_type,
+            multi_sample_quality,
+            discard,
+            surface,
+        })
+    }
+
+    fn create_index_buffer(
+        &self,
+        length: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Resource> {
+        self.create(|index_buffer| Call::CreateIndexBuffer {
+            length,
+            usage,
+            format,
+            pool,
+            index_buffer,
+        })
+    }
+
+    fn create_offscreen_plain_surface(
+        &self,
+        width: u32,
+        height: u32,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Resource> {
+        self.create(|surface| Call::CreateOffscreenPlainSurface {
+            width,
+            height,
+            format,
+            pool,
+            surface,
+        })
+    }
+
+    fn create_pixel_shader(&self, shader: &[u32]) -> WindowsResult<Resource> {
+        self.create(|pixel_shader| Call::CreatePixelShader {
+            shader: shader.to_vec(),
+            pixel_shader,
+        })
+    }
+
+    fn create_query(&self, query_type: QueryType) -> WindowsResult<Resource> {
+        self.create(|query| Call::CreateQuery { query_type, query })
+    }
+
+    fn create_render_target(
+        &self,
+        width: u32,
+        height: u32,
+        format: Format,
+        multi_sample_type: MultiSampleType,
+        multi_sample_quality: u32,
+        lockable: bool,
+    ) -> WindowsResult<Resource> {
+        self.create(|surface| Call::CreateRenderTarget {
+            width,
+            height,
+            format,
+            multi_sample_type,
+            multi_sample_quality,
+            lockable,
+            surface,
+        })
+    }
+
+    fn create_state_block(&self, state_block_type: StateBlockType) -> WindowsResult<Resource> {
+        self.create(|state_block| Call::CreateStateBlock {
+            state_block_type,
+            state_block,
+        })
+    }
+
+    fn create_texture(
+        &self,
+        width: u32,
+        height: u32,
+        levels: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Resource> {
+        self.create(|texture| Call::CreateTexture {
+            width,
+            height,
+            levels,
+            usage,
+            format,
+            pool,
+            texture,
+        })
+    }
+
+    fn create_vertex_buffer(
+        &self,
+        length: u32,
+        usage: Usage,
+        fvf: FVF,
+        pool: Pool,
+    ) -> WindowsResult<Resource> {
+        self.create(|vertex_buffer| Call::CreateVertexBuffer {
+            length,
+            usage,
+            fvf,
+            pool,
+            vertex_buffer,
+        })
+    }
+
+    fn create_vertex_declaration(
+        &self,
+        vertex_elements: &[VertexElement],
+    ) -> WindowsResult<Resource> {
+        self.create(|vertex_declaration| Call::CreateVertexDeclaration {
+            vertex_elements: vertex_elements.to_vec(),
+            vertex_declaration,
+        })
+    }
+
+    fn create_vertex_shader(&self, shader: &[u32]) -> WindowsResult<Resource> {
+        self.create(|vertex_shader| Call::CreateVertexShader {
+            shader: shader.to_vec(),
+            vertex_shader,
+        })
+    }
+
+    fn create_volume_texture(
+        &self,
+        width: u32,
+        height: u32,
+        depth: u32,
+        levels: u32,
+        usage: Usage,
+        format: Format,
+        pool: Pool,
+    ) -> WindowsResult<Resource> {
+        self.create(|volume_texture| Call::CreateVolumeTexture {
+            width,
+            height,
+            depth,
+            levels,
+            usage,
+            format,
+            pool,
+            volume_texture,
+        })
+    }
+
+    fn delete_patch(&self, handle: Handle) -> WindowsResult<()> {
+        self.record(Call::DeletePatch { handle })
+    }
+
+    fn draw_indexed_primitive(
+        &self,
+        primitive_type: PrimitiveType,
+        base_vertex_index: i32,
+        minimum_vertex_index: u32,
+        n_vertices: u32,
+        indices_index: u32,
+        n_primitives: u32,
+    ) -> WindowsResult<()> {
+        self.record(Call::DrawIndexedPrimitive {
+            primitive_type,
+            base_vertex_index,
+            minimum_vertex_index,
+            n_vertices,
+            indices_index,
+            n_primitives,
+        })
+    }
+
+    fn draw_indexed_primitive_up<V, I>(
+        &self,
+        primitive_type: PrimitiveType,
+        minimum_vertex_index: u32,
+        n_primitives: u32,
+        indices: Vec<I>,
+        indices_format: Format,
+        vertices: Vec<V>,
+        vertices_stride: u32,
+    ) -> WindowsResult<()> {
+        self.record(Call::DrawIndexedPrimitiveUp {
+            primitive_type,
+            minimum_vertex_index,
+            n_primitives,
+            n_indices: indices.len(),
+            indices_format,
+            n_vertices: vertices.len(),
+            vertices_stride,
+        })
+    }
+
+    fn draw_primitive(
+        &self,
+        primitive_type: PrimitiveType,
+        start_vertex: u32,
+        n_primitives: u32,
+    ) -> WindowsResult<()> {
+        self.record(Call::DrawPrimitive {
+            primitive_type,
+            start_vertex,
+            n_primitives,
+        })
+    }
+
+    fn draw_primitive_up<V>(
+        &self,
+        primitive_type: PrimitiveType,
+        n_primitives: u32,
+        vertices: Vec<V>,
+        vertices_stride: u32,
+    ) -> WindowsResult<()> {
+        self.record(Call::DrawPrimitiveUp {
+            primitive_type,
+            n_primitives,
+            n_vertices: vertices.len(),
+            vertices_stride,
+        })
+    }
+
+    fn end_scene(&self) -> WindowsResult<()> {
+        self.record(Call::EndScene)
+    }
+
+    fn end_state_block(&self) -> WindowsResult<Resource> {
+        self.create(|state_block| Call::EndStateBlock { state_block })
+    }
+
+    fn get_back_buffer(&self, swap_chain: u32, index: u32) -> WindowsResult<Resource> {
+        self.create(|surface| Call::GetBackBuffer {
+            swap_chain,
+            index,
+            surface,
+        })
+    }
+
+    fn get_display_mode(&self, swap_chain: u32) -> WindowsResult<DisplayMode> {
+        self.record(Call::GetDisplayMode { swap_chain })?;
+
+        Ok(self.display_mode.borrow().clone())
+    }
+
+    fn get_front_buffer_data(&self, swap_chain: u32, destination: &Resource) -> WindowsResult<()> {
+        self.record(Call::GetFrontBufferData {
+            swap_chain,
+            destination: *destination,
+        })
+    }
+
+    fn get_render_target(&self, index: u32) -> WindowsResult<Resource> {
+        self.create(|surface| Call::GetRenderTarget { index, surface })
+    }
+
+    fn get_render_target_data(
+        &self,
+        render_target: &Resource,
+        destination: &Resource,
+    ) -> WindowsResult<()> {
+        self.record(Call::GetRenderTargetData {
+            render_target: *render_target,
+            destination: *destination,
+        })
+    }
+
+    fn present(
+        &self,
+        src_rect: Option<&Rect>,
+        dest_rect: Option<&Rect>,
+        window_override: Option<HWND>,
+        dirty_region_data: Option<&RegionData>,
+    ) -> WindowsResult<()> {
+        self.record(Call::Present {
+            src_rect: src_rect.copied(),
+            dest_rect: dest_rect.copied(),
+            window_override,
+            dirty_region_data: dirty_region_data.is_some(),
+        })?;
+
+        match self.cooperative_level.get() {
+            CooperativeLevel::Ok => Ok(()),
+            _ => Err(WindowsError::from(ErrorCode::DeviceLost).with_method("Present")),
+        }
+    }
+
+    fn reset(&self, presentation_parameters: &mut PresentationParameters) -> WindowsResult<()> {
+        self.record(Call::Reset {
+            presentation_parameters: presentation_parameters.clone(),
+        })?;
+
+        match self.cooperative_level.get() {
+            CooperativeLevel::Lost => {
+                Err(WindowsError::from(ErrorCode::DeviceLost).with_method("Reset"))
+            }
+            _ => {
+                // Resetting a device restores every render state to its default.
+                self.render_states.borrow_mut().clear();
+                self.cooperative_level.set(CooperativeLevel::Ok);
+                Ok(())
+            }
+        }
+    }
+
+    fn set_vertex_declaration(&self, vertex_declaration: &Resource) -> WindowsResult<()> {
+        self.record(Call::SetVertexDeclaration {
+            vertex_declaration: *vertex_declaration,
+        })
+    }
+
+    fn set_indices(&self, index_buffer: &Resource) -> WindowsResult<()> {
+        self.record(Call::SetIndices {
+            index_buffer: *index_buffer,
+        })
+    }
+
+    fn set_vertex_shader(&self, vertex_shader: &Resource) -> WindowsResult<()> {
+        self.record(Call::SetVertexShader {
+            vertex_shader: *vertex_shader,
+        })
+    }
+
+    fn set_pixel_shader(&self, pixel_shader: &Resource) -> WindowsResult<()> {
+        self.record(Call::SetPixelShader {
+            pixel_shader: *pixel_shader,
+        })
+    }
+
+    fn set_stream_source(
+        &self,
+        index: u32,
+        vertex_buffer: &Resource,
+        byte_offset: u32,
+        stride: u32,
+    ) -> WindowsResult<()> {
+        self.record(Call::SetStreamSource {
+            index,
+            vertex_buffer: *vertex_buffer,
+            byte_offset,
+            stride,
+        })
+    }
+
+    fn set_viewport(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        max_z: f32,
+        min_z: f32,
+    ) -> WindowsResult<()> {
+        self.record(Call::SetViewport {
+            x,
+            y,
+            width,
+            height,
+            max_z,
+            min_z,
+        })
+    }
+
+    fn set_clipping(&self, value: bool) -> WindowsResult<()> {
+        self.record(Call::SetClipping { value })
+    }
+
+    /// Returns the value of the most recent [`Call::SetRenderState`] for `kind` since the last
+    /// reset, or the value Direct3D assigns when a device is created.
+    fn get_render_state(&self, kind: RenderStateType) -> Result<RenderState, StateError> {
+        let state = self.render_states.borrow().get(&kind).copied();
+
+        Ok(state.unwrap_or_else(|| RenderState::default_for(kind)))
+    }
+
+    fn set_render_state(&self, state: RenderState) -> Result<(), StateError> {
+        state.to_raw()?;
+        self.record(Call::SetRenderState { state })?;
+        self.render_states.borrow_mut().insert(state.kind(), state);
+
+        Ok(())
+    }
+
+    fn set_sampler_state(&self, sampler: u32, state: SamplerState) -> Result<(), StateError> {
+        state.to_raw()?;
+        self.record(Call::SetSamplerState { sampler, state })?;
+
+        Ok(())
+    }
+
+    fn set_texture_stage_state(
+        &self,
+        stage: u32,
+        state: TextureStageState,
+    ) -> Result<(), StateError> {
+        state.to_raw()?;
+        self.record(Call::SetTextureStageState { stage, state })?;
+
+        Ok(())
+    }
+
+    fn set_texture(&self, stage: u32, texture: &Resource) -> WindowsResult<()> {
+        self.record(Call::SetTexture {
+            stage,
+            texture: *texture,
+        })
+    }
+
+    fn stretch_rect(
+        &self,
+        source: &Resource,
+        destination: &Resource,
+        filter: TextureFilterType,
+    ) -> WindowsResult<()> {
+        self.record(Call::StretchRect {
+            source: *source,
+            destination: *destination,
+            filter,
+        })
+    }
+
+    fn test_cooperative_level(&self) -> WindowsResult<CooperativeLevel> {
+        Ok(self.cooperative_level.get())
+    }
+
+    fn update_texture(&self, source: &Resource, destination: &Resource) -> WindowsResult<()> {
+        self.record(Call::UpdateTexture {
+            source: *source,
+            destination: *destination,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame<B: DeviceBackend>(device: &B) -> WindowsResult<B::VertexBuffer> {
+        let vertex_buffer =
+            device.create_vertex_buffer(36, Usage::default(), FVF::default(), Pool::Managed)?;
+        let texture =
+            device.create_texture(4, 4, 1, Usage::default(), Format::A8R8G8B8, Pool::Managed)?;
+
+        device.begin_scene()?;
+        device.set_texture(0, &texture)?;
+        device.set_stream_source(0, &vertex_buffer, 0, 12)?;
+        device.draw_primitive(PrimitiveType::TriangleList, 0, 1)?;
+        device.draw_primitive_up(PrimitiveType::TriangleList, 1, vec![[0f32; 3]; 3], 12)?;
+        device.end_scene()?;
+        device.present(None, None, None, None)?;
+
+        Ok(vertex_buffer)
+    }
+
+    #[test]
+    fn records_calls_in_order() {
+        let device = RecordingDevice::new();
+        assert_eq!(frame(&device).unwrap(), Resource(0));
+
+        let calls = device.take_calls();
+        assert_eq!(calls.len(), 9);
+        assert_eq!(
+            calls[3],
+            Call::SetTexture {
+                stage: 0,
+                texture: Resource(1),
+            }
+        );
+        assert_eq!(
+            calls[6],
+            Call::DrawPrimitiveUp {
+                primitive_type: PrimitiveType::TriangleList,
+                n_primitives: 1,
+                n_vertices: 3,
+                vertices_stride: 12,
+            }
+        );
+        assert!(device.calls().is_empty());
+    }
+
+    #[test]
+    fn records_present_and_reset_while_lost() {
+        let device = RecordingDevice::new();
+        let mut presentation_parameters = PresentationParameters::default();
+        device.set_cooperative_level(CooperativeLevel::Lost);
+
+        let error = device.present(None, None, None, None).unwrap_err();
+        assert_eq!(error.code(), Some(ErrorCode::DeviceLost));
+        assert_eq!(error.method(), Some("Present"));
+
+        let error = device.reset(&mut presentation_parameters).unwrap_err();
+        assert_eq!(error.code(), Some(ErrorCode::DeviceLost));
+        assert_eq!(error.method(), Some("Reset"));
+        assert_eq!(
+            device.test_cooperative_level().unwrap(),
+            CooperativeLevel::Lost
+        );
+
+        device.set_cooperative_level(CooperativeLevel::NotReset);
+        device.reset(&mut presentation_parameters).unwrap();
+        assert_eq!(
+            device.test_cooperative_level().unwrap(),
+            CooperativeLevel::Ok
+        );
+
+        let reset = Call::Reset {
+            presentation_parameters,
+        };
+        assert!(matches!(device.calls()[0], Call::Present { .. }));
+        assert_eq!(device.calls()[1..], [reset.clone(), reset]);
+    }
+
+    #[test]
+    fn get_render_state_returns_the_last_set_value() {
+        let device = RecordingDevice::new();
+        assert_eq!(
+            device.get_render_state(RenderStateType::Lighting).unwrap(),
+            RenderState::Lighting(true)
+        );
+
+        device
+            .set_render_state(RenderState::Lighting(false))
+            .unwrap();
+        device
+            .set_render_state(RenderState::ZWriteEnable(false))
+            .unwrap();
+        assert_eq!(
+            device.get_render_state(RenderStateType::Lighting).unwrap(),
+            RenderState::Lighting(false)
+        );
+    }
+
+    #[test]
+    fn get_render_state_outlives_the_call_log() {
+        let device = RecordingDevice::new();
+        device
+            .set_render_state(RenderState::Lighting(false))
+            .unwrap();
+
+        assert_eq!(device.take_calls().len(), 1);
+        assert_eq!(
+            device.get_render_state(RenderStateType::Lighting).unwrap(),
+            RenderState::Lighting(false)
+        );
+    }
+
+    #[test]
+    fn reset_restores_default_render_states() {
+        let device = RecordingDevice::new();
+        device
+            .set_render_state(RenderState::Lighting(false))
+            .unwrap();
+
+        device
+            .reset(&mut PresentationParameters::default())
+            .unwrap();
+        assert_eq!(
+            device.get_render_state(RenderStateType::Lighting).unwrap(),
+            RenderState::Lighting(true)
+        );
+    }
+
+    #[test]
+    fn records_capture_calls() {
+        let device = RecordingDevice::new();
+        let mode = DisplayMode {
+            width: 640,
+            height: 480,
+            refresh_rate: 60,
+            format: Format::X8R8G8B8,
+        };
+        assert_eq!(device.get_display_mode(0).unwrap(), DisplayMode::default());
+        device.set_display_mode(mode.clone());
+        assert_eq!(device.get_display_mode(0).unwrap(), mode);
+
+        let back_buffer = device.get_back_buffer(0, 0).unwrap();
+        let render_target = device.get_render_target(0).unwrap();
+        assert_ne!(back_buffer, render_target);
+        assert_ne!(device.get_back_buffer(0, 0).unwrap(), back_buffer);
+
+        let staging = device
+            .create_offscreen_plain_surface(640, 480, Format::X8R8G8B8, Pool::SystemMem)
+            .unwrap();
+        device
+            .stretch_rect(&back_buffer, &render_target, TextureFilterType::None)
+            .unwrap();
+        device
+            .get_render_target_data(&render_target, &staging)
+            .unwrap();
+        device.get_front_buffer_data(0, &staging).unwrap();
+
+        assert_eq!(
+            device.calls()[2..],
+            [
+                Call::GetBackBuffer {
+                    swap_chain: 0,
+                    index: 0,
+                    surface: back_buffer,
+                },
+                Call::GetRenderTarget {
+                    index: 0,
+                    surface: render_target,
+                },
+                Call::GetBackBuffer {
+                    swap_chain: 0,
+                    index: 0,
+                    surface: Resource(2),
+                },
+                Call::CreateOffscreenPlainSurface {
+                    width: 640,
+                    height: 480,
+                    format: Format::X8R8G8B8,
+                    pool: Pool::SystemMem,
+                    surface: staging,
+                },
+                Call::StretchRect {
+                    source: back_buffer,
+                    destination: render_target,
+                    filter: TextureFilterType::None,
+                },
+                Call::GetRenderTargetData {
+                    render_target,
+                    destination: staging,
+                },
+                Call::GetFrontBufferData {
+                    swap_chain: 0,
+                    destination: staging,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,443 @@
+use std::{
+    cell::{Cell, Ref, RefCell},
+    fmt,
+    fmt::{Debug, Formatter},
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    error::WindowsResult,
+    std::{
+        backend::DeviceBackend,
+        types::{CooperativeLevel, PresentationParameters},
+    },
+};
+
+/// Represents the outcome of [`ResourceRegistry::check_device`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceStatus {
+    /// The device is operational and nothing had to be done.
+    Ready,
+    /// The device is lost. Registered resources have been released, and nothing should be
+    /// rendered until the device can be reset.
+    Lost,
+    /// The device was reset and every registered resource was recreated.
+    Reset,
+}
+
+/// A handle to a resource owned by a [`ResourceRegistry`].
+///
+/// The resource is unavailable while the device is lost. Clones of the resource returned by
+/// [`Managed::get`] keep it alive, so they must not be held across a reset.
+pub struct Managed<T>(Rc<RefCell<Option<T>>>);
+
+impl<T> Managed<T> {
+    /// Returns whether the resource currently exists.
+    pub fn is_available(&self) -> bool {
+        self.0.borrow().is_some()
+    }
+
+    pub fn borrow(&self) -> Ref<'_, Option<T>> {
+        self.0.borrow()
+    }
+}
+
+impl<T: Clone> Managed<T> {
+    /// Returns the resource, or [`None`] while the device is lost.
+    pub fn get(&self) -> Option<T> {
+        self.0.borrow().clone()
+    }
+}
+
+impl<T> Clone for Managed<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Debug> Debug for Managed<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Managed").field(&self.0.borrow()).finish()
+    }
+}
+
+/// A registered resource, erased over its type.
+trait Entry<B> {
+    fn is_alive(&self) -> bool;
+
+    fn release(&self);
+
+    /// Creates the resource if it is still in use but does not currently exist.
+    fn recreate(&self, device: &B) -> WindowsResult<()>;
+}
+
+struct Slot<T, F> {
+    value: Weak<RefCell<Option<T>>>,
+    create: RefCell<F>,
+}
+
+impl<B, T, F: FnMut(&B) -> WindowsResult<T>> Entry<B> for Slot<T, F> {
+    fn is_alive(&self) -> bool {
+        self.value.strong_count() > 0
+    }
+
+    fn release(&self) {
+        if let Some(value) = self.value.upgrade() {
+            value.borrow_mut().take();
+        }
+    }
+
+    fn recreate(&self, device: &B) -> WindowsResult<()> {
+        let Some(value) = self.value.upgrade() else {
+            return Ok(());
+        };
+
+        if value.borrow().is_none() {
+            let resource = (self.create.borrow_mut())(device)?;
+            value.borrow_mut().replace(resource);
+        }
+
+        Ok(())
+    }
+}
+
+/// Keeps track of resources which do not survive a device reset, such as [`Pool::Default`]
+/// textures, render targets, dynamic buffers and queries.
+///
+/// Each resource is registered with a function which creates it. The registry releases every
+/// resource before the device is reset and calls the functions again afterwards. Resources whose
+/// [`Managed`] handles have all been dropped are forgotten.
+///
+/// If a resource cannot be recreated, the ones recreated before it are kept and the registry
+/// stays released. The next [`ResourceRegistry::check_device`] or
+/// [`ResourceRegistry::recreate_all`] only creates the resources which are still missing.
+///
+/// # Usage
+///
+/// ```rs
+/// let registry = ResourceRegistry::new();
+/// let target = registry.register(&device, |device| {
+///     device.create_render_target(1280, 720, Format::A8R8G8B8, MultiSampleType::None, 0, false)
+/// })?;
+///
+/// loop {
+///     match registry.check_device(&device, &mut presentation_parameters)? {
+///         DeviceStatus::Lost => continue,
+///         DeviceStatus::Ready | DeviceStatus::Reset => render(&device, &target.get().unwrap())?,
+///     }
+/// }
+/// ```
+pub struct ResourceRegistry<B: DeviceBackend> {
+    entries: RefCell<Vec<Rc<dyn Entry<B>>>>,
+    released: Cell<bool>,
+}
+
+impl<B: DeviceBackend> ResourceRegistry<B> {
+    pub fn new() -> Self {
+        Self {
+            entries: RefCell::new(Vec::new()),
+            released: Cell::new(false),
+        }
+    }
+
+    /// Registers a resource, creating it right away unless the device is lost.
+    pub fn register<T: 'static>(
+        &self,
+        device: &B,
+        mut create: impl FnMut(&B) -> WindowsResult<T> + 'static,
+    ) -> WindowsResult<Managed<T>> {
+        let value = if self.released.get() {
+            None
+        } else {
+            Some(create(device)?)
+        };
+
+        let managed = Managed(Rc::new(RefCell::new(value)));
+        self.entries.borrow_mut().push(Rc::new(Slot {
+            value: Rc::downgrade(&managed.0),
+            create: RefCell::new(create),
+        }));
+
+        Ok(managed)
+    }
+
+    /// Returns the number of registered resources which are still in use.
+    pub fn len(&self) -> usize {
+        self.entries
+            .borrow()
+            .iter()
+            .filter(|entry| entry.is_alive())
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Releases every registered resource.
+    pub fn release_all(&self) {
+        self.released.set(true);
+
+        for entry in self.live_entries() {
+            entry.release();
+        }
+    }
+
+    /// Recreates every registered resource which does not currently exist.
+    ///
+    /// This stops at the first resource which cannot be created, leaving the registry released.
+    pub fn recreate_all(&self, device: &B) -> WindowsResult<()> {
+        // Resources registered by the create functions are created right away.
+        self.released.set(false);
+
+        for entry in self.live_entries() {
+            if let Err(error) = entry.recreate(device) {
+                self.released.set(true);
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Forgets resources which are no longer in use, and returns the remaining ones.
+    ///
+    /// The list is copied, so that the create functions can use the registry while it is walked.
+    fn live_entries(&self) -> Vec<Rc<dyn Entry<B>>> {
+        let mut entries = self.entries.borrow_mut();
+        entries.retain(|entry| entry.is_alive());
+
+        entries.clone()
+    }
+
+    /// Resets the device, releasing every registered resource beforehand and recreating them
+    /// afterwards.
+    pub fn reset(
+        &self,
+        device: &B,
+        presentation_parameters: &mut PresentationParameters,
+    ) -> WindowsResult<()> {
+        self.release_all();
+        device.reset(presentation_parameters)?;
+        self.recreate_all(device)
+    }
+
+    /// Checks whether the device has been lost, and resets it once that is possible.
+    ///
+    /// This is meant to be called before rendering each frame.
+    pub fn check_device(
+        &self,
+        device: &B,
+        presentation_parameters: &mut PresentationParameters,
+    ) -> WindowsResult<DeviceStatus> {
+        match device.test_cooperative_level()? {
+            CooperativeLevel::Ok if self.released.get() => {
+                self.recreate_all(device)?;
+                Ok(DeviceStatus::Reset)
+            }
+            CooperativeLevel::Ok => Ok(DeviceStatus::Ready),
+            CooperativeLevel::Lost => {
+                if !self.released.get() {
+                    self.release_all();
+                }
+
+                Ok(DeviceStatus::Lost)
+            }
+            CooperativeLevel::NotReset => {
+                self.reset(device, presentation_parameters)?;
+                Ok(DeviceStatus::Reset)
+            }
+        }
+    }
+}
+
+impl<B: DeviceBackend> Default for ResourceRegistry<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::{
+        error::{ErrorCode, WindowsError},
+        std::{
+            backend::recording::{Call, RecordingDevice, Resource},
+            types::{Format, MultiSampleType, Pool, QueryType, Usage, FVF},
+        },
+    };
+
+    fn render_target(device: &RecordingDevice) -> WindowsResult<Resource> {
+        device.create_render_target(64, 64, Format::A8R8G8B8, MultiSampleType::None, 0, false)
+    }
+
+    #[test]
+    fn releases_while_lost_and_recreates_after_reset() {
+        let device = RecordingDevice::new();
+        let registry = ResourceRegistry::new();
+        let mut presentation_parameters = PresentationParameters::default();
+
+        let created = Rc::new(Cell::new(0));
+        let target = registry
+            .register(&device, {
+                let created = created.clone();
+                move |device| {
+                    created.set(created.get() + 1);
+                    render_target(device)
+                }
+            })
+            .unwrap();
+        let query = registry
+            .register(&device, |device| device.create_query(QueryType::Event))
+            .unwrap();
+        let dropped = registry
+            .register(&device, |device| {
+                device.create_vertex_buffer(64, Usage::default(), FVF::default(), Pool::Default)
+            })
+            .unwrap();
+        drop(dropped);
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(target.get(), Some(Resource(0)));
+        assert_eq!(
+            registry
+                .check_device(&device, &mut presentation_parameters)
+                .unwrap(),
+            DeviceStatus::Ready
+        );
+
+        // While the device is lost, resources are released and reset is not attempted.
+        device.set_cooperative_level(CooperativeLevel::Lost);
+        for _ in 0..2 {
+            assert_eq!(
+                registry
+                    .check_device(&device, &mut presentation_parameters)
+                    .unwrap(),
+                DeviceStatus::Lost
+            );
+        }
+        assert!(!target.is_available());
+        assert!(query.get().is_none());
+
+        device.take_calls();
+        device.set_cooperative_level(CooperativeLevel::NotReset);
+        assert_eq!(
+            registry
+                .check_device(&device, &mut presentation_parameters)
+                .unwrap(),
+            DeviceStatus::Reset
+        );
+        assert_eq!(
+            device.test_cooperative_level().unwrap(),
+            CooperativeLevel::Ok
+        );
+        assert_eq!(created.get(), 2);
+        assert_eq!(target.get(), Some(Resource(3)));
+        assert_eq!(query.get(), Some(Resource(4)));
+
+        let calls = device.take_calls();
+        assert_eq!(calls.len(), 3);
+        assert!(matches!(calls[0], Call::Reset { .. }));
+        assert_eq!(registry.len(), 2);
+        assert_eq!(
+            registry
+                .check_device(&device, &mut presentation_parameters)
+                .unwrap(),
+            DeviceStatus::Ready
+        );
+    }
+
+    #[test]
+    fn stays_released_when_reset_fails() {
+        let device = RecordingDevice::new();
+        let registry = ResourceRegistry::new();
+        let target = registry.register(&device, render_target).unwrap();
+
+        device.set_cooperative_level(CooperativeLevel::Lost);
+        assert!(registry
+            .reset(&device, &mut PresentationParameters::default())
+            .is_err());
+        assert!(!target.is_available());
+
+        device.set_cooperative_level(CooperativeLevel::Ok);
+        assert_eq!(
+            registry
+                .check_device(&device, &mut PresentationParameters::default())
+                .unwrap(),
+            DeviceStatus::Reset
+        );
+        assert!(target.is_available());
+    }
+
+    #[test]
+    fn retries_only_missing_resources_after_a_failed_recreate() {
+        let device = RecordingDevice::new();
+        let registry = ResourceRegistry::new();
+        let fail = Rc::new(Cell::new(false));
+
+        let first = registry.register(&device, render_target).unwrap();
+        let second = registry
+            .register(&device, {
+                let fail = fail.clone();
+                move |device| match fail.get() {
+                    true => Err(WindowsError::from(ErrorCode::OutOfVideoMemory)),
+                    false => render_target(device),
+                }
+            })
+            .unwrap();
+
+        registry.release_all();
+        fail.set(true);
+        let error = registry.recreate_all(&device).unwrap_err();
+        assert_eq!(error.code(), Some(ErrorCode::OutOfVideoMemory));
+        assert_eq!(first.get(), Some(Resource(2)));
+        assert!(!second.is_available());
+
+        fail.set(false);
+        assert_eq!(
+            registry
+                .check_device(&device, &mut PresentationParameters::default())
+                .unwrap(),
+            DeviceStatus::Reset
+        );
+        assert_eq!(first.get(), Some(Resource(2)));
+        assert_eq!(second.get(), Some(Resource(3)));
+    }
+
+    #[test]
+    fn create_functions_can_use_the_registry() {
+        let device = RecordingDevice::new();
+        let registry = Rc::new(ResourceRegistry::new());
+        let lengths = Rc::new(RefCell::new(Vec::new()));
+        let queries = Rc::new(RefCell::new(Vec::new()));
+
+        let _target = registry
+            .register(&device, {
+                let registry = Rc::downgrade(&registry);
+                let lengths = lengths.clone();
+                let queries = queries.clone();
+                move |device| {
+                    let registry = registry.upgrade().unwrap();
+                    lengths.borrow_mut().push(registry.len());
+
+                    let query = registry
+                        .register(device, |device| device.create_query(QueryType::Event))?;
+                    queries.borrow_mut().push(query);
+
+                    render_target(device)
+                }
+            })
+            .unwrap();
+
+        registry.release_all();
+        registry.recreate_all(&device).unwrap();
+
+        // The query registered while recreating is created right away.
+        assert_eq!(*lengths.borrow(), [0, 2]);
+        assert!(queries.borrow().iter().all(Managed::is_available));
+        assert_eq!(registry.len(), 3);
+    }
+}
@@ -0,0 +1,1143 @@
+//! Provides a typed view of the capabilities reported by a Direct3D9 device.
+//!
+//! [`Caps`] decodes every field of [`D3DCAPS9`] into named flags and values. Decoding does not call
+//! into Direct3D, so captured caps dumps can be inspected on any platform with
+//! [`Caps::from_bytes`].
+//!
+//! # Usage
+//!
+//! ```rs
+//! let caps = context.get_device_caps(Adapter::default(), DeviceType::Hal)?;
+//!
+//! if !caps.supports_shader_model(3, 0) {
+//!     return Err("shader model 3.0 is required".into());
+//! }
+//!
+//! let (width, height) = caps.max_texture_size();
+//! let anisotropic = caps.texture_filter_caps.min_anisotropic();
+//! ```
+
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+    mem::size_of,
+    ptr,
+};
+
+use bitfield::bitfield;
+
+use crate::{
+    error::WindowsError,
+    shader::ShaderVersion,
+    std::types::DeviceType,
+    sys::d3d9caps::{D3DCAPS9, D3DPSHADERCAPS2_0, D3DVSHADERCAPS2_0},
+};
+
+/// Represents an error which occurred while querying or decoding device capabilities.
+#[derive(Debug, thiserror::Error)]
+pub enum CapsError {
+    #[error("Windows Error: {from}")]
+    Windows {
+        #[from]
+        from: WindowsError,
+    },
+
+    #[error("caps dump is {found} bytes, expected {expected}")]
+    InvalidLength { expected: usize, found: usize },
+
+    #[error("unknown device type {0}")]
+    UnknownDeviceType(u32),
+}
+
+/// Declares a capability bitfield together with a table of its flag names.
+macro_rules! flags {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $(pub $getter:ident, $setter:ident: $bit:literal;)*
+        }
+    ) => {
+        bitfield! {
+            $(#[$meta])*
+            #[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+            pub struct $name(u32);
+
+            impl Debug;
+
+            $(pub $getter, $setter: $bit;)*
+        }
+
+        impl $name {
+            /// The name and bit index of every flag.
+            pub const FLAGS: &'static [(&'static str, u32)] = &[$((stringify!($getter), $bit)),*];
+        }
+    };
+}
+
+flags! {
+    /// Flags for [`Caps::caps`].
+    pub struct DriverCaps {
+        pub overlay, set_overlay: 11;
+        pub read_scanline, set_read_scanline: 17;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::caps2`].
+    pub struct DriverCaps2 {
+        pub fullscreen_gamma, set_fullscreen_gamma: 17;
+        pub can_calibrate_gamma, set_can_calibrate_gamma: 20;
+        pub can_manage_resource, set_can_manage_resource: 28;
+        pub dynamic_textures, set_dynamic_textures: 29;
+        pub can_auto_gen_mipmap, set_can_auto_gen_mipmap: 30;
+        pub can_share_resource, set_can_share_resource: 31;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::caps3`].
+    pub struct DriverCaps3 {
+        pub alpha_fullscreen_flip_or_discard, set_alpha_fullscreen_flip_or_discard: 5;
+        pub linear_to_srgb_presentation, set_linear_to_srgb_presentation: 7;
+        pub copy_to_vidmem, set_copy_to_vidmem: 8;
+        pub copy_to_systemmem, set_copy_to_systemmem: 9;
+        pub dxvahd, set_dxvahd: 10;
+        pub dxvahd_limited, set_dxvahd_limited: 11;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::presentation_intervals`].
+    pub struct PresentIntervalCaps {
+        pub one, set_one: 0;
+        pub two, set_two: 1;
+        pub three, set_three: 2;
+        pub four, set_four: 3;
+        pub immediate, set_immediate: 31;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::cursor_caps`].
+    pub struct CursorCaps {
+        pub color, set_color: 0;
+        pub low_res, set_low_res: 1;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::dev_caps`].
+    pub struct DevCaps {
+        pub execute_system_memory, set_execute_system_memory: 4;
+        pub execute_video_memory, set_execute_video_memory: 5;
+        pub tl_vertex_system_memory, set_tl_vertex_system_memory: 6;
+        pub tl_vertex_video_memory, set_tl_vertex_video_memory: 7;
+        pub texture_system_memory, set_texture_system_memory: 8;
+        pub texture_video_memory, set_texture_video_memory: 9;
+        pub draw_prim_tl_vertex, set_draw_prim_tl_vertex: 10;
+        pub can_render_after_flip, set_can_render_after_flip: 11;
+        pub texture_non_local_vidmem, set_texture_non_local_vidmem: 12;
+        pub draw_primitives2, set_draw_primitives2: 13;
+        pub separate_texture_memories, set_separate_texture_memories: 14;
+        pub draw_primitives2_ex, set_draw_primitives2_ex: 15;
+        pub hw_transform_and_light, set_hw_transform_and_light: 16;
+        pub can_blt_sys_to_non_local, set_can_blt_sys_to_non_local: 17;
+        pub hw_rasterization, set_hw_rasterization: 19;
+        pub pure_device, set_pure_device: 20;
+        pub quintic_rt_patches, set_quintic_rt_patches: 21;
+        pub rt_patches, set_rt_patches: 22;
+        pub rt_patch_handle_zero, set_rt_patch_handle_zero: 23;
+        pub n_patches, set_n_patches: 24;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::primitive_misc_caps`].
+    pub struct PrimitiveMiscCaps {
+        pub mask_z, set_mask_z: 1;
+        pub cull_none, set_cull_none: 4;
+        pub cull_cw, set_cull_cw: 5;
+        pub cull_ccw, set_cull_ccw: 6;
+        pub color_write_enable, set_color_write_enable: 7;
+        pub clip_plane_scaled_points, set_clip_plane_scaled_points: 8;
+        pub clip_tl_verts, set_clip_tl_verts: 9;
+        pub tss_arg_temp, set_tss_arg_temp: 10;
+        pub blend_op, set_blend_op: 11;
+        pub null_reference, set_null_reference: 12;
+        pub independent_write_masks, set_independent_write_masks: 14;
+        pub per_stage_constant, set_per_stage_constant: 15;
+        pub fog_and_specular_alpha, set_fog_and_specular_alpha: 16;
+        pub separate_alpha_blend, set_separate_alpha_blend: 17;
+        pub mrt_independent_bit_depths, set_mrt_independent_bit_depths: 18;
+        pub mrt_post_pixel_shader_blending, set_mrt_post_pixel_shader_blending: 19;
+        pub fog_vertex_clamped, set_fog_vertex_clamped: 20;
+        pub post_blend_srgb_convert, set_post_blend_srgb_convert: 21;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::line_caps`].
+    pub struct LineCaps {
+        pub texture, set_texture: 0;
+        pub z_test, set_z_test: 1;
+        pub blend, set_blend: 2;
+        pub alpha_cmp, set_alpha_cmp: 3;
+        pub fog, set_fog: 4;
+        pub antialias, set_antialias: 5;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::raster_caps`].
+    pub struct RasterCaps {
+        pub dither, set_dither: 0;
+        pub z_test, set_z_test: 4;
+        pub fog_vertex, set_fog_vertex: 7;
+        pub fog_table, set_fog_table: 8;
+        pub mipmap_lod_bias, set_mipmap_lod_bias: 13;
+        pub z_bufferless_hsr, set_z_bufferless_hsr: 15;
+        pub fog_range, set_fog_range: 16;
+        pub anisotropy, set_anisotropy: 17;
+        pub w_buffer, set_w_buffer: 18;
+        pub w_fog, set_w_fog: 20;
+        pub z_fog, set_z_fog: 21;
+        pub color_perspective, set_color_perspective: 22;
+        pub scissor_test, set_scissor_test: 24;
+        pub slope_scale_depth_bias, set_slope_scale_depth_bias: 25;
+        pub depth_bias, set_depth_bias: 26;
+        pub multisample_toggle, set_multisample_toggle: 27;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::z_cmp_caps`] and [`Caps::alpha_cmp_caps`].
+    pub struct CompareCaps {
+        pub never, set_never: 0;
+        pub less, set_less: 1;
+        pub equal, set_equal: 2;
+        pub less_equal, set_less_equal: 3;
+        pub greater, set_greater: 4;
+        pub not_equal, set_not_equal: 5;
+        pub greater_equal, set_greater_equal: 6;
+        pub always, set_always: 7;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::src_blend_caps`] and [`Caps::dest_blend_caps`].
+    pub struct BlendCaps {
+        pub zero, set_zero: 0;
+        pub one, set_one: 1;
+        pub src_color, set_src_color: 2;
+        pub inv_src_color, set_inv_src_color: 3;
+        pub src_alpha, set_src_alpha: 4;
+        pub inv_src_alpha, set_inv_src_alpha: 5;
+        pub dest_alpha, set_dest_alpha: 6;
+        pub inv_dest_alpha, set_inv_dest_alpha: 7;
+        pub dest_color, set_dest_color: 8;
+        pub inv_dest_color, set_inv_dest_color: 9;
+        pub src_alpha_sat, set_src_alpha_sat: 10;
+        pub both_src_alpha, set_both_src_alpha: 11;
+        pub both_inv_src_alpha, set_both_inv_src_alpha: 12;
+        pub blend_factor, set_blend_factor: 13;
+        pub src_color2, set_src_color2: 14;
+        pub inv_src_color2, set_inv_src_color2: 15;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::shade_caps`].
+    pub struct ShadeCaps {
+        pub color_gouraud_rgb, set_color_gouraud_rgb: 3;
+        pub specular_gouraud_rgb, set_specular_gouraud_rgb: 9;
+        pub alpha_gouraud_blend, set_alpha_gouraud_blend: 14;
+        pub fog_gouraud, set_fog_gouraud: 19;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::texture_caps`].
+    pub struct TextureCaps {
+        pub perspective, set_perspective: 0;
+        pub pow2, set_pow2: 1;
+        pub alpha, set_alpha: 2;
+        pub square_only, set_square_only: 5;
+        pub tex_repeat_not_scaled_by_size, set_tex_repeat_not_scaled_by_size: 6;
+        pub alpha_palette, set_alpha_palette: 7;
+        pub non_pow2_conditional, set_non_pow2_conditional: 8;
+        pub projected, set_projected: 10;
+        pub cube_map, set_cube_map: 11;
+        pub volume_map, set_volume_map: 13;
+        pub mipmap, set_mipmap: 14;
+        pub mip_volume_map, set_mip_volume_map: 15;
+        pub mip_cube_map, set_mip_cube_map: 16;
+        pub cube_map_pow2, set_cube_map_pow2: 17;
+        pub volume_map_pow2, set_volume_map_pow2: 18;
+        pub no_projected_bump_env, set_no_projected_bump_env: 21;
+    }
+}
+
+flags! {
+    /// Flags for the texture filtering capabilities of [`Caps`].
+    pub struct FilterCaps {
+        pub min_point, set_min_point: 8;
+        pub min_linear, set_min_linear: 9;
+        pub min_anisotropic, set_min_anisotropic: 10;
+        pub min_pyramidal_quad, set_min_pyramidal_quad: 11;
+        pub min_gaussian_quad, set_min_gaussian_quad: 12;
+        pub mip_point, set_mip_point: 16;
+        pub mip_linear, set_mip_linear: 17;
+        pub convolution_mono, set_convolution_mono: 18;
+        pub mag_point, set_mag_point: 24;
+        pub mag_linear, set_mag_linear: 25;
+        pub mag_anisotropic, set_mag_anisotropic: 26;
+        pub mag_pyramidal_quad, set_mag_pyramidal_quad: 27;
+        pub mag_gaussian_quad, set_mag_gaussian_quad: 28;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::texture_address_caps`] and [`Caps::volume_texture_address_caps`].
+    pub struct AddressCaps {
+        pub wrap, set_wrap: 0;
+        pub mirror, set_mirror: 1;
+        pub clamp, set_clamp: 2;
+        pub border, set_border: 3;
+        pub independent_uv, set_independent_uv: 4;
+        pub mirror_once, set_mirror_once: 5;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::stencil_caps`].
+    pub struct StencilCaps {
+        pub keep, set_keep: 0;
+        pub zero, set_zero: 1;
+        pub replace, set_replace: 2;
+        pub incr_sat, set_incr_sat: 3;
+        pub decr_sat, set_decr_sat: 4;
+        pub invert, set_invert: 5;
+        pub incr, set_incr: 6;
+        pub decr, set_decr: 7;
+        pub two_sided, set_two_sided: 8;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::texture_op_caps`].
+    pub struct TextureOpCaps {
+        pub disable, set_disable: 0;
+        pub select_arg1, set_select_arg1: 1;
+        pub select_arg2, set_select_arg2: 2;
+        pub modulate, set_modulate: 3;
+        pub modulate2x, set_modulate2x: 4;
+        pub modulate4x, set_modulate4x: 5;
+        pub add, set_add: 6;
+        pub add_signed, set_add_signed: 7;
+        pub add_signed2x, set_add_signed2x: 8;
+        pub subtract, set_subtract: 9;
+        pub add_smooth, set_add_smooth: 10;
+        pub blend_diffuse_alpha, set_blend_diffuse_alpha: 11;
+        pub blend_texture_alpha, set_blend_texture_alpha: 12;
+        pub blend_factor_alpha, set_blend_factor_alpha: 13;
+        pub blend_texture_alpha_pm, set_blend_texture_alpha_pm: 14;
+        pub blend_current_alpha, set_blend_current_alpha: 15;
+        pub premodulate, set_premodulate: 16;
+        pub modulate_alpha_add_color, set_modulate_alpha_add_color: 17;
+        pub modulate_color_add_alpha, set_modulate_color_add_alpha: 18;
+        pub modulate_inv_alpha_add_color, set_modulate_inv_alpha_add_color: 19;
+        pub modulate_inv_color_add_alpha, set_modulate_inv_color_add_alpha: 20;
+        pub bump_env_map, set_bump_env_map: 21;
+        pub bump_env_map_luminance, set_bump_env_map_luminance: 22;
+        pub dot_product3, set_dot_product3: 23;
+        pub multiply_add, set_multiply_add: 24;
+        pub lerp, set_lerp: 25;
+    }
+}
+
+bitfield! {
+    /// Flags for [`Caps::fvf_caps`].
+    #[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct FvfCaps(u32);
+
+    impl Debug;
+
+    /// The number of texture coordinate sets which can be used at once.
+    pub u16, texture_coordinate_count, set_texture_coordinate_count: 15, 0;
+    pub do_not_strip_elements, set_do_not_strip_elements: 19;
+    pub psize, set_psize: 20;
+}
+
+impl FvfCaps {
+    /// The name and bit index of every flag, excluding the texture coordinate count.
+    pub const FLAGS: &'static [(&'static str, u32)] =
+        &[("do_not_strip_elements", 19), ("psize", 20)];
+}
+
+flags! {
+    /// Flags for [`Caps::vertex_processing_caps`].
+    pub struct VertexProcessingCaps {
+        pub tex_gen, set_tex_gen: 0;
+        pub material_source7, set_material_source7: 1;
+        pub directional_lights, set_directional_lights: 3;
+        pub positional_lights, set_positional_lights: 4;
+        pub local_viewer, set_local_viewer: 5;
+        pub tweening, set_tweening: 6;
+        pub tex_gen_sphere_map, set_tex_gen_sphere_map: 8;
+        pub no_tex_gen_non_local_viewer, set_no_tex_gen_non_local_viewer: 9;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::dev_caps2`].
+    pub struct DevCaps2 {
+        pub stream_offset, set_stream_offset: 0;
+        pub dmap_n_patch, set_dmap_n_patch: 1;
+        pub adaptive_tess_rt_patch, set_adaptive_tess_rt_patch: 2;
+        pub adaptive_tess_n_patch, set_adaptive_tess_n_patch: 3;
+        pub can_stretch_rect_from_textures, set_can_stretch_rect_from_textures: 4;
+        pub presampled_dmap_n_patch, set_presampled_dmap_n_patch: 5;
+        pub vertex_elements_can_share_stream_offset, set_vertex_elements_can_share_stream_offset: 6;
+    }
+}
+
+flags! {
+    /// Flags for [`Caps::decl_types`].
+    pub struct DeclTypeCaps {
+        pub ubyte4, set_ubyte4: 0;
+        pub ubyte4n, set_ubyte4n: 1;
+        pub short2n, set_short2n: 2;
+        pub short4n, set_short4n: 3;
+        pub ushort2n, set_ushort2n: 4;
+        pub ushort4n, set_ushort4n: 5;
+        pub udec3, set_udec3: 6;
+        pub dec3n, set_dec3n: 7;
+        pub float16_2, set_float16_2: 8;
+        pub float16_4, set_float16_4: 9;
+    }
+}
+
+flags! {
+    /// Flags for [`VertexShaderCaps::caps`].
+    pub struct VertexShader2Caps {
+        pub predication, set_predication: 0;
+    }
+}
+
+flags! {
+    /// Flags for [`PixelShaderCaps::caps`].
+    pub struct PixelShader2Caps {
+        pub arbitrary_swizzle, set_arbitrary_swizzle: 0;
+        pub gradient_instructions, set_gradient_instructions: 1;
+        pub predication, set_predication: 2;
+        pub no_dependent_read_limit, set_no_dependent_read_limit: 3;
+        pub no_tex_instruction_limit, set_no_tex_instruction_limit: 4;
+    }
+}
+
+/// Represents the value of a single named capability.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CapValue {
+    Bool(bool),
+    Uint(u32),
+    Int(i32),
+    Float(f32),
+    ShaderVersion(Option<ShaderVersion>),
+    DeviceType(DeviceType),
+}
+
+impl CapValue {
+    /// Returns the value as a number, or [`None`] if it is not numeric.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Uint(value) => Some(value as f64),
+            Self::Int(value) => Some(value as f64),
+            Self::Float(value) => Some(value as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns whether both values are the same, comparing floats by their bits so that a NaN
+    /// capability matches itself.
+    fn is_same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Float(left), Self::Float(right)) => left.to_bits() == right.to_bits(),
+            _ => self == other,
+        }
+    }
+}
+
+impl Display for CapValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Uint(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value}"),
+            Self::ShaderVersion(Some(version)) => write!(f, "{}.{}", version.major, version.minor),
+            Self::ShaderVersion(None) => write!(f, "none"),
+            Self::DeviceType(device_type) => write!(f, "{device_type:?}"),
+        }
+    }
+}
+
+/// Represents a capability which differs between two [`Caps`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapsDifference {
+    pub name: String,
+    pub left: CapValue,
+    pub right: CapValue,
+}
+
+impl Display for CapsDifference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.name, self.left, self.right)
+    }
+}
+
+/// Collects the named values of a [`Caps`].
+struct Values(Vec<(String, CapValue)>);
+
+impl Values {
+    fn push(&mut self, name: &str, value: CapValue) {
+        self.0.push((name.to_string(), value));
+    }
+
+    fn flags(&mut self, name: &str, raw: u32, flags: &[(&str, u32)]) {
+        for (flag, bit) in flags {
+            self.push(
+                &format!("{name}.{flag}"),
+                CapValue::Bool(raw & 1 << bit != 0),
+            );
+        }
+    }
+}
+
+/// Represents the version 2.0 vertex shader capabilities of a device.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VertexShaderCaps {
+    pub caps: VertexShader2Caps,
+    pub dynamic_flow_control_depth: i32,
+    pub num_temps: i32,
+    pub static_flow_control_depth: i32,
+}
+
+impl From<D3DVSHADERCAPS2_0> for VertexShaderCaps {
+    fn from(caps: D3DVSHADERCAPS2_0) -> Self {
+        Self {
+            caps: VertexShader2Caps(caps.Caps),
+            dynamic_flow_control_depth: caps.DynamicFlowControlDepth,
+            num_temps: caps.NumTemps,
+            static_flow_control_depth: caps.StaticFlowControlDepth,
+        }
+    }
+}
+
+/// Represents the version 2.0 pixel shader capabilities of a device.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PixelShaderCaps {
+    pub caps: PixelShader2Caps,
+    pub dynamic_flow_control_depth: i32,
+    pub num_temps: i32,
+    pub static_flow_control_depth: i32,
+    pub num_instruction_slots: i32,
+}
+
+impl From<D3DPSHADERCAPS2_0> for PixelShaderCaps {
+    fn from(caps: D3DPSHADERCAPS2_0) -> Self {
+        Self {
+            caps: PixelShader2Caps(caps.Caps),
+            dynamic_flow_control_depth: caps.DynamicFlowControlDepth,
+            num_temps: caps.NumTemps,
+            static_flow_control_depth: caps.StaticFlowControlDepth,
+            num_instruction_slots: caps.NumInstructionSlots,
+        }
+    }
+}
+
+/// Represents the capabilities of a device, decoded from [`D3DCAPS9`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Caps {
+    pub device_type: DeviceType,
+    pub adapter_ordinal: u32,
+    pub caps: DriverCaps,
+    pub caps2: DriverCaps2,
+    pub caps3: DriverCaps3,
+    pub presentation_intervals: PresentIntervalCaps,
+    pub cursor_caps: CursorCaps,
+    pub dev_caps: DevCaps,
+    pub primitive_misc_caps: PrimitiveMiscCaps,
+    pub raster_caps: RasterCaps,
+    pub z_cmp_caps: CompareCaps,
+    pub src_blend_caps: BlendCaps,
+    pub dest_blend_caps: BlendCaps,
+    pub alpha_cmp_caps: CompareCaps,
+    pub shade_caps: ShadeCaps,
+    pub texture_caps: TextureCaps,
+    pub texture_filter_caps: FilterCaps,
+    pub cube_texture_filter_caps: FilterCaps,
+    pub volume_texture_filter_caps: FilterCaps,
+    pub texture_address_caps: AddressCaps,
+    pub volume_texture_address_caps: AddressCaps,
+    pub line_caps: LineCaps,
+    pub max_texture_width: u32,
+    pub max_texture_height: u32,
+    pub max_volume_extent: u32,
+    pub max_texture_repeat: u32,
+    pub max_texture_aspect_ratio: u32,
+    pub max_anisotropy: u32,
+    pub max_vertex_w: f32,
+    pub guard_band_left: f32,
+    pub guard_band_top: f32,
+    pub guard_band_right: f32,
+    pub guard_band_bottom: f32,
+    pub extents_adjust: f32,
+    pub stencil_caps: StencilCaps,
+    pub fvf_caps: FvfCaps,
+    pub texture_op_caps: TextureOpCaps,
+    pub max_texture_blend_stages: u32,
+    pub max_simultaneous_textures: u32,
+    pub vertex_processing_caps: VertexProcessingCaps,
+    pub max_active_lights: u32,
+    pub max_user_clip_planes: u32,
+    pub max_vertex_blend_matrices: u32,
+    pub max_vertex_blend_matrix_index: u32,
+    pub max_point_size: f32,
+    pub max_primitive_count: u32,
+    pub max_vertex_index: u32,
+    pub max_streams: u32,
+    pub max_stream_stride: u32,
+    /// The highest supported vertex shader version, or [`None`] if vertex shaders are unsupported.
+    pub vertex_shader_version: Option<ShaderVersion>,
+    pub max_vertex_shader_const: u32,
+    /// The highest supported pixel shader version, or [`None`] if pixel shaders are unsupported.
+    pub pixel_shader_version: Option<ShaderVersion>,
+    pub pixel_shader_1x_max_value: f32,
+    pub dev_caps2: DevCaps2,
+    pub max_npatch_tessellation_level: f32,
+    pub master_adapter_ordinal: u32,
+    pub adapter_ordinal_in_group: u32,
+    pub number_of_adapters_in_group: u32,
+    pub decl_types: DeclTypeCaps,
+    pub num_simultaneous_rts: u32,
+    pub stretch_rect_filter_caps: FilterCaps,
+    pub vs20_caps: VertexShaderCaps,
+    pub ps20_caps: PixelShaderCaps,
+    pub vertex_texture_filter_caps: FilterCaps,
+    pub max_vshader_instructions_executed: u32,
+    pub max_pshader_instructions_executed: u32,
+    pub max_vertex_shader30_instruction_slots: u32,
+    pub max_pixel_shader30_instruction_slots: u32,
+}
+
+impl Caps {
+    /// Decodes a [`D3DCAPS9`] structure.
+    pub fn from_raw(caps: &D3DCAPS9) -> Result<Self, CapsError> {
+        let device_type = DeviceType::from_raw(caps.DeviceType)
+            .ok_or(CapsError::UnknownDeviceType(caps.DeviceType))?;
+
+        Ok(Self {
+            device_type,
+            adapter_ordinal: caps.AdapterOrdinal,
+            caps: DriverCaps(caps.Caps),
+            caps2: DriverCaps2(caps.Caps2),
+            caps3: DriverCaps3(caps.Caps3),
+            presentation_intervals: PresentIntervalCaps(caps.PresentationIntervals),
+            cursor_caps: CursorCaps(caps.CursorCaps),
+            dev_caps: DevCaps(caps.DevCaps),
+            primitive_misc_caps: PrimitiveMiscCaps(caps.PrimitiveMiscCaps),
+            raster_caps: RasterCaps(caps.RasterCaps),
+            z_cmp_caps: CompareCaps(caps.ZCmpCaps),
+            src_blend_caps: BlendCaps(caps.SrcBlendCaps),
+            dest_blend_caps: BlendCaps(caps.DestBlendCaps),
+            alpha_cmp_caps: CompareCaps(caps.AlphaCmpCaps),
+            shade_caps: ShadeCaps(caps.ShadeCaps),
+            texture_caps: TextureCaps(caps.TextureCaps),
+            texture_filter_caps: FilterCaps(caps.TextureFilterCaps),
+            cube_texture_filter_caps: FilterCaps(caps.CubeTextureFilterCaps),
+            volume_texture_filter_caps: FilterCaps(caps.VolumeTextureFilterCaps),
+            texture_address_caps: AddressCaps(caps.TextureAddressCaps),
+            volume_texture_address_caps: AddressCaps(caps.VolumeTextureAddressCaps),
+            line_caps: LineCaps(caps.LineCaps),
+            max_texture_width: caps.MaxTextureWidth,
+            max_texture_height: caps.MaxTextureHeight,
+            max_volume_extent: caps.MaxVolumeExtent,
+            max_texture_repeat: caps.MaxTextureRepeat,
+            max_texture_aspect_ratio: caps.MaxTextureAspectRatio,
+            max_anisotropy: caps.MaxAnisotropy,
+            max_vertex_w: caps.MaxVertexW,
+            guard_band_left: caps.GuardBandLeft,
+            guard_band_top: caps.GuardBandTop,
+            guard_band_right: caps.GuardBandRight,
+            guard_band_bottom: caps.GuardBandBottom,
+            extents_adjust: caps.ExtentsAdjust,
+            stencil_caps: StencilCaps(caps.StencilCaps),
+            fvf_caps: FvfCaps(caps.FVFCaps),
+            texture_op_caps: TextureOpCaps(caps.TextureOpCaps),
+            max_texture_blend_stages: caps.MaxTextureBlendStages,
+            max_simultaneous_textures: caps.MaxSimultaneousTextures,
+            vertex_processing_caps: VertexProcessingCaps(caps.VertexProcessingCaps),
+            max_active_lights: caps.MaxActiveLights,
+            max_user_clip_planes: caps.MaxUserClipPlanes,
+            max_vertex_blend_matrices: caps.MaxVertexBlendMatrices,
+            max_vertex_blend_matrix_index: caps.MaxVertexBlendMatrixIndex,
+            max_point_size: caps.MaxPointSize,
+            max_primitive_count: caps.MaxPrimitiveCount,
+            max_vertex_index: caps.MaxVertexIndex,
+            max_streams: caps.MaxStreams,
+            max_stream_stride: caps.MaxStreamStride,
+            vertex_shader_version: ShaderVersion::from_token(caps.VertexShaderVersion),
+            max_vertex_shader_const: caps.MaxVertexShaderConst,
+            pixel_shader_version: ShaderVersion::from_token(caps.PixelShaderVersion),
+            pixel_shader_1x_max_value: caps.PixelShader1xMaxValue,
+            dev_caps2: DevCaps2(caps.DevCaps2),
+            max_npatch_tessellation_level: caps.MaxNpatchTessellationLevel,
+            master_adapter_ordinal: caps.MasterAdapterOrdinal,
+            adapter_ordinal_in_group: caps.AdapterOrdinalInGroup,
+            number_of_adapters_in_group: caps.NumberOfAdaptersInGroup,
+            decl_types: DeclTypeCaps(caps.DeclTypes),
+            num_simultaneous_rts: caps.NumSimultaneousRTs,
+            stretch_rect_filter_caps: FilterCaps(caps.StretchRectFilterCaps),
+            vs20_caps: caps.VS20Caps.into(),
+            ps20_caps: caps.PS20Caps.into(),
+            vertex_texture_filter_caps: FilterCaps(caps.VertexTextureFilterCaps),
+            max_vshader_instructions_executed: caps.MaxVShaderInstructionsExecuted,
+            max_pshader_instructions_executed: caps.MaxPShaderInstructionsExecuted,
+            max_vertex_shader30_instruction_slots: caps.MaxVertexShader30InstructionSlots,
+            max_pixel_shader30_instruction_slots: caps.MaxPixelShader30InstructionSlots,
+        })
+    }
+
+    /// Decodes a raw [`D3DCAPS9`] dump, such as one captured from another machine.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CapsError> {
+        let expected = size_of::<D3DCAPS9>();
+        if bytes.len() != expected {
+            return Err(CapsError::InvalidLength {
+                expected,
+                found: bytes.len(),
+            });
+        }
+
+        let caps = unsafe { ptr::read_unaligned(bytes.as_ptr() as *const D3DCAPS9) };
+        Self::from_raw(&caps)
+    }
+
+    /// Returns whether both vertex and pixel shaders of at least `major.minor` are supported.
+    pub fn supports_shader_model(&self, major: u8, minor: u8) -> bool {
+        self.supports_vertex_shader(major, minor) && self.supports_pixel_shader(major, minor)
+    }
+
+    /// Returns whether vertex shaders of at least `major.minor` are supported.
+    pub fn supports_vertex_shader(&self, major: u8, minor: u8) -> bool {
+        self.vertex_shader_version
+            .is_some_and(|version| version.at_least(major, minor))
+    }
+
+    /// Returns whether pixel shaders of at least `major.minor` are supported.
+    pub fn supports_pixel_shader(&self, major: u8, minor: u8) -> bool {
+        self.pixel_shader_version
+            .is_some_and(|version| version.at_least(major, minor))
+    }
+
+    /// Returns the maximum texture width and height.
+    pub fn max_texture_size(&self) -> (u32, u32) {
+        (self.max_texture_width, self.max_texture_height)
+    }
+
+    /// Returns whether textures of any size can be created without restrictions.
+    pub fn supports_non_pow2_textures(&self) -> bool {
+        !self.texture_caps.pow2()
+    }
+
+    /// Returns whether non power of two textures can be created, possibly with the restrictions
+    /// implied by [`TextureCaps::non_pow2_conditional`].
+    pub fn supports_conditional_non_pow2_textures(&self) -> bool {
+        !self.texture_caps.pow2() || self.texture_caps.non_pow2_conditional()
+    }
+
+    /// Returns whether the device supports hardware transform and lighting.
+    pub fn supports_hardware_tnl(&self) -> bool {
+        self.dev_caps.hw_transform_and_light()
+    }
+
+    /// Returns whether anisotropic minification filtering is supported.
+    pub fn supports_anisotropic_filtering(&self) -> bool {
+        self.texture_filter_caps.min_anisotropic() && self.max_anisotropy > 1
+    }
+
+    /// Returns whether the alpha channel can be blended separately from the color channels.
+    pub fn supports_separate_alpha_blend(&self) -> bool {
+        self.primitive_misc_caps.separate_alpha_blend()
+    }
+
+    /// Returns whether two sided stencil testing is supported.
+    pub fn supports_two_sided_stencil(&self) -> bool {
+        self.stencil_caps.two_sided()
+    }
+
+    /// Returns whether the device can automatically generate mipmaps.
+    pub fn supports_auto_gen_mipmap(&self) -> bool {
+        self.caps2.can_auto_gen_mipmap()
+    }
+
+    /// Returns every capability as a `(name, value)` pair, in declaration order.
+    ///
+    /// Flags are named after their field and flag, e.g. `raster_caps.anisotropy`.
+    pub fn values(&self) -> Vec<(String, CapValue)> {
+        let mut values = Values(Vec::new());
+
+        values.push("device_type", CapValue::DeviceType(self.device_type));
+        values.push("adapter_ordinal", CapValue::Uint(self.adapter_ordinal));
+        values.flags("caps", self.caps.0, DriverCaps::FLAGS);
+        values.flags("caps2", self.caps2.0, DriverCaps2::FLAGS);
+        values.flags("caps3", self.caps3.0, DriverCaps3::FLAGS);
+        values.flags(
+            "presentation_intervals",
+            self.presentation_intervals.0,
+            PresentIntervalCaps::FLAGS,
+        );
+        values.flags("cursor_caps", self.cursor_caps.0, CursorCaps::FLAGS);
+        values.flags("dev_caps", self.dev_caps.0, DevCaps::FLAGS);
+        values.flags(
+            "primitive_misc_caps",
+            self.primitive_misc_caps.0,
+            PrimitiveMiscCaps::FLAGS,
+        );
+        values.flags("raster_caps", self.raster_caps.0, RasterCaps::FLAGS);
+        values.flags("z_cmp_caps", self.z_cmp_caps.0, CompareCaps::FLAGS);
+        values.flags("src_blend_caps", self.src_blend_caps.0, BlendCaps::FLAGS);
+        values.flags("dest_blend_caps", self.dest_blend_caps.0, BlendCaps::FLAGS);
+        values.flags("alpha_cmp_caps", self.alpha_cmp_caps.0, CompareCaps::FLAGS);
+        values.flags("shade_caps", self.shade_caps.0, ShadeCaps::FLAGS);
+        values.flags("texture_caps", self.texture_caps.0, TextureCaps::FLAGS);
+        values.flags(
+            "texture_filter_caps",
+            self.texture_filter_caps.0,
+            FilterCaps::FLAGS,
+        );
+        values.flags(
+            "cube_texture_filter_caps",
+            self.cube_texture_filter_caps.0,
+            FilterCaps::FLAGS,
+        );
+        values.flags(
+            "volume_texture_filter_caps",
+            self.volume_texture_filter_caps.0,
+            FilterCaps::FLAGS,
+        );
+        values.flags(
+            "texture_address_caps",
+            self.texture_address_caps.0,
+            AddressCaps::FLAGS,
+        );
+        values.flags(
+            "volume_texture_address_caps",
+            self.volume_texture_address_caps.0,
+            AddressCaps::FLAGS,
+        );
+        values.flags("line_caps", self.line_caps.0, LineCaps::FLAGS);
+        values.push("max_texture_width", CapValue::Uint(self.max_texture_width));
+        values.push(
+            "max_texture_height",
+            CapValue::Uint(self.max_texture_height),
+        );
+        values.push("max_volume_extent", CapValue::Uint(self.max_volume_extent));
+        values.push(
+            "max_texture_repeat",
+            CapValue::Uint(self.max_texture_repeat),
+        );
+        values.push(
+            "max_texture_aspect_ratio",
+            CapValue::Uint(self.max_texture_aspect_ratio),
+        );
+        values.push("max_anisotropy", CapValue::Uint(self.max_anisotropy));
+        values.push("max_vertex_w", CapValue::Float(self.max_vertex_w));
+        values.push("guard_band_left", CapValue::Float(self.guard_band_left));
+        values.push("guard_band_top", CapValue::Float(self.guard_band_top));
+        values.push("guard_band_right", CapValue::Float(self.guard_band_right));
+        values.push("guard_band_bottom", CapValue::Float(self.guard_band_bottom));
+        values.push("extents_adjust", CapValue::Float(self.extents_adjust));
+        values.flags("stencil_caps", self.stencil_caps.0, StencilCaps::FLAGS);
+        values.push(
+            "fvf_caps.texture_coordinate_count",
+            CapValue::Uint(self.fvf_caps.texture_coordinate_count() as u32),
+        );
+        values.flags("fvf_caps", self.fvf_caps.0, FvfCaps::FLAGS);
+        values.flags(
+            "texture_op_caps",
+            self.texture_op_caps.0,
+            TextureOpCaps::FLAGS,
+        );
+        values.push(
+            "max_texture_blend_stages",
+            CapValue::Uint(self.max_texture_blend_stages),
+        );
+        values.push(
+            "max_simultaneous_textures",
+            CapValue::Uint(self.max_simultaneous_textures),
+        );
+        values.flags(
+            "vertex_processing_caps",
+            self.vertex_processing_caps.0,
+            VertexProcessingCaps::FLAGS,
+        );
+        values.push("max_active_lights", CapValue::Uint(self.max_active_lights));
+        values.push(
+            "max_user_clip_planes",
+            CapValue::Uint(self.max_user_clip_planes),
+        );
+        values.push(
+            "max_vertex_blend_matrices",
+            CapValue::Uint(self.max_vertex_blend_matrices),
+        );
+        values.push(
+            "max_vertex_blend_matrix_index",
+            CapValue::Uint(self.max_vertex_blend_matrix_index),
+        );
+        values.push("max_point_size", CapValue::Float(self.max_point_size));
+        values.push(
+            "max_primitive_count",
+            CapValue::Uint(self.max_primitive_count),
+        );
+        values.push("max_vertex_index", CapValue::Uint(self.max_vertex_index));
+        values.push("max_streams", CapValue::Uint(self.max_streams));
+        values.push("max_stream_stride", CapValue::Uint(self.max_stream_stride));
+        values.push(
+            "vertex_shader_version",
+            CapValue::ShaderVersion(self.vertex_shader_version),
+        );
+        values.push(
+            "max_vertex_shader_const",
+            CapValue::Uint(self.max_vertex_shader_const),
+        );
+        values.push(
+            "pixel_shader_version",
+            CapValue::ShaderVersion(self.pixel_shader_version),
+        );
+        values.push(
+            "pixel_shader_1x_max_value",
+            CapValue::Float(self.pixel_shader_1x_max_value),
+        );
+        values.flags("dev_caps2", self.dev_caps2.0, DevCaps2::FLAGS);
+        values.push(
+            "max_npatch_tessellation_level",
+            CapValue::Float(self.max_npatch_tessellation_level),
+        );
+        values.push(
+            "master_adapter_ordinal",
+            CapValue::Uint(self.master_adapter_ordinal),
+        );
+        values.push(
+            "adapter_ordinal_in_group",
+            CapValue::Uint(self.adapter_ordinal_in_group),
+        );
+        values.push(
+            "number_of_adapters_in_group",
+            CapValue::Uint(self.number_of_adapters_in_group),
+        );
+        values.flags("decl_types", self.decl_types.0, DeclTypeCaps::FLAGS);
+        values.push(
+            "num_simultaneous_rts",
+            CapValue::Uint(self.num_simultaneous_rts),
+        );
+        values.flags(
+            "stretch_rect_filter_caps",
+            self.stretch_rect_filter_caps.0,
+            FilterCaps::FLAGS,
+        );
+        values.flags(
+            "vs20_caps.caps",
+            self.vs20_caps.caps.0,
+            VertexShader2Caps::FLAGS,
+        );
+        values.push(
+            "vs20_caps.dynamic_flow_control_depth",
+            CapValue::Int(self.vs20_caps.dynamic_flow_control_depth),
+        );
+        values.push(
+            "vs20_caps.num_temps",
+            CapValue::Int(self.vs20_caps.num_temps),
+        );
+        values.push(
+            "vs20_caps.static_flow_control_depth",
+            CapValue::Int(self.vs20_caps.static_flow_control_depth),
+        );
+        values.flags(
+            "ps20_caps.caps",
+            self.ps20_caps.caps.0,
+            PixelShader2Caps::FLAGS,
+        );
+        values.push(
+            "ps20_caps.dynamic_flow_control_depth",
+            CapValue::Int(self.ps20_caps.dynamic_flow_control_depth),
+        );
+        values.push(
+            "ps20_caps.num_temps",
+            CapValue::Int(self.ps20_caps.num_temps),
+        );
+        values.push(
+            "ps20_caps.static_flow_control_depth",
+            CapValue::Int(self.ps20_caps.static_flow_control_depth),
+        );
+        values.push(
+            "ps20_caps.num_instruction_slots",
+            CapValue::Int(self.ps20_caps.num_instruction_slots),
+        );
+        values.flags(
+            "vertex_texture_filter_caps",
+            self.vertex_texture_filter_caps.0,
+            FilterCaps::FLAGS,
+        );
+        values.push(
+            "max_vshader_instructions_executed",
+            CapValue::Uint(self.max_vshader_instructions_executed),
+        );
+        values.push(
+            "max_pshader_instructions_executed",
+            CapValue::Uint(self.max_pshader_instructions_executed),
+        );
+        values.push(
+            "max_vertex_shader30_instruction_slots",
+            CapValue::Uint(self.max_vertex_shader30_instruction_slots),
+        );
+        values.push(
+            "max_pixel_shader30_instruction_slots",
+            CapValue::Uint(self.max_pixel_shader30_instruction_slots),
+        );
+
+        values.0
+    }
+
+    /// Returns the value of the capability with the given name, as used by [`Caps::values`].
+    pub fn get(&self, name: &str) -> Option<CapValue> {
+        self.values()
+            .into_iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns every capability whose value differs between `self` and `other`.
+    pub fn diff(&self, other: &Caps) -> Vec<CapsDifference> {
+        self.values()
+            .into_iter()
+            .zip(other.values())
+            .filter(|((_, left), (_, right))| !left.is_same(right))
+            .map(|((name, left), (_, right))| CapsDifference { name, left, right })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{mem, slice};
+
+    use super::*;
+
+    fn raw_caps(f: impl FnOnce(&mut D3DCAPS9)) -> D3DCAPS9 {
+        let mut caps: D3DCAPS9 = unsafe { mem::zeroed() };
+        caps.DeviceType = DeviceType::Hal as u32;
+        f(&mut caps);
+        caps
+    }
+
+    fn to_bytes(caps: &D3DCAPS9) -> Vec<u8> {
+        let ptr = caps as *const D3DCAPS9 as *const u8;
+        unsafe { slice::from_raw_parts(ptr, size_of::<D3DCAPS9>()) }.to_vec()
+    }
+
+    #[test]
+    fn decodes_capability_queries() {
+        let caps = Caps::from_bytes(&to_bytes(&raw_caps(|caps| {
+            caps.VertexShaderVersion = 0xFFFE0300;
+            caps.PixelShaderVersion = 0xFFFF0200;
+            caps.MaxTextureWidth = 4096;
+            caps.MaxTextureHeight = 2048;
+            caps.TextureCaps = 0x2 | 0x100; // POW2 | NONPOW2CONDITIONAL
+            caps.DevCaps = 0x10000; // HWTRANSFORMANDLIGHT
+            caps.FVFCaps = 0x100000 | 8; // PSIZE and eight texture coordinates
+            caps.StencilCaps = 0x100; // TWOSIDED
+            caps.MaxAnisotropy = 16;
+            caps.TextureFilterCaps = 0x400; // MINFANISOTROPIC
+            caps.PS20Caps.NumInstructionSlots = 512;
+        })))
+        .unwrap();
+
+        assert_eq!(caps.device_type, DeviceType::Hal);
+        assert!(caps.supports_vertex_shader(3, 0));
+        assert!(!caps.supports_pixel_shader(3, 0));
+        assert!(caps.supports_shader_model(2, 0));
+        assert!(!caps.supports_shader_model(3, 0));
+        assert_eq!(caps.max_texture_size(), (4096, 2048));
+        assert!(!caps.supports_non_pow2_textures());
+        assert!(caps.supports_conditional_non_pow2_textures());
+        assert!(caps.supports_hardware_tnl());
+        assert_eq!(caps.fvf_caps.texture_coordinate_count(), 8);
+        assert!(caps.fvf_caps.psize());
+        assert!(caps.supports_two_sided_stencil());
+        assert!(caps.supports_anisotropic_filtering());
+        assert_eq!(caps.ps20_caps.num_instruction_slots, 512);
+    }
+
+    #[test]
+    fn reports_missing_shader_support() {
+        let caps = Caps::from_raw(&raw_caps(|_| {})).unwrap();
+
+        assert_eq!(caps.vertex_shader_version, None);
+        assert!(!caps.supports_shader_model(1, 1));
+    }
+
+    #[test]
+    fn diffs_changed_values_only() {
+        let left = Caps::from_raw(&raw_caps(|caps| {
+            caps.MaxTextureWidth = 4096;
+            caps.MaxPointSize = f32::NAN;
+            caps.RasterCaps = 1 << 24;
+        }))
+        .unwrap();
+        assert!(left.diff(&left).is_empty());
+
+        let right = Caps::from_raw(&raw_caps(|caps| {
+            caps.MaxTextureWidth = 2048;
+            caps.MaxPointSize = f32::NAN;
+        }))
+        .unwrap();
+        let differences = left.diff(&right);
+        assert_eq!(
+            differences,
+            [
+                CapsDifference {
+                    name: "raster_caps.scissor_test".to_string(),
+                    left: CapValue::Bool(true),
+                    right: CapValue::Bool(false),
+                },
+                CapsDifference {
+                    name: "max_texture_width".to_string(),
+                    left: CapValue::Uint(4096),
+                    right: CapValue::Uint(2048),
+                },
+            ]
+        );
+        assert_eq!(
+            differences[1].to_string(),
+            "max_texture_width: 4096 -> 2048"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_dumps() {
+        assert!(matches!(
+            Caps::from_bytes(&[0; 4]),
+            Err(CapsError::InvalidLength { found: 4, .. })
+        ));
+        assert!(matches!(
+            Caps::from_raw(&raw_caps(|caps| caps.DeviceType = 9)),
+            Err(CapsError::UnknownDeviceType(9))
+        ));
+    }
+}
@@ -0,0 +1,969 @@
+//! Provides negotiation of a valid device configuration from a set of desired settings.
+//!
+//! [`negotiate`] walks the same checks an application would perform by hand before calling
+//! [`Context::create_device`], falling back to weaker settings where needed. Every fallback is
+//! recorded as a [`Downgrade`] on the resulting [`DeviceConfig`].
+//!
+//! The checks go through the [`ContextBackend`] trait, so the decision logic can also run against
+//! something other than a real [`Context`].
+//!
+//! # Usage
+//!
+//! ```rs
+//! let settings = DeviceSettings {
+//!     windowed: false,
+//!     width: 1920,
+//!     height: 1080,
+//!     multi_sample: MultiSampleType::Some(4),
+//!     ..Default::default()
+//! };
+//!
+//! let config = negotiate(&context, &settings)?;
+//! for downgrade in &config.downgrades {
+//!     println!("{downgrade}");
+//! }
+//!
+//! let device = config.create_device(&context, window)?;
+//! ```
+
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+};
+
+use crate::{
+    error::{WindowsError, WindowsResult},
+    std::{
+        caps::{Caps, CapsError},
+        types::{
+            Adapter, BehaviorFlags, DeviceType, DisplayMode, Format, MultiSampleType,
+            PresentationParameters, ResourceType, SwapEffect, Usage,
+        },
+    },
+    sys::d3d9caps::{
+        D3DPRESENT_INTERVAL_DEFAULT, D3DPRESENT_INTERVAL_IMMEDIATE, D3DPRESENT_INTERVAL_ONE,
+    },
+};
+#[cfg(windows)]
+use crate::{
+    std::interfaces::{Context, Device},
+    sys::HWND,
+};
+
+/// Back buffer formats tried, in order, when the requested one is unsupported.
+const BACK_BUFFER_FORMATS: [Format; 4] = [
+    Format::X8R8G8B8,
+    Format::A8R8G8B8,
+    Format::R5G6B5,
+    Format::X1R5G5B5,
+];
+
+/// Depth stencil formats tried, in order, when the requested one is unsupported.
+const DEPTH_STENCIL_FORMATS: [Format; 4] = [Format::D24S8, Format::D24X8, Format::D32, Format::D16];
+
+/// The set of [`Context`] queries used by [`negotiate`].
+///
+/// Every method mirrors the [`Context`] method of the same name.
+pub trait ContextBackend {
+    fn check_depth_stencil_match(
+        &self,
+        adapter: Adapter,
+        device_type: DeviceType,
+        adapter_format: Format,
+        render_target_format: Format,
+        depth_stencil_format: Format,
+    ) -> WindowsResult<()>;
+
+    fn check_device_format(
+        &self,
+        adapter: Adapter,
+        device_type: DeviceType,
+        adapter_format: Format,
+        usage: Usage,
+        resource_type: ResourceType,
+        check_format: Format,
+    ) -> WindowsResult<()>;
+
+    fn check_device_multi_sample_type(
+        &self,
+        adapter: Adapter,
+        device_type: DeviceType,
+        surface_format: Format,
+        windowed: bool,
+        multi_sample_type: MultiSampleType,
+        quality_levels: &mut u32,
+    ) -> WindowsResult<()>;
+
+    fn check_device_type(
+        &self,
+        adapter: Adapter,
+        device_type: DeviceType,
+        adapter_format: Format,
+        back_buffer_format: Format,
+        windowed: bool,
+    ) -> WindowsResult<()>;
+
+    fn display_modes(&self, adapter: Adapter, format: Format) -> WindowsResult<Vec<DisplayMode>>;
+
+    fn get_adapter_display_mode(&self, adapter: Adapter) -> WindowsResult<DisplayMode>;
+
+    fn get_device_caps(&self, adapter: Adapter, device_type: DeviceType)
+        -> Result<Caps, CapsError>;
+}
+
+#[cfg(windows)]
+impl ContextBackend for Context {
+    fn check_depth_stencil_match(
+        &self,
+        adapter: Adapter,
+        device_type: DeviceType,
+        adapter_format: Format,
+        render_target_format: Format,
+        depth_stencil_format: Format,
+    ) -> WindowsResult<()> {
+        Context::check_depth_stencil_match(
+            self,
+            adapter,
+            device_type,
+            adapter_format,
+            render_target_format,
+            depth_stencil_format,
+        )
+    }
+
+    fn check_device_format(
+        &self,
+        adapter: Adapter,
+        device_type: DeviceType,
+        adapter_format: Format,
+        usage: Usage,
+        resource_type: ResourceType,
+        check_format: Format,
+    ) -> WindowsResult<()> {
+        Context::check_device_format(
+            self,
+            adapter,
+            device_type,
+            adapter_format,
+            usage,
+            resource_type,
+            check_format,
+        )
+    }
+
+    fn check_device_multi_sample_type(
+        &self,
+        adapter: Adapter,
+        device_type: DeviceType,
+        surface_format: Format,
+        windowed: bool,
+        multi_sample_type: MultiSampleType,
+        quality_levels: &mut u32,
+    ) -> WindowsResult<()> {
+        Context::check_device_multi_sample_type(
+            self,
+            adapter,
+            device_type,
+            surface_format,
+            windowed,
+            multi_sample_type,
+            quality_levels,
+        )
+    }
+
+    fn check_device_type(
+        &self,
+        adapter: Adapter,
+        device_type: DeviceType,
+        adapter_format: Format,
+        back_buffer_format: Format,
+        windowed: bool,
+    ) -> WindowsResult<()> {
+        Context::check_device_type(
+            self,
+            adapter,
+            device_type,
+            adapter_format,
+            back_buffer_format,
+            windowed,
+        )
+    }
+
+    fn display_modes(&self, adapter: Adapter, format: Format) -> WindowsResult<Vec<DisplayMode>> {
+        Context::display_modes(self, adapter, format)
+    }
+
+    fn get_adapter_display_mode(&self, adapter: Adapter) -> WindowsResult<DisplayMode> {
+        Context::get_adapter_display_mode(self, adapter)
+    }
+
+    fn get_device_caps(
+        &self,
+        adapter: Adapter,
+        device_type: DeviceType,
+    ) -> Result<Caps, CapsError> {
+        Context::get_device_caps(self, adapter, device_type)
+    }
+}
+
+/// Represents an error which occurred while negotiating a device configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Windows Error: {from}")]
+    Windows {
+        #[from]
+        from: WindowsError,
+    },
+
+    #[error("failed to query device caps: {0}")]
+    Caps(#[from] CapsError),
+
+    #[error("no supported back buffer format for {device_type:?}")]
+    NoBackBufferFormat { device_type: DeviceType },
+
+    #[error("no fullscreen display mode for {format:?}")]
+    NoDisplayMode { format: Format },
+}
+
+/// Represents how vertices are processed by a device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VertexProcessing {
+    Hardware,
+    Mixed,
+    Software,
+}
+
+/// Represents the settings an application would like its device to have.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeviceSettings {
+    pub adapter: Adapter,
+    pub device_type: DeviceType,
+    pub windowed: bool,
+    pub width: u32,
+    pub height: u32,
+    /// The fullscreen refresh rate, or [`None`] for the highest available.
+    pub refresh_rate: Option<u32>,
+    pub back_buffer_format: Format,
+    /// The format of the automatic depth stencil buffer, or [`None`] to not create one.
+    pub depth_stencil_format: Option<Format>,
+    pub multi_sample: MultiSampleType,
+    pub vsync: bool,
+    pub vertex_processing: VertexProcessing,
+    /// The vertex shader version the application needs, used to decide between hardware and
+    /// mixed vertex processing.
+    pub vertex_shader_version: Option<(u8, u8)>,
+}
+
+impl Default for DeviceSettings {
+    fn default() -> Self {
+        Self {
+            adapter: Adapter::default(),
+            device_type: DeviceType::Hal,
+            windowed: true,
+            width: 800,
+            height: 600,
+            refresh_rate: None,
+            back_buffer_format: Format::X8R8G8B8,
+            depth_stencil_format: Some(Format::D24S8),
+            multi_sample: MultiSampleType::None,
+            vsync: true,
+            vertex_processing: VertexProcessing::Hardware,
+            vertex_shader_version: None,
+        }
+    }
+}
+
+/// Represents a setting which could not be used as requested.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Downgrade {
+    BackBufferFormat {
+        requested: Format,
+        chosen: Format,
+    },
+    DepthStencilFormat {
+        requested: Format,
+        chosen: Option<Format>,
+    },
+    MultiSample {
+        requested: MultiSampleType,
+        chosen: MultiSampleType,
+    },
+    VertexProcessing {
+        requested: VertexProcessing,
+        chosen: VertexProcessing,
+    },
+    DisplayMode {
+        width: u32,
+        height: u32,
+        refresh_rate: Option<u32>,
+        chosen: DisplayMode,
+    },
+    /// Vsync was requested but the device cannot wait for the vertical retrace, or the reverse.
+    Vsync {
+        requested: bool,
+    },
+}
+
+impl Display for Downgrade {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BackBufferFormat { requested, chosen } => {
+                write!(f, "back buffer format {requested:?} -> {chosen:?}")
+            }
+            Self::DepthStencilFormat { requested, chosen } => {
+                write!(f, "depth stencil format {requested:?} -> {chosen:?}")
+            }
+            Self::MultiSample { requested, chosen } => {
+                write!(f, "multi-sampling {requested:?} -> {chosen:?}")
+            }
+            Self::VertexProcessing { requested, chosen } => {
+                write!(f, "vertex processing {requested:?} -> {chosen:?}")
+            }
+            Self::DisplayMode {
+                width,
+                height,
+                refresh_rate,
+                chosen,
+            } => write!(
+                f,
+                "display mode {width}x{height}@{} -> {}x{}@{}",
+                refresh_rate.map_or("any".to_string(), |rate| rate.to_string()),
+                chosen.width,
+                chosen.height,
+                chosen.refresh_rate
+            ),
+            Self::Vsync { requested } => write!(f, "vsync {requested} -> {}", !requested),
+        }
+    }
+}
+
+/// Represents a device configuration which passed every check in [`negotiate`].
+#[derive(Clone, Debug)]
+pub struct DeviceConfig {
+    pub adapter: Adapter,
+    pub device_type: DeviceType,
+    pub adapter_format: Format,
+    pub vertex_processing: VertexProcessing,
+    pub behavior_flags: BehaviorFlags,
+    pub presentation_parameters: PresentationParameters,
+    pub downgrades: Vec<Downgrade>,
+}
+
+#[cfg(windows)]
+impl DeviceConfig {
+    /// Creates a device for `window` using this configuration.
+    pub fn create_device(&self, context: &Context, window: HWND) -> WindowsResult<Device> {
+        let mut presentation_parameters = self.presentation_parameters.clone();
+        presentation_parameters.device_window = window;
+
+        context.create_device(
+            self.adapter,
+            self.device_type,
+            window,
+            self.behavior_flags,
+            &mut presentation_parameters,
+        )
+    }
+}
+
+/// Negotiates the closest valid device configuration to `settings`.
+///
+/// Unsupported settings fall back in the following ways:
+/// - The back buffer format falls back to the first supported 16 or 32-bit format.
+/// - The depth stencil format falls back to the first supported and matching format, or none.
+/// - Multi-sampling drops sample counts until one is supported, down to none.
+/// - Vertex processing moves from hardware to mixed to software, depending on the device's
+///   transform and lighting support and vertex shader version.
+/// - Fullscreen resolutions are replaced by the best matching display mode.
+pub fn negotiate<C: ContextBackend>(
+    context: &C,
+    settings: &DeviceSettings,
+) -> Result<DeviceConfig, ConfigError> {
+    let DeviceSettings {
+        adapter,
+        device_type,
+        windowed,
+        ..
+    } = *settings;
+    let mut downgrades = Vec::new();
+
+    let desktop_format = context.get_adapter_display_mode(adapter)?.format;
+    let adapter_format_for = |back_buffer_format: Format| {
+        if windowed {
+            desktop_format
+        } else {
+            display_format(back_buffer_format)
+        }
+    };
+
+    let back_buffer_format = std::iter::once(settings.back_buffer_format)
+        .chain(BACK_BUFFER_FORMATS)
+        .find(|&format| {
+            context
+                .check_device_type(
+                    adapter,
+                    device_type,
+                    adapter_format_for(format),
+                    format,
+                    windowed,
+                )
+                .is_ok()
+        })
+        .ok_or(ConfigError::NoBackBufferFormat { device_type })?;
+    let adapter_format = adapter_format_for(back_buffer_format);
+
+    if back_buffer_format != settings.back_buffer_format {
+        downgrades.push(Downgrade::BackBufferFormat {
+            requested: settings.back_buffer_format,
+            chosen: back_buffer_format,
+        });
+    }
+
+    let depth_stencil_format = settings.depth_stencil_format.and_then(|requested| {
+        let chosen = std::iter::once(requested)
+            .chain(DEPTH_STENCIL_FORMATS)
+            .find(|&format| {
+                let mut usage = Usage::default();
+                usage.set_depth_stencil(true);
+
+                context
+                    .check_device_format(
+                        adapter,
+                        device_type,
+                        adapter_format,
+                        usage,
+                        ResourceType::Surface,
+                        format,
+                    )
+                    .is_ok()
+                    && context
+                        .check_depth_stencil_match(
+                            adapter,
+                            device_type,
+                            adapter_format,
+                            back_buffer_format,
+                            format,
+                        )
+                        .is_ok()
+            });
+
+        if chosen != Some(requested) {
+            downgrades.push(Downgrade::DepthStencilFormat { requested, chosen });
+        }
+
+        chosen
+    });
+
+    let (multi_sample, multi_sample_quality) =
+        negotiate_multi_sample(context, settings, back_buffer_format, depth_stencil_format);
+    if multi_sample != settings.multi_sample {
+        downgrades.push(Downgrade::MultiSample {
+            requested: settings.multi_sample.clone(),
+            chosen: multi_sample.clone(),
+        });
+    }
+
+    let caps = context.get_device_caps(adapter, device_type)?;
+
+    let vertex_processing = negotiate_vertex_processing(&caps, settings);
+    if vertex_processing != settings.vertex_processing {
+        downgrades.push(Downgrade::VertexProcessing {
+            requested: settings.vertex_processing,
+            chosen: vertex_processing,
+        });
+    }
+
+    let mut behavior_flags = BehaviorFlags::default();
+    match vertex_processing {
+        VertexProcessing::Hardware => behavior_flags.set_hardware_vertex_processing(true),
+        VertexProcessing::Mixed => behavior_flags.set_mixed_vertex_processing(true),
+        VertexProcessing::Software => behavior_flags.set_software_vertex_processing(true),
+    }
+
+    let presentation_interval = match settings.vsync {
+        true if caps.presentation_intervals.one() => D3DPRESENT_INTERVAL_ONE,
+        false if caps.presentation_intervals.immediate() => D3DPRESENT_INTERVAL_IMMEDIATE,
+        requested => {
+            downgrades.push(Downgrade::Vsync { requested });
+
+            if requested {
+                D3DPRESENT_INTERVAL_IMMEDIATE
+            } else {
+                D3DPRESENT_INTERVAL_DEFAULT
+            }
+        }
+    };
+
+    let (width, height, refresh_rate) = if windowed {
+        (settings.width, settings.height, 0)
+    } else {
+        let modes = context.display_modes(adapter, adapter_format)?;
+        let mode = DisplayMode::best_match(
+            &modes,
+            settings.width,
+            settings.height,
+            settings.refresh_rate,
+        )
+        .ok_or(ConfigError::NoDisplayMode {
+            format: adapter_format,
+        })?;
+
+        let refresh_rate_matches = settings
+            .refresh_rate
+            .is_none_or(|rate| rate == mode.refresh_rate);
+        if mode.width != settings.width || mode.height != settings.height || !refresh_rate_matches {
+            downgrades.push(Downgrade::DisplayMode {
+                width: settings.width,
+                height: settings.height,
+                refresh_rate: settings.refresh_rate,
+                chosen: mode.clone(),
+            });
+        }
+
+        (mode.width, mode.height, mode.refresh_rate)
+    };
+
+    let presentation_parameters = PresentationParameters {
+        back_buffer_width: width,
+        back_buffer_height: height,
+        back_buffer_format,
+        back_buffer_count: 1,
+        multi_sample_type: multi_sample,
+        multi_sample_quality,
+        swap_effect: SwapEffect::Discard,
+        windowed,
+        enable_auto_depth_stencil: depth_stencil_format.is_some(),
+        auto_depth_stencil_format: depth_stencil_format.unwrap_or(Format::Unknown),
+        refresh_rate,
+        presentation_interval,
+        ..Default::default()
+    };
+
+    Ok(DeviceConfig {
+        adapter,
+        device_type,
+        adapter_format,
+        vertex_processing,
+        behavior_flags,
+        presentation_parameters,
+        downgrades,
+    })
+}
+
+/// Returns the display format a fullscreen back buffer of `format` is presented with.
+fn display_format(format: Format) -> Format {
+    match format {
+        Format::A8R8G8B8 => Format::X8R8G8B8,
+        Format::A1R5G5B5 => Format::X1R5G5B5,
+        format => format,
+    }
+}
+
+/// Returns the highest supported multi-sample type not above the requested one, along with its
+/// highest quality level.
+fn negotiate_multi_sample<C: ContextBackend>(
+    context: &C,
+    settings: &DeviceSettings,
+    back_buffer_format: Format,
+    depth_stencil_format: Option<Format>,
+) -> (MultiSampleType, u32) {
+    let candidates = match settings.multi_sample {
+        MultiSampleType::None => vec![],
+        MultiSampleType::NonMaskable => vec![MultiSampleType::NonMaskable],
+        MultiSampleType::Some(samples) => (2..=samples).rev().map(MultiSampleType::Some).collect(),
+    };
+
+    let supported = |multi_sample: &MultiSampleType, format: Format| {
+        let mut quality_levels = 0;
+        context
+            .check_device_multi_sample_type(
+                settings.adapter,
+                settings.device_type,
+                format,
+                settings.windowed,
+                multi_sample.clone(),
+                &mut quality_levels,
+            )
+            .ok()
+            .map(|_| quality_levels)
+    };
+
+    candidates
+        .into_iter()
+        .find_map(|multi_sample| {
+            // The render target and depth stencil must agree on the quality level, so only the
+            // levels both support are usable.
+            let mut quality_levels = supported(&multi_sample, back_buffer_format)?;
+            if let Some(format) = depth_stencil_format {
+                quality_levels = quality_levels.min(supported(&multi_sample, format)?);
+            }
+
+            Some((multi_sample, quality_levels.saturating_sub(1)))
+        })
+        .unwrap_or((MultiSampleType::None, 0))
+}
+
+/// Returns the strongest vertex processing mode the device supports, not above the requested one.
+fn negotiate_vertex_processing(caps: &Caps, settings: &DeviceSettings) -> VertexProcessing {
+    if !caps.supports_hardware_tnl() {
+        return VertexProcessing::Software;
+    }
+
+    let shaders_supported = settings
+        .vertex_shader_version
+        .is_none_or(|(major, minor)| caps.supports_vertex_shader(major, minor));
+
+    match settings.vertex_processing {
+        VertexProcessing::Hardware if !shaders_supported => VertexProcessing::Mixed,
+        vertex_processing => vertex_processing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, mem};
+
+    use super::*;
+    use crate::{error::ErrorCode, sys::d3d9caps::D3DCAPS9};
+
+    /// Answers queries from fixed lists of supported formats and sample counts, logging the
+    /// formats it is asked about.
+    struct MockContext {
+        back_buffer_formats: Vec<Format>,
+        depth_stencil_formats: Vec<Format>,
+        samples: Vec<u32>,
+        /// The quality levels reported per format, where unlisted formats report `3`.
+        quality_levels: Vec<(Format, u32)>,
+        hardware_tnl: bool,
+        vertex_shader_version: u32,
+        presentation_intervals: u32,
+        display_modes: Vec<DisplayMode>,
+        log: RefCell<Vec<String>>,
+    }
+
+    impl MockContext {
+        fn new() -> Self {
+            Self {
+                back_buffer_formats: vec![Format::X8R8G8B8, Format::A8R8G8B8],
+                depth_stencil_formats: vec![Format::D24S8, Format::D16],
+                samples: vec![2, 4, 8],
+                quality_levels: vec![],
+                hardware_tnl: true,
+                vertex_shader_version: 0xFFFE0300,
+                presentation_intervals: D3DPRESENT_INTERVAL_ONE | D3DPRESENT_INTERVAL_IMMEDIATE,
+                display_modes: vec![
+                    mode(1280, 720, 60),
+                    mode(1920, 1080, 60),
+                    mode(1920, 1080, 144),
+                ],
+                log: RefCell::default(),
+            }
+        }
+
+        fn supported(&self, supported: bool, query: String) -> WindowsResult<()> {
+            self.log.borrow_mut().push(query);
+
+            match supported {
+                true => Ok(()),
+                false => Err(ErrorCode::NotAvailable.into()),
+            }
+        }
+    }
+
+    impl ContextBackend for MockContext {
+        fn check_depth_stencil_match(
+            &self,
+            _adapter: Adapter,
+            _device_type: DeviceType,
+            _adapter_format: Format,
+            _render_target_format: Format,
+            _depth_stencil_format: Format,
+        ) -> WindowsResult<()> {
+            Ok(())
+        }
+
+        fn check_device_format(
+            &self,
+            _adapter: Adapter,
+            _device_type: DeviceType,
+            _adapter_format: Format,
+            usage: Usage,
+            _resource_type: ResourceType,
+            check_format: Format,
+        ) -> WindowsResult<()> {
+            assert!(usage.depth_stencil());
+            self.supported(
+                self.depth_stencil_formats.contains(&check_format),
+                format!("depth stencil {check_format:?}"),
+            )
+        }
+
+        fn check_device_multi_sample_type(
+            &self,
+            _adapter: Adapter,
+            _device_type: DeviceType,
+            surface_format: Format,
+            _windowed: bool,
+            multi_sample_type: MultiSampleType,
+            quality_levels: &mut u32,
+        ) -> WindowsResult<()> {
+            *quality_levels = self
+                .quality_levels
+                .iter()
+                .find(|(format, _)| *format == surface_format)
+                .map_or(3, |(_, levels)| *levels);
+            self.supported(
+                self.samples.contains(&multi_sample_type.clone().into()),
+                format!("multi-sample {multi_sample_type:?}"),
+            )
+        }
+
+        fn check_device_type(
+            &self,
+            _adapter: Adapter,
+            _device_type: DeviceType,
+            adapter_format: Format,
+            back_buffer_format: Format,
+            _windowed: bool,
+        ) -> WindowsResult<()> {
+            self.supported(
+                self.back_buffer_formats.contains(&back_buffer_format),
+                format!("back buffer {adapter_format:?} {back_buffer_format:?}"),
+            )
+        }
+
+        fn display_modes(
+            &self,
+            _adapter: Adapter,
+            _format: Format,
+        ) -> WindowsResult<Vec<DisplayMode>> {
+            Ok(self.display_modes.clone())
+        }
+
+        fn get_adapter_display_mode(&self, _adapter: Adapter) -> WindowsResult<DisplayMode> {
+            Ok(mode(1920, 1080, 60))
+        }
+
+        fn get_device_caps(
+            &self,
+            _adapter: Adapter,
+            _device_type: DeviceType,
+        ) -> Result<Caps, CapsError> {
+            let mut caps: D3DCAPS9 = unsafe { mem::zeroed() };
+            caps.DeviceType = DeviceType::Hal as u32;
+            caps.DevCaps = if self.hardware_tnl { 0x10000 } else { 0 };
+            caps.VertexShaderVersion = self.vertex_shader_version;
+            caps.PresentationIntervals = self.presentation_intervals;
+
+            Caps::from_raw(&caps)
+        }
+    }
+
+    fn mode(width: u32, height: u32, refresh_rate: u32) -> DisplayMode {
+        DisplayMode {
+            width,
+            height,
+            refresh_rate,
+            format: Format::X8R8G8B8,
+        }
+    }
+
+    fn fullscreen() -> DeviceSettings {
+        DeviceSettings {
+            windowed: false,
+            width: 1920,
+            height: 1200,
+            refresh_rate: Some(120),
+            back_buffer_format: Format::A8R8G8B8,
+            multi_sample: MultiSampleType::Some(8),
+            vertex_shader_version: Some((3, 0)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn keeps_supported_settings() {
+        let settings = DeviceSettings {
+            multi_sample: MultiSampleType::Some(4),
+            ..Default::default()
+        };
+        let config = negotiate(&MockContext::new(), &settings).unwrap();
+        assert!(config.downgrades.is_empty(), "{:?}", config.downgrades);
+        assert!(config.behavior_flags.hardware_vertex_processing());
+
+        let presentation_parameters = &config.presentation_parameters;
+        assert_eq!(
+            presentation_parameters.multi_sample_type,
+            MultiSampleType::Some(4)
+        );
+        assert_eq!(presentation_parameters.multi_sample_quality, 2);
+        assert_eq!(
+            presentation_parameters.presentation_interval,
+            D3DPRESENT_INTERVAL_ONE
+        );
+        assert!(presentation_parameters.enable_auto_depth_stencil);
+        assert!(presentation_parameters.windowed);
+        assert_eq!(
+            (
+                presentation_parameters.back_buffer_width,
+                presentation_parameters.back_buffer_height,
+                presentation_parameters.refresh_rate,
+            ),
+            (800, 600, 0)
+        );
+    }
+
+    #[test]
+    fn uses_the_quality_levels_supported_by_both_surfaces() {
+        let settings = DeviceSettings {
+            multi_sample: MultiSampleType::Some(4),
+            ..Default::default()
+        };
+
+        let mut context = MockContext::new();
+        context.quality_levels = vec![(settings.back_buffer_format, 4), (Format::D24S8, 2)];
+        let config = negotiate(&context, &settings).unwrap();
+        assert_eq!(config.presentation_parameters.multi_sample_quality, 1);
+
+        context.quality_levels = vec![(settings.back_buffer_format, 1), (Format::D24S8, 5)];
+        let config = negotiate(&context, &settings).unwrap();
+        assert_eq!(config.presentation_parameters.multi_sample_quality, 0);
+    }
+
+    #[test]
+    fn falls_back_in_order() {
+        let context = MockContext {
+            back_buffer_formats: vec![Format::R5G6B5],
+            depth_stencil_formats: vec![Format::D16],
+            samples: vec![2],
+            vertex_shader_version: 0xFFFE0200,
+            presentation_intervals: D3DPRESENT_INTERVAL_IMMEDIATE,
+            ..MockContext::new()
+        };
+        let config = negotiate(&context, &fullscreen()).unwrap();
+
+        let downgrades = config
+            .downgrades
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            downgrades,
+            [
+                "back buffer format A8R8G8B8 -> R5G6B5",
+                "depth stencil format D24S8 -> Some(D16)",
+                "multi-sampling Some(8) -> Some(2)",
+                "vertex processing Hardware -> Mixed",
+                "vsync true -> false",
+                "display mode 1920x1200@120 -> 1920x1080@144",
+            ]
+        );
+        assert!(config.behavior_flags.mixed_vertex_processing());
+        assert_eq!(config.adapter_format, Format::R5G6B5);
+
+        // The requested format is tried first, then each fallback once in order. Fullscreen
+        // back buffers are checked against their display format.
+        let log = context.log.borrow();
+        let back_buffers = log
+            .iter()
+            .filter(|query| query.starts_with("back buffer"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            back_buffers,
+            [
+                "back buffer X8R8G8B8 A8R8G8B8",
+                "back buffer X8R8G8B8 X8R8G8B8",
+                "back buffer X8R8G8B8 A8R8G8B8",
+                "back buffer R5G6B5 R5G6B5",
+            ]
+        );
+        let depth_stencils = log
+            .iter()
+            .filter(|query| query.starts_with("depth stencil"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            depth_stencils,
+            [
+                "depth stencil D24S8",
+                "depth stencil D24S8",
+                "depth stencil D24X8",
+                "depth stencil D32",
+                "depth stencil D16",
+            ]
+        );
+        let multi_samples = log
+            .iter()
+            .filter(|query| query.starts_with("multi-sample"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            multi_samples,
+            [
+                "multi-sample Some(8)",
+                "multi-sample Some(7)",
+                "multi-sample Some(6)",
+                "multi-sample Some(5)",
+                "multi-sample Some(4)",
+                "multi-sample Some(3)",
+                "multi-sample Some(2)",
+                "multi-sample Some(2)",
+            ]
+        );
+    }
+
+    #[test]
+    fn drops_optional_settings_when_nothing_is_supported() {
+        let context = MockContext {
+            depth_stencil_formats: vec![],
+            samples: vec![],
+            hardware_tnl: false,
+            presentation_intervals: 0,
+            ..MockContext::new()
+        };
+        let config = negotiate(&context, &fullscreen()).unwrap();
+        let presentation_parameters = &config.presentation_parameters;
+
+        assert!(config.behavior_flags.software_vertex_processing());
+        assert_eq!(
+            presentation_parameters.multi_sample_type,
+            MultiSampleType::None
+        );
+        assert!(!presentation_parameters.enable_auto_depth_stencil);
+        assert_eq!(
+            presentation_parameters.auto_depth_stencil_format,
+            Format::Unknown
+        );
+        assert_eq!(
+            presentation_parameters.presentation_interval,
+            D3DPRESENT_INTERVAL_IMMEDIATE
+        );
+        assert!(config.downgrades.contains(&Downgrade::DepthStencilFormat {
+            requested: Format::D24S8,
+            chosen: None,
+        }));
+    }
+
+    #[test]
+    fn fails_without_a_back_buffer_format_or_display_mode() {
+        let context = MockContext {
+            back_buffer_formats: vec![],
+            ..MockContext::new()
+        };
+        assert!(matches!(
+            negotiate(&context, &fullscreen()),
+            Err(ConfigError::NoBackBufferFormat {
+                device_type: DeviceType::Hal,
+            })
+        ));
+
+        let context = MockContext {
+            display_modes: vec![],
+            ..MockContext::new()
+        };
+        assert!(matches!(
+            negotiate(&context, &fullscreen()),
+            Err(ConfigError::NoDisplayMode {
+                format: Format::X8R8G8B8,
+            })
+        ));
+    }
+}
@@ -0,0 +1,6 @@
+use winapi::shared::d3d9::IDirect3DBaseTexture9;
+
+/// Implemented by every texture interface which derives from [`IDirect3DBaseTexture9`].
+pub trait BaseTexture {
+    fn as_base_ptr(&self) -> *mut IDirect3DBaseTexture9;
+}
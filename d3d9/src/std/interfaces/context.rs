@@ -1,8 +1,4 @@
-use std::{
-    mem::{transmute, MaybeUninit},
-    ptr,
-    ptr::NonNull,
-};
+use std::{mem::MaybeUninit, ptr, ptr::NonNull};
 
 use winapi::shared::{
     d3d9::{Direct3DCreate9, IDirect3D9, IDirect3DDevice9, D3D_SDK_VERSION},
@@ -16,10 +12,12 @@ use crate::{
     com::Com,
     error::WindowsResult,
     std::{
+        caps::{Caps, CapsError},
         interfaces::device::Device,
+        profile::Profile,
         types::{
-            Adapter, AdapterIdentifier, BehaviorFlags, Caps, DeviceType, DisplayMode, Format,
-            MultiSampleType, PresentationParameters, ResourceType, Usage,
+            Adapter, AdapterIdentifier, AdapterInfo, BehaviorFlags, DeviceType, DisplayMode,
+            Format, MultiSampleType, PresentationParameters, ResourceType, Usage,
         },
     },
 };
@@ -54,9 +52,9 @@ impl Context {
             check_hresult!(self.inner.CheckDepthStencilMatch(
                 adapter.into(),
                 device_type as u32,
-                adapter_format as u32,
-                render_target_format as u32,
-                depth_stencil_format as u32,
+                adapter_format.into(),
+                render_target_format.into(),
+                depth_stencil_format.into(),
             ))?;
         }
 
@@ -76,10 +74,10 @@ impl Context {
             check_hresult!(self.inner.CheckDeviceFormat(
                 adapter.into(),
                 device_type as u32,
-                adapter_format as u32,
+                adapter_format.into(),
                 usage.0,
                 resource_type as u32,
-                check_format as u32,
+                check_format.into(),
             ))?;
         }
 
@@ -97,8 +95,8 @@ impl Context {
             check_hresult!(self.inner.CheckDeviceFormatConversion(
                 adapter.into(),
                 device_type as u32,
-                source_format as u32,
-                target_format as u32,
+                source_format.into(),
+                target_format.into(),
             ))?;
         }
 
@@ -118,7 +116,7 @@ impl Context {
             check_hresult!(self.inner.CheckDeviceMultiSampleType(
                 adapter.into(),
                 device_type as u32,
-                surface_format as u32,
+                surface_format.into(),
                 windowed as i32,
                 multi_sample_type.into(),
                 quality_levels,
@@ -140,8 +138,8 @@ impl Context {
             check_hresult!(self.inner.CheckDeviceType(
                 adapter.into(),
                 device_type as u32,
-                adapter_format as u32,
-                back_buffer_format as u32,
+                adapter_format.into(),
+                back_buffer_format.into(),
                 windowed as i32,
             ))?;
         }
@@ -162,7 +160,7 @@ impl Context {
                 presentation_parameters.clone().into();
             let mut c_device: *mut IDirect3DDevice9 = ptr::null_mut();
 
-            check_hresult!(self.inner.CreateDevice(
+            check_hresult_mut!(self.inner.CreateDevice(
                 adapter.into(),
                 device_type as u32,
                 window,
@@ -176,7 +174,7 @@ impl Context {
             presentation_parameters.back_buffer_height = c_presentation_parameters.BackBufferHeight;
             presentation_parameters.back_buffer_count = c_presentation_parameters.BackBufferCount;
             presentation_parameters.back_buffer_format =
-                transmute(c_presentation_parameters.BackBufferFormat);
+                c_presentation_parameters.BackBufferFormat.into();
 
             Device::with_ptr(NonNull::new(c_device).expect("returned device is null"))
         };
@@ -200,7 +198,7 @@ impl Context {
 
             check_hresult_mut!(self.inner.EnumAdapterModes(
                 adapter.into(),
-                format as u32,
+                format.into(),
                 mode,
                 &mut c_display_mode as *mut _,
             ))?;
@@ -209,6 +207,51 @@ impl Context {
         }
     }
 
+    /// Returns an iterator over every adapter on the system.
+    pub fn adapters(&self) -> impl Iterator<Item = WindowsResult<AdapterInfo>> + '_ {
+        (0..self.get_adapter_count()).map(|ordinal| {
+            let adapter = Adapter::new(ordinal);
+
+            Ok(AdapterInfo {
+                adapter,
+                identifier: self.get_adapter_identifier(adapter, 0)?,
+                monitor: self.get_adapter_monitor(adapter),
+                display_mode: self.get_adapter_display_mode(adapter)?,
+            })
+        })
+    }
+
+    /// Returns the display modes of an adapter for the given format, without duplicates and
+    /// sorted by resolution and refresh rate.
+    pub fn display_modes(
+        &self,
+        adapter: Adapter,
+        format: Format,
+    ) -> WindowsResult<Vec<DisplayMode>> {
+        let mut modes = (0..self.get_adapter_mode_count(adapter, format))
+            .map(|mode| self.enum_adapter_modes(adapter, format, mode))
+            .collect::<WindowsResult<Vec<_>>>()?;
+
+        modes.sort_by_key(|mode| (mode.width, mode.height, mode.refresh_rate));
+        modes.dedup();
+
+        Ok(modes)
+    }
+
+    /// Returns the display mode best matching the requested resolution and refresh rate, as
+    /// picked by [`DisplayMode::best_match`].
+    pub fn find_fullscreen_mode(
+        &self,
+        adapter: Adapter,
+        format: Format,
+        width: u32,
+        height: u32,
+        refresh_rate: Option<u32>,
+    ) -> WindowsResult<Option<DisplayMode>> {
+        let modes = self.display_modes(adapter, format)?;
+        Ok(DisplayMode::best_match(&modes, width, height, refresh_rate).cloned())
+    }
+
     pub fn get_adapter_count(&self) -> u32 {
         unsafe { self.inner.GetAdapterCount() }
     }
@@ -252,7 +295,7 @@ impl Context {
     pub fn get_adapter_mode_count(&self, adapter: Adapter, format: Format) -> u32 {
         unsafe {
             self.inner
-                .GetAdapterModeCount(adapter.into(), format as u32)
+                .GetAdapterModeCount(adapter.into(), format.into())
         }
     }
 
@@ -264,7 +307,7 @@ impl Context {
         &self,
         adapter: Adapter,
         device_type: DeviceType,
-    ) -> WindowsResult<Caps> {
+    ) -> Result<Caps, CapsError> {
         unsafe {
             let mut caps: D3DCAPS9 = MaybeUninit::zeroed().assume_init();
             check_hresult_mut!(self.inner.GetDeviceCaps(
@@ -273,7 +316,21 @@ impl Context {
                 &mut caps as *mut _
             ))?;
 
-            Ok(Caps(caps))
+            Caps::from_raw(&caps)
         }
     }
+
+    /// Captures a [`Profile`] of an adapter, including every display mode of the given format.
+    pub fn get_profile(
+        &self,
+        adapter: Adapter,
+        device_type: DeviceType,
+        format: Format,
+    ) -> Result<Profile, CapsError> {
+        Ok(Profile {
+            identifier: self.get_adapter_identifier(adapter, 0)?,
+            caps: self.get_device_caps(adapter, device_type)?,
+            display_modes: self.display_modes(adapter, format)?,
+        })
+    }
 }
@@ -1,8 +1,16 @@
-use std::ptr::NonNull;
+use std::{ptr, ptr::NonNull};
 
-use winapi::shared::d3d9::IDirect3DCubeTexture9;
+use winapi::shared::d3d9::{IDirect3DBaseTexture9, IDirect3DCubeTexture9, IDirect3DSurface9};
 
-use crate::com::Com;
+use crate::{
+    check_hresult_mut,
+    com::Com,
+    error::WindowsResult,
+    std::{
+        interfaces::{BaseTexture, Surface},
+        types::CubeFace,
+    },
+};
 
 #[derive(Clone)]
 pub struct CubeTexture {
@@ -19,4 +27,26 @@ impl CubeTexture {
     pub fn as_ptr(&self) -> *mut IDirect3DCubeTexture9 {
         self.inner.as_ptr()
     }
+
+    pub fn get_cube_map_surface(&self, face: CubeFace, level: u32) -> WindowsResult<Surface> {
+        unsafe {
+            let mut c_surface: *mut IDirect3DSurface9 = ptr::null_mut();
+
+            check_hresult_mut!(self.inner.GetCubeMapSurface(
+                face as u32,
+                level,
+                &mut c_surface as *mut _
+            ))?;
+
+            Ok(Surface::with_ptr(
+                NonNull::new(c_surface).expect("returned cube map surface is null"),
+            ))
+        }
+    }
+}
+
+impl BaseTexture for CubeTexture {
+    fn as_base_ptr(&self) -> *mut IDirect3DBaseTexture9 {
+        self.as_ptr() as *mut _
+    }
 }
@@ -1,4 +1,10 @@
-use std::{cmp::min, marker::PhantomData, mem::transmute, ptr, ptr::NonNull};
+use std::{
+    cmp::min,
+    marker::PhantomData,
+    mem::{transmute, MaybeUninit},
+    ptr,
+    ptr::NonNull,
+};
 
 use winapi::{
     shared::{
@@ -8,7 +14,10 @@ use winapi::{
             IDirect3DTexture9, IDirect3DVertexBuffer9, IDirect3DVertexDeclaration9,
             IDirect3DVertexShader9, IDirect3DVolumeTexture9,
         },
-        d3d9types::{D3DPRESENT_PARAMETERS, D3DRS_CLIPPING, D3DVERTEXELEMENT9, D3DVIEWPORT9},
+        d3d9types::{
+            D3DBACKBUFFER_TYPE_MONO, D3DDISPLAYMODE, D3DPRESENT_PARAMETERS, D3DRS_CLIPPING,
+            D3DVERTEXELEMENT9, D3DVIEWPORT9,
+        },
         windef::HWND,
     },
     um::winnt::VOID,
@@ -17,15 +26,20 @@ use winapi::{
 use crate::{
     check_hresult, check_hresult_mut,
     com::Com,
-    error::WindowsResult,
+    error::{ErrorCode, WindowsError, WindowsResult},
     std::{
         interfaces::{
-            CubeTexture, IndexBuffer, PixelShader, Query, StateBlock, Surface, SwapChain, Texture,
-            VertexBuffer, VertexDeclaration, VertexShader, VolumeTexture,
+            BaseTexture, CubeTexture, IndexBuffer, PixelShader, Query, StateBlock, Surface,
+            SwapChain, Texture, VertexBuffer, VertexDeclaration, VertexShader, VolumeTexture,
+        },
+        states::{
+            RenderState, RenderStateType, SamplerState, StateError, TextureFilterType,
+            TextureStageState,
         },
         types::{
-            Clear, Color, Format, Handle, MultiSampleType, Pool, PresentationParameters,
-            PrimitiveType, QueryType, Rect, RegionData, StateBlockType, Usage, VertexElement, FVF,
+            Clear, Color, CooperativeLevel, DisplayMode, Format, Handle, MultiSampleType, Pool,
+            PresentationParameters, PrimitiveType, QueryType, Rect, RegionData, StateBlockType,
+            Usage, VertexElement, FVF,
         },
     },
 };
@@ -122,7 +136,7 @@ impl Device {
             presentation_parameters.back_buffer_height = c_presentation_parameters.BackBufferHeight;
             presentation_parameters.back_buffer_count = c_presentation_parameters.BackBufferCount;
             presentation_parameters.back_buffer_format =
-                transmute(c_presentation_parameters.BackBufferFormat);
+                c_presentation_parameters.BackBufferFormat.into();
 
             Ok(SwapChain::with_ptr(
                 NonNull::new(c_swap_chain).expect("returned swap chain is null"),
@@ -145,8 +159,8 @@ impl Device {
                 edge_length,
                 levels,
                 usage,
-                format as u32,
-                pool as u32,
+                format.into(),
+                pool.into(),
                 &mut c_cube_texture as *mut _,
                 ptr::null_mut()
             ))?;
@@ -172,7 +186,7 @@ impl Device {
             check_hresult!(self.inner.CreateDepthStencilSurface(
                 width,
                 height,
-                format as u32,
+                format.into(),
                 multi_sample_type.into(),
                 multi_sample_quality,
                 discard as i32,
@@ -199,8 +213,8 @@ impl Device {
             check_hresult_mut!(self.inner.CreateIndexBuffer(
                 length,
                 usage.0,
-                format as u32,
-                pool as u32,
+                format.into(),
+                pool.into(),
                 &mut c_index_buffer as *mut _,
                 ptr::null_mut()
             ))?;
@@ -224,8 +238,8 @@ impl Device {
             check_hresult_mut!(self.inner.CreateOffscreenPlainSurface(
                 width,
                 height,
-                format as u32,
-                pool as u32,
+                format.into(),
+                pool.into(),
                 &mut c_surface as *mut _,
                 ptr::null_mut()
             ))?;
@@ -279,7 +293,7 @@ impl Device {
             check_hresult!(self.inner.CreateRenderTarget(
                 width,
                 height,
-                format as u32,
+                format.into(),
                 multi_sample_type.into(),
                 multi_sample_quality,
                 lockable as i32,
@@ -327,15 +341,14 @@ impl Device {
                 height,
                 levels,
                 usage.0,
-                format as u32,
-                pool as u32,
+                format.into(),
+                pool.into(),
                 &mut c_texture as *mut _,
                 ptr::null_mut(),
             ))?;
 
             Ok(Texture::with_ptr(
                 NonNull::new(c_texture).expect("returned texture is null"),
-                (width, height),
             ))
         }
     }
@@ -354,7 +367,7 @@ impl Device {
                 length,
                 usage.0,
                 fvf.0,
-                pool as u32,
+                pool.into(),
                 &mut c_vertex_buffer as *mut _,
                 ptr::null_mut(),
             ))?;
@@ -430,8 +443,8 @@ impl Device {
                 depth,
                 levels,
                 usage.0,
-                format as u32,
-                pool as u32,
+                format.into(),
+                pool.into(),
                 &mut c_volume_texture as *mut _,
                 ptr::null_mut()
             ))?;
@@ -490,7 +503,7 @@ impl Device {
                 vertices.len() as u32,
                 n_primitives,
                 indices.as_ptr() as *const VOID,
-                indices_format as u32,
+                indices_format.into(),
                 vertices.as_ptr() as *const VOID,
                 vertices_stride
             ))?;
@@ -558,6 +571,86 @@ impl Device {
         }
     }
 
+    /// Returns a back buffer of an implicit swap chain. Index `0` is the next buffer to be
+    /// presented.
+    pub fn get_back_buffer(&self, swap_chain: u32, index: u32) -> WindowsResult<Surface> {
+        unsafe {
+            let mut c_surface: *mut IDirect3DSurface9 = ptr::null_mut();
+
+            check_hresult_mut!(self.inner.GetBackBuffer(
+                swap_chain,
+                index,
+                D3DBACKBUFFER_TYPE_MONO,
+                &mut c_surface as *mut _
+            ))?;
+
+            Ok(Surface::with_ptr(
+                NonNull::new(c_surface).expect("returned back buffer is null"),
+            ))
+        }
+    }
+
+    pub fn get_display_mode(&self, swap_chain: u32) -> WindowsResult<DisplayMode> {
+        unsafe {
+            let mut c_display_mode: D3DDISPLAYMODE = MaybeUninit::zeroed().assume_init();
+
+            check_hresult_mut!(self
+                .inner
+                .GetDisplayMode(swap_chain, &mut c_display_mode as *mut _))?;
+
+            Ok(c_display_mode.into())
+        }
+    }
+
+    /// Copies the front buffer of an implicit swap chain into `destination`.
+    ///
+    /// `destination` must be a [`Format::A8R8G8B8`] surface in [`Pool::SystemMem`] the size of
+    /// the [display mode](Self::get_display_mode). In windowed mode, it receives the whole
+    /// desktop.
+    pub fn get_front_buffer_data(
+        &self,
+        swap_chain: u32,
+        destination: &Surface,
+    ) -> WindowsResult<()> {
+        unsafe {
+            check_hresult!(self
+                .inner
+                .GetFrontBufferData(swap_chain, destination.as_ptr()))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn get_render_target(&self, index: u32) -> WindowsResult<Surface> {
+        unsafe {
+            let mut c_surface: *mut IDirect3DSurface9 = ptr::null_mut();
+
+            check_hresult_mut!(self.inner.GetRenderTarget(index, &mut c_surface as *mut _))?;
+
+            Ok(Surface::with_ptr(
+                NonNull::new(c_surface).expect("returned render target is null"),
+            ))
+        }
+    }
+
+    /// Copies a render target into `destination`, which must be a [`Pool::SystemMem`] surface
+    /// with the same size and format.
+    ///
+    /// Multisampled render targets must first be resolved with [`Device::stretch_rect`].
+    pub fn get_render_target_data(
+        &self,
+        render_target: &Surface,
+        destination: &Surface,
+    ) -> WindowsResult<()> {
+        unsafe {
+            check_hresult!(self
+                .inner
+                .GetRenderTargetData(render_target.as_ptr(), destination.as_ptr()))?;
+        }
+
+        Ok(())
+    }
+
     // ...
 
     pub fn present(
@@ -585,6 +678,27 @@ impl Device {
         Ok(())
     }
 
+    /// Resets the device, which is required to recover from [`CooperativeLevel::NotReset`].
+    ///
+    /// Every resource in [`Pool::Default`] must be released beforehand.
+    pub fn reset(&self, presentation_parameters: &mut PresentationParameters) -> WindowsResult<()> {
+        unsafe {
+            let mut c_presentation_parameters: D3DPRESENT_PARAMETERS =
+                presentation_parameters.clone().into();
+
+            check_hresult_mut!(self.inner.Reset((&mut c_presentation_parameters) as *mut _))?;
+
+            // Reset *might* modify these properties.
+            presentation_parameters.back_buffer_width = c_presentation_parameters.BackBufferWidth;
+            presentation_parameters.back_buffer_height = c_presentation_parameters.BackBufferHeight;
+            presentation_parameters.back_buffer_count = c_presentation_parameters.BackBufferCount;
+            presentation_parameters.back_buffer_format =
+                c_presentation_parameters.BackBufferFormat.into();
+        }
+
+        Ok(())
+    }
+
     pub fn set_vertex_declaration(
         &self,
         vertex_declaration: &VertexDeclaration,
@@ -659,6 +773,50 @@ impl Device {
         Ok(())
     }
 
+    pub fn get_render_state(&self, kind: RenderStateType) -> Result<RenderState, StateError> {
+        let mut value = 0;
+
+        unsafe {
+            check_hresult_mut!(self.inner.GetRenderState(kind as u32, &mut value))?;
+        }
+
+        RenderState::from_raw(kind, value)
+    }
+
+    pub fn set_render_state(&self, state: RenderState) -> Result<(), StateError> {
+        let (kind, value) = state.to_raw()?;
+
+        unsafe {
+            check_hresult!(self.inner.SetRenderState(kind, value))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn set_sampler_state(&self, sampler: u32, state: SamplerState) -> Result<(), StateError> {
+        let (kind, value) = state.to_raw()?;
+
+        unsafe {
+            check_hresult!(self.inner.SetSamplerState(sampler, kind, value))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn set_texture_stage_state(
+        &self,
+        stage: u32,
+        state: TextureStageState,
+    ) -> Result<(), StateError> {
+        let (kind, value) = state.to_raw()?;
+
+        unsafe {
+            check_hresult!(self.inner.SetTextureStageState(stage, kind, value))?;
+        }
+
+        Ok(())
+    }
+
     pub fn set_texture(&self, stage: u32, texture: &Texture) -> WindowsResult<()> {
         unsafe {
             check_hresult!(self.inner.SetTexture(stage, texture.as_ptr() as *mut _))?;
@@ -666,4 +824,48 @@ impl Device {
 
         Ok(())
     }
+
+    /// Copies the whole of `source` into the whole of `destination`, scaling it with `filter` if
+    /// their sizes differ.
+    pub fn stretch_rect(
+        &self,
+        source: &Surface,
+        destination: &Surface,
+        filter: TextureFilterType,
+    ) -> WindowsResult<()> {
+        unsafe {
+            check_hresult!(self.inner.StretchRect(
+                source.as_ptr(),
+                ptr::null(),
+                destination.as_ptr(),
+                ptr::null(),
+                filter as u32
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn test_cooperative_level(&self) -> WindowsResult<CooperativeLevel> {
+        let hresult = unsafe { self.inner.TestCooperativeLevel() };
+        if hresult == 0 {
+            return Ok(CooperativeLevel::Ok);
+        }
+
+        match ErrorCode::from_hresult(hresult) {
+            Some(ErrorCode::DeviceLost) => Ok(CooperativeLevel::Lost),
+            Some(ErrorCode::DeviceNotReset) => Ok(CooperativeLevel::NotReset),
+            _ => Err(WindowsError::from_hresult(hresult).with_method("TestCooperativeLevel")),
+        }
+    }
+
+    pub fn update_texture<T: BaseTexture>(&self, source: &T, destination: &T) -> WindowsResult<()> {
+        unsafe {
+            check_hresult!(self
+                .inner
+                .UpdateTexture(source.as_base_ptr(), destination.as_base_ptr()))?;
+        }
+
+        Ok(())
+    }
 }
@@ -1,18 +1,38 @@
-use std::{ptr, ptr::NonNull};
+use std::{
+    marker::PhantomData,
+    mem::{self, size_of, MaybeUninit},
+    ops::{Deref, DerefMut},
+    ptr,
+    ptr::NonNull,
+    slice,
+};
 
-use winapi::{shared::d3d9::IDirect3DIndexBuffer9, um::winnt::VOID};
+use winapi::{
+    shared::{d3d9::IDirect3DIndexBuffer9, d3d9types::D3DINDEXBUFFER_DESC},
+    um::winnt::VOID,
+};
 
-use crate::{check_hresult, check_hresult_mut, com::Com, error::WindowsResult};
+use crate::{
+    check_hresult, check_hresult_mut,
+    com::Com,
+    error::WindowsResult,
+    std::{
+        lock::{check_alignment, check_size, LockState, Pod},
+        types::LockFlags,
+    },
+};
 
 #[derive(Clone)]
 pub struct IndexBuffer {
     inner: Com<IDirect3DIndexBuffer9>,
+    lock: LockState,
 }
 
 impl IndexBuffer {
     pub fn with_ptr(inner: NonNull<IDirect3DIndexBuffer9>) -> Self {
         Self {
             inner: Com::with_ptr(inner),
+            lock: LockState::default(),
         }
     }
 
@@ -20,24 +40,126 @@ impl IndexBuffer {
         self.inner.as_ptr()
     }
 
-    pub fn lock<T>(&self, offset: u32, size: u32) -> WindowsResult<&mut [T]> {
+    /// Returns the size of the buffer in bytes.
+    pub fn size(&self) -> WindowsResult<u32> {
         unsafe {
-            let mut c_data: *mut VOID = ptr::null_mut();
+            let mut c_desc: D3DINDEXBUFFER_DESC = MaybeUninit::zeroed().assume_init();
 
-            check_hresult_mut!(self.inner.Lock(offset, size, &mut c_data as *mut _, 0))?;
+            check_hresult_mut!(self.inner.GetDesc(&mut c_desc))?;
 
-            Ok(std::slice::from_raw_parts_mut(
-                c_data as *mut T,
-                (size as usize) / std::mem::size_of::<T>(),
-            ))
+            Ok(c_desc.Size)
         }
     }
 
-    pub fn unlock(&self) -> WindowsResult<()> {
+    /// Locks `size` bytes of the buffer starting at `offset`, or the rest of the buffer if `size`
+    /// is `0`. The buffer is unlocked when the returned guard is dropped.
+    ///
+    /// Fails if `T` is zero sized or the locked memory is not aligned for it, or if the buffer is
+    /// already locked through a clone.
+    ///
+    /// The guard borrows the buffer, so it cannot outlive it and the buffer cannot be locked again
+    /// while it exists:
+    ///
+    /// ```compile_fail
+    /// # use d3d9::std::{interfaces::IndexBuffer, types::LockFlags};
+    /// fn lock_twice(buffer: &mut IndexBuffer) {
+    ///     let first = buffer.lock::<u16>(0, 0, LockFlags::default()).unwrap();
+    ///     let second = buffer.lock::<u16>(0, 0, LockFlags::default()).unwrap();
+    ///     drop(first);
+    /// }
+    /// ```
+    ///
+    /// ```compile_fail
+    /// # use d3d9::std::{interfaces::IndexBuffer, types::LockFlags};
+    /// fn use_after_drop(buffer: IndexBuffer) {
+    ///     let mut buffer = buffer;
+    ///     let mut indices = buffer.lock::<u16>(0, 0, LockFlags::default()).unwrap();
+    ///     drop(buffer);
+    ///     indices[0] = 0;
+    /// }
+    /// ```
+    pub fn lock<T: Pod>(
+        &mut self,
+        offset: u32,
+        size: u32,
+        flags: LockFlags,
+    ) -> WindowsResult<IndexBufferLock<'_, T>> {
+        let size = match size {
+            0 => self.size()?.saturating_sub(offset),
+            size => size,
+        };
+
+        check_size::<T>()?;
+        self.lock.acquire()?;
+
+        let c_data = unsafe {
+            let mut c_data: *mut VOID = ptr::null_mut();
+
+            check_hresult_mut!(self.inner.Lock(offset, size, &mut c_data, flags.0))
+                .inspect_err(|_| self.lock.release())?;
+
+            c_data
+        };
+
+        // The guard unlocks the buffer if the memory turns out to be unusable.
+        let guard = IndexBufferLock {
+            buffer: self,
+            data: c_data as *mut T,
+            len: size as usize / size_of::<T>(),
+            marker: PhantomData,
+        };
+        check_alignment::<T>(c_data as *const u8, &[])?;
+
+        Ok(guard)
+    }
+}
+
+impl PartialEq for IndexBuffer {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ptr() == other.as_ptr()
+    }
+}
+
+impl Eq for IndexBuffer {}
+
+/// Represents a locked range of an [`IndexBuffer`], which is unlocked when dropped.
+pub struct IndexBufferLock<'a, T> {
+    buffer: &'a mut IndexBuffer,
+    data: *mut T,
+    len: usize,
+    marker: PhantomData<&'a mut [T]>,
+}
+
+impl<T> IndexBufferLock<'_, T> {
+    /// Unlocks the buffer, returning any error instead of ignoring it as dropping the guard does.
+    pub fn unlock(self) -> WindowsResult<()> {
+        let result = unsafe { check_hresult!(self.buffer.inner.Unlock()) };
+        self.buffer.lock.release();
+        mem::forget(self);
+
+        result
+    }
+}
+
+impl<T> Deref for IndexBufferLock<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.data, self.len) }
+    }
+}
+
+impl<T> DerefMut for IndexBufferLock<'_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.data, self.len) }
+    }
+}
+
+impl<T> Drop for IndexBufferLock<'_, T> {
+    fn drop(&mut self) {
         unsafe {
-            check_hresult!(self.inner.Unlock())?;
+            self.buffer.inner.Unlock();
         }
-
-        Ok(())
+        self.buffer.lock.release();
     }
 }
@@ -1,3 +1,4 @@
+mod base_texture;
 mod context;
 mod cube_texture;
 mod device;
@@ -13,17 +14,18 @@ mod vertex_declaration;
 mod vertex_shader;
 mod volume_texture;
 
+pub use base_texture::BaseTexture;
 pub use context::Context;
 pub use cube_texture::CubeTexture;
 pub use device::Device;
-pub use index_buffer::IndexBuffer;
+pub use index_buffer::{IndexBuffer, IndexBufferLock};
 pub use pixel_shader::PixelShader;
 pub use query::Query;
 pub use state_block::StateBlock;
-pub use surface::Surface;
+pub use surface::{Surface, SurfaceLock};
 pub use swap_chain::SwapChain;
-pub use texture::Texture;
-pub use vertex_buffer::VertexBuffer;
+pub use texture::{Texture, TextureLock};
+pub use vertex_buffer::{VertexBuffer, VertexBufferLock};
 pub use vertex_declaration::VertexDeclaration;
 pub use vertex_shader::VertexShader;
-pub use volume_texture::VolumeTexture;
+pub use volume_texture::{VolumeTexture, VolumeTextureLock};
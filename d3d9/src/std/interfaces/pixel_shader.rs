@@ -20,3 +20,11 @@ impl PixelShader {
         self.inner.as_ptr()
     }
 }
+
+impl PartialEq for PixelShader {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ptr() == other.as_ptr()
+    }
+}
+
+impl Eq for PixelShader {}
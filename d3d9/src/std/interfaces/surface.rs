@@ -1,22 +1,187 @@
-use std::{marker::PhantomData, ptr::NonNull};
+use std::{
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ptr,
+    ptr::NonNull,
+    slice,
+};
 
-use winapi::shared::d3d9::IDirect3DSurface9;
+use winapi::shared::{
+    d3d9::IDirect3DSurface9,
+    d3d9types::{D3DLOCKED_RECT, D3DSURFACE_DESC},
+};
 
-use crate::com::Com;
+use crate::{
+    check_hresult, check_hresult_mut,
+    com::Com,
+    error::WindowsResult,
+    format::{FormatError, Image},
+    std::{
+        lock::{check_alignment, check_size, row_layout, LockState, Pod},
+        types::{LockFlags, LockedRect, SurfaceDesc},
+    },
+};
 
 #[derive(Clone)]
 pub struct Surface {
     inner: Com<IDirect3DSurface9>,
+    lock: LockState,
 }
 
 impl Surface {
     pub fn with_ptr(inner: NonNull<IDirect3DSurface9>) -> Self {
         Self {
             inner: Com::with_ptr(inner),
+            lock: LockState::default(),
         }
     }
 
     pub fn as_ptr(&self) -> *mut IDirect3DSurface9 {
         self.inner.as_ptr()
     }
+
+    pub fn get_desc(&self) -> WindowsResult<SurfaceDesc> {
+        unsafe {
+            let mut c_desc: D3DSURFACE_DESC = MaybeUninit::zeroed().assume_init();
+
+            check_hresult_mut!(self.inner.GetDesc(&mut c_desc))?;
+
+            Ok(c_desc.into())
+        }
+    }
+
+    /// Locks the surface, which is unlocked when the returned guard is dropped.
+    ///
+    /// Rows are laid out as in [`Texture::lock_rect`](crate::std::interfaces::Texture::lock_rect).
+    /// Fails if `T` is zero sized or the rows are not aligned for it, or if the surface is already
+    /// locked through a clone.
+    pub fn lock_rect<T: Pod>(&mut self, flags: LockFlags) -> WindowsResult<SurfaceLock<'_, T>> {
+        check_size::<T>()?;
+        let desc = self.get_desc()?;
+        self.lock.acquire()?;
+
+        let locked_rect: LockedRect = unsafe {
+            let mut c_locked_rect: D3DLOCKED_RECT = MaybeUninit::zeroed().assume_init();
+
+            check_hresult_mut!(self
+                .inner
+                .LockRect(&mut c_locked_rect, ptr::null(), flags.0))
+            .inspect_err(|_| self.lock.release())?;
+
+            c_locked_rect.into()
+        };
+
+        let pitch = locked_rect.pitch as usize;
+        let (width, height) = row_layout::<T>(desc.format, desc.width, desc.height, pitch);
+
+        // The guard unlocks the surface if the memory turns out to be unusable.
+        let guard = SurfaceLock {
+            surface: self,
+            bits: locked_rect.bits,
+            pitch,
+            width,
+            height,
+            marker: PhantomData,
+        };
+        check_alignment::<T>(locked_rect.bits, &[pitch])?;
+
+        Ok(guard)
+    }
+
+    /// Copies the contents of the surface into an [`Image`] of the same format.
+    ///
+    /// The surface must be lockable, such as a [`Pool::SystemMem`] offscreen plain surface filled
+    /// by [`Device::get_render_target_data`].
+    ///
+    /// [`Pool::SystemMem`]: crate::std::types::Pool::SystemMem
+    /// [`Device::get_render_target_data`]: crate::std::interfaces::Device::get_render_target_data
+    pub fn read_image(&mut self) -> Result<Image, FormatError> {
+        let desc = self.get_desc()?;
+        let mut image = Image::new(desc.format, desc.width, desc.height)?;
+
+        let mut flags = LockFlags::default();
+        flags.set_readonly(true);
+
+        let lock = self.lock_rect::<u8>(flags)?;
+        for (row, source) in lock.rows().enumerate() {
+            image.row_mut(row).copy_from_slice(source);
+        }
+        lock.unlock()?;
+
+        Ok(image)
+    }
+}
+
+/// Represents a locked [`Surface`], which is unlocked when dropped.
+pub struct SurfaceLock<'a, T> {
+    surface: &'a mut Surface,
+    bits: *mut u8,
+    pitch: usize,
+    width: usize,
+    height: usize,
+    marker: PhantomData<&'a mut [T]>,
+}
+
+impl<T> SurfaceLock<'_, T> {
+    /// Returns the number of `T` values in each row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the number of bytes between the start of each row, which may include padding.
+    pub fn pitch(&self) -> usize {
+        self.pitch
+    }
+
+    /// Returns row `y`, or [`None`] if it is out of bounds.
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        (y < self.height).then(|| unsafe { slice::from_raw_parts(self.row_ptr(y), self.width) })
+    }
+
+    /// Returns row `y` mutably, or [`None`] if it is out of bounds.
+    pub fn row_mut(&mut self, y: usize) -> Option<&mut [T]> {
+        (y < self.height).then(|| unsafe { slice::from_raw_parts_mut(self.row_ptr(y), self.width) })
+    }
+
+    /// Returns an iterator over the rows, from top to bottom.
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = &[T]> + '_ {
+        (0..self.height).map(|y| unsafe { slice::from_raw_parts(self.row_ptr(y), self.width) })
+    }
+
+    /// Returns an iterator over the rows mutably, from top to bottom.
+    pub fn rows_mut(&mut self) -> impl ExactSizeIterator<Item = &mut [T]> + '_ {
+        let (bits, pitch, width) = (self.bits, self.pitch, self.width);
+
+        // Rows never overlap, so handing out every row at once is sound.
+        (0..self.height).map(move |y| unsafe {
+            slice::from_raw_parts_mut(bits.add(y * pitch) as *mut T, width)
+        })
+    }
+
+    /// Unlocks the surface, returning any error instead of ignoring it as dropping the guard does.
+    pub fn unlock(self) -> WindowsResult<()> {
+        let result = unsafe { check_hresult!(self.surface.inner.UnlockRect()) };
+        self.surface.lock.release();
+        mem::forget(self);
+
+        result
+    }
+
+    fn row_ptr(&self, y: usize) -> *mut T {
+        unsafe { self.bits.add(y * self.pitch) as *mut T }
+    }
+}
+
+impl<T> Drop for SurfaceLock<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.surface.inner.UnlockRect();
+        }
+        self.surface.lock.release();
+    }
 }
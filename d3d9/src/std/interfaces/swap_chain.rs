@@ -1,8 +1,11 @@
-use std::ptr::NonNull;
+use std::{ptr, ptr::NonNull};
 
-use winapi::shared::d3d9::IDirect3DSwapChain9;
+use winapi::shared::{
+    d3d9::{IDirect3DSurface9, IDirect3DSwapChain9},
+    d3d9types::D3DBACKBUFFER_TYPE_MONO,
+};
 
-use crate::com::Com;
+use crate::{check_hresult_mut, com::Com, error::WindowsResult, std::interfaces::Surface};
 
 #[derive(Clone)]
 pub struct SwapChain {
@@ -19,4 +22,21 @@ impl SwapChain {
     pub fn as_ptr(&self) -> *mut IDirect3DSwapChain9 {
         self.inner.as_ptr()
     }
+
+    /// Returns a back buffer of the swap chain. Index `0` is the next buffer to be presented.
+    pub fn get_back_buffer(&self, index: u32) -> WindowsResult<Surface> {
+        unsafe {
+            let mut c_surface: *mut IDirect3DSurface9 = ptr::null_mut();
+
+            check_hresult_mut!(self.inner.GetBackBuffer(
+                index,
+                D3DBACKBUFFER_TYPE_MONO,
+                &mut c_surface as *mut _
+            ))?;
+
+            Ok(Surface::with_ptr(
+                NonNull::new(c_surface).expect("returned back buffer is null"),
+            ))
+        }
+    }
 }
@@ -1,26 +1,39 @@
 use std::{
-    mem::{size_of, MaybeUninit},
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
     ptr,
     ptr::NonNull,
+    slice,
 };
 
-use winapi::shared::{d3d9::IDirect3DTexture9, d3d9types::D3DLOCKED_RECT};
+use winapi::shared::{
+    d3d9::{IDirect3DBaseTexture9, IDirect3DSurface9, IDirect3DTexture9},
+    d3d9types::{D3DLOCKED_RECT, D3DSURFACE_DESC},
+};
 
 use crate::{
-    check_hresult, check_hresult_mut, com::Com, error::WindowsResult, std::types::LockFlags,
+    check_hresult, check_hresult_mut,
+    com::Com,
+    error::WindowsResult,
+    format::{mip, FormatError, Image, MipOptions},
+    std::{
+        interfaces::{BaseTexture, Surface},
+        lock::{check_alignment, check_size, row_layout, LockState, Pod},
+        types::{LockFlags, LockedRect, SurfaceDesc},
+    },
 };
 
 #[derive(Clone)]
 pub struct Texture {
     inner: Com<IDirect3DTexture9>,
-    size: (u32, u32),
+    lock: LockState,
 }
 
 impl Texture {
-    pub fn with_ptr(inner: NonNull<IDirect3DTexture9>, size: (u32, u32)) -> Self {
+    pub fn with_ptr(inner: NonNull<IDirect3DTexture9>) -> Self {
         Self {
             inner: Com::with_ptr(inner),
-            size,
+            lock: LockState::default(),
         }
     }
 
@@ -28,26 +41,210 @@ impl Texture {
         self.inner.as_ptr()
     }
 
-    pub fn lock_rect<T>(&self, level: u32, flags: LockFlags) -> WindowsResult<(&mut [T])> {
+    /// Returns the description of `level`.
+    pub fn get_level_desc(&self, level: u32) -> WindowsResult<SurfaceDesc> {
         unsafe {
-            let mut locked_rect: D3DLOCKED_RECT = MaybeUninit::zeroed().assume_init();
+            let mut c_desc: D3DSURFACE_DESC = MaybeUninit::zeroed().assume_init();
 
-            check_hresult_mut!(self
-                .inner
-                .LockRect(level, &mut locked_rect, ptr::null(), flags.0))?;
+            check_hresult_mut!(self.inner.GetLevelDesc(level, &mut c_desc))?;
 
-            Ok(std::slice::from_raw_parts_mut(
-                locked_rect.pBits as *mut T,
-                (self.size.0 * self.size.1 * size_of::<T>() as u32) as usize,
-            ))
+            Ok(c_desc.into())
         }
     }
 
-    pub fn unlock_rect(&self, level: u32) -> WindowsResult<()> {
+    /// Returns the number of mip levels.
+    pub fn level_count(&self) -> u32 {
+        unsafe { self.inner.GetLevelCount() }
+    }
+
+    pub fn get_surface_level(&self, level: u32) -> WindowsResult<Surface> {
         unsafe {
-            check_hresult!(self.inner.UnlockRect(level))?;
+            let mut c_surface: *mut IDirect3DSurface9 = ptr::null_mut();
+
+            check_hresult_mut!(self.inner.GetSurfaceLevel(level, &mut c_surface as *mut _))?;
+
+            Ok(Surface::with_ptr(
+                NonNull::new(c_surface).expect("returned surface level is null"),
+            ))
+        }
+    }
+
+    /// Locks `level` of the texture, which is unlocked when the returned guard is dropped.
+    ///
+    /// Each row of the lock holds one row of pixels, or one row of blocks for compressed formats,
+    /// as `T` values. Fails if `T` is zero sized or the rows are not aligned for it, or if the
+    /// texture is already locked through a clone.
+    pub fn lock_rect<T: Pod>(
+        &mut self,
+        level: u32,
+        flags: LockFlags,
+    ) -> WindowsResult<TextureLock<'_, T>> {
+        check_size::<T>()?;
+        let desc = self.get_level_desc(level)?;
+        self.lock.acquire()?;
+
+        let locked_rect: LockedRect = unsafe {
+            let mut c_locked_rect: D3DLOCKED_RECT = MaybeUninit::zeroed().assume_init();
+
+            check_hresult_mut!(self.inner.LockRect(
+                level,
+                &mut c_locked_rect,
+                ptr::null(),
+                flags.0
+            ))
+            .inspect_err(|_| self.lock.release())?;
+
+            c_locked_rect.into()
+        };
+
+        let pitch = locked_rect.pitch as usize;
+        let (width, height) = row_layout::<T>(desc.format, desc.width, desc.height, pitch);
+
+        // The guard unlocks the texture if the memory turns out to be unusable.
+        let guard = TextureLock {
+            texture: self,
+            level,
+            bits: locked_rect.bits,
+            pitch,
+            width,
+            height,
+            marker: PhantomData,
+        };
+        check_alignment::<T>(locked_rect.bits, &[pitch])?;
+
+        Ok(guard)
+    }
+
+    /// Writes `image` into `level`, converting it to the format of the texture if needed.
+    ///
+    /// The texture must be lockable, so [`Pool::Default`](crate::std::types::Pool::Default)
+    /// textures should be filled through a system memory texture and
+    /// [`Device::update_texture`](crate::std::interfaces::Device::update_texture).
+    pub fn write_image(&mut self, level: u32, image: &Image) -> Result<(), FormatError> {
+        let desc = self.get_level_desc(level)?;
+        if (image.width, image.height) != (desc.width, desc.height) {
+            return Err(FormatError::SizeMismatch {
+                expected: (desc.width, desc.height),
+                found: (image.width, image.height),
+            });
+        }
+
+        let converted;
+        let image = if image.format == desc.format {
+            image
+        } else {
+            converted = image.convert(desc.format)?;
+            &converted
+        };
+
+        let mut lock = self.lock_rect::<u8>(level, LockFlags::default())?;
+        for (row, destination) in lock.rows_mut().enumerate() {
+            destination.copy_from_slice(image.row(row));
+        }
+        lock.unlock()?;
+
+        Ok(())
+    }
+
+    /// Generates a mip chain from `base` and writes it into every level of the texture.
+    ///
+    /// `options.levels` is ignored in favour of the level count of the texture.
+    pub fn write_mips(&mut self, base: &Image, options: &MipOptions) -> Result<(), FormatError> {
+        let options = options.levels(self.level_count());
+
+        for (level, image) in mip::generate_mips(base, &options)?.iter().enumerate() {
+            self.write_image(level as u32, image)?;
         }
 
         Ok(())
     }
 }
+
+impl BaseTexture for Texture {
+    fn as_base_ptr(&self) -> *mut IDirect3DBaseTexture9 {
+        self.as_ptr() as *mut _
+    }
+}
+
+impl PartialEq for Texture {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ptr() == other.as_ptr()
+    }
+}
+
+impl Eq for Texture {}
+
+/// Represents a locked level of a [`Texture`], which is unlocked when dropped.
+pub struct TextureLock<'a, T> {
+    texture: &'a mut Texture,
+    level: u32,
+    bits: *mut u8,
+    pitch: usize,
+    width: usize,
+    height: usize,
+    marker: PhantomData<&'a mut [T]>,
+}
+
+impl<T> TextureLock<'_, T> {
+    /// Returns the number of `T` values in each row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the number of bytes between the start of each row, which may include padding.
+    pub fn pitch(&self) -> usize {
+        self.pitch
+    }
+
+    /// Returns row `y`, or [`None`] if it is out of bounds.
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        (y < self.height).then(|| unsafe { slice::from_raw_parts(self.row_ptr(y), self.width) })
+    }
+
+    /// Returns row `y` mutably, or [`None`] if it is out of bounds.
+    pub fn row_mut(&mut self, y: usize) -> Option<&mut [T]> {
+        (y < self.height).then(|| unsafe { slice::from_raw_parts_mut(self.row_ptr(y), self.width) })
+    }
+
+    /// Returns an iterator over the rows, from top to bottom.
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = &[T]> + '_ {
+        (0..self.height).map(|y| unsafe { slice::from_raw_parts(self.row_ptr(y), self.width) })
+    }
+
+    /// Returns an iterator over the rows mutably, from top to bottom.
+    pub fn rows_mut(&mut self) -> impl ExactSizeIterator<Item = &mut [T]> + '_ {
+        let (bits, pitch, width) = (self.bits, self.pitch, self.width);
+
+        // Rows never overlap, so handing out every row at once is sound.
+        (0..self.height).map(move |y| unsafe {
+            slice::from_raw_parts_mut(bits.add(y * pitch) as *mut T, width)
+        })
+    }
+
+    /// Unlocks the texture, returning any error instead of ignoring it as dropping the guard does.
+    pub fn unlock(self) -> WindowsResult<()> {
+        let result = unsafe { check_hresult!(self.texture.inner.UnlockRect(self.level)) };
+        self.texture.lock.release();
+        mem::forget(self);
+
+        result
+    }
+
+    fn row_ptr(&self, y: usize) -> *mut T {
+        unsafe { self.bits.add(y * self.pitch) as *mut T }
+    }
+}
+
+impl<T> Drop for TextureLock<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.texture.inner.UnlockRect(self.level);
+        }
+        self.texture.lock.release();
+    }
+}
@@ -20,3 +20,11 @@ impl VertexDeclaration {
         self.inner.as_ptr()
     }
 }
+
+impl PartialEq for VertexDeclaration {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ptr() == other.as_ptr()
+    }
+}
+
+impl Eq for VertexDeclaration {}
@@ -20,3 +20,11 @@ impl VertexShader {
         self.inner.as_ptr()
     }
 }
+
+impl PartialEq for VertexShader {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ptr() == other.as_ptr()
+    }
+}
+
+impl Eq for VertexShader {}
@@ -1,22 +1,203 @@
-use std::ptr::NonNull;
+use std::{
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ptr,
+    ptr::NonNull,
+    slice,
+};
 
-use winapi::shared::d3d9::IDirect3DVolumeTexture9;
+use winapi::shared::{
+    d3d9::{IDirect3DBaseTexture9, IDirect3DVolumeTexture9},
+    d3d9types::{D3DLOCKED_BOX, D3DVOLUME_DESC},
+};
 
-use crate::com::Com;
+use crate::{
+    check_hresult, check_hresult_mut,
+    com::Com,
+    error::WindowsResult,
+    std::{
+        interfaces::BaseTexture,
+        lock::{check_alignment, check_size, row_layout, LockState, Pod},
+        types::{LockFlags, LockedBox},
+    },
+};
 
 #[derive(Clone)]
 pub struct VolumeTexture {
     inner: Com<IDirect3DVolumeTexture9>,
+    lock: LockState,
 }
 
 impl VolumeTexture {
     pub fn with_ptr(inner: NonNull<IDirect3DVolumeTexture9>) -> Self {
         Self {
             inner: Com::with_ptr(inner),
+            lock: LockState::default(),
         }
     }
 
     pub fn as_ptr(&self) -> *mut IDirect3DVolumeTexture9 {
         self.inner.as_ptr()
     }
+
+    /// Locks `level` of the texture, which is unlocked when the returned guard is dropped.
+    ///
+    /// Each row of the lock holds one row of pixels, or one row of blocks for compressed formats,
+    /// as `T` values. Fails if `T` is zero sized or the rows are not aligned for it, or if the
+    /// texture is already locked through a clone.
+    pub fn lock_box<T: Pod>(
+        &mut self,
+        level: u32,
+        flags: LockFlags,
+    ) -> WindowsResult<VolumeTextureLock<'_, T>> {
+        check_size::<T>()?;
+        let desc = self.get_level_desc(level)?;
+        self.lock.acquire()?;
+
+        let locked_box: LockedBox = unsafe {
+            let mut c_locked_box: D3DLOCKED_BOX = MaybeUninit::zeroed().assume_init();
+
+            check_hresult_mut!(self
+                .inner
+                .LockBox(level, &mut c_locked_box, ptr::null(), flags.0))
+            .inspect_err(|_| self.lock.release())?;
+
+            c_locked_box.into()
+        };
+
+        let row_pitch = locked_box.row_pitch as usize;
+        let slice_pitch = locked_box.slice_pitch as usize;
+        let (width, height) =
+            row_layout::<T>(desc.Format.into(), desc.Width, desc.Height, row_pitch);
+
+        // The guard unlocks the texture if the memory turns out to be unusable.
+        let guard = VolumeTextureLock {
+            texture: self,
+            level,
+            bits: locked_box.bits,
+            row_pitch,
+            slice_pitch,
+            width,
+            height,
+            depth: desc.Depth.max(1) as usize,
+            marker: PhantomData,
+        };
+        check_alignment::<T>(locked_box.bits, &[row_pitch, slice_pitch])?;
+
+        Ok(guard)
+    }
+
+    fn get_level_desc(&self, level: u32) -> WindowsResult<D3DVOLUME_DESC> {
+        unsafe {
+            let mut c_desc: D3DVOLUME_DESC = MaybeUninit::zeroed().assume_init();
+
+            check_hresult_mut!(self.inner.GetLevelDesc(level, &mut c_desc))?;
+
+            Ok(c_desc)
+        }
+    }
+}
+
+impl BaseTexture for VolumeTexture {
+    fn as_base_ptr(&self) -> *mut IDirect3DBaseTexture9 {
+        self.as_ptr() as *mut _
+    }
+}
+
+/// Represents a locked level of a [`VolumeTexture`], which is unlocked when dropped.
+pub struct VolumeTextureLock<'a, T> {
+    texture: &'a mut VolumeTexture,
+    level: u32,
+    bits: *mut u8,
+    row_pitch: usize,
+    slice_pitch: usize,
+    width: usize,
+    height: usize,
+    depth: usize,
+    marker: PhantomData<&'a mut [T]>,
+}
+
+impl<T> VolumeTextureLock<'_, T> {
+    /// Returns the number of `T` values in each row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of rows in each depth slice.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the number of depth slices.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the number of bytes between the start of each row, which may include padding.
+    pub fn row_pitch(&self) -> usize {
+        self.row_pitch
+    }
+
+    /// Returns the number of bytes between the start of each depth slice, which may include
+    /// padding.
+    pub fn slice_pitch(&self) -> usize {
+        self.slice_pitch
+    }
+
+    /// Returns row `y` of slice `z`, or [`None`] if either is out of bounds.
+    pub fn row(&self, y: usize, z: usize) -> Option<&[T]> {
+        (y < self.height && z < self.depth)
+            .then(|| unsafe { slice::from_raw_parts(self.row_ptr(y, z), self.width) })
+    }
+
+    /// Returns row `y` of slice `z` mutably, or [`None`] if either is out of bounds.
+    pub fn row_mut(&mut self, y: usize, z: usize) -> Option<&mut [T]> {
+        (y < self.height && z < self.depth)
+            .then(|| unsafe { slice::from_raw_parts_mut(self.row_ptr(y, z), self.width) })
+    }
+
+    /// Returns an iterator over the rows of every slice, from top to bottom and front to back.
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = &[T]> + '_ {
+        (0..self.height * self.depth).map(|row| unsafe {
+            slice::from_raw_parts(
+                self.row_ptr(row % self.height, row / self.height),
+                self.width,
+            )
+        })
+    }
+
+    /// Returns an iterator over the rows of every slice mutably, from top to bottom and front to
+    /// back.
+    pub fn rows_mut(&mut self) -> impl ExactSizeIterator<Item = &mut [T]> + '_ {
+        let (bits, row_pitch, slice_pitch) = (self.bits, self.row_pitch, self.slice_pitch);
+        let (width, height) = (self.width, self.height);
+
+        // Rows never overlap, so handing out every row at once is sound.
+        (0..height * self.depth).map(move |row| unsafe {
+            let offset = (row / height) * slice_pitch + (row % height) * row_pitch;
+            slice::from_raw_parts_mut(bits.add(offset) as *mut T, width)
+        })
+    }
+
+    /// Unlocks the texture, returning any error instead of ignoring it as dropping the guard does.
+    pub fn unlock(self) -> WindowsResult<()> {
+        let result = unsafe { check_hresult!(self.texture.inner.UnlockBox(self.level)) };
+        self.texture.lock.release();
+        mem::forget(self);
+
+        result
+    }
+
+    fn row_ptr(&self, y: usize, z: usize) -> *mut T {
+        unsafe { self.bits.add(z * self.slice_pitch + y * self.row_pitch) as *mut T }
+    }
+}
+
+impl<T> Drop for VolumeTextureLock<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.texture.inner.UnlockBox(self.level);
+        }
+        self.texture.lock.release();
+    }
 }
@@ -0,0 +1,168 @@
+//! Provides the element types and bookkeeping shared by the lock guards of buffers and textures.
+
+use std::{
+    cell::Cell,
+    mem::{align_of, size_of},
+    rc::Rc,
+};
+
+use crate::{
+    error::{WindowsError, WindowsResult},
+    std::types::Format,
+    sys::D3DERR_INVALIDCALL,
+};
+
+/// Marks types which can be viewed directly in locked buffer or texture memory.
+///
+/// This is implemented for the integer and float types which make up vertices, indices and
+/// pixels, for arrays of them, and by `#[derive(Vertex)]`.
+///
+/// # Safety
+///
+/// Implementors must contain no padding and be valid for any bit pattern, since locked memory
+/// holds whatever the application, driver or GPU last wrote to it.
+pub unsafe trait Pod: Copy + 'static {}
+
+unsafe impl Pod for u8 {}
+unsafe impl Pod for u16 {}
+unsafe impl Pod for u32 {}
+unsafe impl Pod for i8 {}
+unsafe impl Pod for i16 {}
+unsafe impl Pod for i32 {}
+unsafe impl Pod for f32 {}
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// Tracks whether a resource is locked, shared between every clone of its wrapper.
+///
+/// Clones refer to the same COM object, so without this each clone could hand out a guard over
+/// the same memory.
+#[derive(Clone, Default)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) struct LockState(Rc<Cell<bool>>);
+
+#[cfg_attr(not(windows), allow(dead_code))]
+impl LockState {
+    /// Marks the resource as locked, failing if it already is.
+    pub(crate) fn acquire(&self) -> WindowsResult<()> {
+        if self.0.replace(true) {
+            return Err(invalid_call());
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn release(&self) {
+        self.0.set(false);
+    }
+}
+
+/// Fails if `T` is zero sized, as a lock would then hold an unbounded number of values.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn check_size<T>() -> WindowsResult<()> {
+    if size_of::<T>() == 0 {
+        return Err(invalid_call());
+    }
+
+    Ok(())
+}
+
+/// Fails if `bits`, or the start of any row or slice `pitches` bytes apart, is misaligned for `T`.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn check_alignment<T>(bits: *const u8, pitches: &[usize]) -> WindowsResult<()> {
+    let align = align_of::<T>();
+    if !(bits as usize).is_multiple_of(align)
+        || pitches.iter().any(|pitch| !pitch.is_multiple_of(align))
+    {
+        return Err(invalid_call());
+    }
+
+    Ok(())
+}
+
+/// Returns the number of `T` values in each row of a locked `width` by `height` surface of
+/// `format`, and the number of rows.
+///
+/// A row of a block compressed format is a row of blocks. Rows of formats without a known layout
+/// span the whole `pitch`, and the last partial `T` of a row is never included.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn row_layout<T>(
+    format: Format,
+    width: u32,
+    height: u32,
+    pitch: usize,
+) -> (usize, usize) {
+    let (row_bytes, rows) = format
+        .info()
+        .map_or((pitch, height.max(1) as usize), |info| {
+            info.surface_layout(width, height)
+        });
+
+    (row_bytes.min(pitch) / size_of::<T>(), rows)
+}
+
+fn invalid_call() -> WindowsError {
+    WindowsError::from_hresult(D3DERR_INVALIDCALL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lays_out_rows_of_pixels_and_blocks() {
+        assert_eq!(row_layout::<u32>(Format::A8R8G8B8, 4, 2, 16), (4, 2));
+        assert_eq!(row_layout::<u8>(Format::A8R8G8B8, 4, 2, 16), (16, 2));
+        assert_eq!(row_layout::<u16>(Format::R5G6B5, 3, 1, 8), (3, 1));
+
+        // A DXT1 level 10x6 pixels wide holds 2 rows of 3 blocks, which are 8 bytes each.
+        assert_eq!(row_layout::<[u8; 8]>(Format::DXT1, 10, 6, 24), (3, 2));
+        assert_eq!(row_layout::<u8>(Format::DXT5, 10, 6, 48), (48, 2));
+
+        // Levels smaller than a block still hold one.
+        assert_eq!(row_layout::<u8>(Format::DXT1, 1, 1, 8), (8, 1));
+    }
+
+    #[test]
+    fn excludes_padding_when_the_pitch_is_wider_than_a_row() {
+        assert_eq!(row_layout::<u32>(Format::X8R8G8B8, 3, 5, 64), (3, 5));
+        assert_eq!(row_layout::<[u8; 8]>(Format::DXT1, 8, 8, 256), (2, 2));
+    }
+
+    #[test]
+    fn spans_the_pitch_for_unknown_layouts() {
+        assert_eq!(row_layout::<u8>(Format::Unknown, 4, 3, 20), (20, 3));
+        assert_eq!(row_layout::<u32>(Format::Unknown, 4, 3, 22), (5, 3));
+    }
+
+    #[test]
+    fn never_exceeds_the_pitch() {
+        assert_eq!(row_layout::<u8>(Format::R8G8B8, 4, 3, 8), (8, 3));
+    }
+
+    #[test]
+    fn rejects_zero_sized_and_misaligned_elements() {
+        assert!(check_size::<u8>().is_ok());
+        assert!(check_size::<()>().is_err());
+        assert!(check_size::<[u32; 0]>().is_err());
+
+        let data = [0u32; 4];
+        let bits = data.as_ptr() as *const u8;
+        assert!(check_alignment::<u32>(bits, &[16, 64]).is_ok());
+        assert!(check_alignment::<u32>(bits, &[18]).is_err());
+        assert!(check_alignment::<u32>(unsafe { bits.add(2) }, &[16]).is_err());
+        assert!(check_alignment::<u16>(unsafe { bits.add(2) }, &[18]).is_ok());
+    }
+
+    #[test]
+    fn shares_the_lock_between_clones() {
+        let state = LockState::default();
+        let clone = state.clone();
+
+        state.acquire().unwrap();
+        assert!(clone.acquire().is_err());
+
+        state.release();
+        clone.acquire().unwrap();
+        assert!(state.acquire().is_err());
+    }
+}
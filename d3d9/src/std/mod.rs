@@ -1,2 +1,10 @@
+pub mod backend;
+pub mod caps;
+pub mod config;
+#[cfg(windows)]
 pub mod interfaces;
+pub mod lock;
+pub mod profile;
+pub mod states;
 pub mod types;
+pub mod vertex;
@@ -0,0 +1,367 @@
+//! Provides capability profiles, which describe what an adapter supports independently of the
+//! machine it was captured on.
+//!
+//! A [`Profile`] bundles the [`AdapterIdentifier`], [`Caps`] and display modes of an adapter. With
+//! the `serde` feature enabled, profiles can be stored and later diffed or checked against a list
+//! of [`Requirement`]s on any platform.
+//!
+//! # Usage
+//!
+//! ```rs
+//! let profile = context.get_profile(Adapter::default(), DeviceType::Hal, Format::X8R8G8B8)?;
+//! std::fs::write("local.json", serde_json::to_string_pretty(&profile)?)?;
+//!
+//! let customer: Profile = serde_json::from_str(&std::fs::read_to_string("customer.json")?)?;
+//! for difference in profile.diff(&customer).caps {
+//!     println!("{difference}");
+//! }
+//!
+//! let requirements = [
+//!     Requirement::ShaderModel { major: 3, minor: 0 },
+//!     Requirement::Flag("raster_caps.scissor_test".to_string()),
+//!     Requirement::AtLeast { name: "max_texture_width".to_string(), value: 4096.0 },
+//! ];
+//! for failure in customer.check(&requirements) {
+//!     println!("{failure}");
+//! }
+//! ```
+
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+};
+
+use crate::std::{
+    caps::{CapValue, Caps, CapsDifference},
+    types::{AdapterIdentifier, DisplayMode},
+};
+
+/// Represents the capabilities and display modes of an adapter.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Profile {
+    pub identifier: AdapterIdentifier,
+    pub caps: Caps,
+    pub display_modes: Vec<DisplayMode>,
+}
+
+impl Profile {
+    /// Returns how `other` differs from `self`.
+    pub fn diff(&self, other: &Profile) -> ProfileDiff {
+        ProfileDiff {
+            caps: self.caps.diff(&other.caps),
+            removed_display_modes: self
+                .display_modes
+                .iter()
+                .filter(|mode| !other.display_modes.contains(mode))
+                .cloned()
+                .collect(),
+            added_display_modes: other
+                .display_modes
+                .iter()
+                .filter(|mode| !self.display_modes.contains(mode))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Returns every requirement which the profile does not meet.
+    pub fn check(&self, requirements: &[Requirement]) -> Vec<RequirementFailure> {
+        requirements
+            .iter()
+            .filter_map(|requirement| requirement.check(self).err())
+            .collect()
+    }
+}
+
+/// Represents the differences between two [`Profile`]s.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProfileDiff {
+    pub caps: Vec<CapsDifference>,
+    /// Display modes which are only present in the first profile.
+    pub removed_display_modes: Vec<DisplayMode>,
+    /// Display modes which are only present in the second profile.
+    pub added_display_modes: Vec<DisplayMode>,
+}
+
+impl ProfileDiff {
+    pub fn is_empty(&self) -> bool {
+        self.caps.is_empty()
+            && self.removed_display_modes.is_empty()
+            && self.added_display_modes.is_empty()
+    }
+}
+
+/// Represents a capability which a [`Profile`] must have.
+///
+/// Capabilities are referred to by the names returned from [`Caps::values`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Requirement {
+    /// Vertex and pixel shaders of at least the given version.
+    ShaderModel {
+        major: u8,
+        minor: u8,
+    },
+    VertexShader {
+        major: u8,
+        minor: u8,
+    },
+    PixelShader {
+        major: u8,
+        minor: u8,
+    },
+    /// A flag which must be set, such as `raster_caps.anisotropy`.
+    Flag(String),
+    /// A numeric capability with a minimum value, such as `max_texture_width`.
+    AtLeast {
+        name: String,
+        value: f64,
+    },
+    /// A display mode of at least the given resolution.
+    Resolution {
+        width: u32,
+        height: u32,
+    },
+}
+
+impl Requirement {
+    /// Checks the requirement against a profile.
+    pub fn check(&self, profile: &Profile) -> Result<(), RequirementFailure> {
+        let caps = &profile.caps;
+
+        let (met, found) = match self {
+            Self::ShaderModel { major, minor } => (
+                caps.supports_shader_model(*major, *minor),
+                [caps.vertex_shader_version, caps.pixel_shader_version]
+                    .into_iter()
+                    .min_by_key(|version| version.map(|version| (version.major, version.minor)))
+                    .map(CapValue::ShaderVersion),
+            ),
+            Self::VertexShader { major, minor } => (
+                caps.supports_vertex_shader(*major, *minor),
+                Some(CapValue::ShaderVersion(caps.vertex_shader_version)),
+            ),
+            Self::PixelShader { major, minor } => (
+                caps.supports_pixel_shader(*major, *minor),
+                Some(CapValue::ShaderVersion(caps.pixel_shader_version)),
+            ),
+            Self::Flag(name) => {
+                let found = caps.get(name);
+                (found == Some(CapValue::Bool(true)), found)
+            }
+            Self::AtLeast { name, value } => {
+                let found = caps.get(name);
+                let met = found
+                    .and_then(|found| found.as_f64())
+                    .is_some_and(|found| found >= *value);
+                (met, found)
+            }
+            Self::Resolution { width, height } => (
+                profile
+                    .display_modes
+                    .iter()
+                    .any(|mode| mode.width >= *width && mode.height >= *height),
+                None,
+            ),
+        };
+
+        if met {
+            Ok(())
+        } else {
+            Err(RequirementFailure {
+                requirement: self.clone(),
+                found,
+            })
+        }
+    }
+}
+
+impl Display for Requirement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ShaderModel { major, minor } => write!(f, "shader model {major}.{minor}"),
+            Self::VertexShader { major, minor } => write!(f, "vertex shader {major}.{minor}"),
+            Self::PixelShader { major, minor } => write!(f, "pixel shader {major}.{minor}"),
+            Self::Flag(name) => write!(f, "{name}"),
+            Self::AtLeast { name, value } => write!(f, "{name} >= {value}"),
+            Self::Resolution { width, height } => write!(f, "display mode of {width}x{height}"),
+        }
+    }
+}
+
+/// Represents a [`Requirement`] which a [`Profile`] does not meet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequirementFailure {
+    pub requirement: Requirement,
+    /// The value found in the profile, or [`None`] if there is no single relevant value.
+    pub found: Option<CapValue>,
+}
+
+impl Display for RequirementFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.found {
+            Some(found) => write!(f, "requires {}, found {found}", self.requirement),
+            None => write!(f, "requires {}", self.requirement),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use super::*;
+    use crate::{
+        std::types::Format,
+        sys::{d3d9caps::D3DCAPS9, GUID},
+    };
+
+    fn caps(f: impl FnOnce(&mut D3DCAPS9)) -> Caps {
+        let mut caps: D3DCAPS9 = unsafe { mem::zeroed() };
+        caps.DeviceType = 1;
+        f(&mut caps);
+        Caps::from_raw(&caps).unwrap()
+    }
+
+    fn profile(caps: Caps, modes: &[(u32, u32)]) -> Profile {
+        Profile {
+            identifier: AdapterIdentifier {
+                driver: "nvldumd.dll".to_string(),
+                description: "GeForce".to_string(),
+                device_name: "\\\\.\\DISPLAY1".to_string(),
+                driver_version: 123,
+                vendor_id: 0x10DE,
+                device_id: 1,
+                sub_sys_id: 2,
+                revision: 3,
+                device_identifier: GUID {
+                    Data1: 0xD7B71E3E,
+                    Data2: 0x4340,
+                    Data3: 0x11CF,
+                    Data4: [0xA5, 0x00, 0x00, 0xAA, 0x00, 0xC2, 0xC3, 0x35],
+                },
+                whql_level: 1,
+            },
+            caps,
+            display_modes: modes
+                .iter()
+                .map(|&(width, height)| DisplayMode {
+                    width,
+                    height,
+                    refresh_rate: 60,
+                    format: Format::X8R8G8B8,
+                })
+                .collect(),
+        }
+    }
+
+    fn high_end() -> Profile {
+        profile(
+            caps(|caps| {
+                caps.PixelShaderVersion = 0xFFFF0300;
+                caps.VertexShaderVersion = 0xFFFE0300;
+                caps.RasterCaps = 1 << 24;
+                caps.MaxTextureWidth = 8192;
+            }),
+            &[(1920, 1080), (1280, 720)],
+        )
+    }
+
+    fn low_end() -> Profile {
+        profile(
+            caps(|caps| {
+                caps.PixelShaderVersion = 0xFFFF0200;
+                caps.VertexShaderVersion = 0xFFFE0300;
+                caps.MaxTextureWidth = 2048;
+            }),
+            &[(1280, 720), (800, 600)],
+        )
+    }
+
+    fn requirements() -> Vec<Requirement> {
+        vec![
+            Requirement::ShaderModel { major: 3, minor: 0 },
+            Requirement::Flag("raster_caps.scissor_test".to_string()),
+            Requirement::AtLeast {
+                name: "max_texture_width".to_string(),
+                value: 4096.0,
+            },
+            Requirement::Resolution {
+                width: 1920,
+                height: 1080,
+            },
+            Requirement::Flag("unknown".to_string()),
+        ]
+    }
+
+    #[test]
+    fn diffs_caps_and_display_modes() {
+        let (high_end, low_end) = (high_end(), low_end());
+        assert!(high_end.diff(&high_end).is_empty());
+
+        let diff = high_end.diff(&low_end);
+        let names = diff
+            .caps
+            .iter()
+            .map(|difference| difference.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "raster_caps.scissor_test",
+                "max_texture_width",
+                "pixel_shader_version"
+            ]
+        );
+        assert_eq!(diff.caps[2].to_string(), "pixel_shader_version: 3.0 -> 2.0");
+        assert_eq!(diff.removed_display_modes.len(), 1);
+        assert_eq!(diff.removed_display_modes[0].width, 1920);
+        assert_eq!(diff.added_display_modes.len(), 1);
+        assert_eq!(diff.added_display_modes[0].width, 800);
+    }
+
+    #[test]
+    fn checks_requirements() {
+        let requirements = requirements();
+
+        let failures = high_end().check(&requirements);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].found, None);
+
+        let failures = low_end().check(&requirements);
+        assert_eq!(failures.len(), 5);
+        assert_eq!(
+            failures[0].to_string(),
+            "requires shader model 3.0, found 2.0"
+        );
+        assert_eq!(failures[2].found, Some(CapValue::Uint(2048)));
+        assert_eq!(
+            failures[3].to_string(),
+            "requires display mode of 1920x1080"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_through_serde() {
+        let profile = high_end();
+        let json = serde_json::to_string_pretty(&profile).unwrap();
+        assert!(json.contains("D7B71E3E-4340-11CF-A500-00AA00C2C335"));
+        assert!(json.contains("X8R8G8B8"));
+
+        let parsed: Profile = serde_json::from_str(&json).unwrap();
+        assert!(profile.diff(&parsed).is_empty());
+        assert_eq!(
+            format!("{:?}", profile.identifier),
+            format!("{:?}", parsed.identifier)
+        );
+
+        let requirements = requirements();
+        let json = serde_json::to_string(&requirements).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<Requirement>>(&json).unwrap(),
+            requirements
+        );
+    }
+}
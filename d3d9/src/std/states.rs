@@ -0,0 +1,1115 @@
+//! Provides typed render, sampler and texture stage states.
+//!
+//! Each state pairs the `D3DRS_*`, `D3DSAMP_*` or `D3DTSS_*` type with a typed value. Encoding and
+//! decoding the raw `DWORD` values never touches Direct3D.
+//!
+//! # Usage
+//!
+//! ```rs
+//! device.set_render_state(RenderState::AlphaBlendEnable(true))?;
+//! device.set_render_state(RenderState::SrcBlend(Blend::SrcAlpha))?;
+//! device.set_render_state(RenderState::DestBlend(Blend::InvSrcAlpha))?;
+//! device.set_sampler_state(0, SamplerState::MinFilter(TextureFilterType::Linear))?;
+//!
+//! let cull = device.get_render_state(RenderStateType::CullMode)?;
+//! assert_eq!(cull, RenderState::CullMode(Cull::Ccw));
+//! ```
+
+use crate::{
+    error::WindowsError,
+    std::types::Color,
+    sys::d3d9types::{
+        D3DBLENDOP_ADD, D3DBLENDOP_MAX, D3DBLENDOP_MIN, D3DBLENDOP_REVSUBTRACT,
+        D3DBLENDOP_SUBTRACT, D3DBLEND_BLENDFACTOR, D3DBLEND_BOTHINVSRCALPHA, D3DBLEND_BOTHSRCALPHA,
+        D3DBLEND_DESTALPHA, D3DBLEND_DESTCOLOR, D3DBLEND_INVBLENDFACTOR, D3DBLEND_INVDESTALPHA,
+        D3DBLEND_INVDESTCOLOR, D3DBLEND_INVSRCALPHA, D3DBLEND_INVSRCCOLOR, D3DBLEND_INVSRCCOLOR2,
+        D3DBLEND_ONE, D3DBLEND_SRCALPHA, D3DBLEND_SRCALPHASAT, D3DBLEND_SRCCOLOR,
+        D3DBLEND_SRCCOLOR2, D3DBLEND_ZERO, D3DCMP_ALWAYS, D3DCMP_EQUAL, D3DCMP_GREATER,
+        D3DCMP_GREATEREQUAL, D3DCMP_LESS, D3DCMP_LESSEQUAL, D3DCMP_NEVER, D3DCMP_NOTEQUAL,
+        D3DCULL_CCW, D3DCULL_CW, D3DCULL_NONE, D3DDEGREE_CUBIC, D3DDEGREE_LINEAR,
+        D3DDEGREE_QUADRATIC, D3DDEGREE_QUINTIC, D3DDMT_DISABLE, D3DDMT_ENABLE, D3DFILL_POINT,
+        D3DFILL_SOLID, D3DFILL_WIREFRAME, D3DFOG_EXP, D3DFOG_EXP2, D3DFOG_LINEAR, D3DFOG_NONE,
+        D3DMCS_COLOR1, D3DMCS_COLOR2, D3DMCS_MATERIAL, D3DPATCHEDGE_CONTINUOUS,
+        D3DPATCHEDGE_DISCRETE, D3DRS_ADAPTIVETESS_W, D3DRS_ADAPTIVETESS_X, D3DRS_ADAPTIVETESS_Y,
+        D3DRS_ADAPTIVETESS_Z, D3DRS_ALPHABLENDENABLE, D3DRS_ALPHAFUNC, D3DRS_ALPHAREF,
+        D3DRS_ALPHATESTENABLE, D3DRS_AMBIENT, D3DRS_AMBIENTMATERIALSOURCE,
+        D3DRS_ANTIALIASEDLINEENABLE, D3DRS_BLENDFACTOR, D3DRS_BLENDOP, D3DRS_BLENDOPALPHA,
+        D3DRS_CCW_STENCILFAIL, D3DRS_CCW_STENCILFUNC, D3DRS_CCW_STENCILPASS,
+        D3DRS_CCW_STENCILZFAIL, D3DRS_CLIPPING, D3DRS_CLIPPLANEENABLE, D3DRS_COLORVERTEX,
+        D3DRS_COLORWRITEENABLE, D3DRS_COLORWRITEENABLE1, D3DRS_COLORWRITEENABLE2,
+        D3DRS_COLORWRITEENABLE3, D3DRS_CULLMODE, D3DRS_DEBUGMONITORTOKEN, D3DRS_DEPTHBIAS,
+        D3DRS_DESTBLEND, D3DRS_DESTBLENDALPHA, D3DRS_DIFFUSEMATERIALSOURCE, D3DRS_DITHERENABLE,
+        D3DRS_EMISSIVEMATERIALSOURCE, D3DRS_ENABLEADAPTIVETESSELLATION, D3DRS_FILLMODE,
+        D3DRS_FOGCOLOR, D3DRS_FOGDENSITY, D3DRS_FOGENABLE, D3DRS_FOGEND, D3DRS_FOGSTART,
+        D3DRS_FOGTABLEMODE, D3DRS_FOGVERTEXMODE, D3DRS_INDEXEDVERTEXBLENDENABLE, D3DRS_LASTPIXEL,
+        D3DRS_LIGHTING, D3DRS_LOCALVIEWER, D3DRS_MAXTESSELLATIONLEVEL, D3DRS_MINTESSELLATIONLEVEL,
+        D3DRS_MULTISAMPLEANTIALIAS, D3DRS_MULTISAMPLEMASK, D3DRS_NORMALDEGREE,
+        D3DRS_NORMALIZENORMALS, D3DRS_PATCHEDGESTYLE, D3DRS_POINTSCALEENABLE, D3DRS_POINTSCALE_A,
+        D3DRS_POINTSCALE_B, D3DRS_POINTSCALE_C, D3DRS_POINTSIZE, D3DRS_POINTSIZE_MAX,
+        D3DRS_POINTSIZE_MIN, D3DRS_POINTSPRITEENABLE, D3DRS_POSITIONDEGREE, D3DRS_RANGEFOGENABLE,
+        D3DRS_SCISSORTESTENABLE, D3DRS_SEPARATEALPHABLENDENABLE, D3DRS_SHADEMODE,
+        D3DRS_SLOPESCALEDEPTHBIAS, D3DRS_SPECULARENABLE, D3DRS_SPECULARMATERIALSOURCE,
+        D3DRS_SRCBLEND, D3DRS_SRCBLENDALPHA, D3DRS_SRGBWRITEENABLE, D3DRS_STENCILENABLE,
+        D3DRS_STENCILFAIL, D3DRS_STENCILFUNC, D3DRS_STENCILMASK, D3DRS_STENCILPASS,
+        D3DRS_STENCILREF, D3DRS_STENCILWRITEMASK, D3DRS_STENCILZFAIL, D3DRS_TEXTUREFACTOR,
+        D3DRS_TWEENFACTOR, D3DRS_TWOSIDEDSTENCILMODE, D3DRS_VERTEXBLEND, D3DRS_WRAP0, D3DRS_WRAP1,
+        D3DRS_WRAP10, D3DRS_WRAP11, D3DRS_WRAP12, D3DRS_WRAP13, D3DRS_WRAP14, D3DRS_WRAP15,
+        D3DRS_WRAP2, D3DRS_WRAP3, D3DRS_WRAP4, D3DRS_WRAP5, D3DRS_WRAP6, D3DRS_WRAP7, D3DRS_WRAP8,
+        D3DRS_WRAP9, D3DRS_ZENABLE, D3DRS_ZFUNC, D3DRS_ZWRITEENABLE, D3DSAMP_ADDRESSU,
+        D3DSAMP_ADDRESSV, D3DSAMP_ADDRESSW, D3DSAMP_BORDERCOLOR, D3DSAMP_DMAPOFFSET,
+        D3DSAMP_ELEMENTINDEX, D3DSAMP_MAGFILTER, D3DSAMP_MAXANISOTROPY, D3DSAMP_MAXMIPLEVEL,
+        D3DSAMP_MINFILTER, D3DSAMP_MIPFILTER, D3DSAMP_MIPMAPLODBIAS, D3DSAMP_SRGBTEXTURE,
+        D3DSHADE_FLAT, D3DSHADE_GOURAUD, D3DSHADE_PHONG, D3DSTENCILOP_DECR, D3DSTENCILOP_DECRSAT,
+        D3DSTENCILOP_INCR, D3DSTENCILOP_INCRSAT, D3DSTENCILOP_INVERT, D3DSTENCILOP_KEEP,
+        D3DSTENCILOP_REPLACE, D3DSTENCILOP_ZERO, D3DTADDRESS_BORDER, D3DTADDRESS_CLAMP,
+        D3DTADDRESS_MIRROR, D3DTADDRESS_MIRRORONCE, D3DTADDRESS_WRAP, D3DTA_ALPHAREPLICATE,
+        D3DTA_COMPLEMENT, D3DTA_CONSTANT, D3DTA_CURRENT, D3DTA_DIFFUSE, D3DTA_SELECTMASK,
+        D3DTA_SPECULAR, D3DTA_TEMP, D3DTA_TEXTURE, D3DTA_TFACTOR, D3DTEXF_ANISOTROPIC,
+        D3DTEXF_CONVOLUTIONMONO, D3DTEXF_GAUSSIANQUAD, D3DTEXF_LINEAR, D3DTEXF_NONE, D3DTEXF_POINT,
+        D3DTEXF_PYRAMIDALQUAD, D3DTOP_ADD, D3DTOP_ADDSIGNED, D3DTOP_ADDSIGNED2X, D3DTOP_ADDSMOOTH,
+        D3DTOP_BLENDCURRENTALPHA, D3DTOP_BLENDDIFFUSEALPHA, D3DTOP_BLENDFACTORALPHA,
+        D3DTOP_BLENDTEXTUREALPHA, D3DTOP_BLENDTEXTUREALPHAPM, D3DTOP_BUMPENVMAP,
+        D3DTOP_BUMPENVMAPLUMINANCE, D3DTOP_DISABLE, D3DTOP_DOTPRODUCT3, D3DTOP_LERP,
+        D3DTOP_MODULATE, D3DTOP_MODULATE2X, D3DTOP_MODULATE4X, D3DTOP_MODULATEALPHA_ADDCOLOR,
+        D3DTOP_MODULATECOLOR_ADDALPHA, D3DTOP_MODULATEINVALPHA_ADDCOLOR,
+        D3DTOP_MODULATEINVCOLOR_ADDALPHA, D3DTOP_MULTIPLYADD, D3DTOP_PREMODULATE,
+        D3DTOP_SELECTARG1, D3DTOP_SELECTARG2, D3DTOP_SUBTRACT, D3DTSS_ALPHAARG0, D3DTSS_ALPHAARG1,
+        D3DTSS_ALPHAARG2, D3DTSS_ALPHAOP, D3DTSS_BUMPENVLOFFSET, D3DTSS_BUMPENVLSCALE,
+        D3DTSS_BUMPENVMAT00, D3DTSS_BUMPENVMAT01, D3DTSS_BUMPENVMAT10, D3DTSS_BUMPENVMAT11,
+        D3DTSS_COLORARG0, D3DTSS_COLORARG1, D3DTSS_COLORARG2, D3DTSS_COLOROP, D3DTSS_CONSTANT,
+        D3DTSS_RESULTARG, D3DTSS_TCI_CAMERASPACENORMAL, D3DTSS_TCI_CAMERASPACEPOSITION,
+        D3DTSS_TCI_CAMERASPACEREFLECTIONVECTOR, D3DTSS_TCI_PASSTHRU, D3DTSS_TCI_SPHEREMAP,
+        D3DTSS_TEXCOORDINDEX, D3DTSS_TEXTURETRANSFORMFLAGS, D3DTTFF_PROJECTED, D3DVBF_0WEIGHTS,
+        D3DVBF_1WEIGHTS, D3DVBF_2WEIGHTS, D3DVBF_3WEIGHTS, D3DVBF_DISABLE, D3DVBF_TWEENING,
+        D3DZB_FALSE, D3DZB_TRUE, D3DZB_USEW,
+    },
+};
+use bitfield::bitfield;
+
+/// Represents an error which occurred while encoding, decoding or applying a state.
+#[derive(Debug, thiserror::Error)]
+pub enum StateError {
+    #[error("Windows Error: {from}")]
+    Windows {
+        #[from]
+        from: WindowsError,
+    },
+
+    #[error("invalid raw value {value:#X} for {state}")]
+    InvalidValue { state: &'static str, value: u32 },
+
+    #[error("value for {state} is out of range")]
+    OutOfRange { state: &'static str },
+}
+
+/// Converts a typed state value to and from its raw `DWORD`.
+trait StateValue: Sized {
+    fn encode(self) -> u32;
+
+    fn decode(value: u32) -> Option<Self>;
+}
+
+impl StateValue for bool {
+    fn encode(self) -> u32 {
+        self as u32
+    }
+
+    fn decode(value: u32) -> Option<Self> {
+        Some(value != 0)
+    }
+}
+
+impl StateValue for u8 {
+    fn encode(self) -> u32 {
+        self as u32
+    }
+
+    fn decode(value: u32) -> Option<Self> {
+        value.try_into().ok()
+    }
+}
+
+impl StateValue for u32 {
+    fn encode(self) -> u32 {
+        self
+    }
+
+    fn decode(value: u32) -> Option<Self> {
+        Some(value)
+    }
+}
+
+impl StateValue for f32 {
+    fn encode(self) -> u32 {
+        self.to_bits()
+    }
+
+    fn decode(value: u32) -> Option<Self> {
+        Some(f32::from_bits(value))
+    }
+}
+
+impl StateValue for Color {
+    fn encode(self) -> u32 {
+        self.0
+    }
+
+    fn decode(value: u32) -> Option<Self> {
+        Some(Color(value))
+    }
+}
+
+macro_rules! values {
+    ($(
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($variant:ident = $raw:ident,)*
+        }
+    )*) => {$(
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[repr(u32)]
+        pub enum $name {
+            $($variant = $raw,)*
+        }
+
+        impl $name {
+            pub fn from_raw(value: u32) -> Option<Self> {
+                match value {
+                    $($raw => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl StateValue for $name {
+            fn encode(self) -> u32 {
+                self as u32
+            }
+
+            fn decode(value: u32) -> Option<Self> {
+                Self::from_raw(value)
+            }
+        }
+    )*};
+}
+
+values! {
+    /// Represents a depth buffering mode.
+    pub enum ZBufferType {
+        False = D3DZB_FALSE,
+        True = D3DZB_TRUE,
+        UseW = D3DZB_USEW,
+    }
+
+    /// Represents how primitives are rasterized.
+    pub enum FillMode {
+        Point = D3DFILL_POINT,
+        Wireframe = D3DFILL_WIREFRAME,
+        Solid = D3DFILL_SOLID,
+    }
+
+    /// Represents how colors are interpolated across a primitive.
+    pub enum ShadeMode {
+        Flat = D3DSHADE_FLAT,
+        Gouraud = D3DSHADE_GOURAUD,
+        Phong = D3DSHADE_PHONG,
+    }
+
+    /// Represents a source or destination blend factor.
+    pub enum Blend {
+        Zero = D3DBLEND_ZERO,
+        One = D3DBLEND_ONE,
+        SrcColor = D3DBLEND_SRCCOLOR,
+        InvSrcColor = D3DBLEND_INVSRCCOLOR,
+        SrcAlpha = D3DBLEND_SRCALPHA,
+        InvSrcAlpha = D3DBLEND_INVSRCALPHA,
+        DestAlpha = D3DBLEND_DESTALPHA,
+        InvDestAlpha = D3DBLEND_INVDESTALPHA,
+        DestColor = D3DBLEND_DESTCOLOR,
+        InvDestColor = D3DBLEND_INVDESTCOLOR,
+        SrcAlphaSat = D3DBLEND_SRCALPHASAT,
+        BothSrcAlpha = D3DBLEND_BOTHSRCALPHA,
+        BothInvSrcAlpha = D3DBLEND_BOTHINVSRCALPHA,
+        BlendFactor = D3DBLEND_BLENDFACTOR,
+        InvBlendFactor = D3DBLEND_INVBLENDFACTOR,
+        SrcColor2 = D3DBLEND_SRCCOLOR2,
+        InvSrcColor2 = D3DBLEND_INVSRCCOLOR2,
+    }
+
+    /// Represents how the source and destination blend terms are combined.
+    pub enum BlendOp {
+        Add = D3DBLENDOP_ADD,
+        Subtract = D3DBLENDOP_SUBTRACT,
+        RevSubtract = D3DBLENDOP_REVSUBTRACT,
+        Min = D3DBLENDOP_MIN,
+        Max = D3DBLENDOP_MAX,
+    }
+
+    /// Represents which faces are culled, by winding order.
+    pub enum Cull {
+        None = D3DCULL_NONE,
+        Cw = D3DCULL_CW,
+        Ccw = D3DCULL_CCW,
+    }
+
+    /// Represents a comparison used by depth, alpha and stencil tests.
+    pub enum CompareFunc {
+        Never = D3DCMP_NEVER,
+        Less = D3DCMP_LESS,
+        Equal = D3DCMP_EQUAL,
+        LessEqual = D3DCMP_LESSEQUAL,
+        Greater = D3DCMP_GREATER,
+        NotEqual = D3DCMP_NOTEQUAL,
+        GreaterEqual = D3DCMP_GREATEREQUAL,
+        Always = D3DCMP_ALWAYS,
+    }
+
+    /// Represents an operation applied to the stencil buffer.
+    pub enum StencilOp {
+        Keep = D3DSTENCILOP_KEEP,
+        Zero = D3DSTENCILOP_ZERO,
+        Replace = D3DSTENCILOP_REPLACE,
+        IncrSat = D3DSTENCILOP_INCRSAT,
+        DecrSat = D3DSTENCILOP_DECRSAT,
+        Invert = D3DSTENCILOP_INVERT,
+        Incr = D3DSTENCILOP_INCR,
+        Decr = D3DSTENCILOP_DECR,
+    }
+
+    /// Represents a fog formula.
+    pub enum FogMode {
+        None = D3DFOG_NONE,
+        Exp = D3DFOG_EXP,
+        Exp2 = D3DFOG_EXP2,
+        Linear = D3DFOG_LINEAR,
+    }
+
+    /// Represents where a fixed function lighting color is taken from.
+    pub enum MaterialColorSource {
+        Material = D3DMCS_MATERIAL,
+        Color1 = D3DMCS_COLOR1,
+        Color2 = D3DMCS_COLOR2,
+    }
+
+    /// Represents the number of fixed function vertex blending weights.
+    pub enum VertexBlendFlags {
+        Disable = D3DVBF_DISABLE,
+        Weights1 = D3DVBF_1WEIGHTS,
+        Weights2 = D3DVBF_2WEIGHTS,
+        Weights3 = D3DVBF_3WEIGHTS,
+        Tweening = D3DVBF_TWEENING,
+        Weights0 = D3DVBF_0WEIGHTS,
+    }
+
+    /// Represents how patch edges are tessellated.
+    pub enum PatchEdgeStyle {
+        Discrete = D3DPATCHEDGE_DISCRETE,
+        Continuous = D3DPATCHEDGE_CONTINUOUS,
+    }
+
+    /// Represents whether debug monitoring is enabled.
+    pub enum DebugMonitorTokens {
+        Enable = D3DDMT_ENABLE,
+        Disable = D3DDMT_DISABLE,
+    }
+
+    /// Represents the degree of an N-patch interpolation.
+    pub enum DegreeType {
+        Linear = D3DDEGREE_LINEAR,
+        Quadratic = D3DDEGREE_QUADRATIC,
+        Cubic = D3DDEGREE_CUBIC,
+        Quintic = D3DDEGREE_QUINTIC,
+    }
+
+    /// Represents how texture coordinates outside `[0, 1]` are resolved.
+    pub enum TextureAddress {
+        Wrap = D3DTADDRESS_WRAP,
+        Mirror = D3DTADDRESS_MIRROR,
+        Clamp = D3DTADDRESS_CLAMP,
+        Border = D3DTADDRESS_BORDER,
+        MirrorOnce = D3DTADDRESS_MIRRORONCE,
+    }
+
+    /// Represents a texture filter.
+    pub enum TextureFilterType {
+        None = D3DTEXF_NONE,
+        Point = D3DTEXF_POINT,
+        Linear = D3DTEXF_LINEAR,
+        Anisotropic = D3DTEXF_ANISOTROPIC,
+        PyramidalQuad = D3DTEXF_PYRAMIDALQUAD,
+        GaussianQuad = D3DTEXF_GAUSSIANQUAD,
+        ConvolutionMono = D3DTEXF_CONVOLUTIONMONO,
+    }
+
+    /// Represents a fixed function texture blending operation.
+    pub enum TextureOp {
+        Disable = D3DTOP_DISABLE,
+        SelectArg1 = D3DTOP_SELECTARG1,
+        SelectArg2 = D3DTOP_SELECTARG2,
+        Modulate = D3DTOP_MODULATE,
+        Modulate2x = D3DTOP_MODULATE2X,
+        Modulate4x = D3DTOP_MODULATE4X,
+        Add = D3DTOP_ADD,
+        AddSigned = D3DTOP_ADDSIGNED,
+        AddSigned2x = D3DTOP_ADDSIGNED2X,
+        Subtract = D3DTOP_SUBTRACT,
+        AddSmooth = D3DTOP_ADDSMOOTH,
+        BlendDiffuseAlpha = D3DTOP_BLENDDIFFUSEALPHA,
+        BlendTextureAlpha = D3DTOP_BLENDTEXTUREALPHA,
+        BlendFactorAlpha = D3DTOP_BLENDFACTORALPHA,
+        BlendTextureAlphaPm = D3DTOP_BLENDTEXTUREALPHAPM,
+        BlendCurrentAlpha = D3DTOP_BLENDCURRENTALPHA,
+        PreModulate = D3DTOP_PREMODULATE,
+        ModulateAlphaAddColor = D3DTOP_MODULATEALPHA_ADDCOLOR,
+        ModulateColorAddAlpha = D3DTOP_MODULATECOLOR_ADDALPHA,
+        ModulateInvAlphaAddColor = D3DTOP_MODULATEINVALPHA_ADDCOLOR,
+        ModulateInvColorAddAlpha = D3DTOP_MODULATEINVCOLOR_ADDALPHA,
+        BumpEnvMap = D3DTOP_BUMPENVMAP,
+        BumpEnvMapLuminance = D3DTOP_BUMPENVMAPLUMINANCE,
+        DotProduct3 = D3DTOP_DOTPRODUCT3,
+        MultiplyAdd = D3DTOP_MULTIPLYADD,
+        Lerp = D3DTOP_LERP,
+    }
+
+    /// Represents the register a texture stage argument reads from.
+    pub enum TextureArgumentSource {
+        Diffuse = D3DTA_DIFFUSE,
+        Current = D3DTA_CURRENT,
+        Texture = D3DTA_TEXTURE,
+        TFactor = D3DTA_TFACTOR,
+        Specular = D3DTA_SPECULAR,
+        Temp = D3DTA_TEMP,
+        Constant = D3DTA_CONSTANT,
+    }
+
+    /// Represents how texture coordinates are generated for a texture stage.
+    pub enum TexCoordGeneration {
+        PassThru = D3DTSS_TCI_PASSTHRU,
+        CameraSpaceNormal = D3DTSS_TCI_CAMERASPACENORMAL,
+        CameraSpacePosition = D3DTSS_TCI_CAMERASPACEPOSITION,
+        CameraSpaceReflectionVector = D3DTSS_TCI_CAMERASPACEREFLECTIONVECTOR,
+        SphereMap = D3DTSS_TCI_SPHEREMAP,
+    }
+}
+
+bitfield! {
+    /// Flags for [`RenderState::ColorWriteEnable`].
+    #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ColorWriteEnable(u32);
+
+    impl Debug;
+
+    pub red, set_red: 0;
+    pub green, set_green: 1;
+    pub blue, set_blue: 2;
+    pub alpha, set_alpha: 3;
+}
+
+impl ColorWriteEnable {
+    pub const ALL: Self = Self(0xF);
+}
+
+impl Default for ColorWriteEnable {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl StateValue for ColorWriteEnable {
+    fn encode(self) -> u32 {
+        self.0
+    }
+
+    fn decode(value: u32) -> Option<Self> {
+        (value & !0xF == 0).then_some(Self(value))
+    }
+}
+
+bitfield! {
+    /// Flags for [`RenderState::Wrap0`] and the other texture coordinate wrapping states.
+    #[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub struct Wrap(u32);
+
+    impl Debug;
+
+    pub u, set_u: 0;
+    pub v, set_v: 1;
+    pub w, set_w: 2;
+    pub coord3, set_coord3: 3;
+}
+
+impl StateValue for Wrap {
+    fn encode(self) -> u32 {
+        self.0
+    }
+
+    fn decode(value: u32) -> Option<Self> {
+        (value & !0xF == 0).then_some(Self(value))
+    }
+}
+
+bitfield! {
+    /// Flags for [`RenderState::ClipPlaneEnable`], one per user clip plane.
+    #[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub struct ClipPlanes(u32);
+
+    impl Debug;
+
+    pub plane0, set_plane0: 0;
+    pub plane1, set_plane1: 1;
+    pub plane2, set_plane2: 2;
+    pub plane3, set_plane3: 3;
+    pub plane4, set_plane4: 4;
+    pub plane5, set_plane5: 5;
+}
+
+impl StateValue for ClipPlanes {
+    fn encode(self) -> u32 {
+        self.0
+    }
+
+    fn decode(value: u32) -> Option<Self> {
+        (value & !0x3F == 0).then_some(Self(value))
+    }
+}
+
+/// Represents an argument of a fixed function texture stage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureArgument {
+    pub source: TextureArgumentSource,
+    /// Inverts the argument, as in `1 - x`.
+    pub complement: bool,
+    /// Replicates the alpha channel to every color channel.
+    pub alpha_replicate: bool,
+}
+
+impl TextureArgument {
+    pub fn new(source: TextureArgumentSource) -> Self {
+        Self {
+            source,
+            complement: false,
+            alpha_replicate: false,
+        }
+    }
+}
+
+impl StateValue for TextureArgument {
+    fn encode(self) -> u32 {
+        let mut value = self.source as u32;
+        if self.complement {
+            value |= D3DTA_COMPLEMENT;
+        }
+        if self.alpha_replicate {
+            value |= D3DTA_ALPHAREPLICATE;
+        }
+
+        value
+    }
+
+    fn decode(value: u32) -> Option<Self> {
+        if value & !(D3DTA_SELECTMASK | D3DTA_COMPLEMENT | D3DTA_ALPHAREPLICATE) != 0 {
+            return None;
+        }
+
+        Some(Self {
+            source: TextureArgumentSource::from_raw(value & D3DTA_SELECTMASK)?,
+            complement: value & D3DTA_COMPLEMENT != 0,
+            alpha_replicate: value & D3DTA_ALPHAREPLICATE != 0,
+        })
+    }
+}
+
+/// Represents the texture coordinate set used by a texture stage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TexCoordIndex {
+    /// The index of the texture coordinate set in the vertex, between `0` and `7`.
+    pub index: u32,
+    pub generation: TexCoordGeneration,
+}
+
+impl StateValue for TexCoordIndex {
+    fn encode(self) -> u32 {
+        self.index | self.generation as u32
+    }
+
+    fn decode(value: u32) -> Option<Self> {
+        let index = value & 0xFFFF;
+        if index >= 8 {
+            return None;
+        }
+
+        Some(Self {
+            index,
+            generation: TexCoordGeneration::from_raw(value & 0xFFFF_0000)?,
+        })
+    }
+}
+
+/// Represents how texture coordinates are transformed for a texture stage.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TextureTransformFlags {
+    /// The number of transformed coordinates passed to the rasterizer, between `0` (disabled) and
+    /// `4`.
+    pub count: u32,
+    /// Divides the coordinates by their last element before sampling.
+    pub projected: bool,
+}
+
+impl StateValue for TextureTransformFlags {
+    fn encode(self) -> u32 {
+        if self.projected {
+            self.count | D3DTTFF_PROJECTED
+        } else {
+            self.count
+        }
+    }
+
+    fn decode(value: u32) -> Option<Self> {
+        let count = value & !D3DTTFF_PROJECTED;
+        (count <= 4).then_some(Self {
+            count,
+            projected: value & D3DTTFF_PROJECTED != 0,
+        })
+    }
+}
+
+macro_rules! states {
+    (
+        $(#[$type_meta:meta])*
+        pub enum $type_name:ident;
+
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($variant:ident($value:ty) = $raw:ident,)*
+        }
+    ) => {
+        $(#[$type_meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[repr(u32)]
+        pub enum $type_name {
+            $($variant = $raw,)*
+        }
+
+        impl $type_name {
+            pub fn from_raw(value: u32) -> Option<Self> {
+                match value {
+                    $($raw => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => stringify!($variant),)*
+                }
+            }
+        }
+
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        pub enum $name {
+            $($variant($value),)*
+        }
+
+        impl $name {
+            pub fn kind(&self) -> $type_name {
+                match self {
+                    $(Self::$variant(_) => $type_name::$variant,)*
+                }
+            }
+
+            /// Returns the raw state type and value, or an error if the value is out of range.
+            pub fn to_raw(&self) -> Result<(u32, u32), StateError> {
+                if !self.in_range() {
+                    return Err(StateError::OutOfRange {
+                        state: self.kind().name(),
+                    });
+                }
+
+                let value = match *self {
+                    $(Self::$variant(value) => StateValue::encode(value),)*
+                };
+
+                Ok((self.kind() as u32, value))
+            }
+
+            /// Decodes a raw value of the state `kind`.
+            pub fn from_raw(kind: $type_name, value: u32) -> Result<Self, StateError> {
+                let state = match kind {
+                    $($type_name::$variant => StateValue::decode(value).map(Self::$variant),)*
+                };
+
+                state.ok_or(StateError::InvalidValue {
+                    state: kind.name(),
+                    value,
+                })
+            }
+        }
+    };
+}
+
+states! {
+    /// Represents the type of a [`RenderState`].
+    pub enum RenderStateType;
+
+    /// Represents a render state along with its value.
+    pub enum RenderState {
+        ZEnable(ZBufferType) = D3DRS_ZENABLE,
+        FillMode(FillMode) = D3DRS_FILLMODE,
+        ShadeMode(ShadeMode) = D3DRS_SHADEMODE,
+        ZWriteEnable(bool) = D3DRS_ZWRITEENABLE,
+        AlphaTestEnable(bool) = D3DRS_ALPHATESTENABLE,
+        LastPixel(bool) = D3DRS_LASTPIXEL,
+        SrcBlend(Blend) = D3DRS_SRCBLEND,
+        DestBlend(Blend) = D3DRS_DESTBLEND,
+        CullMode(Cull) = D3DRS_CULLMODE,
+        ZFunc(CompareFunc) = D3DRS_ZFUNC,
+        AlphaRef(u8) = D3DRS_ALPHAREF,
+        AlphaFunc(CompareFunc) = D3DRS_ALPHAFUNC,
+        DitherEnable(bool) = D3DRS_DITHERENABLE,
+        AlphaBlendEnable(bool) = D3DRS_ALPHABLENDENABLE,
+        FogEnable(bool) = D3DRS_FOGENABLE,
+        SpecularEnable(bool) = D3DRS_SPECULARENABLE,
+        FogColor(Color) = D3DRS_FOGCOLOR,
+        FogTableMode(FogMode) = D3DRS_FOGTABLEMODE,
+        FogStart(f32) = D3DRS_FOGSTART,
+        FogEnd(f32) = D3DRS_FOGEND,
+        FogDensity(f32) = D3DRS_FOGDENSITY,
+        RangeFogEnable(bool) = D3DRS_RANGEFOGENABLE,
+        StencilEnable(bool) = D3DRS_STENCILENABLE,
+        StencilFail(StencilOp) = D3DRS_STENCILFAIL,
+        StencilZFail(StencilOp) = D3DRS_STENCILZFAIL,
+        StencilPass(StencilOp) = D3DRS_STENCILPASS,
+        StencilFunc(CompareFunc) = D3DRS_STENCILFUNC,
+        StencilRef(u32) = D3DRS_STENCILREF,
+        StencilMask(u32) = D3DRS_STENCILMASK,
+        StencilWriteMask(u32) = D3DRS_STENCILWRITEMASK,
+        TextureFactor(Color) = D3DRS_TEXTUREFACTOR,
+        Wrap0(Wrap) = D3DRS_WRAP0,
+        Wrap1(Wrap) = D3DRS_WRAP1,
+        Wrap2(Wrap) = D3DRS_WRAP2,
+        Wrap3(Wrap) = D3DRS_WRAP3,
+        Wrap4(Wrap) = D3DRS_WRAP4,
+        Wrap5(Wrap) = D3DRS_WRAP5,
+        Wrap6(Wrap) = D3DRS_WRAP6,
+        Wrap7(Wrap) = D3DRS_WRAP7,
+        Clipping(bool) = D3DRS_CLIPPING,
+        Lighting(bool) = D3DRS_LIGHTING,
+        Ambient(Color) = D3DRS_AMBIENT,
+        FogVertexMode(FogMode) = D3DRS_FOGVERTEXMODE,
+        ColorVertex(bool) = D3DRS_COLORVERTEX,
+        LocalViewer(bool) = D3DRS_LOCALVIEWER,
+        NormalizeNormals(bool) = D3DRS_NORMALIZENORMALS,
+        DiffuseMaterialSource(MaterialColorSource) = D3DRS_DIFFUSEMATERIALSOURCE,
+        SpecularMaterialSource(MaterialColorSource) = D3DRS_SPECULARMATERIALSOURCE,
+        AmbientMaterialSource(MaterialColorSource) = D3DRS_AMBIENTMATERIALSOURCE,
+        EmissiveMaterialSource(MaterialColorSource) = D3DRS_EMISSIVEMATERIALSOURCE,
+        VertexBlend(VertexBlendFlags) = D3DRS_VERTEXBLEND,
+        ClipPlaneEnable(ClipPlanes) = D3DRS_CLIPPLANEENABLE,
+        PointSize(f32) = D3DRS_POINTSIZE,
+        PointSizeMin(f32) = D3DRS_POINTSIZE_MIN,
+        PointSpriteEnable(bool) = D3DRS_POINTSPRITEENABLE,
+        PointScaleEnable(bool) = D3DRS_POINTSCALEENABLE,
+        PointScaleA(f32) = D3DRS_POINTSCALE_A,
+        PointScaleB(f32) = D3DRS_POINTSCALE_B,
+        PointScaleC(f32) = D3DRS_POINTSCALE_C,
+        MultiSampleAntialias(bool) = D3DRS_MULTISAMPLEANTIALIAS,
+        MultiSampleMask(u32) = D3DRS_MULTISAMPLEMASK,
+        PatchEdgeStyle(PatchEdgeStyle) = D3DRS_PATCHEDGESTYLE,
+        DebugMonitorToken(DebugMonitorTokens) = D3DRS_DEBUGMONITORTOKEN,
+        PointSizeMax(f32) = D3DRS_POINTSIZE_MAX,
+        IndexedVertexBlendEnable(bool) = D3DRS_INDEXEDVERTEXBLENDENABLE,
+        ColorWriteEnable(ColorWriteEnable) = D3DRS_COLORWRITEENABLE,
+        TweenFactor(f32) = D3DRS_TWEENFACTOR,
+        BlendOp(BlendOp) = D3DRS_BLENDOP,
+        PositionDegree(DegreeType) = D3DRS_POSITIONDEGREE,
+        NormalDegree(DegreeType) = D3DRS_NORMALDEGREE,
+        ScissorTestEnable(bool) = D3DRS_SCISSORTESTENABLE,
+        SlopeScaleDepthBias(f32) = D3DRS_SLOPESCALEDEPTHBIAS,
+        AntialiasedLineEnable(bool) = D3DRS_ANTIALIASEDLINEENABLE,
+        MinTessellationLevel(f32) = D3DRS_MINTESSELLATIONLEVEL,
+        MaxTessellationLevel(f32) = D3DRS_MAXTESSELLATIONLEVEL,
+        AdaptiveTessX(f32) = D3DRS_ADAPTIVETESS_X,
+        AdaptiveTessY(f32) = D3DRS_ADAPTIVETESS_Y,
+        AdaptiveTessZ(f32) = D3DRS_ADAPTIVETESS_Z,
+        AdaptiveTessW(f32) = D3DRS_ADAPTIVETESS_W,
+        EnableAdaptiveTessellation(bool) = D3DRS_ENABLEADAPTIVETESSELLATION,
+        TwoSidedStencilMode(bool) = D3DRS_TWOSIDEDSTENCILMODE,
+        CcwStencilFail(StencilOp) = D3DRS_CCW_STENCILFAIL,
+        CcwStencilZFail(StencilOp) = D3DRS_CCW_STENCILZFAIL,
+        CcwStencilPass(StencilOp) = D3DRS_CCW_STENCILPASS,
+        CcwStencilFunc(CompareFunc) = D3DRS_CCW_STENCILFUNC,
+        ColorWriteEnable1(ColorWriteEnable) = D3DRS_COLORWRITEENABLE1,
+        ColorWriteEnable2(ColorWriteEnable) = D3DRS_COLORWRITEENABLE2,
+        ColorWriteEnable3(ColorWriteEnable) = D3DRS_COLORWRITEENABLE3,
+        BlendFactor(Color) = D3DRS_BLENDFACTOR,
+        SrgbWriteEnable(bool) = D3DRS_SRGBWRITEENABLE,
+        DepthBias(f32) = D3DRS_DEPTHBIAS,
+        Wrap8(Wrap) = D3DRS_WRAP8,
+        Wrap9(Wrap) = D3DRS_WRAP9,
+        Wrap10(Wrap) = D3DRS_WRAP10,
+        Wrap11(Wrap) = D3DRS_WRAP11,
+        Wrap12(Wrap) = D3DRS_WRAP12,
+        Wrap13(Wrap) = D3DRS_WRAP13,
+        Wrap14(Wrap) = D3DRS_WRAP14,
+        Wrap15(Wrap) = D3DRS_WRAP15,
+        SeparateAlphaBlendEnable(bool) = D3DRS_SEPARATEALPHABLENDENABLE,
+        SrcBlendAlpha(Blend) = D3DRS_SRCBLENDALPHA,
+        DestBlendAlpha(Blend) = D3DRS_DESTBLENDALPHA,
+        BlendOpAlpha(BlendOp) = D3DRS_BLENDOPALPHA,
+    }
+}
+
+impl RenderState {
+    /// Returns the value Direct3D assigns to the state `kind` when a device is created.
+    ///
+    /// [`RenderStateType::ZEnable`] defaults to [`ZBufferType::True`] only when the device was
+    /// created with an automatic depth stencil, which is assumed here.
+    pub fn default_for(kind: RenderStateType) -> Self {
+        use RenderStateType as Type;
+
+        let no_wrap = Wrap::default();
+        let white = Color(0xFFFF_FFFF);
+
+        match kind {
+            Type::ZEnable => Self::ZEnable(ZBufferType::True),
+            Type::FillMode => Self::FillMode(FillMode::Solid),
+            Type::ShadeMode => Self::ShadeMode(ShadeMode::Gouraud),
+            Type::ZWriteEnable => Self::ZWriteEnable(true),
+            Type::AlphaTestEnable => Self::AlphaTestEnable(false),
+            Type::LastPixel => Self::LastPixel(true),
+            Type::SrcBlend => Self::SrcBlend(Blend::One),
+            Type::DestBlend => Self::DestBlend(Blend::Zero),
+            Type::CullMode => Self::CullMode(Cull::Ccw),
+            Type::ZFunc => Self::ZFunc(CompareFunc::LessEqual),
+            Type::AlphaRef => Self::AlphaRef(0),
+            Type::AlphaFunc => Self::AlphaFunc(CompareFunc::Always),
+            Type::DitherEnable => Self::DitherEnable(false),
+            Type::AlphaBlendEnable => Self::AlphaBlendEnable(false),
+            Type::FogEnable => Self::FogEnable(false),
+            Type::SpecularEnable => Self::SpecularEnable(false),
+            Type::FogColor => Self::FogColor(Color(0)),
+            Type::FogTableMode => Self::FogTableMode(FogMode::None),
+            Type::FogStart => Self::FogStart(0.0),
+            Type::FogEnd => Self::FogEnd(1.0),
+            Type::FogDensity => Self::FogDensity(1.0),
+            Type::RangeFogEnable => Self::RangeFogEnable(false),
+            Type::StencilEnable => Self::StencilEnable(false),
+            Type::StencilFail => Self::StencilFail(StencilOp::Keep),
+            Type::StencilZFail => Self::StencilZFail(StencilOp::Keep),
+            Type::StencilPass => Self::StencilPass(StencilOp::Keep),
+            Type::StencilFunc => Self::StencilFunc(CompareFunc::Always),
+            Type::StencilRef => Self::StencilRef(0),
+            Type::StencilMask => Self::StencilMask(0xFFFF_FFFF),
+            Type::StencilWriteMask => Self::StencilWriteMask(0xFFFF_FFFF),
+            Type::TextureFactor => Self::TextureFactor(white),
+            Type::Wrap0 => Self::Wrap0(no_wrap),
+            Type::Wrap1 => Self::Wrap1(no_wrap),
+            Type::Wrap2 => Self::Wrap2(no_wrap),
+            Type::Wrap3 => Self::Wrap3(no_wrap),
+            Type::Wrap4 => Self::Wrap4(no_wrap),
+            Type::Wrap5 => Self::Wrap5(no_wrap),
+            Type::Wrap6 => Self::Wrap6(no_wrap),
+            Type::Wrap7 => Self::Wrap7(no_wrap),
+            Type::Clipping => Self::Clipping(true),
+            Type::Lighting => Self::Lighting(true),
+            Type::Ambient => Self::Ambient(Color(0)),
+            Type::FogVertexMode => Self::FogVertexMode(FogMode::None),
+            Type::ColorVertex => Self::ColorVertex(true),
+            Type::LocalViewer => Self::LocalViewer(true),
+            Type::NormalizeNormals => Self::NormalizeNormals(false),
+            Type::DiffuseMaterialSource => Self::DiffuseMaterialSource(MaterialColorSource::Color1),
+            Type::SpecularMaterialSource => {
+                Self::SpecularMaterialSource(MaterialColorSource::Color2)
+            }
+            Type::AmbientMaterialSource => {
+                Self::AmbientMaterialSource(MaterialColorSource::Material)
+            }
+            Type::EmissiveMaterialSource => {
+                Self::EmissiveMaterialSource(MaterialColorSource::Material)
+            }
+            Type::VertexBlend => Self::VertexBlend(VertexBlendFlags::Disable),
+            Type::ClipPlaneEnable => Self::ClipPlaneEnable(ClipPlanes::default()),
+            Type::PointSize => Self::PointSize(1.0),
+            Type::PointSizeMin => Self::PointSizeMin(1.0),
+            Type::PointSpriteEnable => Self::PointSpriteEnable(false),
+            Type::PointScaleEnable => Self::PointScaleEnable(false),
+            Type::PointScaleA => Self::PointScaleA(1.0),
+            Type::PointScaleB => Self::PointScaleB(0.0),
+            Type::PointScaleC => Self::PointScaleC(0.0),
+            Type::MultiSampleAntialias => Self::MultiSampleAntialias(true),
+            Type::MultiSampleMask => Self::MultiSampleMask(0xFFFF_FFFF),
+            Type::PatchEdgeStyle => Self::PatchEdgeStyle(PatchEdgeStyle::Discrete),
+            Type::DebugMonitorToken => Self::DebugMonitorToken(DebugMonitorTokens::Enable),
+            Type::PointSizeMax => Self::PointSizeMax(64.0),
+            Type::IndexedVertexBlendEnable => Self::IndexedVertexBlendEnable(false),
+            Type::ColorWriteEnable => Self::ColorWriteEnable(ColorWriteEnable::ALL),
+            Type::TweenFactor => Self::TweenFactor(0.0),
+            Type::BlendOp => Self::BlendOp(BlendOp::Add),
+            Type::PositionDegree => Self::PositionDegree(DegreeType::Cubic),
+            Type::NormalDegree => Self::NormalDegree(DegreeType::Linear),
+            Type::ScissorTestEnable => Self::ScissorTestEnable(false),
+            Type::SlopeScaleDepthBias => Self::SlopeScaleDepthBias(0.0),
+            Type::AntialiasedLineEnable => Self::AntialiasedLineEnable(false),
+            Type::MinTessellationLevel => Self::MinTessellationLevel(1.0),
+            Type::MaxTessellationLevel => Self::MaxTessellationLevel(1.0),
+            Type::AdaptiveTessX => Self::AdaptiveTessX(0.0),
+            Type::AdaptiveTessY => Self::AdaptiveTessY(0.0),
+            Type::AdaptiveTessZ => Self::AdaptiveTessZ(1.0),
+            Type::AdaptiveTessW => Self::AdaptiveTessW(0.0),
+            Type::EnableAdaptiveTessellation => Self::EnableAdaptiveTessellation(false),
+            Type::TwoSidedStencilMode => Self::TwoSidedStencilMode(false),
+            Type::CcwStencilFail => Self::CcwStencilFail(StencilOp::Keep),
+            Type::CcwStencilZFail => Self::CcwStencilZFail(StencilOp::Keep),
+            Type::CcwStencilPass => Self::CcwStencilPass(StencilOp::Keep),
+            Type::CcwStencilFunc => Self::CcwStencilFunc(CompareFunc::Always),
+            Type::ColorWriteEnable1 => Self::ColorWriteEnable1(ColorWriteEnable::ALL),
+            Type::ColorWriteEnable2 => Self::ColorWriteEnable2(ColorWriteEnable::ALL),
+            Type::ColorWriteEnable3 => Self::ColorWriteEnable3(ColorWriteEnable::ALL),
+            Type::BlendFactor => Self::BlendFactor(white),
+            Type::SrgbWriteEnable => Self::SrgbWriteEnable(false),
+            Type::DepthBias => Self::DepthBias(0.0),
+            Type::Wrap8 => Self::Wrap8(no_wrap),
+            Type::Wrap9 => Self::Wrap9(no_wrap),
+            Type::Wrap10 => Self::Wrap10(no_wrap),
+            Type::Wrap11 => Self::Wrap11(no_wrap),
+            Type::Wrap12 => Self::Wrap12(no_wrap),
+            Type::Wrap13 => Self::Wrap13(no_wrap),
+            Type::Wrap14 => Self::Wrap14(no_wrap),
+            Type::Wrap15 => Self::Wrap15(no_wrap),
+            Type::SeparateAlphaBlendEnable => Self::SeparateAlphaBlendEnable(false),
+            Type::SrcBlendAlpha => Self::SrcBlendAlpha(Blend::One),
+            Type::DestBlendAlpha => Self::DestBlendAlpha(Blend::Zero),
+            Type::BlendOpAlpha => Self::BlendOpAlpha(BlendOp::Add),
+        }
+    }
+
+    fn in_range(&self) -> bool {
+        match *self {
+            Self::FogDensity(value)
+            | Self::PointSize(value)
+            | Self::PointSizeMin(value)
+            | Self::PointSizeMax(value)
+            | Self::MinTessellationLevel(value)
+            | Self::MaxTessellationLevel(value) => value.is_finite() && value >= 0.0,
+            Self::TweenFactor(value) => (0.0..=1.0).contains(&value),
+            Self::FogStart(value)
+            | Self::FogEnd(value)
+            | Self::PointScaleA(value)
+            | Self::PointScaleB(value)
+            | Self::PointScaleC(value)
+            | Self::SlopeScaleDepthBias(value)
+            | Self::DepthBias(value)
+            | Self::AdaptiveTessX(value)
+            | Self::AdaptiveTessY(value)
+            | Self::AdaptiveTessZ(value)
+            | Self::AdaptiveTessW(value) => value.is_finite(),
+            Self::ColorWriteEnable(flags)
+            | Self::ColorWriteEnable1(flags)
+            | Self::ColorWriteEnable2(flags)
+            | Self::ColorWriteEnable3(flags) => flags.0 & !0xF == 0,
+            Self::Wrap0(flags)
+            | Self::Wrap1(flags)
+            | Self::Wrap2(flags)
+            | Self::Wrap3(flags)
+            | Self::Wrap4(flags)
+            | Self::Wrap5(flags)
+            | Self::Wrap6(flags)
+            | Self::Wrap7(flags)
+            | Self::Wrap8(flags)
+            | Self::Wrap9(flags)
+            | Self::Wrap10(flags)
+            | Self::Wrap11(flags)
+            | Self::Wrap12(flags)
+            | Self::Wrap13(flags)
+            | Self::Wrap14(flags)
+            | Self::Wrap15(flags) => flags.0 & !0xF == 0,
+            Self::ClipPlaneEnable(planes) => planes.0 & !0x3F == 0,
+            _ => true,
+        }
+    }
+}
+
+states! {
+    /// Represents the type of a [`SamplerState`].
+    pub enum SamplerStateType;
+
+    /// Represents a sampler state along with its value.
+    pub enum SamplerState {
+        AddressU(TextureAddress) = D3DSAMP_ADDRESSU,
+        AddressV(TextureAddress) = D3DSAMP_ADDRESSV,
+        AddressW(TextureAddress) = D3DSAMP_ADDRESSW,
+        BorderColor(Color) = D3DSAMP_BORDERCOLOR,
+        MagFilter(TextureFilterType) = D3DSAMP_MAGFILTER,
+        MinFilter(TextureFilterType) = D3DSAMP_MINFILTER,
+        MipFilter(TextureFilterType) = D3DSAMP_MIPFILTER,
+        MipMapLodBias(f32) = D3DSAMP_MIPMAPLODBIAS,
+        MaxMipLevel(u32) = D3DSAMP_MAXMIPLEVEL,
+        MaxAnisotropy(u32) = D3DSAMP_MAXANISOTROPY,
+        SrgbTexture(bool) = D3DSAMP_SRGBTEXTURE,
+        ElementIndex(u32) = D3DSAMP_ELEMENTINDEX,
+        DMapOffset(u32) = D3DSAMP_DMAPOFFSET,
+    }
+}
+
+impl SamplerState {
+    fn in_range(&self) -> bool {
+        match *self {
+            Self::MagFilter(filter) => !matches!(filter, TextureFilterType::None),
+            Self::MipMapLodBias(value) => value.is_finite(),
+            Self::MaxAnisotropy(value) => value >= 1,
+            _ => true,
+        }
+    }
+}
+
+states! {
+    /// Represents the type of a [`TextureStageState`].
+    pub enum TextureStageStateType;
+
+    /// Represents a fixed function texture stage state along with its value.
+    pub enum TextureStageState {
+        ColorOp(TextureOp) = D3DTSS_COLOROP,
+        ColorArg1(TextureArgument) = D3DTSS_COLORARG1,
+        ColorArg2(TextureArgument) = D3DTSS_COLORARG2,
+        AlphaOp(TextureOp) = D3DTSS_ALPHAOP,
+        AlphaArg1(TextureArgument) = D3DTSS_ALPHAARG1,
+        AlphaArg2(TextureArgument) = D3DTSS_ALPHAARG2,
+        BumpEnvMat00(f32) = D3DTSS_BUMPENVMAT00,
+        BumpEnvMat01(f32) = D3DTSS_BUMPENVMAT01,
+        BumpEnvMat10(f32) = D3DTSS_BUMPENVMAT10,
+        BumpEnvMat11(f32) = D3DTSS_BUMPENVMAT11,
+        TexCoordIndex(TexCoordIndex) = D3DTSS_TEXCOORDINDEX,
+        BumpEnvLScale(f32) = D3DTSS_BUMPENVLSCALE,
+        BumpEnvLOffset(f32) = D3DTSS_BUMPENVLOFFSET,
+        TextureTransformFlags(TextureTransformFlags) = D3DTSS_TEXTURETRANSFORMFLAGS,
+        ColorArg0(TextureArgument) = D3DTSS_COLORARG0,
+        AlphaArg0(TextureArgument) = D3DTSS_ALPHAARG0,
+        ResultArg(TextureArgument) = D3DTSS_RESULTARG,
+        Constant(Color) = D3DTSS_CONSTANT,
+    }
+}
+
+impl TextureStageState {
+    fn in_range(&self) -> bool {
+        match *self {
+            Self::BumpEnvMat00(value)
+            | Self::BumpEnvMat01(value)
+            | Self::BumpEnvMat10(value)
+            | Self::BumpEnvMat11(value)
+            | Self::BumpEnvLScale(value)
+            | Self::BumpEnvLOffset(value) => value.is_finite(),
+            Self::TexCoordIndex(index) => index.index < 8,
+            Self::TextureTransformFlags(flags) => flags.count <= 4,
+            // Only the current and temporary registers can be written to.
+            Self::ResultArg(argument) => matches!(
+                argument.source,
+                TextureArgumentSource::Current | TextureArgumentSource::Temp
+            ),
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_and_decodes_render_states() {
+        assert_eq!(
+            RenderState::SrcBlend(Blend::SrcAlpha).to_raw().unwrap(),
+            (D3DRS_SRCBLEND, 5)
+        );
+        assert_eq!(
+            RenderState::FogStart(2.5).to_raw().unwrap(),
+            (D3DRS_FOGSTART, 2.5f32.to_bits())
+        );
+        assert_eq!(
+            RenderState::from_raw(RenderStateType::CullMode, 2).unwrap(),
+            RenderState::CullMode(Cull::Cw)
+        );
+        assert_eq!(
+            RenderStateType::from_raw(D3DRS_BLENDOPALPHA),
+            Some(RenderStateType::BlendOpAlpha)
+        );
+        assert!(matches!(
+            RenderState::from_raw(RenderStateType::CullMode, 9),
+            Err(StateError::InvalidValue {
+                state: "CullMode",
+                value: 9,
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_out_of_range_values() {
+        assert!(matches!(
+            RenderState::PointSize(-1.0).to_raw(),
+            Err(StateError::OutOfRange { state: "PointSize" })
+        ));
+        assert!(RenderState::ColorWriteEnable(ColorWriteEnable(0x1F))
+            .to_raw()
+            .is_err());
+        assert!(SamplerState::MaxAnisotropy(0).to_raw().is_err());
+        assert!(SamplerState::MipMapLodBias(f32::NAN).to_raw().is_err());
+    }
+
+    #[test]
+    fn encodes_and_decodes_texture_stage_states() {
+        let argument = TextureArgument {
+            source: TextureArgumentSource::Texture,
+            complement: true,
+            alpha_replicate: false,
+        };
+        assert_eq!(
+            TextureStageState::ColorArg1(argument).to_raw().unwrap(),
+            (D3DTSS_COLORARG1, 0x12)
+        );
+        assert_eq!(
+            TextureStageState::from_raw(TextureStageStateType::ColorArg1, 0x12).unwrap(),
+            TextureStageState::ColorArg1(argument)
+        );
+
+        let index = TexCoordIndex {
+            index: 1,
+            generation: TexCoordGeneration::SphereMap,
+        };
+        assert_eq!(
+            TextureStageState::TexCoordIndex(index).to_raw().unwrap(),
+            (D3DTSS_TEXCOORDINDEX, 0x40001)
+        );
+        assert_eq!(
+            TextureStageState::from_raw(TextureStageStateType::TexCoordIndex, 0x40001).unwrap(),
+            TextureStageState::TexCoordIndex(index)
+        );
+        assert!(
+            TextureStageState::from_raw(TextureStageStateType::TexCoordIndex, 0x40008).is_err()
+        );
+
+        let flags = TextureTransformFlags {
+            count: 3,
+            projected: true,
+        };
+        assert_eq!(
+            TextureStageState::TextureTransformFlags(flags)
+                .to_raw()
+                .unwrap(),
+            (D3DTSS_TEXTURETRANSFORMFLAGS, 0x103)
+        );
+        assert!(TextureStageState::ResultArg(argument).to_raw().is_err());
+    }
+
+    #[test]
+    fn render_state_defaults_round_trip() {
+        for kind in (0..=u8::MAX as u32).filter_map(RenderStateType::from_raw) {
+            let state = RenderState::default_for(kind);
+            assert_eq!(state.kind(), kind);
+
+            let (raw_kind, value) = state.to_raw().unwrap();
+            assert_eq!(raw_kind, kind as u32);
+            assert_eq!(RenderState::from_raw(kind, value).unwrap(), state);
+        }
+    }
+}
@@ -1,73 +1,141 @@
 //! Provides wrappers/Rusty types over the `d3d9types.h` file.
 
-use std::{ffi::CStr, mem::transmute, ptr};
+use std::{
+    cmp::Reverse,
+    ffi::CStr,
+    fmt,
+    fmt::{Debug, Formatter},
+    mem::transmute,
+    ptr,
+};
 
 use bitfield::bitfield;
-use winapi::{
-    shared::{
-        d3d9::D3DADAPTER_DEFAULT,
-        d3d9caps::D3DCAPS9,
-        d3d9types::{
-            D3DFMT_CxV8U8, D3DADAPTER_IDENTIFIER9, D3DCOLOR, D3DDECLMETHOD_CROSSUV,
-            D3DDECLMETHOD_DEFAULT, D3DDECLMETHOD_LOOKUP, D3DDECLMETHOD_LOOKUPPRESAMPLED,
-            D3DDECLMETHOD_PARTIALU, D3DDECLMETHOD_PARTIALV, D3DDECLMETHOD_UV, D3DDECLTYPE_D3DCOLOR,
-            D3DDECLTYPE_DEC3N, D3DDECLTYPE_FLOAT1, D3DDECLTYPE_FLOAT16_2, D3DDECLTYPE_FLOAT16_4,
-            D3DDECLTYPE_FLOAT2, D3DDECLTYPE_FLOAT3, D3DDECLTYPE_FLOAT4, D3DDECLTYPE_SHORT2,
-            D3DDECLTYPE_SHORT2N, D3DDECLTYPE_SHORT4, D3DDECLTYPE_SHORT4N, D3DDECLTYPE_UBYTE4,
-            D3DDECLTYPE_UBYTE4N, D3DDECLTYPE_UDEC3, D3DDECLTYPE_USHORT2N, D3DDECLTYPE_USHORT4N,
-            D3DDECLUSAGE_BINORMAL, D3DDECLUSAGE_BLENDINDICES, D3DDECLUSAGE_BLENDWEIGHT,
-            D3DDECLUSAGE_COLOR, D3DDECLUSAGE_DEPTH, D3DDECLUSAGE_FOG, D3DDECLUSAGE_NORMAL,
-            D3DDECLUSAGE_POSITION, D3DDECLUSAGE_POSITIONT, D3DDECLUSAGE_PSIZE, D3DDECLUSAGE_SAMPLE,
-            D3DDECLUSAGE_TANGENT, D3DDECLUSAGE_TESSFACTOR, D3DDECLUSAGE_TEXCOORD, D3DDEVTYPE_HAL,
-            D3DDEVTYPE_NULLREF, D3DDEVTYPE_REF, D3DDEVTYPE_SW, D3DDISPLAYMODE, D3DFMT_A1,
-            D3DFMT_A16B16G16R16, D3DFMT_A1R5G5B5, D3DFMT_A2B10G10R10, D3DFMT_A2R10G10B10,
-            D3DFMT_A2W10V10U10, D3DFMT_A4L4, D3DFMT_A4R4G4B4, D3DFMT_A8, D3DFMT_A8B8G8R8,
-            D3DFMT_A8L8, D3DFMT_A8P8, D3DFMT_A8R3G3B2, D3DFMT_A8R8G8B8, D3DFMT_BINARYBUFFER,
-            D3DFMT_D15S1, D3DFMT_D16, D3DFMT_D16_LOCKABLE, D3DFMT_D24FS8, D3DFMT_D24S8,
-            D3DFMT_D24X4S4, D3DFMT_D24X8, D3DFMT_D32, D3DFMT_D32F_LOCKABLE, D3DFMT_D32_LOCKABLE,
-            D3DFMT_DXT1, D3DFMT_DXT2, D3DFMT_DXT3, D3DFMT_DXT4, D3DFMT_DXT5, D3DFMT_G16R16,
-            D3DFMT_G16R16F, D3DFMT_G32R32F, D3DFMT_G8R8_G8B8, D3DFMT_INDEX16, D3DFMT_INDEX32,
-            D3DFMT_L16, D3DFMT_L6V5U5, D3DFMT_L8, D3DFMT_P8, D3DFMT_Q16W16V16U16, D3DFMT_Q8W8V8U8,
-            D3DFMT_R16F, D3DFMT_R32F, D3DFMT_R3G3B2, D3DFMT_R5G6B5, D3DFMT_R8G8B8,
-            D3DFMT_R8G8_B8G8, D3DFMT_S8_LOCKABLE, D3DFMT_UNKNOWN, D3DFMT_UYVY, D3DFMT_V16U16,
-            D3DFMT_V8U8, D3DFMT_VERTEXDATA, D3DFMT_X1R5G5B5, D3DFMT_X4R4G4B4, D3DFMT_X8B8G8R8,
-            D3DFMT_X8L8V8U8, D3DFMT_X8R8G8B8, D3DFMT_YUY2, D3DPOOL_DEFAULT, D3DPOOL_MANAGED,
-            D3DPRESENT_PARAMETERS, D3DPT_LINELIST, D3DPT_LINESTRIP, D3DPT_POINTLIST,
-            D3DPT_TRIANGLEFAN, D3DPT_TRIANGLELIST, D3DPT_TRIANGLESTRIP,
-            D3DQUERYTYPE_BANDWIDTHTIMINGS, D3DQUERYTYPE_CACHEUTILIZATION, D3DQUERYTYPE_EVENT,
-            D3DQUERYTYPE_INTERFACETIMINGS, D3DQUERYTYPE_MEMORYPRESSURE, D3DQUERYTYPE_OCCLUSION,
-            D3DQUERYTYPE_PIPELINETIMINGS, D3DQUERYTYPE_PIXELTIMINGS, D3DQUERYTYPE_RESOURCEMANAGER,
-            D3DQUERYTYPE_TIMESTAMP, D3DQUERYTYPE_TIMESTAMPDISJOINT, D3DQUERYTYPE_TIMESTAMPFREQ,
-            D3DQUERYTYPE_VCACHE, D3DQUERYTYPE_VERTEXSTATS, D3DQUERYTYPE_VERTEXTIMINGS,
-            D3DRTYPE_CUBETEXTURE, D3DRTYPE_INDEXBUFFER, D3DRTYPE_SURFACE, D3DRTYPE_TEXTURE,
-            D3DRTYPE_VERTEXBUFFER, D3DRTYPE_VOLUME, D3DRTYPE_VOLUMETEXTURE, D3DSBT_ALL,
-            D3DSBT_PIXELSTATE, D3DSBT_VERTEXSTATE, D3DSWAPEFFECT_COPY, D3DSWAPEFFECT_DISCARD,
-            D3DSWAPEFFECT_FLIP, D3DSWAPEFFECT_OVERLAY, D3DVERTEXELEMENT9,
-        },
-        guiddef::GUID,
-        windef::HWND,
+
+use crate::sys::{
+    d3d9types::{
+        D3DFMT_CxV8U8, D3DADAPTER_IDENTIFIER9, D3DCOLOR, D3DCUBEMAP_FACE_NEGATIVE_X,
+        D3DCUBEMAP_FACE_NEGATIVE_Y, D3DCUBEMAP_FACE_NEGATIVE_Z, D3DCUBEMAP_FACE_POSITIVE_X,
+        D3DCUBEMAP_FACE_POSITIVE_Y, D3DCUBEMAP_FACE_POSITIVE_Z, D3DDECLMETHOD_CROSSUV,
+        D3DDECLMETHOD_DEFAULT, D3DDECLMETHOD_LOOKUP, D3DDECLMETHOD_LOOKUPPRESAMPLED,
+        D3DDECLMETHOD_PARTIALU, D3DDECLMETHOD_PARTIALV, D3DDECLMETHOD_UV, D3DDECLTYPE_D3DCOLOR,
+        D3DDECLTYPE_DEC3N, D3DDECLTYPE_FLOAT1, D3DDECLTYPE_FLOAT16_2, D3DDECLTYPE_FLOAT16_4,
+        D3DDECLTYPE_FLOAT2, D3DDECLTYPE_FLOAT3, D3DDECLTYPE_FLOAT4, D3DDECLTYPE_SHORT2,
+        D3DDECLTYPE_SHORT2N, D3DDECLTYPE_SHORT4, D3DDECLTYPE_SHORT4N, D3DDECLTYPE_UBYTE4,
+        D3DDECLTYPE_UBYTE4N, D3DDECLTYPE_UDEC3, D3DDECLTYPE_USHORT2N, D3DDECLTYPE_USHORT4N,
+        D3DDECLUSAGE_BINORMAL, D3DDECLUSAGE_BLENDINDICES, D3DDECLUSAGE_BLENDWEIGHT,
+        D3DDECLUSAGE_COLOR, D3DDECLUSAGE_DEPTH, D3DDECLUSAGE_FOG, D3DDECLUSAGE_NORMAL,
+        D3DDECLUSAGE_POSITION, D3DDECLUSAGE_POSITIONT, D3DDECLUSAGE_PSIZE, D3DDECLUSAGE_SAMPLE,
+        D3DDECLUSAGE_TANGENT, D3DDECLUSAGE_TESSFACTOR, D3DDECLUSAGE_TEXCOORD, D3DDEVTYPE_HAL,
+        D3DDEVTYPE_NULLREF, D3DDEVTYPE_REF, D3DDEVTYPE_SW, D3DDISPLAYMODE, D3DFMT_A1,
+        D3DFMT_A16B16G16R16, D3DFMT_A16B16G16R16F, D3DFMT_A1R5G5B5, D3DFMT_A2B10G10R10,
+        D3DFMT_A2R10G10B10, D3DFMT_A2W10V10U10, D3DFMT_A32B32G32R32F, D3DFMT_A4L4, D3DFMT_A4R4G4B4,
+        D3DFMT_A8, D3DFMT_A8B8G8R8, D3DFMT_A8L8, D3DFMT_A8P8, D3DFMT_A8R3G3B2, D3DFMT_A8R8G8B8,
+        D3DFMT_BINARYBUFFER, D3DFMT_D15S1, D3DFMT_D16, D3DFMT_D16_LOCKABLE, D3DFMT_D24FS8,
+        D3DFMT_D24S8, D3DFMT_D24X4S4, D3DFMT_D24X8, D3DFMT_D32, D3DFMT_D32F_LOCKABLE,
+        D3DFMT_D32_LOCKABLE, D3DFMT_DXT1, D3DFMT_DXT2, D3DFMT_DXT3, D3DFMT_DXT4, D3DFMT_DXT5,
+        D3DFMT_G16R16, D3DFMT_G16R16F, D3DFMT_G32R32F, D3DFMT_G8R8_G8B8, D3DFMT_INDEX16,
+        D3DFMT_INDEX32, D3DFMT_L16, D3DFMT_L6V5U5, D3DFMT_L8, D3DFMT_P8, D3DFMT_Q16W16V16U16,
+        D3DFMT_Q8W8V8U8, D3DFMT_R16F, D3DFMT_R32F, D3DFMT_R3G3B2, D3DFMT_R5G6B5, D3DFMT_R8G8B8,
+        D3DFMT_R8G8_B8G8, D3DFMT_S8_LOCKABLE, D3DFMT_UNKNOWN, D3DFMT_UYVY, D3DFMT_V16U16,
+        D3DFMT_V8U8, D3DFMT_VERTEXDATA, D3DFMT_X1R5G5B5, D3DFMT_X4R4G4B4, D3DFMT_X8B8G8R8,
+        D3DFMT_X8L8V8U8, D3DFMT_X8R8G8B8, D3DFMT_YUY2, D3DLOCKED_BOX, D3DLOCKED_RECT,
+        D3DPOOL_DEFAULT, D3DPOOL_MANAGED, D3DPOOL_SCRATCH, D3DPOOL_SYSTEMMEM,
+        D3DPRESENT_PARAMETERS, D3DPT_LINELIST, D3DPT_LINESTRIP, D3DPT_POINTLIST, D3DPT_TRIANGLEFAN,
+        D3DPT_TRIANGLELIST, D3DPT_TRIANGLESTRIP, D3DQUERYTYPE_BANDWIDTHTIMINGS,
+        D3DQUERYTYPE_CACHEUTILIZATION, D3DQUERYTYPE_EVENT, D3DQUERYTYPE_INTERFACETIMINGS,
+        D3DQUERYTYPE_MEMORYPRESSURE, D3DQUERYTYPE_OCCLUSION, D3DQUERYTYPE_PIPELINETIMINGS,
+        D3DQUERYTYPE_PIXELTIMINGS, D3DQUERYTYPE_RESOURCEMANAGER, D3DQUERYTYPE_TIMESTAMP,
+        D3DQUERYTYPE_TIMESTAMPDISJOINT, D3DQUERYTYPE_TIMESTAMPFREQ, D3DQUERYTYPE_VCACHE,
+        D3DQUERYTYPE_VERTEXSTATS, D3DQUERYTYPE_VERTEXTIMINGS, D3DRTYPE_CUBETEXTURE,
+        D3DRTYPE_INDEXBUFFER, D3DRTYPE_SURFACE, D3DRTYPE_TEXTURE, D3DRTYPE_VERTEXBUFFER,
+        D3DRTYPE_VOLUME, D3DRTYPE_VOLUMETEXTURE, D3DSBT_ALL, D3DSBT_PIXELSTATE, D3DSBT_VERTEXSTATE,
+        D3DSURFACE_DESC, D3DSWAPEFFECT_COPY, D3DSWAPEFFECT_DISCARD, D3DSWAPEFFECT_FLIP,
+        D3DSWAPEFFECT_OVERLAY, D3DVERTEXELEMENT9,
     },
-    um::wingdi::RGNDATA,
+    D3DADAPTER_DEFAULT, GUID, HMONITOR, HWND, LARGE_INTEGER, RGNDATA,
 };
 
+/// Defines an enum over the values of a Direct3D enumeration, with a variant holding any value
+/// which is not listed. Values returned by drivers are converted with [`From<u32>`], so formats
+/// and pools which this crate does not know about can never produce an invalid enum.
+///
+/// [`From<u32>`] normalises listed values to their named variant, so the catch all variant never
+/// holds one and values compare equal however they were produced.
+macro_rules! open_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($variant:ident = $raw:ident,)*
+            $(#[$other_meta:meta])+
+            $other:ident(u32),
+        }
+    ) => {
+        $(#[$meta])*
+        pub enum $name {
+            $($variant,)*
+            $(#[$other_meta])*
+            $other(u32),
+        }
+
+        impl $name {
+            /// Every listed variant, in declaration order.
+            #[cfg(test)]
+            const LISTED: &'static [Self] = &[$(Self::$variant,)*];
+        }
+
+        impl From<$name> for u32 {
+            fn from(value: $name) -> Self {
+                match value {
+                    $($name::$variant => $raw,)*
+                    $name::$other(value) => value,
+                }
+            }
+        }
+
+        // Some constants, such as `D3DFMT_CxV8U8`, are not upper case.
+        #[allow(non_upper_case_globals)]
+        impl From<u32> for $name {
+            fn from(value: u32) -> Self {
+                match value {
+                    $($raw => Self::$variant,)*
+                    value => Self::$other(value),
+                }
+            }
+        }
+    };
+}
+
 /// Represents an adapter (graphics card, integrated graphics, etc).
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(transparent)]
 pub struct Adapter(u32);
 
+impl Adapter {
+    /// Returns the adapter with the given ordinal, in the range of [`Context::get_adapter_count`].
+    pub fn new(ordinal: u32) -> Self {
+        Self(ordinal)
+    }
+
+    pub fn ordinal(self) -> u32 {
+        self.0
+    }
+}
+
 impl Default for Adapter {
     fn default() -> Self {
         Self(D3DADAPTER_DEFAULT)
     }
 }
 
-impl Into<u32> for Adapter {
-    fn into(self) -> u32 {
-        self.0
+impl From<Adapter> for u32 {
+    fn from(value: Adapter) -> Self {
+        value.0
     }
 }
 
 /// Represents the identifier structure for an [`Adapter`].
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AdapterIdentifier {
     pub driver: String,
     pub description: String,
@@ -77,26 +145,103 @@ pub struct AdapterIdentifier {
     pub device_id: u32,
     pub sub_sys_id: u32,
     pub revision: u32,
+    #[cfg_attr(feature = "serde", serde(with = "guid"))]
     pub device_identifier: GUID,
     pub whql_level: u32,
 }
 
+impl Debug for AdapterIdentifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdapterIdentifier")
+            .field("driver", &self.driver)
+            .field("description", &self.description)
+            .field("device_name", &self.device_name)
+            .field("driver_version", &self.driver_version)
+            .field("vendor_id", &self.vendor_id)
+            .field("device_id", &self.device_id)
+            .field("sub_sys_id", &self.sub_sys_id)
+            .field("revision", &self.revision)
+            .field(
+                "device_identifier",
+                &guid::to_string(&self.device_identifier),
+            )
+            .field("whql_level", &self.whql_level)
+            .finish()
+    }
+}
+
+/// Converts a [`GUID`] to and from its `XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX` form.
+mod guid {
+    use crate::sys::GUID;
+
+    pub fn to_string(guid: &GUID) -> String {
+        let d4 = guid.Data4;
+        format!(
+            "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+            guid.Data1,
+            guid.Data2,
+            guid.Data3,
+            d4[0],
+            d4[1],
+            d4[2],
+            d4[3],
+            d4[4],
+            d4[5],
+            d4[6],
+            d4[7]
+        )
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn parse(value: &str) -> Option<GUID> {
+        let hex: String = value.chars().filter(|c| *c != '-').collect();
+        if hex.len() != 32 || !hex.is_ascii() {
+            return None;
+        }
+
+        let mut data4 = [0u8; 8];
+        for (i, byte) in data4.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[16 + i * 2..18 + i * 2], 16).ok()?;
+        }
+
+        Some(GUID {
+            Data1: u32::from_str_radix(&hex[0..8], 16).ok()?,
+            Data2: u16::from_str_radix(&hex[8..12], 16).ok()?,
+            Data3: u16::from_str_radix(&hex[12..16], 16).ok()?,
+            Data4: data4,
+        })
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn serialize<S: serde::Serializer>(guid: &GUID, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_string(guid))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<GUID, D::Error> {
+        let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+        parse(&value).ok_or_else(|| serde::de::Error::custom(format!("invalid guid {value}")))
+    }
+}
+
 impl From<D3DADAPTER_IDENTIFIER9> for AdapterIdentifier {
     fn from(value: D3DADAPTER_IDENTIFIER9) -> Self {
         let c_driver = unsafe {
-            CStr::from_bytes_with_nul(transmute(value.Driver.as_ref()))
+            CStr::from_bytes_with_nul(transmute::<&[i8], &[u8]>(value.Driver.as_ref()))
                 .expect("failed to parse driver name")
         };
         let driver = c_driver.to_str().expect("failed to parse driver name");
 
         let c_description = unsafe {
-            CStr::from_bytes_with_nul(transmute(value.Description.as_ref()))
+            CStr::from_bytes_with_nul(transmute::<&[i8], &[u8]>(value.Description.as_ref()))
                 .expect("failed to parse description")
         };
         let description = c_description.to_str().expect("failed to parse description");
 
         let c_device_name = unsafe {
-            CStr::from_bytes_with_nul(transmute(value.DeviceName.as_ref()))
+            CStr::from_bytes_with_nul(transmute::<&[i8], &[u8]>(value.DeviceName.as_ref()))
                 .expect("failed to parse device name")
         };
         let device_name = c_device_name.to_str().expect("failed to parse device name");
@@ -105,7 +250,7 @@ impl From<D3DADAPTER_IDENTIFIER9> for AdapterIdentifier {
             driver: driver.to_string(),
             description: description.to_string(),
             device_name: device_name.to_string(),
-            driver_version: unsafe { transmute(value.DriverVersion) },
+            driver_version: unsafe { transmute::<LARGE_INTEGER, i64>(value.DriverVersion) },
             vendor_id: value.VendorId,
             device_id: value.DeviceId,
             sub_sys_id: value.SubSysId,
@@ -116,11 +261,22 @@ impl From<D3DADAPTER_IDENTIFIER9> for AdapterIdentifier {
     }
 }
 
+/// Represents an [`Adapter`] along with its identifier, monitor and current display mode.
+#[derive(Clone, Debug)]
+pub struct AdapterInfo {
+    pub adapter: Adapter,
+    pub identifier: AdapterIdentifier,
+    pub monitor: HMONITOR,
+    pub display_mode: DisplayMode,
+}
+
 bitfield! {
     /// Flags for [`Context::create_device`].
-    #[derive(Default)]
+    #[derive(Clone, Copy, Default, PartialEq, Eq)]
     pub struct BehaviorFlags(u32);
 
+    impl Debug;
+
     pub fpu_preserve, set_fpu_preserve: 1;
     pub multithreaded, set_multithreaded: 2;
     pub pure_device, set_pure_device: 4;
@@ -135,15 +291,15 @@ bitfield! {
     // TODO: other flags.
 }
 
-impl Into<u32> for BehaviorFlags {
-    fn into(self) -> u32 {
-        self.0
+impl From<BehaviorFlags> for u32 {
+    fn from(value: BehaviorFlags) -> Self {
+        value.0
     }
 }
 
 bitfield! {
     /// Flags for [`Device::clear`].
-    #[derive(Default)]
+    #[derive(Clone, Copy, Default, PartialEq, Eq)]
     pub struct Clear(u32);
 
     impl Debug;
@@ -170,28 +326,61 @@ impl Clear {
     }
 }
 
-impl Into<u32> for Clear {
-    fn into(self) -> u32 {
-        self.0
+impl From<Clear> for u32 {
+    fn from(value: Clear) -> Self {
+        value.0
     }
 }
 
 /// Represents a color in Direct3D9.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(transparent)]
 pub struct Color(pub u32);
 
-impl Into<D3DCOLOR> for Color {
-    fn into(self) -> D3DCOLOR {
-        self.0
+impl From<Color> for D3DCOLOR {
+    fn from(value: Color) -> Self {
+        value.0
     }
 }
 
-/// Thin wrapper around [`D3DCAPS9`].
-// TODO: Implement this as a rust-safe wrapper.
-pub struct Caps(pub D3DCAPS9);
+/// Represents the state of a device, as returned by [`Device::test_cooperative_level`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CooperativeLevel {
+    /// The device is operational.
+    #[default]
+    Ok,
+    /// The device has been lost and cannot be reset yet.
+    Lost,
+    /// The device has been lost and can now be reset.
+    NotReset,
+}
+
+/// Represents a face of a [`CubeTexture`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum CubeFace {
+    PositiveX = D3DCUBEMAP_FACE_POSITIVE_X,
+    NegativeX = D3DCUBEMAP_FACE_NEGATIVE_X,
+    PositiveY = D3DCUBEMAP_FACE_POSITIVE_Y,
+    NegativeY = D3DCUBEMAP_FACE_NEGATIVE_Y,
+    PositiveZ = D3DCUBEMAP_FACE_POSITIVE_Z,
+    NegativeZ = D3DCUBEMAP_FACE_NEGATIVE_Z,
+}
+
+impl CubeFace {
+    /// Every face, in the order they are stored in memory.
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::PositiveX,
+        CubeFace::NegativeX,
+        CubeFace::PositiveY,
+        CubeFace::NegativeY,
+        CubeFace::PositiveZ,
+        CubeFace::NegativeZ,
+    ];
+}
 
 /// Represents the type of a [`VertexElement`].
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum DeclType {
     Float1 = D3DDECLTYPE_FLOAT1 as u8,
@@ -214,7 +403,7 @@ pub enum DeclType {
 }
 
 /// Represents the method type of a [`VertexElement`].
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum DeclMethod {
     Default = D3DDECLMETHOD_DEFAULT as u8,
@@ -227,7 +416,7 @@ pub enum DeclMethod {
 }
 
 /// Represents the usage/semantic type of a [`VertexElement`].
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum DeclUsage {
     Position = D3DDECLUSAGE_POSITION as u8,
@@ -247,7 +436,8 @@ pub enum DeclUsage {
 }
 
 /// Represents the type of device.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum DeviceType {
     Hal = D3DDEVTYPE_HAL,
@@ -256,8 +446,21 @@ pub enum DeviceType {
     Sw = D3DDEVTYPE_SW,
 }
 
+impl DeviceType {
+    pub fn from_raw(value: u32) -> Option<Self> {
+        match value {
+            D3DDEVTYPE_HAL => Some(Self::Hal),
+            D3DDEVTYPE_NULLREF => Some(Self::NullRef),
+            D3DDEVTYPE_REF => Some(Self::Ref),
+            D3DDEVTYPE_SW => Some(Self::Sw),
+            _ => None,
+        }
+    }
+}
+
 /// Represents the display mode of a monitor.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DisplayMode {
     pub width: u32,
     pub height: u32,
@@ -265,13 +468,13 @@ pub struct DisplayMode {
     pub format: Format,
 }
 
-impl Into<D3DDISPLAYMODE> for DisplayMode {
-    fn into(self) -> D3DDISPLAYMODE {
+impl From<DisplayMode> for D3DDISPLAYMODE {
+    fn from(value: DisplayMode) -> Self {
         D3DDISPLAYMODE {
-            Width: self.width,
-            Height: self.height,
-            RefreshRate: self.refresh_rate,
-            Format: self.format as u32,
+            Width: value.width,
+            Height: value.height,
+            RefreshRate: value.refresh_rate,
+            Format: value.format.into(),
         }
     }
 }
@@ -282,11 +485,32 @@ impl From<D3DDISPLAYMODE> for DisplayMode {
             width: value.Width,
             height: value.Height,
             refresh_rate: value.RefreshRate,
-            format: unsafe { transmute(value.Format) },
+            format: value.Format.into(),
         }
     }
 }
 
+impl DisplayMode {
+    /// Picks the mode closest to the requested resolution and refresh rate.
+    ///
+    /// The resolution is matched first, by the smallest sum of the width and height differences.
+    /// Among equally close resolutions, the refresh rate closest to `refresh_rate` is picked, or
+    /// the highest one if `refresh_rate` is [`None`]. Remaining ties favor the higher refresh rate.
+    pub fn best_match(
+        modes: &[DisplayMode],
+        width: u32,
+        height: u32,
+        refresh_rate: Option<u32>,
+    ) -> Option<&DisplayMode> {
+        modes.iter().min_by_key(|mode| {
+            let resolution = mode.width.abs_diff(width) + mode.height.abs_diff(height);
+            let refresh = refresh_rate.map_or(0, |rate| mode.refresh_rate.abs_diff(rate));
+
+            (resolution, refresh, Reverse(mode.refresh_rate))
+        })
+    }
+}
+
 impl Default for DisplayMode {
     fn default() -> Self {
         Self {
@@ -298,75 +522,83 @@ impl Default for DisplayMode {
     }
 }
 
-/// Represents a buffer/surface format.
-#[derive(Clone, Copy, Debug)]
-#[repr(u32)]
-pub enum Format {
-    Unknown = D3DFMT_UNKNOWN,
-    R8G8B8 = D3DFMT_R8G8B8,
-    A8R8G8B8 = D3DFMT_A8R8G8B8,
-    X8R8G8B8 = D3DFMT_X8R8G8B8,
-    R5G6B5 = D3DFMT_R5G6B5,
-    X1R5G5B5 = D3DFMT_X1R5G5B5,
-    A1R5G5B5 = D3DFMT_A1R5G5B5,
-    A4R4G4B4 = D3DFMT_A4R4G4B4,
-    R3G3B2 = D3DFMT_R3G3B2,
-    A8 = D3DFMT_A8,
-    A8R3G3B2 = D3DFMT_A8R3G3B2,
-    X4R4G4B4 = D3DFMT_X4R4G4B4,
-    A2B10G10R10 = D3DFMT_A2B10G10R10,
-    A8B8G8R8 = D3DFMT_A8B8G8R8,
-    X8B8G8R8 = D3DFMT_X8B8G8R8,
-    G16R16 = D3DFMT_G16R16,
-    A2R10G10B10 = D3DFMT_A2R10G10B10,
-    A8P8 = D3DFMT_A8P8,
-    P8 = D3DFMT_P8,
-    L8 = D3DFMT_L8,
-    A8L8 = D3DFMT_A8L8,
-    A4L4 = D3DFMT_A4L4,
-    V8U8 = D3DFMT_V8U8,
-    L6V5U5 = D3DFMT_L6V5U5,
-    X8L8V8U8 = D3DFMT_X8L8V8U8,
-    Q8W8V8U8 = D3DFMT_Q8W8V8U8,
-    V16U16 = D3DFMT_V16U16,
-    A2W10V10U10 = D3DFMT_A2W10V10U10,
-    UYVY = D3DFMT_UYVY,
-    R8g8B8g8 = D3DFMT_R8G8_B8G8,
-    YUY2 = D3DFMT_YUY2,
-    G8r8G8b8 = D3DFMT_G8R8_G8B8,
-    DXT1 = D3DFMT_DXT1,
-    DXT2 = D3DFMT_DXT2,
-    DXT3 = D3DFMT_DXT3,
-    DXT4 = D3DFMT_DXT4,
-    DXT5 = D3DFMT_DXT5,
-    D16Lockable = D3DFMT_D16_LOCKABLE,
-    D32 = D3DFMT_D32,
-    D15S1 = D3DFMT_D15S1,
-    D24S8 = D3DFMT_D24S8,
-    D24X8 = D3DFMT_D24X8,
-    D24X4S4 = D3DFMT_D24X4S4,
-    D16 = D3DFMT_D16,
-    D32fLockable = D3DFMT_D32F_LOCKABLE,
-    D24FS8 = D3DFMT_D24FS8,
-    D32Lockable = D3DFMT_D32_LOCKABLE,
-    S8Lockable = D3DFMT_S8_LOCKABLE,
-    L16 = D3DFMT_L16,
-    VertexData = D3DFMT_VERTEXDATA,
-    Index16 = D3DFMT_INDEX16,
-    Index32 = D3DFMT_INDEX32,
-    Q16W16V16U16 = D3DFMT_Q16W16V16U16,
-    R16F = D3DFMT_R16F,
-    G16R16F = D3DFMT_G16R16F,
-    A16B16G16R16 = D3DFMT_A16B16G16R16,
-    R32F = D3DFMT_R32F,
-    G32R32F = D3DFMT_G32R32F,
-    CxV8U8 = D3DFMT_CxV8U8,
-    A1 = D3DFMT_A1,
-    BinaryBuffer = D3DFMT_BINARYBUFFER,
+open_enum! {
+    /// Represents a buffer/surface format.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum Format {
+        Unknown = D3DFMT_UNKNOWN,
+        R8G8B8 = D3DFMT_R8G8B8,
+        A8R8G8B8 = D3DFMT_A8R8G8B8,
+        X8R8G8B8 = D3DFMT_X8R8G8B8,
+        R5G6B5 = D3DFMT_R5G6B5,
+        X1R5G5B5 = D3DFMT_X1R5G5B5,
+        A1R5G5B5 = D3DFMT_A1R5G5B5,
+        A4R4G4B4 = D3DFMT_A4R4G4B4,
+        R3G3B2 = D3DFMT_R3G3B2,
+        A8 = D3DFMT_A8,
+        A8R3G3B2 = D3DFMT_A8R3G3B2,
+        X4R4G4B4 = D3DFMT_X4R4G4B4,
+        A2B10G10R10 = D3DFMT_A2B10G10R10,
+        A8B8G8R8 = D3DFMT_A8B8G8R8,
+        X8B8G8R8 = D3DFMT_X8B8G8R8,
+        G16R16 = D3DFMT_G16R16,
+        A2R10G10B10 = D3DFMT_A2R10G10B10,
+        A8P8 = D3DFMT_A8P8,
+        P8 = D3DFMT_P8,
+        L8 = D3DFMT_L8,
+        A8L8 = D3DFMT_A8L8,
+        A4L4 = D3DFMT_A4L4,
+        V8U8 = D3DFMT_V8U8,
+        L6V5U5 = D3DFMT_L6V5U5,
+        X8L8V8U8 = D3DFMT_X8L8V8U8,
+        Q8W8V8U8 = D3DFMT_Q8W8V8U8,
+        V16U16 = D3DFMT_V16U16,
+        A2W10V10U10 = D3DFMT_A2W10V10U10,
+        UYVY = D3DFMT_UYVY,
+        R8g8B8g8 = D3DFMT_R8G8_B8G8,
+        YUY2 = D3DFMT_YUY2,
+        G8r8G8b8 = D3DFMT_G8R8_G8B8,
+        DXT1 = D3DFMT_DXT1,
+        DXT2 = D3DFMT_DXT2,
+        DXT3 = D3DFMT_DXT3,
+        DXT4 = D3DFMT_DXT4,
+        DXT5 = D3DFMT_DXT5,
+        D16Lockable = D3DFMT_D16_LOCKABLE,
+        D32 = D3DFMT_D32,
+        D15S1 = D3DFMT_D15S1,
+        D24S8 = D3DFMT_D24S8,
+        D24X8 = D3DFMT_D24X8,
+        D24X4S4 = D3DFMT_D24X4S4,
+        D16 = D3DFMT_D16,
+        D32fLockable = D3DFMT_D32F_LOCKABLE,
+        D24FS8 = D3DFMT_D24FS8,
+        D32Lockable = D3DFMT_D32_LOCKABLE,
+        S8Lockable = D3DFMT_S8_LOCKABLE,
+        L16 = D3DFMT_L16,
+        VertexData = D3DFMT_VERTEXDATA,
+        Index16 = D3DFMT_INDEX16,
+        Index32 = D3DFMT_INDEX32,
+        Q16W16V16U16 = D3DFMT_Q16W16V16U16,
+        R16F = D3DFMT_R16F,
+        G16R16F = D3DFMT_G16R16F,
+        A16B16G16R16 = D3DFMT_A16B16G16R16,
+        R32F = D3DFMT_R32F,
+        G32R32F = D3DFMT_G32R32F,
+        A16B16G16R16F = D3DFMT_A16B16G16R16F,
+        A32B32G32R32F = D3DFMT_A32B32G32R32F,
+        CxV8U8 = D3DFMT_CxV8U8,
+        A1 = D3DFMT_A1,
+        BinaryBuffer = D3DFMT_BINARYBUFFER,
+        /// A format which is not listed above, such as a driver specific FOURCC format like
+        /// `INTZ`. Never holds the value of a listed format.
+        Other(u32),
+    }
 }
 
 bitfield! {
     /// Represents the fixed vertex function pipeline configuration.
+    #[derive(Clone, Copy, Default, PartialEq, Eq)]
     pub struct FVF(u32);
 
     impl Debug;
@@ -396,34 +628,104 @@ bitfield! {
 }
 
 /// Represents a general purpose resource in Direct3D 9.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Handle(pub u32);
 
-impl Into<u32> for Handle {
-    fn into(self) -> u32 {
-        self.0
+impl From<Handle> for u32 {
+    fn from(value: Handle) -> Self {
+        value.0
     }
 }
 
 bitfield! {
+    /// Flags for locking a resource.
+    #[derive(Clone, Copy, Default, PartialEq, Eq)]
     pub struct LockFlags(u32);
 
     impl Debug;
 
-    set_discard, discard: 13;
+    pub readonly, set_readonly: 4;
+    pub nosyslock, set_nosyslock: 11;
+    pub nooverwrite, set_nooverwrite: 12;
+    pub discard, set_discard: 13;
+    pub donotwait, set_donotwait: 14;
+    pub no_dirty_update, set_no_dirty_update: 15;
+}
+
+/// Represents a locked rectangle of a [`Surface`] or texture level.
+#[derive(Clone, Copy, Debug)]
+pub struct LockedRect {
+    /// The number of bytes in a single row.
+    pub pitch: i32,
+    pub bits: *mut u8,
+}
+
+impl From<D3DLOCKED_RECT> for LockedRect {
+    fn from(value: D3DLOCKED_RECT) -> Self {
+        Self {
+            pitch: value.Pitch,
+            bits: value.pBits as *mut u8,
+        }
+    }
+}
+
+/// Describes a [`Surface`](crate::std::interfaces::Surface) or a texture level.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SurfaceDesc {
+    pub format: Format,
+    pub usage: Usage,
+    pub pool: Pool,
+    pub multi_sample_type: MultiSampleType,
+    pub multi_sample_quality: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl From<D3DSURFACE_DESC> for SurfaceDesc {
+    fn from(value: D3DSURFACE_DESC) -> Self {
+        Self {
+            format: value.Format.into(),
+            usage: Usage(value.Usage),
+            pool: value.Pool.into(),
+            multi_sample_type: value.MultiSampleType.into(),
+            multi_sample_quality: value.MultiSampleQuality,
+            width: value.Width,
+            height: value.Height,
+        }
+    }
+}
+
+/// Represents a locked box of a [`VolumeTexture`] level.
+#[derive(Clone, Copy, Debug)]
+pub struct LockedBox {
+    /// The number of bytes in a single row.
+    pub row_pitch: i32,
+    /// The number of bytes in a single depth slice.
+    pub slice_pitch: i32,
+    pub bits: *mut u8,
+}
+
+impl From<D3DLOCKED_BOX> for LockedBox {
+    fn from(value: D3DLOCKED_BOX) -> Self {
+        Self {
+            row_pitch: value.RowPitch,
+            slice_pitch: value.SlicePitch,
+            bits: value.pBits as *mut u8,
+        }
+    }
 }
 
 /// Represents the type of multi-sampling for buffers/surfaces.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum MultiSampleType {
     None,
     NonMaskable,
     Some(u32),
 }
 
-impl Into<u32> for MultiSampleType {
-    fn into(self) -> u32 {
-        match self {
+impl From<MultiSampleType> for u32 {
+    fn from(value: MultiSampleType) -> Self {
+        match value {
             MultiSampleType::None => 0,
             MultiSampleType::NonMaskable => 1,
             MultiSampleType::Some(n) => n,
@@ -431,16 +733,31 @@ impl Into<u32> for MultiSampleType {
     }
 }
 
-/// Represents a memory pool location.
-#[derive(Clone, Copy, Debug)]
-#[repr(u32)]
-pub enum Pool {
-    Default = D3DPOOL_DEFAULT,
-    Managed = D3DPOOL_MANAGED,
+impl From<u32> for MultiSampleType {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => MultiSampleType::None,
+            1 => MultiSampleType::NonMaskable,
+            n => MultiSampleType::Some(n),
+        }
+    }
+}
+
+open_enum! {
+    /// Represents a memory pool location.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Pool {
+        Default = D3DPOOL_DEFAULT,
+        Managed = D3DPOOL_MANAGED,
+        SystemMem = D3DPOOL_SYSTEMMEM,
+        Scratch = D3DPOOL_SCRATCH,
+        /// A pool which is not listed above. Never holds the value of a listed pool.
+        Other(u32),
+    }
 }
 
 /// Represents the "presentation parameters" for a [`SwapChain`].
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct PresentationParameters {
     pub back_buffer_width: u32,
     pub back_buffer_height: u32,
@@ -479,29 +796,29 @@ impl Default for PresentationParameters {
     }
 }
 
-impl Into<D3DPRESENT_PARAMETERS> for PresentationParameters {
-    fn into(self) -> D3DPRESENT_PARAMETERS {
+impl From<PresentationParameters> for D3DPRESENT_PARAMETERS {
+    fn from(value: PresentationParameters) -> Self {
         D3DPRESENT_PARAMETERS {
-            BackBufferWidth: self.back_buffer_width,
-            BackBufferHeight: self.back_buffer_height,
-            BackBufferFormat: self.back_buffer_format as u32,
-            BackBufferCount: self.back_buffer_count,
-            MultiSampleType: self.multi_sample_type.into(),
-            MultiSampleQuality: self.multi_sample_quality,
-            SwapEffect: self.swap_effect as u32,
-            hDeviceWindow: self.device_window,
-            Windowed: self.windowed as i32,
-            EnableAutoDepthStencil: self.enable_auto_depth_stencil as i32,
-            AutoDepthStencilFormat: self.auto_depth_stencil_format as u32,
-            Flags: self.flags,
-            FullScreen_RefreshRateInHz: self.refresh_rate,
-            PresentationInterval: self.presentation_interval,
+            BackBufferWidth: value.back_buffer_width,
+            BackBufferHeight: value.back_buffer_height,
+            BackBufferFormat: value.back_buffer_format.into(),
+            BackBufferCount: value.back_buffer_count,
+            MultiSampleType: value.multi_sample_type.into(),
+            MultiSampleQuality: value.multi_sample_quality,
+            SwapEffect: value.swap_effect as u32,
+            hDeviceWindow: value.device_window,
+            Windowed: value.windowed as i32,
+            EnableAutoDepthStencil: value.enable_auto_depth_stencil as i32,
+            AutoDepthStencilFormat: value.auto_depth_stencil_format.into(),
+            Flags: value.flags,
+            FullScreen_RefreshRateInHz: value.refresh_rate,
+            PresentationInterval: value.presentation_interval,
         }
     }
 }
 
 /// Represents the type of primitive to render.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum PrimitiveType {
     PointList = D3DPT_POINTLIST,
@@ -513,7 +830,7 @@ pub enum PrimitiveType {
 }
 
 /// Represents the type of a [`Query`].
-#[derive(Copy, Clone)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum QueryType {
     VertexCache = D3DQUERYTYPE_VCACHE,
@@ -534,6 +851,7 @@ pub enum QueryType {
 }
 
 /// Represents the `RECT` structure used by certain [`Device`] functions.
+#[derive(Clone, Copy, Debug, PartialEq)]
 #[repr(C)]
 pub struct Rect {
     pub x1: f32,
@@ -546,7 +864,7 @@ pub struct Rect {
 pub struct RegionData(pub RGNDATA);
 
 /// Represents the type of Direct3D resource.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum ResourceType {
     Surface = D3DRTYPE_SURFACE,
@@ -559,7 +877,7 @@ pub enum ResourceType {
 }
 
 /// Represents the type of a [`StateBlock`].
-#[derive(Copy, Clone)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum StateBlockType {
     All = D3DSBT_ALL,
@@ -568,7 +886,7 @@ pub enum StateBlockType {
 }
 
 /// Represents the swap-effect mode of a [`Device`].
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum SwapEffect {
     Discard = D3DSWAPEFFECT_DISCARD,
@@ -579,7 +897,7 @@ pub enum SwapEffect {
 
 bitfield! {
     /// Represents the usage type for a buffer resource.
-    #[derive(Default)]
+    #[derive(Clone, Copy, Default, PartialEq, Eq)]
     pub struct Usage(u32);
 
     impl Debug;
@@ -610,7 +928,7 @@ bitfield! {
 }
 
 /// Represents the layout and size of a vertex within the pipeline.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct VertexElement {
     pub stream: u16,
     pub offset: u16,
@@ -620,15 +938,136 @@ pub struct VertexElement {
     pub usage_index: u8,
 }
 
-impl Into<D3DVERTEXELEMENT9> for VertexElement {
-    fn into(self) -> D3DVERTEXELEMENT9 {
+impl From<VertexElement> for D3DVERTEXELEMENT9 {
+    fn from(value: VertexElement) -> Self {
         D3DVERTEXELEMENT9 {
-            Stream: self.stream,
-            Offset: self.offset,
-            Type: self.kind as u8,
-            Method: self.method as u8,
-            Usage: self.usage as u8,
-            UsageIndex: self.usage_index,
+            Stream: value.stream,
+            Offset: value.offset,
+            Type: value.kind as u8,
+            Method: value.method as u8,
+            Usage: value.usage as u8,
+            UsageIndex: value.usage_index,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(width: u32, height: u32, refresh_rate: u32) -> DisplayMode {
+        DisplayMode {
+            width,
+            height,
+            refresh_rate,
+            format: Format::X8R8G8B8,
         }
     }
+
+    #[test]
+    fn best_match_prefers_resolution_then_refresh_rate() {
+        let modes = [
+            mode(800, 600, 60),
+            mode(1280, 720, 60),
+            mode(1920, 1080, 60),
+            mode(1920, 1080, 144),
+            mode(1920, 1080, 75),
+            mode(2560, 1440, 165),
+        ];
+        let best_match = |width, height, refresh_rate| {
+            DisplayMode::best_match(&modes, width, height, refresh_rate).cloned()
+        };
+
+        assert_eq!(best_match(1920, 1080, None), Some(mode(1920, 1080, 144)));
+        assert_eq!(best_match(1920, 1080, Some(60)), Some(mode(1920, 1080, 60)));
+        assert_eq!(
+            best_match(1920, 1080, Some(100)),
+            Some(mode(1920, 1080, 75))
+        );
+        assert_eq!(
+            best_match(1920, 1080, Some(110)),
+            Some(mode(1920, 1080, 144))
+        );
+        assert_eq!(best_match(1900, 1000, Some(60)), Some(mode(1920, 1080, 60)));
+        assert_eq!(best_match(4000, 3000, None), Some(mode(2560, 1440, 165)));
+        assert_eq!(best_match(640, 480, None), Some(mode(800, 600, 60)));
+    }
+
+    #[test]
+    fn best_match_breaks_refresh_rate_ties_upwards() {
+        let modes = [mode(1920, 1080, 50), mode(1920, 1080, 70)];
+
+        assert_eq!(
+            DisplayMode::best_match(&modes, 1920, 1080, Some(60)),
+            Some(&modes[1])
+        );
+    }
+
+    #[test]
+    fn best_match_of_no_modes_is_none() {
+        assert_eq!(DisplayMode::best_match(&[], 1920, 1080, None), None);
+    }
+
+    /// `MAKEFOURCC`, which drivers use to expose formats that `d3d9types.h` does not list.
+    fn four_cc(code: &[u8; 4]) -> u32 {
+        u32::from_le_bytes(*code)
+    }
+
+    #[test]
+    fn converts_formats_and_pools_from_raw_values() {
+        assert_eq!(Format::from(D3DFMT_A8R8G8B8), Format::A8R8G8B8);
+        assert_eq!(Format::from(D3DFMT_CxV8U8), Format::CxV8U8);
+        assert_eq!(u32::from(Format::DXT5), D3DFMT_DXT5);
+        assert_eq!(
+            Format::from(four_cc(b"INTZ")),
+            Format::Other(four_cc(b"INTZ"))
+        );
+
+        assert_eq!(Pool::from(D3DPOOL_SCRATCH), Pool::Scratch);
+        assert_eq!(Pool::from(7), Pool::Other(7));
+
+        for raw in (0..256).chain([four_cc(b"NULL"), four_cc(b"ATI2"), u32::MAX]) {
+            assert_eq!(u32::from(Format::from(raw)), raw);
+            assert_eq!(u32::from(Pool::from(raw)), raw);
+        }
+    }
+
+    #[test]
+    fn normalises_listed_values_to_their_variant() {
+        // `Other` is never produced for a listed value, so it cannot be mistaken for a new format.
+        for &format in Format::LISTED {
+            assert_eq!(Format::from(u32::from(format)), format);
+        }
+
+        for &pool in Pool::LISTED {
+            assert_eq!(Pool::from(u32::from(pool)), pool);
+        }
+    }
+
+    #[test]
+    fn keeps_unknown_formats_in_descriptions() {
+        let desc = SurfaceDesc::from(D3DSURFACE_DESC {
+            Format: four_cc(b"INTZ"),
+            Type: 1,  // D3DRTYPE_SURFACE
+            Usage: 2, // D3DUSAGE_DEPTHSTENCIL
+            Pool: D3DPOOL_DEFAULT,
+            MultiSampleType: 0,
+            MultiSampleQuality: 0,
+            Width: 640,
+            Height: 480,
+        });
+        assert_eq!(desc.format, Format::Other(four_cc(b"INTZ")));
+        assert_eq!(desc.pool, Pool::Default);
+        assert_eq!(desc.multi_sample_type, MultiSampleType::None);
+
+        let raw = D3DDISPLAYMODE {
+            Width: 1920,
+            Height: 1080,
+            RefreshRate: 60,
+            Format: four_cc(b"NULL"),
+        };
+        let mode = DisplayMode::from(raw);
+        assert_eq!(mode.format, Format::Other(four_cc(b"NULL")));
+        assert_eq!(D3DDISPLAYMODE::from(mode).Format, four_cc(b"NULL"));
+    }
 }
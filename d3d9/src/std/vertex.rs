@@ -0,0 +1,95 @@
+//! Provides the [`Vertex`] trait, which describes the layout of a vertex struct.
+//!
+//! With the `derive` feature enabled, the trait can be derived:
+//!
+//! ```rs
+//! #[derive(Clone, Copy, Vertex)]
+//! #[repr(C)]
+//! struct ColoredVertex {
+//!     #[vertex(position)]
+//!     position: [f32; 3],
+//!     #[vertex(color)]
+//!     color: u32,
+//! }
+//!
+//! let declaration = device.create_vertex_declaration(ColoredVertex::ELEMENTS)?;
+//! device.set_stream_source(0, &vertex_buffer, 0, ColoredVertex::STRIDE)?;
+//! ```
+
+#[cfg(feature = "derive")]
+pub use d3d9_derive::Vertex;
+
+use crate::std::types::{VertexElement, FVF};
+
+/// Represents a vertex struct with a known layout.
+pub trait Vertex {
+    /// The elements of the vertex, in field order, all within stream `0`.
+    const ELEMENTS: &'static [VertexElement];
+
+    /// The size of the vertex in bytes.
+    const STRIDE: u32;
+
+    /// The matching fixed function vertex format, if the layout can be expressed as one.
+    const FVF: Option<FVF>;
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use super::*;
+    use crate::std::types::{DeclType, DeclUsage};
+
+    #[derive(Clone, Copy, Vertex)]
+    #[repr(C)]
+    struct TexturedVertex {
+        #[vertex(position)]
+        position: [f32; 3],
+        #[vertex(normal)]
+        normal: [f32; 3],
+        #[vertex(color)]
+        color: u32,
+        #[vertex(texcoord(0))]
+        uv: [f32; 2],
+        #[vertex(texcoord(1))]
+        uvw: [f32; 3],
+    }
+
+    #[derive(Clone, Copy, Vertex)]
+    #[repr(C)]
+    struct PackedVertex {
+        #[vertex(position)]
+        position: [f32; 4],
+        #[vertex(skip)]
+        padding: u32,
+        #[vertex(texcoord(0), kind = Float16By2)]
+        uv: [u16; 2],
+        #[vertex(color)]
+        color: [u8; 4],
+    }
+
+    #[test]
+    fn derives_fvf_compatible_layouts() {
+        let elements = TexturedVertex::ELEMENTS;
+        assert_eq!(TexturedVertex::STRIDE, 48);
+        assert_eq!(elements.len(), 5);
+        assert_eq!(elements[2].offset, 24);
+        assert_eq!(elements[2].kind, DeclType::Color);
+        assert_eq!(elements[4].offset, 36);
+        assert_eq!(elements[4].usage, DeclUsage::TexCoord);
+        assert_eq!(elements[4].usage_index, 1);
+        assert_eq!(
+            TexturedVertex::FVF,
+            Some(FVF(0x002 | 0x010 | 0x040 | 0x200 | (1 << 18)))
+        );
+    }
+
+    #[test]
+    fn derives_layouts_with_explicit_types_and_padding() {
+        let elements = PackedVertex::ELEMENTS;
+        assert_eq!(PackedVertex::STRIDE, 28);
+        assert_eq!(elements.len(), 3);
+        assert_eq!(elements[1].offset, 20);
+        assert_eq!(elements[1].kind, DeclType::Float16By2);
+        assert_eq!(elements[2].kind, DeclType::UByte4N);
+        assert_eq!(PackedVertex::FVF, None);
+    }
+}
@@ -0,0 +1,98 @@
+#![allow(non_camel_case_types, non_snake_case, dead_code)]
+
+pub const D3DPRESENT_INTERVAL_DEFAULT: u32 = 0x00000000;
+pub const D3DPRESENT_INTERVAL_IMMEDIATE: u32 = 0x80000000;
+pub const D3DPRESENT_INTERVAL_ONE: u32 = 0x00000001;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct D3DCAPS9 {
+    pub DeviceType: u32,
+    pub AdapterOrdinal: u32,
+    pub Caps: u32,
+    pub Caps2: u32,
+    pub Caps3: u32,
+    pub PresentationIntervals: u32,
+    pub CursorCaps: u32,
+    pub DevCaps: u32,
+    pub PrimitiveMiscCaps: u32,
+    pub RasterCaps: u32,
+    pub ZCmpCaps: u32,
+    pub SrcBlendCaps: u32,
+    pub DestBlendCaps: u32,
+    pub AlphaCmpCaps: u32,
+    pub ShadeCaps: u32,
+    pub TextureCaps: u32,
+    pub TextureFilterCaps: u32,
+    pub CubeTextureFilterCaps: u32,
+    pub VolumeTextureFilterCaps: u32,
+    pub TextureAddressCaps: u32,
+    pub VolumeTextureAddressCaps: u32,
+    pub LineCaps: u32,
+    pub MaxTextureWidth: u32,
+    pub MaxTextureHeight: u32,
+    pub MaxVolumeExtent: u32,
+    pub MaxTextureRepeat: u32,
+    pub MaxTextureAspectRatio: u32,
+    pub MaxAnisotropy: u32,
+    pub MaxVertexW: f32,
+    pub GuardBandLeft: f32,
+    pub GuardBandTop: f32,
+    pub GuardBandRight: f32,
+    pub GuardBandBottom: f32,
+    pub ExtentsAdjust: f32,
+    pub StencilCaps: u32,
+    pub FVFCaps: u32,
+    pub TextureOpCaps: u32,
+    pub MaxTextureBlendStages: u32,
+    pub MaxSimultaneousTextures: u32,
+    pub VertexProcessingCaps: u32,
+    pub MaxActiveLights: u32,
+    pub MaxUserClipPlanes: u32,
+    pub MaxVertexBlendMatrices: u32,
+    pub MaxVertexBlendMatrixIndex: u32,
+    pub MaxPointSize: f32,
+    pub MaxPrimitiveCount: u32,
+    pub MaxVertexIndex: u32,
+    pub MaxStreams: u32,
+    pub MaxStreamStride: u32,
+    pub VertexShaderVersion: u32,
+    pub MaxVertexShaderConst: u32,
+    pub PixelShaderVersion: u32,
+    pub PixelShader1xMaxValue: f32,
+    pub DevCaps2: u32,
+    pub MaxNpatchTessellationLevel: f32,
+    pub Reserved5: u32,
+    pub MasterAdapterOrdinal: u32,
+    pub AdapterOrdinalInGroup: u32,
+    pub NumberOfAdaptersInGroup: u32,
+    pub DeclTypes: u32,
+    pub NumSimultaneousRTs: u32,
+    pub StretchRectFilterCaps: u32,
+    pub VS20Caps: D3DVSHADERCAPS2_0,
+    pub PS20Caps: D3DPSHADERCAPS2_0,
+    pub VertexTextureFilterCaps: u32,
+    pub MaxVShaderInstructionsExecuted: u32,
+    pub MaxPShaderInstructionsExecuted: u32,
+    pub MaxVertexShader30InstructionSlots: u32,
+    pub MaxPixelShader30InstructionSlots: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct D3DPSHADERCAPS2_0 {
+    pub Caps: u32,
+    pub DynamicFlowControlDepth: i32,
+    pub NumTemps: i32,
+    pub StaticFlowControlDepth: i32,
+    pub NumInstructionSlots: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct D3DVSHADERCAPS2_0 {
+    pub Caps: u32,
+    pub DynamicFlowControlDepth: i32,
+    pub NumTemps: i32,
+    pub StaticFlowControlDepth: i32,
+}
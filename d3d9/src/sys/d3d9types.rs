@@ -0,0 +1,504 @@
+#![allow(
+    non_camel_case_types,
+    non_snake_case,
+    non_upper_case_globals,
+    dead_code
+)]
+
+use std::ffi::c_void;
+
+use super::{GUID, HWND, LARGE_INTEGER};
+
+pub const D3DBLENDOP_ADD: u32 = 1;
+pub const D3DBLENDOP_MAX: u32 = 5;
+pub const D3DBLENDOP_MIN: u32 = 4;
+pub const D3DBLENDOP_REVSUBTRACT: u32 = 3;
+pub const D3DBLENDOP_SUBTRACT: u32 = 2;
+pub const D3DBLEND_BLENDFACTOR: u32 = 14;
+pub const D3DBLEND_BOTHINVSRCALPHA: u32 = 13;
+pub const D3DBLEND_BOTHSRCALPHA: u32 = 12;
+pub const D3DBLEND_DESTALPHA: u32 = 7;
+pub const D3DBLEND_DESTCOLOR: u32 = 9;
+pub const D3DBLEND_INVBLENDFACTOR: u32 = 15;
+pub const D3DBLEND_INVDESTALPHA: u32 = 8;
+pub const D3DBLEND_INVDESTCOLOR: u32 = 10;
+pub const D3DBLEND_INVSRCALPHA: u32 = 6;
+pub const D3DBLEND_INVSRCCOLOR: u32 = 4;
+pub const D3DBLEND_INVSRCCOLOR2: u32 = 17;
+pub const D3DBLEND_ONE: u32 = 2;
+pub const D3DBLEND_SRCALPHA: u32 = 5;
+pub const D3DBLEND_SRCALPHASAT: u32 = 11;
+pub const D3DBLEND_SRCCOLOR: u32 = 3;
+pub const D3DBLEND_SRCCOLOR2: u32 = 16;
+pub const D3DBLEND_ZERO: u32 = 1;
+pub const D3DCMP_ALWAYS: u32 = 8;
+pub const D3DCMP_EQUAL: u32 = 3;
+pub const D3DCMP_GREATER: u32 = 5;
+pub const D3DCMP_GREATEREQUAL: u32 = 7;
+pub const D3DCMP_LESS: u32 = 2;
+pub const D3DCMP_LESSEQUAL: u32 = 4;
+pub const D3DCMP_NEVER: u32 = 1;
+pub const D3DCMP_NOTEQUAL: u32 = 6;
+pub const D3DCUBEMAP_FACE_NEGATIVE_X: u32 = 1;
+pub const D3DCUBEMAP_FACE_NEGATIVE_Y: u32 = 3;
+pub const D3DCUBEMAP_FACE_NEGATIVE_Z: u32 = 5;
+pub const D3DCUBEMAP_FACE_POSITIVE_X: u32 = 0;
+pub const D3DCUBEMAP_FACE_POSITIVE_Y: u32 = 2;
+pub const D3DCUBEMAP_FACE_POSITIVE_Z: u32 = 4;
+pub const D3DCULL_CCW: u32 = 3;
+pub const D3DCULL_CW: u32 = 2;
+pub const D3DCULL_NONE: u32 = 1;
+pub const D3DDECLMETHOD_CROSSUV: u32 = 3;
+pub const D3DDECLMETHOD_DEFAULT: u32 = 0;
+pub const D3DDECLMETHOD_LOOKUP: u32 = 5;
+pub const D3DDECLMETHOD_LOOKUPPRESAMPLED: u32 = 6;
+pub const D3DDECLMETHOD_PARTIALU: u32 = 1;
+pub const D3DDECLMETHOD_PARTIALV: u32 = 2;
+pub const D3DDECLMETHOD_UV: u32 = 4;
+pub const D3DDECLTYPE_D3DCOLOR: u32 = 4;
+pub const D3DDECLTYPE_DEC3N: u32 = 14;
+pub const D3DDECLTYPE_FLOAT1: u32 = 0;
+pub const D3DDECLTYPE_FLOAT16_2: u32 = 15;
+pub const D3DDECLTYPE_FLOAT16_4: u32 = 16;
+pub const D3DDECLTYPE_FLOAT2: u32 = 1;
+pub const D3DDECLTYPE_FLOAT3: u32 = 2;
+pub const D3DDECLTYPE_FLOAT4: u32 = 3;
+pub const D3DDECLTYPE_SHORT2: u32 = 6;
+pub const D3DDECLTYPE_SHORT2N: u32 = 9;
+pub const D3DDECLTYPE_SHORT4: u32 = 7;
+pub const D3DDECLTYPE_SHORT4N: u32 = 10;
+pub const D3DDECLTYPE_UBYTE4: u32 = 5;
+pub const D3DDECLTYPE_UBYTE4N: u32 = 8;
+pub const D3DDECLTYPE_UDEC3: u32 = 13;
+pub const D3DDECLTYPE_USHORT2N: u32 = 11;
+pub const D3DDECLTYPE_USHORT4N: u32 = 12;
+pub const D3DDECLUSAGE_BINORMAL: u32 = 7;
+pub const D3DDECLUSAGE_BLENDINDICES: u32 = 2;
+pub const D3DDECLUSAGE_BLENDWEIGHT: u32 = 1;
+pub const D3DDECLUSAGE_COLOR: u32 = 10;
+pub const D3DDECLUSAGE_DEPTH: u32 = 12;
+pub const D3DDECLUSAGE_FOG: u32 = 11;
+pub const D3DDECLUSAGE_NORMAL: u32 = 3;
+pub const D3DDECLUSAGE_POSITION: u32 = 0;
+pub const D3DDECLUSAGE_POSITIONT: u32 = 9;
+pub const D3DDECLUSAGE_PSIZE: u32 = 4;
+pub const D3DDECLUSAGE_SAMPLE: u32 = 13;
+pub const D3DDECLUSAGE_TANGENT: u32 = 6;
+pub const D3DDECLUSAGE_TESSFACTOR: u32 = 8;
+pub const D3DDECLUSAGE_TEXCOORD: u32 = 5;
+pub const D3DDEGREE_CUBIC: u32 = 3;
+pub const D3DDEGREE_LINEAR: u32 = 1;
+pub const D3DDEGREE_QUADRATIC: u32 = 2;
+pub const D3DDEGREE_QUINTIC: u32 = 5;
+pub const D3DDEVTYPE_HAL: u32 = 1;
+pub const D3DDEVTYPE_NULLREF: u32 = 4;
+pub const D3DDEVTYPE_REF: u32 = 2;
+pub const D3DDEVTYPE_SW: u32 = 3;
+pub const D3DDMT_DISABLE: u32 = 1;
+pub const D3DDMT_ENABLE: u32 = 0;
+pub const D3DFILL_POINT: u32 = 1;
+pub const D3DFILL_SOLID: u32 = 3;
+pub const D3DFILL_WIREFRAME: u32 = 2;
+pub const D3DFMT_A1: u32 = 118;
+pub const D3DFMT_A16B16G16R16: u32 = 36;
+pub const D3DFMT_A16B16G16R16F: u32 = 113;
+pub const D3DFMT_A1R5G5B5: u32 = 25;
+pub const D3DFMT_A2B10G10R10: u32 = 31;
+pub const D3DFMT_A2R10G10B10: u32 = 35;
+pub const D3DFMT_A2W10V10U10: u32 = 67;
+pub const D3DFMT_A32B32G32R32F: u32 = 116;
+pub const D3DFMT_A4L4: u32 = 52;
+pub const D3DFMT_A4R4G4B4: u32 = 26;
+pub const D3DFMT_A8: u32 = 28;
+pub const D3DFMT_A8B8G8R8: u32 = 32;
+pub const D3DFMT_A8L8: u32 = 51;
+pub const D3DFMT_A8P8: u32 = 40;
+pub const D3DFMT_A8R3G3B2: u32 = 29;
+pub const D3DFMT_A8R8G8B8: u32 = 21;
+pub const D3DFMT_BINARYBUFFER: u32 = 199;
+pub const D3DFMT_CxV8U8: u32 = 117;
+pub const D3DFMT_D15S1: u32 = 73;
+pub const D3DFMT_D16: u32 = 80;
+pub const D3DFMT_D16_LOCKABLE: u32 = 70;
+pub const D3DFMT_D24FS8: u32 = 83;
+pub const D3DFMT_D24S8: u32 = 75;
+pub const D3DFMT_D24X4S4: u32 = 79;
+pub const D3DFMT_D24X8: u32 = 77;
+pub const D3DFMT_D32: u32 = 71;
+pub const D3DFMT_D32F_LOCKABLE: u32 = 82;
+pub const D3DFMT_D32_LOCKABLE: u32 = 84;
+pub const D3DFMT_DXT1: u32 = u32::from_le_bytes(*b"DXT1");
+pub const D3DFMT_DXT2: u32 = u32::from_le_bytes(*b"DXT2");
+pub const D3DFMT_DXT3: u32 = u32::from_le_bytes(*b"DXT3");
+pub const D3DFMT_DXT4: u32 = u32::from_le_bytes(*b"DXT4");
+pub const D3DFMT_DXT5: u32 = u32::from_le_bytes(*b"DXT5");
+pub const D3DFMT_G16R16: u32 = 34;
+pub const D3DFMT_G16R16F: u32 = 112;
+pub const D3DFMT_G32R32F: u32 = 115;
+pub const D3DFMT_G8R8_G8B8: u32 = u32::from_le_bytes(*b"GRGB");
+pub const D3DFMT_INDEX16: u32 = 101;
+pub const D3DFMT_INDEX32: u32 = 102;
+pub const D3DFMT_L16: u32 = 81;
+pub const D3DFMT_L6V5U5: u32 = 61;
+pub const D3DFMT_L8: u32 = 50;
+pub const D3DFMT_P8: u32 = 41;
+pub const D3DFMT_Q16W16V16U16: u32 = 110;
+pub const D3DFMT_Q8W8V8U8: u32 = 63;
+pub const D3DFMT_R16F: u32 = 111;
+pub const D3DFMT_R32F: u32 = 114;
+pub const D3DFMT_R3G3B2: u32 = 27;
+pub const D3DFMT_R5G6B5: u32 = 23;
+pub const D3DFMT_R8G8B8: u32 = 20;
+pub const D3DFMT_R8G8_B8G8: u32 = u32::from_le_bytes(*b"RGBG");
+pub const D3DFMT_S8_LOCKABLE: u32 = 85;
+pub const D3DFMT_UNKNOWN: u32 = 0;
+pub const D3DFMT_UYVY: u32 = u32::from_le_bytes(*b"UYVY");
+pub const D3DFMT_V16U16: u32 = 64;
+pub const D3DFMT_V8U8: u32 = 60;
+pub const D3DFMT_VERTEXDATA: u32 = 100;
+pub const D3DFMT_X1R5G5B5: u32 = 24;
+pub const D3DFMT_X4R4G4B4: u32 = 30;
+pub const D3DFMT_X8B8G8R8: u32 = 33;
+pub const D3DFMT_X8L8V8U8: u32 = 62;
+pub const D3DFMT_X8R8G8B8: u32 = 22;
+pub const D3DFMT_YUY2: u32 = u32::from_le_bytes(*b"YUY2");
+pub const D3DFOG_EXP: u32 = 1;
+pub const D3DFOG_EXP2: u32 = 2;
+pub const D3DFOG_LINEAR: u32 = 3;
+pub const D3DFOG_NONE: u32 = 0;
+pub const D3DMCS_COLOR1: u32 = 1;
+pub const D3DMCS_COLOR2: u32 = 2;
+pub const D3DMCS_MATERIAL: u32 = 0;
+pub const D3DPATCHEDGE_CONTINUOUS: u32 = 1;
+pub const D3DPATCHEDGE_DISCRETE: u32 = 0;
+pub const D3DPOOL_DEFAULT: u32 = 0;
+pub const D3DPOOL_MANAGED: u32 = 1;
+pub const D3DPOOL_SCRATCH: u32 = 3;
+pub const D3DPOOL_SYSTEMMEM: u32 = 2;
+pub const D3DPT_LINELIST: u32 = 2;
+pub const D3DPT_LINESTRIP: u32 = 3;
+pub const D3DPT_POINTLIST: u32 = 1;
+pub const D3DPT_TRIANGLEFAN: u32 = 6;
+pub const D3DPT_TRIANGLELIST: u32 = 4;
+pub const D3DPT_TRIANGLESTRIP: u32 = 5;
+pub const D3DQUERYTYPE_BANDWIDTHTIMINGS: u32 = 17;
+pub const D3DQUERYTYPE_CACHEUTILIZATION: u32 = 18;
+pub const D3DQUERYTYPE_EVENT: u32 = 8;
+pub const D3DQUERYTYPE_INTERFACETIMINGS: u32 = 14;
+pub const D3DQUERYTYPE_MEMORYPRESSURE: u32 = 19;
+pub const D3DQUERYTYPE_OCCLUSION: u32 = 9;
+pub const D3DQUERYTYPE_PIPELINETIMINGS: u32 = 13;
+pub const D3DQUERYTYPE_PIXELTIMINGS: u32 = 16;
+pub const D3DQUERYTYPE_RESOURCEMANAGER: u32 = 5;
+pub const D3DQUERYTYPE_TIMESTAMP: u32 = 10;
+pub const D3DQUERYTYPE_TIMESTAMPDISJOINT: u32 = 11;
+pub const D3DQUERYTYPE_TIMESTAMPFREQ: u32 = 12;
+pub const D3DQUERYTYPE_VCACHE: u32 = 4;
+pub const D3DQUERYTYPE_VERTEXSTATS: u32 = 6;
+pub const D3DQUERYTYPE_VERTEXTIMINGS: u32 = 15;
+pub const D3DRS_ADAPTIVETESS_W: u32 = 183;
+pub const D3DRS_ADAPTIVETESS_X: u32 = 180;
+pub const D3DRS_ADAPTIVETESS_Y: u32 = 181;
+pub const D3DRS_ADAPTIVETESS_Z: u32 = 182;
+pub const D3DRS_ALPHABLENDENABLE: u32 = 27;
+pub const D3DRS_ALPHAFUNC: u32 = 25;
+pub const D3DRS_ALPHAREF: u32 = 24;
+pub const D3DRS_ALPHATESTENABLE: u32 = 15;
+pub const D3DRS_AMBIENT: u32 = 139;
+pub const D3DRS_AMBIENTMATERIALSOURCE: u32 = 147;
+pub const D3DRS_ANTIALIASEDLINEENABLE: u32 = 176;
+pub const D3DRS_BLENDFACTOR: u32 = 193;
+pub const D3DRS_BLENDOP: u32 = 171;
+pub const D3DRS_BLENDOPALPHA: u32 = 209;
+pub const D3DRS_CCW_STENCILFAIL: u32 = 186;
+pub const D3DRS_CCW_STENCILFUNC: u32 = 189;
+pub const D3DRS_CCW_STENCILPASS: u32 = 188;
+pub const D3DRS_CCW_STENCILZFAIL: u32 = 187;
+pub const D3DRS_CLIPPING: u32 = 136;
+pub const D3DRS_CLIPPLANEENABLE: u32 = 152;
+pub const D3DRS_COLORVERTEX: u32 = 141;
+pub const D3DRS_COLORWRITEENABLE: u32 = 168;
+pub const D3DRS_COLORWRITEENABLE1: u32 = 190;
+pub const D3DRS_COLORWRITEENABLE2: u32 = 191;
+pub const D3DRS_COLORWRITEENABLE3: u32 = 192;
+pub const D3DRS_CULLMODE: u32 = 22;
+pub const D3DRS_DEBUGMONITORTOKEN: u32 = 165;
+pub const D3DRS_DEPTHBIAS: u32 = 195;
+pub const D3DRS_DESTBLEND: u32 = 20;
+pub const D3DRS_DESTBLENDALPHA: u32 = 208;
+pub const D3DRS_DIFFUSEMATERIALSOURCE: u32 = 145;
+pub const D3DRS_DITHERENABLE: u32 = 26;
+pub const D3DRS_EMISSIVEMATERIALSOURCE: u32 = 148;
+pub const D3DRS_ENABLEADAPTIVETESSELLATION: u32 = 184;
+pub const D3DRS_FILLMODE: u32 = 8;
+pub const D3DRS_FOGCOLOR: u32 = 34;
+pub const D3DRS_FOGDENSITY: u32 = 38;
+pub const D3DRS_FOGENABLE: u32 = 28;
+pub const D3DRS_FOGEND: u32 = 37;
+pub const D3DRS_FOGSTART: u32 = 36;
+pub const D3DRS_FOGTABLEMODE: u32 = 35;
+pub const D3DRS_FOGVERTEXMODE: u32 = 140;
+pub const D3DRS_INDEXEDVERTEXBLENDENABLE: u32 = 167;
+pub const D3DRS_LASTPIXEL: u32 = 16;
+pub const D3DRS_LIGHTING: u32 = 137;
+pub const D3DRS_LOCALVIEWER: u32 = 142;
+pub const D3DRS_MAXTESSELLATIONLEVEL: u32 = 179;
+pub const D3DRS_MINTESSELLATIONLEVEL: u32 = 178;
+pub const D3DRS_MULTISAMPLEANTIALIAS: u32 = 161;
+pub const D3DRS_MULTISAMPLEMASK: u32 = 162;
+pub const D3DRS_NORMALDEGREE: u32 = 173;
+pub const D3DRS_NORMALIZENORMALS: u32 = 143;
+pub const D3DRS_PATCHEDGESTYLE: u32 = 163;
+pub const D3DRS_POINTSCALEENABLE: u32 = 157;
+pub const D3DRS_POINTSCALE_A: u32 = 158;
+pub const D3DRS_POINTSCALE_B: u32 = 159;
+pub const D3DRS_POINTSCALE_C: u32 = 160;
+pub const D3DRS_POINTSIZE: u32 = 154;
+pub const D3DRS_POINTSIZE_MAX: u32 = 166;
+pub const D3DRS_POINTSIZE_MIN: u32 = 155;
+pub const D3DRS_POINTSPRITEENABLE: u32 = 156;
+pub const D3DRS_POSITIONDEGREE: u32 = 172;
+pub const D3DRS_RANGEFOGENABLE: u32 = 48;
+pub const D3DRS_SCISSORTESTENABLE: u32 = 174;
+pub const D3DRS_SEPARATEALPHABLENDENABLE: u32 = 206;
+pub const D3DRS_SHADEMODE: u32 = 9;
+pub const D3DRS_SLOPESCALEDEPTHBIAS: u32 = 175;
+pub const D3DRS_SPECULARENABLE: u32 = 29;
+pub const D3DRS_SPECULARMATERIALSOURCE: u32 = 146;
+pub const D3DRS_SRCBLEND: u32 = 19;
+pub const D3DRS_SRCBLENDALPHA: u32 = 207;
+pub const D3DRS_SRGBWRITEENABLE: u32 = 194;
+pub const D3DRS_STENCILENABLE: u32 = 52;
+pub const D3DRS_STENCILFAIL: u32 = 53;
+pub const D3DRS_STENCILFUNC: u32 = 56;
+pub const D3DRS_STENCILMASK: u32 = 58;
+pub const D3DRS_STENCILPASS: u32 = 55;
+pub const D3DRS_STENCILREF: u32 = 57;
+pub const D3DRS_STENCILWRITEMASK: u32 = 59;
+pub const D3DRS_STENCILZFAIL: u32 = 54;
+pub const D3DRS_TEXTUREFACTOR: u32 = 60;
+pub const D3DRS_TWEENFACTOR: u32 = 170;
+pub const D3DRS_TWOSIDEDSTENCILMODE: u32 = 185;
+pub const D3DRS_VERTEXBLEND: u32 = 151;
+pub const D3DRS_WRAP0: u32 = 128;
+pub const D3DRS_WRAP1: u32 = 129;
+pub const D3DRS_WRAP10: u32 = 200;
+pub const D3DRS_WRAP11: u32 = 201;
+pub const D3DRS_WRAP12: u32 = 202;
+pub const D3DRS_WRAP13: u32 = 203;
+pub const D3DRS_WRAP14: u32 = 204;
+pub const D3DRS_WRAP15: u32 = 205;
+pub const D3DRS_WRAP2: u32 = 130;
+pub const D3DRS_WRAP3: u32 = 131;
+pub const D3DRS_WRAP4: u32 = 132;
+pub const D3DRS_WRAP5: u32 = 133;
+pub const D3DRS_WRAP6: u32 = 134;
+pub const D3DRS_WRAP7: u32 = 135;
+pub const D3DRS_WRAP8: u32 = 198;
+pub const D3DRS_WRAP9: u32 = 199;
+pub const D3DRS_ZENABLE: u32 = 7;
+pub const D3DRS_ZFUNC: u32 = 23;
+pub const D3DRS_ZWRITEENABLE: u32 = 14;
+pub const D3DRTYPE_CUBETEXTURE: u32 = 5;
+pub const D3DRTYPE_INDEXBUFFER: u32 = 7;
+pub const D3DRTYPE_SURFACE: u32 = 1;
+pub const D3DRTYPE_TEXTURE: u32 = 3;
+pub const D3DRTYPE_VERTEXBUFFER: u32 = 6;
+pub const D3DRTYPE_VOLUME: u32 = 2;
+pub const D3DRTYPE_VOLUMETEXTURE: u32 = 4;
+pub const D3DSAMP_ADDRESSU: u32 = 1;
+pub const D3DSAMP_ADDRESSV: u32 = 2;
+pub const D3DSAMP_ADDRESSW: u32 = 3;
+pub const D3DSAMP_BORDERCOLOR: u32 = 4;
+pub const D3DSAMP_DMAPOFFSET: u32 = 13;
+pub const D3DSAMP_ELEMENTINDEX: u32 = 12;
+pub const D3DSAMP_MAGFILTER: u32 = 5;
+pub const D3DSAMP_MAXANISOTROPY: u32 = 10;
+pub const D3DSAMP_MAXMIPLEVEL: u32 = 9;
+pub const D3DSAMP_MINFILTER: u32 = 6;
+pub const D3DSAMP_MIPFILTER: u32 = 7;
+pub const D3DSAMP_MIPMAPLODBIAS: u32 = 8;
+pub const D3DSAMP_SRGBTEXTURE: u32 = 11;
+pub const D3DSBT_ALL: u32 = 1;
+pub const D3DSBT_PIXELSTATE: u32 = 2;
+pub const D3DSBT_VERTEXSTATE: u32 = 3;
+pub const D3DSHADE_FLAT: u32 = 1;
+pub const D3DSHADE_GOURAUD: u32 = 2;
+pub const D3DSHADE_PHONG: u32 = 3;
+pub const D3DSTENCILOP_DECR: u32 = 8;
+pub const D3DSTENCILOP_DECRSAT: u32 = 5;
+pub const D3DSTENCILOP_INCR: u32 = 7;
+pub const D3DSTENCILOP_INCRSAT: u32 = 4;
+pub const D3DSTENCILOP_INVERT: u32 = 6;
+pub const D3DSTENCILOP_KEEP: u32 = 1;
+pub const D3DSTENCILOP_REPLACE: u32 = 3;
+pub const D3DSTENCILOP_ZERO: u32 = 2;
+pub const D3DSWAPEFFECT_COPY: u32 = 3;
+pub const D3DSWAPEFFECT_DISCARD: u32 = 1;
+pub const D3DSWAPEFFECT_FLIP: u32 = 2;
+pub const D3DSWAPEFFECT_OVERLAY: u32 = 4;
+pub const D3DTADDRESS_BORDER: u32 = 4;
+pub const D3DTADDRESS_CLAMP: u32 = 3;
+pub const D3DTADDRESS_MIRROR: u32 = 2;
+pub const D3DTADDRESS_MIRRORONCE: u32 = 5;
+pub const D3DTADDRESS_WRAP: u32 = 1;
+pub const D3DTA_ALPHAREPLICATE: u32 = 0x00000020;
+pub const D3DTA_COMPLEMENT: u32 = 0x00000010;
+pub const D3DTA_CONSTANT: u32 = 0x00000006;
+pub const D3DTA_CURRENT: u32 = 0x00000001;
+pub const D3DTA_DIFFUSE: u32 = 0x00000000;
+pub const D3DTA_SELECTMASK: u32 = 0x0000000f;
+pub const D3DTA_SPECULAR: u32 = 0x00000004;
+pub const D3DTA_TEMP: u32 = 0x00000005;
+pub const D3DTA_TEXTURE: u32 = 0x00000002;
+pub const D3DTA_TFACTOR: u32 = 0x00000003;
+pub const D3DTEXF_ANISOTROPIC: u32 = 3;
+pub const D3DTEXF_CONVOLUTIONMONO: u32 = 8;
+pub const D3DTEXF_GAUSSIANQUAD: u32 = 7;
+pub const D3DTEXF_LINEAR: u32 = 2;
+pub const D3DTEXF_NONE: u32 = 0;
+pub const D3DTEXF_POINT: u32 = 1;
+pub const D3DTEXF_PYRAMIDALQUAD: u32 = 6;
+pub const D3DTOP_ADD: u32 = 7;
+pub const D3DTOP_ADDSIGNED: u32 = 8;
+pub const D3DTOP_ADDSIGNED2X: u32 = 9;
+pub const D3DTOP_ADDSMOOTH: u32 = 11;
+pub const D3DTOP_BLENDCURRENTALPHA: u32 = 16;
+pub const D3DTOP_BLENDDIFFUSEALPHA: u32 = 12;
+pub const D3DTOP_BLENDFACTORALPHA: u32 = 14;
+pub const D3DTOP_BLENDTEXTUREALPHA: u32 = 13;
+pub const D3DTOP_BLENDTEXTUREALPHAPM: u32 = 15;
+pub const D3DTOP_BUMPENVMAP: u32 = 22;
+pub const D3DTOP_BUMPENVMAPLUMINANCE: u32 = 23;
+pub const D3DTOP_DISABLE: u32 = 1;
+pub const D3DTOP_DOTPRODUCT3: u32 = 24;
+pub const D3DTOP_LERP: u32 = 26;
+pub const D3DTOP_MODULATE: u32 = 4;
+pub const D3DTOP_MODULATE2X: u32 = 5;
+pub const D3DTOP_MODULATE4X: u32 = 6;
+pub const D3DTOP_MODULATEALPHA_ADDCOLOR: u32 = 18;
+pub const D3DTOP_MODULATECOLOR_ADDALPHA: u32 = 19;
+pub const D3DTOP_MODULATEINVALPHA_ADDCOLOR: u32 = 20;
+pub const D3DTOP_MODULATEINVCOLOR_ADDALPHA: u32 = 21;
+pub const D3DTOP_MULTIPLYADD: u32 = 25;
+pub const D3DTOP_PREMODULATE: u32 = 17;
+pub const D3DTOP_SELECTARG1: u32 = 2;
+pub const D3DTOP_SELECTARG2: u32 = 3;
+pub const D3DTOP_SUBTRACT: u32 = 10;
+pub const D3DTSS_ALPHAARG0: u32 = 27;
+pub const D3DTSS_ALPHAARG1: u32 = 5;
+pub const D3DTSS_ALPHAARG2: u32 = 6;
+pub const D3DTSS_ALPHAOP: u32 = 4;
+pub const D3DTSS_BUMPENVLOFFSET: u32 = 23;
+pub const D3DTSS_BUMPENVLSCALE: u32 = 22;
+pub const D3DTSS_BUMPENVMAT00: u32 = 7;
+pub const D3DTSS_BUMPENVMAT01: u32 = 8;
+pub const D3DTSS_BUMPENVMAT10: u32 = 9;
+pub const D3DTSS_BUMPENVMAT11: u32 = 10;
+pub const D3DTSS_COLORARG0: u32 = 26;
+pub const D3DTSS_COLORARG1: u32 = 2;
+pub const D3DTSS_COLORARG2: u32 = 3;
+pub const D3DTSS_COLOROP: u32 = 1;
+pub const D3DTSS_CONSTANT: u32 = 32;
+pub const D3DTSS_RESULTARG: u32 = 28;
+pub const D3DTSS_TCI_CAMERASPACENORMAL: u32 = 0x00010000;
+pub const D3DTSS_TCI_CAMERASPACEPOSITION: u32 = 0x00020000;
+pub const D3DTSS_TCI_CAMERASPACEREFLECTIONVECTOR: u32 = 0x00030000;
+pub const D3DTSS_TCI_PASSTHRU: u32 = 0x00000000;
+pub const D3DTSS_TCI_SPHEREMAP: u32 = 0x00040000;
+pub const D3DTSS_TEXCOORDINDEX: u32 = 11;
+pub const D3DTSS_TEXTURETRANSFORMFLAGS: u32 = 24;
+pub const D3DTTFF_PROJECTED: u32 = 256;
+pub const D3DVBF_0WEIGHTS: u32 = 256;
+pub const D3DVBF_1WEIGHTS: u32 = 1;
+pub const D3DVBF_2WEIGHTS: u32 = 2;
+pub const D3DVBF_3WEIGHTS: u32 = 3;
+pub const D3DVBF_DISABLE: u32 = 0;
+pub const D3DVBF_TWEENING: u32 = 255;
+pub const D3DZB_FALSE: u32 = 0;
+pub const D3DZB_TRUE: u32 = 1;
+pub const D3DZB_USEW: u32 = 2;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct D3DADAPTER_IDENTIFIER9 {
+    pub Driver: [i8; 512],
+    pub Description: [i8; 512],
+    pub DeviceName: [i8; 32],
+    pub DriverVersion: LARGE_INTEGER,
+    pub VendorId: u32,
+    pub DeviceId: u32,
+    pub SubSysId: u32,
+    pub Revision: u32,
+    pub DeviceIdentifier: GUID,
+    pub WHQLLevel: u32,
+}
+
+pub type D3DCOLOR = u32;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct D3DDISPLAYMODE {
+    pub Width: u32,
+    pub Height: u32,
+    pub RefreshRate: u32,
+    pub Format: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct D3DLOCKED_BOX {
+    pub RowPitch: i32,
+    pub SlicePitch: i32,
+    pub pBits: *mut c_void,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct D3DLOCKED_RECT {
+    pub Pitch: i32,
+    pub pBits: *mut c_void,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct D3DPRESENT_PARAMETERS {
+    pub BackBufferWidth: u32,
+    pub BackBufferHeight: u32,
+    pub BackBufferFormat: u32,
+    pub BackBufferCount: u32,
+    pub MultiSampleType: u32,
+    pub MultiSampleQuality: u32,
+    pub SwapEffect: u32,
+    pub hDeviceWindow: HWND,
+    pub Windowed: i32,
+    pub EnableAutoDepthStencil: i32,
+    pub AutoDepthStencilFormat: u32,
+    pub Flags: u32,
+    pub FullScreen_RefreshRateInHz: u32,
+    pub PresentationInterval: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct D3DSURFACE_DESC {
+    pub Format: u32,
+    pub Type: u32,
+    pub Usage: u32,
+    pub Pool: u32,
+    pub MultiSampleType: u32,
+    pub MultiSampleQuality: u32,
+    pub Width: u32,
+    pub Height: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct D3DVERTEXELEMENT9 {
+    pub Stream: u16,
+    pub Offset: u16,
+    pub Type: u8,
+    pub Method: u8,
+    pub Usage: u8,
+    pub UsageIndex: u8,
+}
@@ -0,0 +1,62 @@
+//! Raw Direct3D9 constants and structures.
+//!
+//! On Windows, these are re-exported from `winapi`. Elsewhere `winapi` is empty, so the subset
+//! used by the platform-independent modules is mirrored here with the same names and layouts.
+//! This lets file parsing, format conversion, shader tooling and the recording backend be built
+//! and tested on any platform.
+
+#[cfg(windows)]
+pub use winapi::{
+    shared::{
+        d3d9::D3DADAPTER_DEFAULT,
+        d3d9caps, d3d9types,
+        guiddef::GUID,
+        windef::{HMONITOR, HWND},
+    },
+    um::{
+        wingdi::RGNDATA,
+        winnt::{HRESULT, LARGE_INTEGER},
+    },
+};
+
+/// `MAKE_D3DHRESULT(2156)`, which `winapi` does not define on any platform.
+pub const D3DERR_INVALIDCALL: HRESULT = 0x8876086Cu32 as HRESULT;
+
+#[cfg(not(windows))]
+pub mod d3d9caps;
+#[cfg(not(windows))]
+pub mod d3d9types;
+
+#[cfg(not(windows))]
+pub use self::other::*;
+
+#[cfg(not(windows))]
+#[allow(non_camel_case_types, non_snake_case, clippy::upper_case_acronyms)]
+mod other {
+    use std::ffi::c_void;
+
+    pub const D3DADAPTER_DEFAULT: u32 = 0;
+
+    pub type HRESULT = i32;
+    pub type HMONITOR = *mut c_void;
+    pub type HWND = *mut c_void;
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct GUID {
+        pub Data1: u32,
+        pub Data2: u16,
+        pub Data3: u16,
+        pub Data4: [u8; 8],
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct LARGE_INTEGER(pub i64);
+
+    /// Region data is only ever passed through to `Present`, which does not exist here.
+    #[repr(C)]
+    pub struct RGNDATA {
+        _private: [u8; 0],
+    }
+}
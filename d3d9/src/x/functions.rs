@@ -1,5 +1,7 @@
 use std::{
-    ffi::{CString, NulError},
+    ffi::{CStr, CString, NulError},
+    io,
+    path::{Path, PathBuf},
     ptr,
     ptr::NonNull,
     str::Utf8Error,
@@ -7,19 +9,20 @@ use std::{
 
 use d3dx9_sys::{
     d3dx9core::ID3DXBuffer,
-    d3dx9shader::{
-        D3DXCompileShader, D3DXCompileShaderFromFileA, ID3DXConstantTable, D3DXMACRO,
-        D3DXSHADER_DEBUG,
-    },
+    d3dx9shader::{D3DXCompileShader, D3DXCompileShaderFromFileA, ID3DXConstantTable, D3DXMACRO},
 };
-use d3dx9_sys::d3dx9shader::D3DXSHADER_PACKMATRIX_COLUMNMAJOR;
 
 use crate::{
     check_hresult, check_hresult_mut,
     error::WindowsError,
+    shader::{
+        parse_diagnostics, CacheKey, CachedShader, Diagnostic, IncludeError, IncludeHandler,
+        ResolvedInclude, ShaderCache,
+    },
     x::{
+        include::Include,
         interfaces::{Buffer, ConstantTable},
-        types::Macro,
+        types::CompileOptions,
     },
 };
 
@@ -36,58 +39,233 @@ pub enum CompileError {
         #[from]
         from: NulError,
     },
+
+    #[error("Include Error: {from}")]
+    Include {
+        #[from]
+        from: IncludeError,
+    },
+
+    #[error("Io Error: {from}")]
+    Io {
+        #[from]
+        from: io::Error,
+    },
+
+    #[error("path {0:?} is not valid unicode")]
+    InvalidPath(PathBuf),
+
+    #[error("failed to compile shader ({from}):\n{log}")]
+    Compilation {
+        from: WindowsError,
+        /// The output of the compiler.
+        log: String,
+        diagnostics: Vec<Diagnostic>,
+    },
 }
 
+impl CompileError {
+    /// Returns the messages reported by the compiler, if it got to run.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        match self {
+            Self::Compilation { diagnostics, .. } => diagnostics,
+            _ => &[],
+        }
+    }
+}
+
+/// Represents the output of a successful compilation.
+#[derive(Clone)]
+pub struct CompiledShader {
+    pub shader: Buffer,
+    pub constant_table: ConstantTable,
+    /// The warnings reported by the compiler.
+    pub diagnostics: Vec<Diagnostic>,
+    /// The files opened through the [`IncludeHandler`].
+    pub includes: Vec<ResolvedInclude>,
+}
+
+/// Compiles HLSL source code.
+///
+/// Without an `include_handler`, any `#include` directive fails.
 pub fn compile_shader<S: Into<String>, F: Into<String>, P: Into<String>>(
     src_data: S,
-    defines: Option<&[Macro]>,
     function_name: F,
     profile: P,
-) -> Result<(Buffer, ConstantTable), CompileError> {
+    options: &CompileOptions,
+    include_handler: Option<&mut dyn IncludeHandler>,
+) -> Result<CompiledShader, CompileError> {
     let src_data = src_data.into();
     let c_src_data = CString::new(src_data.clone())?;
-    let c_function_name = CString::new(function_name.into())?;
-    let c_profile = CString::new(profile.into())?;
-
-    let defines_ptr = match defines {
-        Some(defines) => {
-            let c_defines = Vec::from(defines);
-            let mut c_defines = c_defines
-                .into_iter()
-                .map(|define| unsafe { define.as_d3dx() })
-                .collect::<Vec<D3DXMACRO>>();
-            c_defines.push(D3DXMACRO {
-                Name: ptr::null(),
-                Definition: ptr::null(),
-            });
-            c_defines.as_ptr()
-        }
-        None => ptr::null(),
+
+    compile(
+        Source::Memory(&c_src_data),
+        function_name.into(),
+        profile.into(),
+        options,
+        include_handler,
+    )
+}
+
+/// Compiles an HLSL file.
+///
+/// Without an `include_handler`, D3DX resolves includes relative to the file itself.
+pub fn compile_shader_from_file<Q: AsRef<Path>, F: Into<String>, P: Into<String>>(
+    path: Q,
+    function_name: F,
+    profile: P,
+    options: &CompileOptions,
+    include_handler: Option<&mut dyn IncludeHandler>,
+) -> Result<CompiledShader, CompileError> {
+    let path = path.as_ref();
+    let path = path
+        .to_str()
+        .ok_or_else(|| CompileError::InvalidPath(path.to_path_buf()))?;
+    let c_path = CString::new(path)?;
+
+    compile(
+        Source::File(&c_path),
+        function_name.into(),
+        profile.into(),
+        options,
+        include_handler,
+    )
+}
+
+/// Compiles HLSL source code, reusing the output of an earlier compilation stored in `cache`.
+///
+/// Compiler warnings are only reported when the shader is actually compiled.
+pub fn compile_shader_cached(
+    cache: &ShaderCache,
+    src_data: &str,
+    function_name: &str,
+    profile: &str,
+    options: &CompileOptions,
+    mut include_handler: Option<&mut dyn IncludeHandler>,
+) -> Result<CachedShader, CompileError> {
+    let key = CacheKey::new(src_data, function_name, profile, options);
+    let handler = include_handler
+        .as_mut()
+        .map(|handler| &mut **handler as &mut dyn IncludeHandler);
+    if let Some(shader) = cache.get(key, handler)? {
+        return Ok(shader);
+    }
+
+    let compiled = compile_shader(src_data, function_name, profile, options, include_handler)?;
+
+    Ok(cache.insert(key, &compiled.includes, compiled.shader.as_bytes())?)
+}
+
+enum Source<'a> {
+    Memory(&'a CStr),
+    File(&'a CStr),
+}
+
+fn compile(
+    source: Source,
+    function_name: String,
+    profile: String,
+    options: &CompileOptions,
+    include_handler: Option<&mut dyn IncludeHandler>,
+) -> Result<CompiledShader, CompileError> {
+    let c_function_name = CString::new(function_name)?;
+    let c_profile = CString::new(profile)?;
+
+    // The strings must outlive the `D3DXMACRO` array which points into them.
+    let c_define_strings = options
+        .macros
+        .iter()
+        .map(|define| {
+            Ok((
+                CString::new(define.name.as_str())?,
+                CString::new(define.definition.as_str())?,
+            ))
+        })
+        .collect::<Result<Vec<_>, NulError>>()?;
+    let mut c_defines = c_define_strings
+        .iter()
+        .map(|(name, definition)| D3DXMACRO {
+            Name: name.as_ptr(),
+            Definition: definition.as_ptr(),
+        })
+        .collect::<Vec<D3DXMACRO>>();
+    c_defines.push(D3DXMACRO {
+        Name: ptr::null(),
+        Definition: ptr::null(),
+    });
+
+    let defines_ptr = if options.macros.is_empty() {
+        ptr::null()
+    } else {
+        c_defines.as_ptr()
+    };
+
+    let root = match source {
+        Source::File(path) => Some(path.to_string_lossy()),
+        Source::Memory(_) => None,
+    };
+    let mut include = include_handler.map(|handler| Include::new(handler, root.as_deref()));
+    let include_ptr = match &mut include {
+        Some(include) => include.as_ptr(),
+        None => ptr::null_mut(),
     };
 
     let mut c_shader: *mut ID3DXBuffer = ptr::null_mut();
     let mut c_error_messages: *mut ID3DXBuffer = ptr::null_mut();
     let mut c_constant_table: *mut ID3DXConstantTable = ptr::null_mut();
 
-    unsafe {
-        check_hresult_mut!(D3DXCompileShader(
-            c_src_data.as_ptr(),
-            src_data.len() as u32,
-            defines_ptr,
-            ptr::null(),
-            c_function_name.as_ptr(),
-            c_profile.as_ptr(),
-            D3DXSHADER_DEBUG | D3DXSHADER_PACKMATRIX_COLUMNMAJOR,
-            &mut c_shader as *mut _,
-            &mut c_error_messages as *mut _,
-            &mut c_constant_table as *mut _,
-        ))?;
+    let result = unsafe {
+        match source {
+            Source::Memory(src_data) => check_hresult_mut!(D3DXCompileShader(
+                src_data.as_ptr(),
+                src_data.to_bytes().len() as u32,
+                defines_ptr,
+                include_ptr,
+                c_function_name.as_ptr(),
+                c_profile.as_ptr(),
+                options.flags(),
+                &mut c_shader as *mut _,
+                &mut c_error_messages as *mut _,
+                &mut c_constant_table as *mut _,
+            )),
+            Source::File(path) => check_hresult_mut!(D3DXCompileShaderFromFileA(
+                path.as_ptr(),
+                defines_ptr,
+                include_ptr,
+                c_function_name.as_ptr(),
+                c_profile.as_ptr(),
+                options.flags(),
+                &mut c_shader as *mut _,
+                &mut c_error_messages as *mut _,
+                &mut c_constant_table as *mut _,
+            )),
+        }
+    };
+
+    let log = NonNull::new(c_error_messages)
+        .map(|error_messages| Buffer::with_ptr(error_messages).to_string_lossy())
+        .unwrap_or_default();
+    let diagnostics = parse_diagnostics(&log);
+
+    if let Err(from) = result {
+        // The compiler only reports that an include could not be opened, so prefer the reason.
+        if let Some(error) = include.as_mut().and_then(Include::take_error) {
+            return Err(error.into());
+        }
+
+        return Err(CompileError::Compilation {
+            from,
+            log,
+            diagnostics,
+        });
     }
 
-    Ok((
-        Buffer::with_ptr(NonNull::new(c_shader).expect("returned shader buffer is null")),
-        ConstantTable::with_ptr(
+    Ok(CompiledShader {
+        shader: Buffer::with_ptr(NonNull::new(c_shader).expect("returned shader buffer is null")),
+        constant_table: ConstantTable::with_ptr(
             NonNull::new(c_constant_table).expect("returned constant table is null"),
         ),
-    ))
+        diagnostics,
+        includes: include.map(Include::into_includes).unwrap_or_default(),
+    })
 }
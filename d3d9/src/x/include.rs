@@ -0,0 +1,103 @@
+//! Bridges an [`IncludeHandler`] to the `ID3DXInclude` interface expected by D3DX.
+
+use std::{ffi::CStr, panic, panic::AssertUnwindSafe};
+
+use d3dx9_sys::d3dx9shader::{
+    ID3DXInclude, ID3DXIncludeVtbl, D3DXINCLUDE_TYPE, D3DXINC_SYSTEM, LPD3DXINCLUDE,
+};
+use winapi::{
+    shared::minwindef::LPCVOID,
+    um::winnt::{HRESULT, LPCSTR},
+};
+
+use crate::{
+    error::ErrorCode,
+    shader::{IncludeError, IncludeHandler, IncludeResolver, IncludeType, ResolvedInclude},
+};
+
+static VTBL: ID3DXIncludeVtbl = ID3DXIncludeVtbl {
+    Open: open,
+    Close: close,
+};
+
+/// An `ID3DXInclude` implementation which forwards to an [`IncludeResolver`].
+///
+/// D3DX only sees the first field, so the struct must not move while the compiler is running.
+#[repr(C)]
+pub(crate) struct Include<'a> {
+    base: ID3DXInclude,
+    resolver: IncludeResolver<'a>,
+    error: Option<IncludeError>,
+}
+
+impl<'a> Include<'a> {
+    pub(crate) fn new(handler: &'a mut dyn IncludeHandler, root: Option<&str>) -> Self {
+        let resolver = IncludeResolver::new(handler);
+
+        Self {
+            base: ID3DXInclude { lpVtbl: &VTBL },
+            resolver: match root {
+                Some(root) => resolver.with_root(root),
+                None => resolver,
+            },
+            error: None,
+        }
+    }
+
+    pub(crate) fn as_ptr(&mut self) -> LPD3DXINCLUDE {
+        &mut self.base as *mut _
+    }
+
+    /// Returns the first error reported by the handler.
+    pub(crate) fn take_error(&mut self) -> Option<IncludeError> {
+        self.error.take()
+    }
+
+    /// Returns every file which was included.
+    pub(crate) fn into_includes(self) -> Vec<ResolvedInclude> {
+        self.resolver.into_includes()
+    }
+}
+
+unsafe extern "system" fn open(
+    this: *mut ID3DXInclude,
+    include_type: D3DXINCLUDE_TYPE,
+    file_name: LPCSTR,
+    parent_data: LPCVOID,
+    data: *mut LPCVOID,
+    bytes: *mut u32,
+) -> HRESULT {
+    let this = &mut *(this as *mut Include);
+
+    // Unwinding into D3DX is undefined behaviour, so a panicking handler fails the include instead.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let include_type = match include_type {
+            D3DXINC_SYSTEM => IncludeType::System,
+            _ => IncludeType::Local,
+        };
+        let name = CStr::from_ptr(file_name).to_string_lossy();
+        let parent = this.resolver.find(parent_data as *const u8);
+
+        this.resolver.open(include_type, &name, parent)
+    }));
+
+    match result {
+        Ok(Ok(id)) => {
+            let contents = this.resolver.data(id);
+            *data = contents.as_ptr() as LPCVOID;
+            *bytes = contents.len() as u32;
+
+            0
+        }
+        Ok(Err(error)) => {
+            this.error.get_or_insert(error);
+            ErrorCode::Fail.hresult()
+        }
+        Err(_) => ErrorCode::Fail.hresult(),
+    }
+}
+
+/// The data is owned by the resolver and released after compilation, so there is nothing to do.
+unsafe extern "system" fn close(_this: *mut ID3DXInclude, _data: LPCVOID) -> HRESULT {
+    0
+}
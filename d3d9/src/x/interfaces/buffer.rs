@@ -1,4 +1,4 @@
-use std::ptr::NonNull;
+use std::{ptr::NonNull, slice};
 
 use d3dx9_sys::d3dx9core::ID3DXBuffer;
 use winapi::um::winnt::VOID;
@@ -28,4 +28,25 @@ impl Buffer {
     pub fn get_buffer_size(&self) -> u32 {
         unsafe { self.inner.GetBufferSize() }
     }
+
+    /// Returns the contents of the buffer.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            slice::from_raw_parts(
+                self.get_buffer_ptr() as *const u8,
+                self.get_buffer_size() as usize,
+            )
+        }
+    }
+
+    /// Returns the contents of the buffer as text, up to the first nul.
+    pub fn to_string_lossy(&self) -> String {
+        let bytes = self.as_bytes();
+        let len = bytes
+            .iter()
+            .position(|byte| *byte == 0)
+            .unwrap_or(bytes.len());
+
+        String::from_utf8_lossy(&bytes[..len]).into_owned()
+    }
 }
@@ -55,13 +55,10 @@ impl ConstantTable {
         }
     }
 
-    pub fn get_constant_desc<N: Into<String>>(
-        &self,
-        constant: Handle,
-    ) -> WindowsResult<ConstantDesc> {
+    pub fn get_constant_desc(&self, constant: Handle) -> WindowsResult<ConstantDesc> {
         unsafe {
             let mut c_constant_desc: D3DXCONSTANT_DESC = MaybeUninit::zeroed().assume_init();
-            let mut c_count = 0u32;
+            let mut c_count = 1u32;
 
             check_hresult_mut!(self.inner.GetConstantDesc(
                 constant,
@@ -87,6 +84,20 @@ impl ConstantTable {
         }
     }
 
+    /// Returns an iterator over every constant in the table.
+    ///
+    /// Each top-level constant is followed by its array elements, and each constant or element
+    /// by its struct members, depth first.
+    pub fn constants(&self) -> WindowsResult<Constants<'_>> {
+        let desc = self.get_desc()?;
+        let stack = (0..desc.constants)
+            .rev()
+            .map(|index| (self.get_constant(None, index), None))
+            .collect();
+
+        Ok(Constants { table: self, stack })
+    }
+
     pub fn get_sampler_index(&self, handle: Handle) -> u32 {
         unsafe { self.inner.GetSamplerIndex(handle) }
     }
@@ -208,3 +219,63 @@ impl ConstantTable {
         Ok(())
     }
 }
+
+/// Represents a constant visited by [`ConstantTable::constants`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstantEntry {
+    pub handle: Handle,
+    /// The full name of the constant, such as `lights[1].color`.
+    pub path: String,
+    pub desc: ConstantDesc,
+}
+
+/// An iterator over the constants of a [`ConstantTable`], returned by
+/// [`ConstantTable::constants`].
+pub struct Constants<'a> {
+    table: &'a ConstantTable,
+    stack: Vec<(Handle, Option<String>)>,
+}
+
+impl Iterator for Constants<'_> {
+    type Item = WindowsResult<ConstantEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (handle, path) = loop {
+            let (handle, path) = self.stack.pop()?;
+            if !handle.is_null() {
+                break (handle, path);
+            }
+        };
+
+        let desc = match self.table.get_constant_desc(handle) {
+            Ok(desc) => desc,
+            Err(error) => return Some(Err(error)),
+        };
+        let path = path.unwrap_or_else(|| desc.name.clone());
+
+        // Children are pushed in reverse, so that they are visited in order.
+        if desc.elements > 1 {
+            for index in (0..desc.elements).rev() {
+                self.stack.push((
+                    self.table.get_constant_element(handle, index),
+                    Some(format!("{path}[{index}]")),
+                ));
+            }
+        } else {
+            for index in (0..desc.struct_members).rev() {
+                let member = self.table.get_constant(Some(handle), index);
+                if member.is_null() {
+                    continue;
+                }
+
+                let name = match self.table.get_constant_desc(member) {
+                    Ok(member_desc) => member_desc.name,
+                    Err(error) => return Some(Err(error)),
+                };
+                self.stack.push((member, Some(format!("{path}.{name}"))));
+            }
+        }
+
+        Some(Ok(ConstantEntry { handle, path, desc }))
+    }
+}
@@ -2,4 +2,4 @@ mod buffer;
 mod constant_table;
 
 pub use buffer::Buffer;
-pub use constant_table::ConstantTable;
+pub use constant_table::{ConstantEntry, ConstantTable, Constants};
@@ -1,3 +1,7 @@
+#[cfg(windows)]
 pub mod functions;
+#[cfg(windows)]
+mod include;
+#[cfg(windows)]
 pub mod interfaces;
 pub mod types;
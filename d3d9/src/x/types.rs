@@ -1,52 +1,305 @@
-use std::{
-    ffi::{CStr, CString},
-    mem::transmute,
-};
+#[cfg(windows)]
+use std::{ffi::CStr, slice};
 
-use bitfield::bitfield;
+#[cfg(windows)]
+use d3dx9_sys::d3dx9shader::{D3DXCONSTANTTABLE_DESC, D3DXCONSTANT_DESC};
 use d3dx9_sys::d3dx9shader::{
-    D3DXCONSTANTTABLE_DESC, D3DXCONSTANT_DESC, D3DXMACRO, D3DXPC_MATRIX_COLUMNS,
-    D3DXPC_MATRIX_ROWS, D3DXPC_OBJECT, D3DXPC_SCALAR, D3DXPC_STRUCT, D3DXPC_VECTOR, D3DXPT_BOOL,
-    D3DXPT_FLOAT, D3DXPT_INT, D3DXPT_PIXELFRAGMENT, D3DXPT_PIXELSHADER, D3DXPT_SAMPLER,
-    D3DXPT_SAMPLER1D, D3DXPT_SAMPLER2D, D3DXPT_SAMPLER3D, D3DXPT_SAMPLERCUBE, D3DXPT_STRING,
-    D3DXPT_TEXTURE, D3DXPT_TEXTURE1D, D3DXPT_TEXTURE2D, D3DXPT_TEXTURE3D, D3DXPT_TEXTURECUBE,
-    D3DXPT_UNSUPPORTED, D3DXPT_VERTEXFRAGMENT, D3DXPT_VERTEXSHADER, D3DXPT_VOID, D3DXRS_BOOL,
-    D3DXRS_FLOAT4, D3DXRS_INT4, D3DXRS_SAMPLER,
+    D3DXPC_MATRIX_COLUMNS, D3DXPC_MATRIX_ROWS, D3DXPC_OBJECT, D3DXPC_SCALAR, D3DXPC_STRUCT,
+    D3DXPC_VECTOR, D3DXPT_BOOL, D3DXPT_FLOAT, D3DXPT_INT, D3DXPT_PIXELFRAGMENT, D3DXPT_PIXELSHADER,
+    D3DXPT_SAMPLER, D3DXPT_SAMPLER1D, D3DXPT_SAMPLER2D, D3DXPT_SAMPLER3D, D3DXPT_SAMPLERCUBE,
+    D3DXPT_STRING, D3DXPT_TEXTURE, D3DXPT_TEXTURE1D, D3DXPT_TEXTURE2D, D3DXPT_TEXTURE3D,
+    D3DXPT_TEXTURECUBE, D3DXPT_UNSUPPORTED, D3DXPT_VERTEXFRAGMENT, D3DXPT_VERTEXSHADER,
+    D3DXPT_VOID, D3DXRS_BOOL, D3DXRS_FLOAT4, D3DXRS_INT4, D3DXRS_SAMPLER,
+    D3DXSHADER_AVOID_FLOW_CONTROL, D3DXSHADER_DEBUG, D3DXSHADER_ENABLE_BACKWARDS_COMPATIBILITY,
+    D3DXSHADER_FORCE_PS_SOFTWARE_NOOPT, D3DXSHADER_FORCE_VS_SOFTWARE_NOOPT,
+    D3DXSHADER_IEEE_STRICTNESS, D3DXSHADER_NO_PRESHADER, D3DXSHADER_OPTIMIZATION_LEVEL0,
+    D3DXSHADER_OPTIMIZATION_LEVEL1, D3DXSHADER_OPTIMIZATION_LEVEL2, D3DXSHADER_OPTIMIZATION_LEVEL3,
+    D3DXSHADER_PACKMATRIX_COLUMNMAJOR, D3DXSHADER_PACKMATRIX_ROWMAJOR, D3DXSHADER_PARTIALPRECISION,
+    D3DXSHADER_PREFER_FLOW_CONTROL, D3DXSHADER_SKIPOPTIMIZATION, D3DXSHADER_SKIPVALIDATION,
+    D3DXSHADER_USE_LEGACY_D3DX9_31_DLL,
 };
 
-#[derive(Clone, Debug)]
+/// Represents a preprocessor definition passed to the shader compiler.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Macro {
-    pub name: &'static CStr,
-    pub definition: &'static CStr,
+    pub name: String,
+    pub definition: String,
 }
 
 impl Macro {
-    pub unsafe fn as_d3dx(&self) -> D3DXMACRO {
-        D3DXMACRO {
-            Name: self.name.as_ptr(),
-            Definition: self.definition.as_ptr(),
+    pub fn new<N: Into<String>, D: Into<String>>(name: N, definition: D) -> Self {
+        Self {
+            name: name.into(),
+            definition: definition.into(),
         }
     }
 }
 
+/// Represents how much the shader compiler optimizes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OptimizationLevel {
+    /// Skips optimization entirely, which is only useful for debugging.
+    Skip,
+    Level0,
+    #[default]
+    Level1,
+    Level2,
+    Level3,
+}
+
+/// Represents how matrices are laid out in constant registers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MatrixPacking {
+    #[default]
+    ColumnMajor,
+    RowMajor,
+}
+
+/// Represents the compiler's preference for dynamic flow control.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FlowControl {
+    /// Lets the compiler decide.
+    #[default]
+    Default,
+    Avoid,
+    Prefer,
+}
+
+/// Represents the options passed to the shader compiler.
+///
+/// The defaults match D3DX, which optimizes at [`OptimizationLevel::Level1`] and packs matrices
+/// in column-major order.
+///
+/// # Usage
+///
+/// ```rs
+/// let options = CompileOptions::new()
+///     .optimization(OptimizationLevel::Level3)
+///     .define("USE_SHADOWS", "1");
+///
+/// let compiled = compile_shader(source, "main", "ps_3_0", &options, None)?;
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompileOptions {
+    pub debug: bool,
+    pub skip_validation: bool,
+    pub optimization: OptimizationLevel,
+    pub matrix_packing: MatrixPacking,
+    pub partial_precision: bool,
+    pub force_vs_software_no_opt: bool,
+    pub force_ps_software_no_opt: bool,
+    pub no_preshader: bool,
+    pub flow_control: FlowControl,
+    pub backwards_compatibility: bool,
+    pub ieee_strictness: bool,
+    pub use_legacy_d3dx9_31_dll: bool,
+    pub macros: Vec<Macro>,
+}
+
+impl CompileOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Interprets a combination of `D3DXSHADER_*` flags.
+    ///
+    /// Unknown bits are ignored.
+    pub fn from_flags(flags: u32) -> Self {
+        const OPTIMIZATION_MASK: u32 = D3DXSHADER_OPTIMIZATION_LEVEL2;
+
+        let optimization = if flags & D3DXSHADER_SKIPOPTIMIZATION != 0 {
+            OptimizationLevel::Skip
+        } else {
+            match flags & OPTIMIZATION_MASK {
+                D3DXSHADER_OPTIMIZATION_LEVEL0 => OptimizationLevel::Level0,
+                D3DXSHADER_OPTIMIZATION_LEVEL2 => OptimizationLevel::Level2,
+                D3DXSHADER_OPTIMIZATION_LEVEL3 => OptimizationLevel::Level3,
+                _ => OptimizationLevel::Level1,
+            }
+        };
+
+        let matrix_packing = if flags & D3DXSHADER_PACKMATRIX_ROWMAJOR != 0 {
+            MatrixPacking::RowMajor
+        } else {
+            MatrixPacking::ColumnMajor
+        };
+
+        let flow_control = if flags & D3DXSHADER_AVOID_FLOW_CONTROL != 0 {
+            FlowControl::Avoid
+        } else if flags & D3DXSHADER_PREFER_FLOW_CONTROL != 0 {
+            FlowControl::Prefer
+        } else {
+            FlowControl::Default
+        };
+
+        Self {
+            debug: flags & D3DXSHADER_DEBUG != 0,
+            skip_validation: flags & D3DXSHADER_SKIPVALIDATION != 0,
+            optimization,
+            matrix_packing,
+            partial_precision: flags & D3DXSHADER_PARTIALPRECISION != 0,
+            force_vs_software_no_opt: flags & D3DXSHADER_FORCE_VS_SOFTWARE_NOOPT != 0,
+            force_ps_software_no_opt: flags & D3DXSHADER_FORCE_PS_SOFTWARE_NOOPT != 0,
+            no_preshader: flags & D3DXSHADER_NO_PRESHADER != 0,
+            flow_control,
+            backwards_compatibility: flags & D3DXSHADER_ENABLE_BACKWARDS_COMPATIBILITY != 0,
+            ieee_strictness: flags & D3DXSHADER_IEEE_STRICTNESS != 0,
+            use_legacy_d3dx9_31_dll: flags & D3DXSHADER_USE_LEGACY_D3DX9_31_DLL != 0,
+            macros: Vec::new(),
+        }
+    }
+
+    /// Returns the `D3DXSHADER_*` flags for the options.
+    pub fn flags(&self) -> u32 {
+        let mut flags = match self.optimization {
+            OptimizationLevel::Skip => D3DXSHADER_SKIPOPTIMIZATION,
+            OptimizationLevel::Level0 => D3DXSHADER_OPTIMIZATION_LEVEL0,
+            OptimizationLevel::Level1 => D3DXSHADER_OPTIMIZATION_LEVEL1,
+            OptimizationLevel::Level2 => D3DXSHADER_OPTIMIZATION_LEVEL2,
+            OptimizationLevel::Level3 => D3DXSHADER_OPTIMIZATION_LEVEL3,
+        };
+
+        flags |= match self.matrix_packing {
+            MatrixPacking::ColumnMajor => D3DXSHADER_PACKMATRIX_COLUMNMAJOR,
+            MatrixPacking::RowMajor => D3DXSHADER_PACKMATRIX_ROWMAJOR,
+        };
+
+        flags |= match self.flow_control {
+            FlowControl::Default => 0,
+            FlowControl::Avoid => D3DXSHADER_AVOID_FLOW_CONTROL,
+            FlowControl::Prefer => D3DXSHADER_PREFER_FLOW_CONTROL,
+        };
+
+        for (enabled, flag) in [
+            (self.debug, D3DXSHADER_DEBUG),
+            (self.skip_validation, D3DXSHADER_SKIPVALIDATION),
+            (self.partial_precision, D3DXSHADER_PARTIALPRECISION),
+            (
+                self.force_vs_software_no_opt,
+                D3DXSHADER_FORCE_VS_SOFTWARE_NOOPT,
+            ),
+            (
+                self.force_ps_software_no_opt,
+                D3DXSHADER_FORCE_PS_SOFTWARE_NOOPT,
+            ),
+            (self.no_preshader, D3DXSHADER_NO_PRESHADER),
+            (
+                self.backwards_compatibility,
+                D3DXSHADER_ENABLE_BACKWARDS_COMPATIBILITY,
+            ),
+            (self.ieee_strictness, D3DXSHADER_IEEE_STRICTNESS),
+            (
+                self.use_legacy_d3dx9_31_dll,
+                D3DXSHADER_USE_LEGACY_D3DX9_31_DLL,
+            ),
+        ] {
+            if enabled {
+                flags |= flag;
+            }
+        }
+
+        flags
+    }
+
+    /// Embeds debug information in the shader.
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    pub fn skip_validation(mut self, skip_validation: bool) -> Self {
+        self.skip_validation = skip_validation;
+        self
+    }
+
+    pub fn optimization(mut self, optimization: OptimizationLevel) -> Self {
+        self.optimization = optimization;
+        self
+    }
+
+    pub fn matrix_packing(mut self, matrix_packing: MatrixPacking) -> Self {
+        self.matrix_packing = matrix_packing;
+        self
+    }
+
+    /// Allows the compiler to use lower precision where the hardware supports it.
+    pub fn partial_precision(mut self, partial_precision: bool) -> Self {
+        self.partial_precision = partial_precision;
+        self
+    }
+
+    /// Compiles vertex shaders for the software vertex processor without optimizations.
+    pub fn force_vs_software_no_opt(mut self, force: bool) -> Self {
+        self.force_vs_software_no_opt = force;
+        self
+    }
+
+    /// Compiles pixel shaders for the software rasterizer without optimizations.
+    pub fn force_ps_software_no_opt(mut self, force: bool) -> Self {
+        self.force_ps_software_no_opt = force;
+        self
+    }
+
+    /// Disables preshaders, so constant expressions are evaluated on the GPU.
+    pub fn no_preshader(mut self, no_preshader: bool) -> Self {
+        self.no_preshader = no_preshader;
+        self
+    }
+
+    pub fn flow_control(mut self, flow_control: FlowControl) -> Self {
+        self.flow_control = flow_control;
+        self
+    }
+
+    /// Allows older HLSL syntax, and compiles `ps_1_x` shaders as `ps_2_0`.
+    pub fn backwards_compatibility(mut self, backwards_compatibility: bool) -> Self {
+        self.backwards_compatibility = backwards_compatibility;
+        self
+    }
+
+    pub fn ieee_strictness(mut self, ieee_strictness: bool) -> Self {
+        self.ieee_strictness = ieee_strictness;
+        self
+    }
+
+    /// Uses the compiler from version 31 of D3DX, which still supports `ps_1_x`.
+    pub fn use_legacy_d3dx9_31_dll(mut self, use_legacy_d3dx9_31_dll: bool) -> Self {
+        self.use_legacy_d3dx9_31_dll = use_legacy_d3dx9_31_dll;
+        self
+    }
+
+    /// Adds a preprocessor definition.
+    pub fn define<N: Into<String>, D: Into<String>>(mut self, name: N, definition: D) -> Self {
+        self.macros.push(Macro::new(name, definition));
+        self
+    }
+}
+
+/// Represents the description of a [`ConstantTable`](crate::x::interfaces::ConstantTable).
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ConstantTableDesc {
-    pub creator: &'static CStr,
+    pub creator: String,
     pub version: u32,
     pub constants: u32,
 }
 
+#[cfg(windows)]
 impl From<D3DXCONSTANTTABLE_DESC> for ConstantTableDesc {
     fn from(value: D3DXCONSTANTTABLE_DESC) -> Self {
         Self {
-            creator: unsafe { CStr::from_ptr(value.Creator) },
+            creator: unsafe { CStr::from_ptr(value.Creator) }
+                .to_string_lossy()
+                .into_owned(),
             version: value.Version,
             constants: value.Constants,
         }
     }
 }
 
+/// Represents the description of a single constant, struct member or array element.
+#[derive(Clone, Debug, PartialEq)]
 pub struct ConstantDesc {
-    pub name: &'static CStr,
+    pub name: String,
     pub register_set: RegisterSet,
     pub register_index: u32,
     pub register_count: u32,
@@ -57,47 +310,79 @@ pub struct ConstantDesc {
     pub elements: u32,
     pub struct_members: u32,
     pub bytes: u32,
-    pub default_value: *const (),
-}
-
-impl Into<D3DXCONSTANT_DESC> for ConstantDesc {
-    fn into(self) -> D3DXCONSTANT_DESC {
-        D3DXCONSTANT_DESC {
-            Name: self.name.as_ptr(),
-            RegisterSet: self.register_set.into(),
-            RegisterIndex: self.register_index,
-            RegisterCount: self.register_count,
-            Class: self.class.into(),
-            Type: self.kind.into(),
-            Rows: self.rows,
-            Columns: self.columns,
-            Elements: self.elements,
-            StructMembers: self.struct_members,
-            Bytes: self.bytes,
-            DefaultValue: self.default_value as *const _,
-        }
-    }
+    pub default_value: Option<DefaultValue>,
 }
 
+#[cfg(windows)]
 impl From<D3DXCONSTANT_DESC> for ConstantDesc {
     fn from(value: D3DXCONSTANT_DESC) -> Self {
+        let register_set = RegisterSet::from_raw(value.RegisterSet).expect("unknown register set");
+
+        let default_value = if value.DefaultValue.is_null() {
+            None
+        } else {
+            let data = unsafe {
+                slice::from_raw_parts(
+                    value.DefaultValue as *const u32,
+                    DefaultValue::data_len(register_set, value.RegisterCount),
+                )
+            };
+            DefaultValue::from_registers(register_set, data)
+        };
+
         Self {
-            name: unsafe { CStr::from_ptr(value.Name) },
-            register_set: unsafe { transmute(value.RegisterSet) },
+            name: unsafe { CStr::from_ptr(value.Name) }
+                .to_string_lossy()
+                .into_owned(),
+            register_set,
             register_index: value.RegisterIndex,
             register_count: value.RegisterCount,
-            class: unsafe { transmute(value.Class) },
-            kind: unsafe { transmute(value.Type) },
+            class: ParameterClass::from_raw(value.Class).expect("unknown parameter class"),
+            kind: ParameterType::from_raw(value.Type).expect("unknown parameter type"),
             rows: value.Rows,
             columns: value.Columns,
             elements: value.Elements,
             struct_members: value.StructMembers,
             bytes: value.Bytes,
-            default_value: value.DefaultValue as *const _,
+            default_value,
         }
     }
 }
 
+/// Represents the initial value of a constant, typed by its [`RegisterSet`].
+///
+/// Values are stored per register, so a `float3` occupies four floats.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DefaultValue {
+    Float(Vec<f32>),
+    Int(Vec<i32>),
+    Bool(Vec<bool>),
+}
+
+impl DefaultValue {
+    /// Returns the number of 32-bit values stored for `register_count` registers.
+    pub fn data_len(register_set: RegisterSet, register_count: u32) -> usize {
+        match register_set {
+            RegisterSet::Float4 | RegisterSet::Int4 => register_count as usize * 4,
+            RegisterSet::Bool => register_count as usize,
+            RegisterSet::Sampler => 0,
+        }
+    }
+
+    /// Interprets raw register data, returning [`None`] for samplers.
+    pub fn from_registers(register_set: RegisterSet, data: &[u32]) -> Option<Self> {
+        match register_set {
+            RegisterSet::Float4 => Some(Self::Float(
+                data.iter().copied().map(f32::from_bits).collect(),
+            )),
+            RegisterSet::Int4 => Some(Self::Int(data.iter().map(|value| *value as i32).collect())),
+            RegisterSet::Bool => Some(Self::Bool(data.iter().map(|value| *value != 0).collect())),
+            RegisterSet::Sampler => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum ParameterClass {
     Scalar = D3DXPC_SCALAR,
@@ -108,12 +393,27 @@ pub enum ParameterClass {
     Struct = D3DXPC_STRUCT,
 }
 
-impl Into<u32> for ParameterClass {
-    fn into(self) -> u32 {
-        self as u32
+impl ParameterClass {
+    pub fn from_raw(value: u32) -> Option<Self> {
+        match value {
+            D3DXPC_SCALAR => Some(Self::Scalar),
+            D3DXPC_VECTOR => Some(Self::Vector),
+            D3DXPC_MATRIX_ROWS => Some(Self::MatrixRows),
+            D3DXPC_MATRIX_COLUMNS => Some(Self::MatrixColumns),
+            D3DXPC_OBJECT => Some(Self::Object),
+            D3DXPC_STRUCT => Some(Self::Struct),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParameterClass> for u32 {
+    fn from(value: ParameterClass) -> Self {
+        value as u32
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum ParameterType {
     Void = D3DXPT_VOID,
@@ -138,14 +438,43 @@ pub enum ParameterType {
     Unsupported = D3DXPT_UNSUPPORTED,
 }
 
-impl Into<u32> for ParameterType {
-    fn into(self) -> u32 {
-        self as u32
+impl ParameterType {
+    pub fn from_raw(value: u32) -> Option<Self> {
+        match value {
+            D3DXPT_VOID => Some(Self::Void),
+            D3DXPT_BOOL => Some(Self::Bool),
+            D3DXPT_INT => Some(Self::Int),
+            D3DXPT_FLOAT => Some(Self::Float),
+            D3DXPT_STRING => Some(Self::String),
+            D3DXPT_TEXTURE => Some(Self::Texture),
+            D3DXPT_TEXTURE1D => Some(Self::Texture1D),
+            D3DXPT_TEXTURE2D => Some(Self::Texture2D),
+            D3DXPT_TEXTURE3D => Some(Self::Texture3D),
+            D3DXPT_TEXTURECUBE => Some(Self::TextureCube),
+            D3DXPT_SAMPLER => Some(Self::Sampler),
+            D3DXPT_SAMPLER1D => Some(Self::Sampler1D),
+            D3DXPT_SAMPLER2D => Some(Self::Sampler2D),
+            D3DXPT_SAMPLER3D => Some(Self::Sampler3D),
+            D3DXPT_SAMPLERCUBE => Some(Self::SamplerCube),
+            D3DXPT_PIXELSHADER => Some(Self::PixelShader),
+            D3DXPT_VERTEXSHADER => Some(Self::VertexShader),
+            D3DXPT_PIXELFRAGMENT => Some(Self::PixelFragment),
+            D3DXPT_VERTEXFRAGMENT => Some(Self::VertexFragment),
+            D3DXPT_UNSUPPORTED => Some(Self::Unsupported),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParameterType> for u32 {
+    fn from(value: ParameterType) -> Self {
+        value as u32
     }
 }
 
 pub type Handle = *const ();
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum RegisterSet {
     Bool = D3DXRS_BOOL,
@@ -154,8 +483,145 @@ pub enum RegisterSet {
     Sampler = D3DXRS_SAMPLER,
 }
 
-impl Into<u32> for RegisterSet {
-    fn into(self) -> u32 {
-        self as u32
+impl RegisterSet {
+    pub fn from_raw(value: u32) -> Option<Self> {
+        match value {
+            D3DXRS_BOOL => Some(Self::Bool),
+            D3DXRS_INT4 => Some(Self::Int4),
+            D3DXRS_FLOAT4 => Some(Self::Float4),
+            D3DXRS_SAMPLER => Some(Self::Sampler),
+            _ => None,
+        }
+    }
+}
+
+impl From<RegisterSet> for u32 {
+    fn from(value: RegisterSet) -> Self {
+        value as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_the_d3dx_flags() {
+        assert_eq!(
+            CompileOptions::new().flags(),
+            D3DXSHADER_OPTIMIZATION_LEVEL1 | D3DXSHADER_PACKMATRIX_COLUMNMAJOR
+        );
+        assert_eq!(CompileOptions::from_flags(0), CompileOptions::new());
+    }
+
+    #[test]
+    fn combines_flags() {
+        let options = CompileOptions::new()
+            .debug(true)
+            .optimization(OptimizationLevel::Skip);
+        assert_eq!(
+            options.flags(),
+            D3DXSHADER_DEBUG | D3DXSHADER_SKIPOPTIMIZATION | D3DXSHADER_PACKMATRIX_COLUMNMAJOR
+        );
+
+        let options = CompileOptions::new()
+            .optimization(OptimizationLevel::Level3)
+            .matrix_packing(MatrixPacking::RowMajor)
+            .flow_control(FlowControl::Prefer)
+            .ieee_strictness(true)
+            .partial_precision(true)
+            .define("USE_SHADOWS", "1");
+        assert_eq!(
+            options.flags(),
+            D3DXSHADER_OPTIMIZATION_LEVEL3
+                | D3DXSHADER_PACKMATRIX_ROWMAJOR
+                | D3DXSHADER_PREFER_FLOW_CONTROL
+                | D3DXSHADER_IEEE_STRICTNESS
+                | D3DXSHADER_PARTIALPRECISION
+        );
+        assert_eq!(options.macros, vec![Macro::new("USE_SHADOWS", "1")]);
+    }
+
+    #[test]
+    fn round_trips_flags() {
+        for optimization in [
+            OptimizationLevel::Skip,
+            OptimizationLevel::Level0,
+            OptimizationLevel::Level1,
+            OptimizationLevel::Level2,
+            OptimizationLevel::Level3,
+        ] {
+            for flow_control in [
+                FlowControl::Default,
+                FlowControl::Avoid,
+                FlowControl::Prefer,
+            ] {
+                let options = CompileOptions::new()
+                    .optimization(optimization)
+                    .flow_control(flow_control)
+                    .skip_validation(true)
+                    .no_preshader(true)
+                    .backwards_compatibility(true)
+                    .use_legacy_d3dx9_31_dll(true)
+                    .force_vs_software_no_opt(true)
+                    .force_ps_software_no_opt(true);
+
+                assert_eq!(CompileOptions::from_flags(options.flags()), options);
+            }
+        }
+    }
+
+    #[test]
+    fn sizes_default_values_by_register_set() {
+        assert_eq!(DefaultValue::data_len(RegisterSet::Float4, 3), 12);
+        assert_eq!(DefaultValue::data_len(RegisterSet::Int4, 1), 4);
+        assert_eq!(DefaultValue::data_len(RegisterSet::Bool, 2), 2);
+        assert_eq!(DefaultValue::data_len(RegisterSet::Sampler, 1), 0);
+    }
+
+    #[test]
+    fn types_default_values_by_register_set() {
+        let data = [1.5f32.to_bits(), u32::MAX, 0, 2];
+
+        assert_eq!(
+            DefaultValue::from_registers(RegisterSet::Float4, &data[..1]),
+            Some(DefaultValue::Float(vec![1.5]))
+        );
+        assert_eq!(
+            DefaultValue::from_registers(RegisterSet::Int4, &data[1..]),
+            Some(DefaultValue::Int(vec![-1, 0, 2]))
+        );
+        assert_eq!(
+            DefaultValue::from_registers(RegisterSet::Bool, &data[1..]),
+            Some(DefaultValue::Bool(vec![true, false, true]))
+        );
+        assert_eq!(
+            DefaultValue::from_registers(RegisterSet::Sampler, &data),
+            None
+        );
+    }
+
+    #[test]
+    fn converts_enums_to_and_from_raw_values() {
+        for class in [
+            ParameterClass::Scalar,
+            ParameterClass::Vector,
+            ParameterClass::MatrixRows,
+            ParameterClass::MatrixColumns,
+            ParameterClass::Object,
+            ParameterClass::Struct,
+        ] {
+            assert_eq!(ParameterClass::from_raw(class.into()), Some(class));
+        }
+        for set in [
+            RegisterSet::Bool,
+            RegisterSet::Int4,
+            RegisterSet::Float4,
+            RegisterSet::Sampler,
+        ] {
+            assert_eq!(RegisterSet::from_raw(set.into()), Some(set));
+        }
+        assert_eq!(ParameterClass::from_raw(u32::MAX), None);
+        assert_eq!(ParameterType::from_raw(u32::MAX), None);
     }
 }
@@ -2,6 +2,7 @@
 #![allow(non_upper_case_globals)]
 #![allow(non_snake_case)]
 
+#[cfg(windows)]
 use winapi::{
     shared::{
         d3d9::LPDIRECT3DDEVICE9,
@@ -11,9 +12,10 @@ use winapi::{
         unknwnbase::{IUnknown, IUnknownVtbl},
         winnt::{HRESULT, LPCSTR},
     },
-    DEFINE_GUID, ENUM, RIDL, STRUCT,
+    DEFINE_GUID, RIDL, STRUCT,
 };
 
+#[cfg(windows)]
 use crate::d3dx9core::LPD3DXBUFFER;
 
 pub const D3DXSHADER_DEBUG: u32 = 1 << 0;
@@ -39,61 +41,62 @@ pub const D3DXCONSTTABLE_LARGEADDRESSAWARE: u32 = 1 << 17;
 
 pub type D3DXHANDLE = *const ();
 
+#[cfg(windows)]
 STRUCT! {struct D3DXMACRO {
     Name: LPCSTR,
     Definition: LPCSTR,
 }}
 
+#[cfg(windows)]
 STRUCT! {struct D3DXSEMANTIC {
     Usage: u32,
     UsageIndex: u32,
 }}
 
-ENUM! {enum D3DXREGISTER_SET {
-    D3DXRS_BOOL,
-    D3DXRS_INT4,
-    D3DXRS_FLOAT4,
-    D3DXRS_SAMPLER,
-}}
+pub type D3DXREGISTER_SET = u32;
+pub const D3DXRS_BOOL: D3DXREGISTER_SET = 0;
+pub const D3DXRS_INT4: D3DXREGISTER_SET = 1;
+pub const D3DXRS_FLOAT4: D3DXREGISTER_SET = 2;
+pub const D3DXRS_SAMPLER: D3DXREGISTER_SET = 3;
 
-ENUM! {enum D3DXPARAMETER_CLASS {
-    D3DXPC_SCALAR,
-    D3DXPC_VECTOR,
-    D3DXPC_MATRIX_ROWS,
-    D3DXPC_MATRIX_COLUMNS,
-    D3DXPC_OBJECT,
-    D3DXPC_STRUCT,
-}}
+pub type D3DXPARAMETER_CLASS = u32;
+pub const D3DXPC_SCALAR: D3DXPARAMETER_CLASS = 0;
+pub const D3DXPC_VECTOR: D3DXPARAMETER_CLASS = 1;
+pub const D3DXPC_MATRIX_ROWS: D3DXPARAMETER_CLASS = 2;
+pub const D3DXPC_MATRIX_COLUMNS: D3DXPARAMETER_CLASS = 3;
+pub const D3DXPC_OBJECT: D3DXPARAMETER_CLASS = 4;
+pub const D3DXPC_STRUCT: D3DXPARAMETER_CLASS = 5;
 
-ENUM! {enum D3DXPARAMETER_TYPE {
-    D3DXPT_VOID,
-    D3DXPT_BOOL,
-    D3DXPT_INT,
-    D3DXPT_FLOAT,
-    D3DXPT_STRING,
-    D3DXPT_TEXTURE,
-    D3DXPT_TEXTURE1D,
-    D3DXPT_TEXTURE2D,
-    D3DXPT_TEXTURE3D,
-    D3DXPT_TEXTURECUBE,
-    D3DXPT_SAMPLER,
-    D3DXPT_SAMPLER1D,
-    D3DXPT_SAMPLER2D,
-    D3DXPT_SAMPLER3D,
-    D3DXPT_SAMPLERCUBE,
-    D3DXPT_PIXELSHADER,
-    D3DXPT_VERTEXSHADER,
-    D3DXPT_PIXELFRAGMENT,
-    D3DXPT_VERTEXFRAGMENT,
-    D3DXPT_UNSUPPORTED,
-}}
+pub type D3DXPARAMETER_TYPE = u32;
+pub const D3DXPT_VOID: D3DXPARAMETER_TYPE = 0;
+pub const D3DXPT_BOOL: D3DXPARAMETER_TYPE = 1;
+pub const D3DXPT_INT: D3DXPARAMETER_TYPE = 2;
+pub const D3DXPT_FLOAT: D3DXPARAMETER_TYPE = 3;
+pub const D3DXPT_STRING: D3DXPARAMETER_TYPE = 4;
+pub const D3DXPT_TEXTURE: D3DXPARAMETER_TYPE = 5;
+pub const D3DXPT_TEXTURE1D: D3DXPARAMETER_TYPE = 6;
+pub const D3DXPT_TEXTURE2D: D3DXPARAMETER_TYPE = 7;
+pub const D3DXPT_TEXTURE3D: D3DXPARAMETER_TYPE = 8;
+pub const D3DXPT_TEXTURECUBE: D3DXPARAMETER_TYPE = 9;
+pub const D3DXPT_SAMPLER: D3DXPARAMETER_TYPE = 10;
+pub const D3DXPT_SAMPLER1D: D3DXPARAMETER_TYPE = 11;
+pub const D3DXPT_SAMPLER2D: D3DXPARAMETER_TYPE = 12;
+pub const D3DXPT_SAMPLER3D: D3DXPARAMETER_TYPE = 13;
+pub const D3DXPT_SAMPLERCUBE: D3DXPARAMETER_TYPE = 14;
+pub const D3DXPT_PIXELSHADER: D3DXPARAMETER_TYPE = 15;
+pub const D3DXPT_VERTEXSHADER: D3DXPARAMETER_TYPE = 16;
+pub const D3DXPT_PIXELFRAGMENT: D3DXPARAMETER_TYPE = 17;
+pub const D3DXPT_VERTEXFRAGMENT: D3DXPARAMETER_TYPE = 18;
+pub const D3DXPT_UNSUPPORTED: D3DXPARAMETER_TYPE = 19;
 
+#[cfg(windows)]
 STRUCT! {struct D3DXCONSTANTTABLE_DESC {
     Creator: LPCSTR,
     Version: u32,
     Constants: u32,
 }}
 
+#[cfg(windows)]
 STRUCT! {struct D3DXCONSTANT_DESC {
     Name: LPCSTR,
 
@@ -113,9 +116,40 @@ STRUCT! {struct D3DXCONSTANT_DESC {
     DefaultValue: LPCVOID,
 }}
 
+pub type D3DXINCLUDE_TYPE = u32;
+pub const D3DXINC_LOCAL: D3DXINCLUDE_TYPE = 0;
+pub const D3DXINC_SYSTEM: D3DXINCLUDE_TYPE = 1;
+
+/// `ID3DXInclude` is not a COM interface, so it has neither an IID nor `IUnknown` methods, and
+/// is implemented by the application.
+#[cfg(windows)]
+#[repr(C)]
+pub struct ID3DXIncludeVtbl {
+    pub Open: unsafe extern "system" fn(
+        This: *mut ID3DXInclude,
+        IncludeType: D3DXINCLUDE_TYPE,
+        pFileName: LPCSTR,
+        pParentData: LPCVOID,
+        ppData: *mut LPCVOID,
+        pBytes: *mut u32,
+    ) -> HRESULT,
+    pub Close: unsafe extern "system" fn(This: *mut ID3DXInclude, pData: LPCVOID) -> HRESULT,
+}
+
+#[cfg(windows)]
+#[repr(C)]
+pub struct ID3DXInclude {
+    pub lpVtbl: *const ID3DXIncludeVtbl,
+}
+
+#[cfg(windows)]
+pub type LPD3DXINCLUDE = *mut ID3DXInclude;
+
+#[cfg(windows)]
 DEFINE_GUID! {IID_ID3DXConstantTable,
 0xab3c758f, 0x93e, 0x4356, 0xb7, 0x62, 0x4d, 0xb1, 0x8f, 0x1b, 0x3a, 0x1}
 
+#[cfg(windows)]
 RIDL! {#[uuid(0xab3c758f, 0x93e, 0x4356, 0xb7, 0x62, 0x4d, 0xb1, 0x8f, 0x1b, 0x3a, 0x1)]
 interface ID3DXConstantTable(ID3DXConstantTableVtbl): IUnknown(IUnknownVtbl) {
     fn GetBufferPointer() -> LPVOID,
@@ -232,14 +266,16 @@ interface ID3DXConstantTable(ID3DXConstantTableVtbl): IUnknown(IUnknownVtbl) {
     ) -> HRESULT,
 }}
 
+#[cfg(windows)]
 pub type LPD3DXCONSTANTTABLE = *mut ID3DXConstantTable;
 
+#[cfg(windows)]
 #[link(name = "d3dx9")]
 extern "system" {
     pub fn D3DXCompileShaderFromFileA(
         pSrcFile: LPCSTR,
         pDefines: *const D3DXMACRO,
-        pInclude: *const (),
+        pInclude: LPD3DXINCLUDE,
         pFunctionName: LPCSTR,
         pProfile: LPCSTR,
         flags: DWORD,
@@ -252,7 +288,7 @@ extern "system" {
         pSrcData: LPCSTR,
         SrcDataLen: u32,
         pDefines: *const D3DXMACRO,
-        pInclude: *const (),
+        pInclude: LPD3DXINCLUDE,
         pFunctionName: LPCSTR,
         pProfile: LPCSTR,
         flags: DWORD,
@@ -1,3 +1,4 @@
 pub mod d3dx9;
+#[cfg(windows)]
 pub mod d3dx9core;
 pub mod d3dx9shader;